@@ -0,0 +1,293 @@
+//! Module for reading AIFF and AIFF-C files.
+//!
+//! AIFF files store big-endian PCM samples in a ``SSND`` chunk described by a ``COMM`` chunk. AIFF-C files additionally record a compression type in the ``COMM`` chunk.
+//! Uncompressed PCM (big and little-endian), 32 and 64-bit float, mu-law and A-law AIFF-C files are supported. Signed 8-bit PCM is not.
+use std::{
+    io::{Read, SeekFrom},
+    path::Path,
+};
+
+use i24::i24;
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::{alloc_box_buffer, ReadSeek, Wav},
+    error::{FormatError, WaversResult},
+    wav_type::WavType,
+    writer::Endianness,
+};
+
+/// The FORM chunk ID "FORM", which contains every other chunk of an AIFF file
+pub const FORM: [u8; 4] = *b"FORM";
+/// The AIFF form type "AIFF"
+pub const AIFF: [u8; 4] = *b"AIFF";
+/// The AIFF-C form type "AIFC"
+pub const AIFC: [u8; 4] = *b"AIFC";
+/// The common chunk ID "COMM"
+pub const COMM: [u8; 4] = *b"COMM";
+/// The sound data chunk ID "SSND"
+pub const SSND: [u8; 4] = *b"SSND";
+
+/// The header of an AIFF or AIFF-C file, taken from the COMM and SSND chunks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AiffHeader {
+    pub n_channels: u16,
+    /// The number of samples per channel.
+    pub n_frames: u32,
+    pub sample_size: u16,
+    pub sample_rate: f64,
+    /// The compression type of an AIFF-C file, or "NONE" for an AIFF file.
+    pub compression_type: [u8; 4],
+    /// The position of the first sample in the file.
+    pub data_offset: u64,
+}
+
+impl AiffHeader {
+    /// Reads the AIFF header from the start of the reader.
+    pub fn from_reader(reader: &mut Box<dyn ReadSeek>) -> WaversResult<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf)?;
+        if buf != FORM {
+            return Err(FormatError::InvalidAiffData("missing FORM chunk").into());
+        }
+        reader.read_exact(&mut buf)?; // the size of the FORM chunk
+        reader.read_exact(&mut buf)?;
+        let is_aifc = match buf {
+            AIFF => false,
+            AIFC => true,
+            _ => return Err(FormatError::InvalidAiffData("not an AIFF or AIFF-C file").into()),
+        };
+
+        let mut comm: Option<Box<[u8]>> = None;
+        let mut data_offset: Option<u64> = None;
+        while reader.read_exact(&mut buf).is_ok() {
+            let id = buf;
+            reader.read_exact(&mut buf)?;
+            let size = u32::from_be_bytes(buf) as u64;
+            let chunk_start = reader.stream_position()?;
+            match id {
+                COMM => {
+                    let mut bytes = alloc_box_buffer(size as usize);
+                    reader.read_exact(&mut bytes)?;
+                    comm = Some(bytes);
+                }
+                SSND => {
+                    // The samples start after the offset and block size fields, skipping any offset
+                    reader.read_exact(&mut buf)?;
+                    data_offset = Some(chunk_start + 8 + u32::from_be_bytes(buf) as u64);
+                }
+                _ => (),
+            }
+            // Chunks with an odd size are followed by a padding byte
+            reader.seek(SeekFrom::Start(chunk_start + size + size % 2))?;
+        }
+
+        let comm = comm.ok_or(FormatError::InvalidAiffData("missing COMM chunk"))?;
+        let data_offset = data_offset.ok_or(FormatError::InvalidAiffData("missing SSND chunk"))?;
+        if comm.len() < 18 || (is_aifc && comm.len() < 22) {
+            return Err(FormatError::InvalidAiffData("COMM chunk is too small").into());
+        }
+
+        let mut sample_rate = [0; 10];
+        sample_rate.copy_from_slice(&comm[8..18]);
+        let compression_type = match is_aifc {
+            true => [comm[18], comm[19], comm[20], comm[21]],
+            false => *b"NONE",
+        };
+        Ok(AiffHeader {
+            n_channels: u16::from_be_bytes([comm[0], comm[1]]),
+            n_frames: u32::from_be_bytes([comm[2], comm[3], comm[4], comm[5]]),
+            sample_size: u16::from_be_bytes([comm[6], comm[7]]),
+            sample_rate: extended_to_f64(sample_rate),
+            compression_type,
+            data_offset,
+        })
+    }
+
+    /// Returns the byte order of the samples.
+    pub fn endianness(&self) -> Endianness {
+        match &self.compression_type {
+            b"sowt" => Endianness::Little,
+            _ => Endianness::Big,
+        }
+    }
+
+    /// Returns the encoding of the samples, as the equivalent ``WavType``.
+    /// Samples are stored in whole bytes, so for example 20-bit samples are read as 24-bit.
+    pub fn wav_type(&self) -> WaversResult<WavType> {
+        match (&self.compression_type, self.sample_size.div_ceil(8)) {
+            (b"NONE" | b"twos" | b"sowt", 2) => Ok(WavType::Pcm16),
+            (b"NONE" | b"twos" | b"sowt", 3) => Ok(WavType::Pcm24),
+            (b"NONE" | b"twos" | b"sowt", 4) => Ok(WavType::Pcm32),
+            (b"fl32" | b"FL32", _) => Ok(WavType::Float32),
+            (b"fl64" | b"FL64", _) => Ok(WavType::Float64),
+            (b"ulaw" | b"ULAW", _) => Ok(WavType::MuLaw),
+            (b"alaw" | b"ALAW", _) => Ok(WavType::ALaw),
+            (compression_type, _) => Err(FormatError::UnsupportedAiffCompression(
+                String::from_utf8_lossy(compression_type).to_string(),
+            )
+            .into()),
+        }
+    }
+}
+
+/// Converts an 80-bit IEEE 754 extended precision float, as used for the AIFF sample rate, to an f64.
+fn extended_to_f64(bytes: [u8; 10]) -> f64 {
+    let sign = match bytes[0] & 0x80 {
+        0 => 1.0,
+        _ => -1.0,
+    };
+    let exponent = (u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7FFF) as i32;
+    let mut mantissa = [0; 8];
+    mantissa.copy_from_slice(&bytes[2..10]);
+    let mantissa = u64::from_be_bytes(mantissa);
+    if exponent == 0 && mantissa == 0 {
+        return 0.0;
+    }
+    sign * mantissa as f64 * 2f64.powi(exponent - 16383 - 63)
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new Wav struct from an AIFF or AIFF-C file.
+    /// The samples are read using the same API as a wav file and can be written as a wav file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let f = std::fs::File::open("path/to/audio.aiff").unwrap();
+    ///     let mut aiff: Wav<f32> = Wav::from_aiff(Box::new(f)).unwrap();
+    ///     let samples = aiff.read().unwrap();
+    /// }
+    /// ```
+    pub fn from_aiff(mut reader: Box<dyn ReadSeek>) -> WaversResult<Self> {
+        let header = AiffHeader::from_reader(&mut reader)?;
+        reader.seek(SeekFrom::Start(header.data_offset))?;
+        Wav::from_raw_parts(
+            reader,
+            header.sample_rate.round() as i32,
+            header.n_channels,
+            header.wav_type()?,
+            header.endianness(),
+            Some(header.n_frames as usize * header.n_channels as usize),
+        )
+    }
+
+    /// Construct a new Wav struct from the path of an AIFF or AIFF-C file.
+    /// Uses a BufReader to read the file.
+    pub fn from_aiff_path<P: AsRef<Path>>(path: P) -> WaversResult<Self> {
+        let f = std::fs::File::open(path)?;
+        let buf_reader: Box<dyn ReadSeek> = Box::new(std::io::BufReader::new(f));
+        Self::from_aiff(buf_reader)
+    }
+}
+
+#[cfg(test)]
+mod aiff_tests {
+    use super::*;
+    use crate::Samples;
+
+    const ONE_CHANNEL_WAV_I16: &str = "./test_resources/one_channel_i16.wav";
+
+    /// Converts a positive whole number to an 80-bit extended precision float.
+    fn f64_to_extended(value: f64) -> [u8; 10] {
+        let value = value as u64;
+        let shift = value.leading_zeros();
+        let exponent = (16383 + 63 - shift) as u16;
+        let mut bytes = [0; 10];
+        bytes[0..2].copy_from_slice(&exponent.to_be_bytes());
+        bytes[2..10].copy_from_slice(&(value << shift).to_be_bytes());
+        bytes
+    }
+
+    /// Builds an AIFF or AIFF-C file containing the samples of the one channel i16 test file.
+    fn aiff_bytes(compression_type: Option<&[u8; 4]>) -> Vec<u8> {
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        let data = &wav_bytes[44..];
+
+        let mut comm = Vec::new();
+        comm.extend_from_slice(&1u16.to_be_bytes());
+        comm.extend_from_slice(&((data.len() / 2) as u32).to_be_bytes());
+        comm.extend_from_slice(&16u16.to_be_bytes());
+        comm.extend_from_slice(&f64_to_extended(16000.0));
+        if let Some(compression_type) = compression_type {
+            comm.extend_from_slice(compression_type);
+            comm.extend_from_slice(&[0, 0]); // empty, padded, compression name
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&FORM);
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(match compression_type {
+            Some(_) => &AIFC,
+            None => &AIFF,
+        });
+        bytes.extend_from_slice(&COMM);
+        bytes.extend_from_slice(&(comm.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&comm);
+        bytes.extend_from_slice(&SSND);
+        bytes.extend_from_slice(&(data.len() as u32 + 8).to_be_bytes());
+        bytes.extend_from_slice(&[0; 8]); // offset and block size
+        for sample in data.chunks_exact(2) {
+            match compression_type {
+                Some(b"sowt") => bytes.extend_from_slice(sample),
+                _ => bytes.extend_from_slice(&[sample[1], sample[0]]),
+            }
+        }
+        let form_size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&form_size.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn can_convert_extended_floats() {
+        for value in [8000.0, 16000.0, 44100.0, 48000.0, 96000.0] {
+            assert_eq!(extended_to_f64(f64_to_extended(value)), value);
+        }
+        assert_eq!(extended_to_f64([0; 10]), 0.0);
+    }
+
+    #[test]
+    fn can_read_samples() {
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected_samples: Samples<i16> = wav.read().unwrap();
+
+        for compression_type in [None, Some(b"NONE"), Some(b"sowt")] {
+            let reader: Box<dyn ReadSeek> =
+                Box::new(std::io::Cursor::new(aiff_bytes(compression_type)));
+            let mut aiff: Wav<i16> = Wav::from_aiff(reader).unwrap();
+            assert_eq!(aiff.sample_rate(), 16000);
+            assert_eq!(aiff.n_channels(), 1);
+            assert_eq!(aiff.n_samples(), expected_samples.len());
+            let samples: Samples<i16> = aiff.read().unwrap();
+            assert_eq!(&samples[..], &expected_samples[..], "Samples do not match");
+        }
+    }
+
+    #[test]
+    fn unsupported_compression_is_an_error() {
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(aiff_bytes(Some(b"ima4"))));
+        assert!(Wav::<i16>::from_aiff(reader).is_err());
+
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(
+            std::fs::read(ONE_CHANNEL_WAV_I16).unwrap(),
+        ));
+        assert!(Wav::<i16>::from_aiff(reader).is_err());
+    }
+}
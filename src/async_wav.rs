@@ -0,0 +1,650 @@
+//! Module containing the ``AsyncWav`` struct and the ``write`` functions, which read and write wav files without blocking, available with the ``tokio`` or ``futures`` features.
+//!
+//! Only the bytes of a file are read and written asynchronously, through the ``AsyncWavReader`` and ``AsyncWavWriter`` traits,
+//! while the samples are decoded and encoded in the same way as by the ``Wav`` struct.
+//! This allows web services to serve and ingest wav files without blocking their worker threads.
+//!
+//! With the ``tokio`` feature, the traits are implemented for every reader and writer implementing ``tokio``'s ``AsyncRead``, ``AsyncSeek`` and ``AsyncWrite`` traits.
+//! With the ``futures`` feature, they are implemented for every reader and writer implementing the ``futures`` traits of the same names once wrapped in ``FuturesIo``,
+//! so that the same code can be used with smol, async-std or custom transports.
+use std::future::Future;
+use std::io::{Cursor, SeekFrom};
+#[cfg(feature = "tokio")]
+use std::path::Path;
+
+use i24::i24;
+#[cfg(feature = "tokio")]
+use tokio::fs::File;
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::{FormatError, WaversResult};
+use crate::header::header_bytes_needed;
+use crate::writer::WriteOptions;
+
+/// The maximum number of bytes read at a time while reading the header, so that a corrupt chunk size cannot cause a huge allocation.
+const HEADER_READ_SIZE: usize = 1 << 16;
+
+/// An asynchronous reader which an ``AsyncWav`` can read a wav file from.
+///
+/// It is implemented for every ``tokio`` ``AsyncRead + AsyncSeek`` reader with the ``tokio`` feature,
+/// and for every ``futures`` ``AsyncRead + AsyncSeek`` reader wrapped in ``FuturesIo`` with the ``futures`` feature.
+pub trait AsyncWavReader {
+    /// Reads exactly ``buf.len()`` bytes, starting ``pos`` bytes from the start of the reader.
+    fn read_exact_at<'a>(
+        &'a mut self,
+        pos: u64,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a;
+}
+
+/// An asynchronous writer which wav files can be written to with ``write`` and ``write_with_options``.
+///
+/// It is implemented for every ``tokio`` ``AsyncWrite`` writer with the ``tokio`` feature,
+/// and for every ``futures`` ``AsyncWrite`` writer wrapped in ``FuturesIo`` with the ``futures`` feature.
+pub trait AsyncWavWriter {
+    /// Writes all of ``buf`` and then flushes the writer.
+    fn write_all_and_flush<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a;
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin> AsyncWavReader for R {
+    fn read_exact_at<'a>(
+        &'a mut self,
+        pos: u64,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        async move {
+            self.seek(SeekFrom::Start(pos)).await?;
+            self.read_exact(buf).await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> AsyncWavWriter for W {
+    fn write_all_and_flush<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a {
+        use tokio::io::AsyncWriteExt;
+
+        async move {
+            self.write_all(buf).await?;
+            self.flush().await
+        }
+    }
+}
+
+/// Wrapper for readers and writers implementing the runtime-agnostic ``futures`` traits, such as those of smol and async-std,
+/// so that they can be used with ``AsyncWav`` and the ``write`` functions.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::async_wav::{AsyncWav, FuturesIo};
+///
+/// async fn read<R: futures_io::AsyncRead + futures_io::AsyncSeek + Unpin>(reader: R) -> Vec<f32> {
+///     let mut wav: AsyncWav<f32, _> = AsyncWav::new(FuturesIo::new(reader)).await.unwrap();
+///     wav.read().await.unwrap().to_vec()
+/// }
+/// ```
+#[cfg(feature = "futures")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuturesIo<T>(pub T);
+
+#[cfg(feature = "futures")]
+impl<T> FuturesIo<T> {
+    /// Wraps a ``futures`` reader or writer.
+    pub fn new(inner: T) -> Self {
+        FuturesIo(inner)
+    }
+
+    /// Returns the wrapped reader or writer.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Consumes the wrapper and returns the wrapped reader or writer.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: futures_io::AsyncRead + futures_io::AsyncSeek + Unpin> AsyncWavReader for FuturesIo<R> {
+    fn read_exact_at<'a>(
+        &'a mut self,
+        pos: u64,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a {
+        use std::future::poll_fn;
+        use std::pin::Pin;
+
+        async move {
+            let reader = &mut self.0;
+            poll_fn(|cx| Pin::new(&mut *reader).poll_seek(cx, SeekFrom::Start(pos))).await?;
+            let mut n_filled = 0;
+            while n_filled < buf.len() {
+                match poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf[n_filled..])).await
+                {
+                    Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                    Ok(n_read) => n_filled += n_read,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<W: futures_io::AsyncWrite + Unpin> AsyncWavWriter for FuturesIo<W> {
+    fn write_all_and_flush<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Future<Output = std::io::Result<()>> + 'a {
+        use std::future::poll_fn;
+        use std::pin::Pin;
+
+        async move {
+            let writer = &mut self.0;
+            let mut n_written = 0;
+            while n_written < buf.len() {
+                match poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, &buf[n_written..])).await {
+                    Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                    Ok(n) => n_written += n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await
+        }
+    }
+}
+
+/// A wav file which is read asynchronously from an ``AsyncWavReader``, such as a ``tokio::fs::File`` or a ``futures`` reader wrapped in ``FuturesIo``.
+///
+/// The header is read when the AsyncWav is constructed, and the samples are only read when ``read`` or ``read_block`` is awaited.
+/// Only the chunks before the data chunk are read, so chunks following the data chunk, such as a trailing LIST chunk, are not discovered.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::async_wav::AsyncWav;
+///
+/// async fn peak(path: &str) -> f32 {
+///     let mut wav: AsyncWav<f32, _> = AsyncWav::from_path(path).await.unwrap();
+///     let mut buf = vec![0.0; 1024 * wav.n_channels() as usize];
+///     let mut peak = 0.0f32;
+///     loop {
+///         let n_frames = wav.read_block(&mut buf, 1024).await.unwrap();
+///         if n_frames == 0 {
+///             break;
+///         }
+///         let block = &buf[..n_frames * wav.n_channels() as usize];
+///         peak = block.iter().fold(peak, |peak, sample| peak.max(sample.abs()));
+///     }
+///     peak
+/// }
+/// ```
+pub struct AsyncWav<T: AudioSample, R>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    reader: R,
+    /// The bytes of the file up to the start of the data chunk.
+    header: Vec<u8>,
+    /// A Wav struct over the bytes of the header, which keeps track of the position of the reader in the data chunk.
+    wav: Wav<T>,
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AudioSample> AsyncWav<T, File>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Opens the file at the given path with ``tokio::fs`` and reads its header.
+    ///
+    /// This function will return an error if the file cannot be opened or if its header is invalid.
+    pub async fn from_path<P: AsRef<Path>>(path: P) -> WaversResult<Self> {
+        Self::new(File::open(path).await?).await
+    }
+}
+
+impl<T: AudioSample, R: AsyncWavReader> AsyncWav<T, R>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new AsyncWav from a reader, reading the header from the start of the reader.
+    ///
+    /// This function will return an error if there is an issue reading the header or if the header is invalid.
+    pub async fn new(mut reader: R) -> WaversResult<Self> {
+        let mut header: Vec<u8> = Vec::new();
+        loop {
+            let n_needed = header_bytes_needed(&header)?.min(HEADER_READ_SIZE);
+            if n_needed == 0 {
+                break;
+            }
+            let n_read = header.len();
+            header.resize(n_read + n_needed, 0);
+            reader
+                .read_exact_at(n_read as u64, &mut header[n_read..])
+                .await?;
+        }
+
+        let wav = Wav::from_stream(Cursor::new(header.clone()))?;
+        Ok(AsyncWav {
+            reader,
+            header,
+            wav,
+        })
+    }
+
+    /// Returns the Wav struct describing the header, e.g. to inspect its format or the metadata chunks before the data chunk.
+    /// Its samples cannot be read, since they are read through the AsyncWav.
+    pub fn wav(&self) -> &Wav<T> {
+        &self.wav
+    }
+
+    /// Returns the Wav struct describing the header, e.g. to read the metadata chunks before the data chunk.
+    pub fn wav_mut(&mut self) -> &mut Wav<T> {
+        &mut self.wav
+    }
+
+    /// Consumes the AsyncWav and returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Returns the sample rate of the wav file.
+    pub fn sample_rate(&self) -> i32 {
+        self.wav.sample_rate()
+    }
+
+    /// Returns the number of channels of the wav file.
+    pub fn n_channels(&self) -> u16 {
+        self.wav.n_channels()
+    }
+
+    /// Returns the number of frames in the wav file.
+    pub fn n_frames(&self) -> usize {
+        self.wav.n_frames()
+    }
+
+    /// Moves to the start of the given frame, counted from the start of the data chunk, so that reading resumes from that frame. See ``Wav::seek_frame``.
+    /// Nothing is read until the next read, so this function does not need to be awaited.
+    ///
+    /// Returns the frame moved to, or an error if the frame is out of range or is not at the start of a block.
+    pub fn seek_frame(&mut self, frame: u64) -> WaversResult<u64> {
+        self.wav.seek_frame(frame)
+    }
+
+    /// Returns the frame reading resumes from, counted from the start of the data chunk.
+    pub fn current_frame(&mut self) -> WaversResult<u64> {
+        self.wav.current_frame()
+    }
+
+    /// Reads the remaining samples of the wav file in the same way as ``Wav::read``, moving back to the start of the data chunk afterwards.
+    /// Block encoded files, such as MS ADPCM, are always read whole.
+    ///
+    /// This function will return an error if there is an issue reading the file.
+    pub async fn read(&mut self) -> WaversResult<Samples<T>> {
+        let start = self.wav.current_pos()?;
+        self.wav.to_data()?;
+        let data_start = self.wav.current_pos()?;
+        if self.wav.encoding().is_block_encoded() {
+            // Decoding block encoded samples also needs the chunks of the header, such as the coefficients of the fmt chunk
+            let data = self.read_bytes(data_start, self.wav.max_data_pos()).await?;
+            let mut decoder = self
+                .wav
+                .with_bytes_at([&self.header[..], &data].concat(), 0)?;
+            decoder.to_data()?;
+            return decoder.read();
+        }
+        let start = start.max(data_start);
+        let bytes = self.read_bytes(start, self.wav.max_data_pos()).await?;
+        self.wav.with_bytes_at(bytes, start)?.read()
+    }
+
+    /// Reads up to ``n_frames`` frames from the current position into the start of ``buf`` in the same way as ``Wav::read_block``.
+    ///
+    /// Returns the number of frames read, which is zero once the end of the data chunk is reached,
+    /// or an error if there is an issue reading the file or the file is block encoded.
+    pub async fn read_block(&mut self, buf: &mut [T], n_frames: usize) -> WaversResult<usize> {
+        let wav_type = self.wav.encoding();
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_channels = self.n_channels().max(1) as usize;
+        let frame_size = (wav_type.n_bytes() * n_channels) as u64;
+        let start = self.wav.current_pos()?;
+        let frames_left = self.wav.max_data_pos().saturating_sub(start) / frame_size;
+
+        let n_frames = n_frames
+            .min(buf.len() / n_channels)
+            .min(frames_left as usize);
+        let end = start + n_frames as u64 * frame_size;
+        let bytes = self.read_bytes(start, end).await?;
+        let n_frames = self
+            .wav
+            .with_bytes_at(bytes, start)?
+            .read_block(buf, n_frames)?;
+        self.wav.seek_relative((end - start) as i64)?;
+        Ok(n_frames)
+    }
+
+    /// Reads the bytes of the file from ``start`` up to ``end``.
+    async fn read_bytes(&mut self, start: u64, end: u64) -> WaversResult<Vec<u8>> {
+        let mut bytes = vec![0; end.saturating_sub(start) as usize];
+        if !bytes.is_empty() {
+            self.reader.read_exact_at(start, &mut bytes).await?;
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the samples of the wav file and writes them to ``writer`` using the provided ``WriteOptions``, in the same way as ``write_with_options``.
+    /// Only the samples are written, so the metadata chunks of the file are not copied.
+    ///
+    /// This function will return an error if there is an issue reading the samples or writing them.
+    pub async fn write<W: AsyncWavWriter>(
+        &mut self,
+        writer: &mut W,
+        options: &WriteOptions,
+    ) -> WaversResult<()> {
+        let samples = self.read().await?;
+        write_with_options(
+            writer,
+            &samples,
+            self.sample_rate(),
+            self.n_channels(),
+            options,
+        )
+        .await
+    }
+}
+
+/// Writes wav samples to an ``AsyncWavWriter``, such as a ``tokio::fs::File``, in the same way as ``wavers::write``.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::async_wav;
+///
+/// async fn save(samples: &[f32]) {
+///     let mut file = tokio::fs::File::create("./output.wav").await.unwrap();
+///     async_wav::write(&mut file, samples, 16000, 1).await.unwrap();
+/// }
+/// ```
+pub async fn write<T: AudioSample, W: AsyncWavWriter>(
+    writer: &mut W,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_with_options(
+        writer,
+        samples,
+        sample_rate,
+        n_channels,
+        &WriteOptions::new(),
+    )
+    .await
+}
+
+/// Writes wav samples to an ``AsyncWavWriter`` using the provided ``WriteOptions``, in the same way as ``wavers::write_with_options``.
+/// The file is encoded in memory and then written in one go, so the progress callback of the options only reports the encoding.
+///
+/// This function will return an error if the samples cannot be encoded or there is an issue writing them.
+pub async fn write_with_options<T: AudioSample, W: AsyncWavWriter>(
+    writer: &mut W,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let bytes = crate::write_to_vec_with_options(samples, sample_rate, n_channels, options)?;
+    writer.write_all_and_flush(&bytes).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod async_wav_tests {
+    use super::*;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+    #[cfg(feature = "tokio")]
+    const ONE_CHANNEL_WAV_MS_ADPCM: &str = "./test_resources/one_channel_ms_adpcm.wav";
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls a future until it completes, which is enough for the in-memory readers and writers used here, so the tests do not need a runtime.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_reads_match_sync_reads() {
+        let mut sync_wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = sync_wav.read().unwrap();
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+
+        let mut wav: AsyncWav<i16, _> = block_on(AsyncWav::new(Cursor::new(bytes))).unwrap();
+        assert_eq!(wav.sample_rate(), sync_wav.sample_rate());
+        assert_eq!(wav.n_frames(), sync_wav.n_frames());
+        assert_eq!(block_on(wav.read()).unwrap(), expected);
+
+        let mut buf = [0i16; 64];
+        wav.seek_frame(10).unwrap();
+        assert_eq!(block_on(wav.read_block(&mut buf, 32)).unwrap(), 32);
+        assert_eq!(&buf[..], &expected[20..84]);
+        assert_eq!(wav.current_frame().unwrap(), 42);
+
+        let n_frames = wav.n_frames() as u64;
+        wav.seek_frame(n_frames - 5).unwrap();
+        assert_eq!(block_on(wav.read_block(&mut buf, 32)).unwrap(), 5);
+        assert_eq!(block_on(wav.read_block(&mut buf, 32)).unwrap(), 0);
+
+        // Block encoded files can only be read whole
+        let bytes = std::fs::read(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        let expected = Wav::<i16>::from_path(ONE_CHANNEL_WAV_MS_ADPCM)
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut wav: AsyncWav<i16, _> = block_on(AsyncWav::new(Cursor::new(bytes))).unwrap();
+        assert!(block_on(wav.read_block(&mut buf, 32)).is_err());
+        assert_eq!(block_on(wav.read()).unwrap(), expected);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_writes_match_sync_writes() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let samples = wav.read().unwrap();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        block_on(write(
+            &mut bytes,
+            &samples,
+            wav.sample_rate(),
+            wav.n_channels(),
+        ))
+        .unwrap();
+        let mut written: Wav<i16> = Wav::from_vec(bytes.clone()).unwrap();
+        assert_eq!(written.read().unwrap(), samples);
+
+        let mut async_wav: AsyncWav<i16, _> = block_on(AsyncWav::new(Cursor::new(bytes))).unwrap();
+        let mut copied: Vec<u8> = Vec::new();
+        block_on(async_wav.write(&mut copied, &WriteOptions::new())).unwrap();
+        let mut copied: Wav<i16> = Wav::from_vec(copied).unwrap();
+        assert_eq!(copied.read().unwrap(), samples);
+    }
+
+    /// An in-memory reader and writer implementing the ``futures`` traits, which ``FuturesIo`` is tested with.
+    #[cfg(feature = "futures")]
+    struct FuturesCursor(Cursor<Vec<u8>>);
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncRead for FuturesCursor {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(std::io::Read::read(&mut self.get_mut().0, buf))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncSeek for FuturesCursor {
+        fn poll_seek(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut Context<'_>,
+            pos: SeekFrom,
+        ) -> Poll<std::io::Result<u64>> {
+            Poll::Ready(std::io::Seek::seek(&mut self.get_mut().0, pos))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncWrite for FuturesCursor {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(std::io::Write::write(&mut self.get_mut().0, buf))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn futures_readers_and_writers_are_supported() {
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = Wav::<i16>::from_vec(bytes.clone()).unwrap().read().unwrap();
+
+        let mut wav: AsyncWav<i16, _> = block_on(AsyncWav::new(FuturesIo::new(FuturesCursor(
+            Cursor::new(bytes),
+        ))))
+        .unwrap();
+        let mut buf = [0i16; 64];
+        wav.seek_frame(10).unwrap();
+        assert_eq!(block_on(wav.read_block(&mut buf, 32)).unwrap(), 32);
+        assert_eq!(&buf[..], &expected[20..84]);
+
+        let mut written = FuturesIo::new(FuturesCursor(Cursor::new(Vec::new())));
+        wav.seek_frame(0).unwrap();
+        block_on(wav.write(&mut written, &WriteOptions::new())).unwrap();
+        let mut written: Wav<i16> = Wav::from_vec(written.into_inner().0.into_inner()).unwrap();
+        assert_eq!(written.read().unwrap(), expected);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn invalid_headers_are_rejected() {
+        let bytes = b"not a wav file at all".to_vec();
+        assert!(block_on(AsyncWav::<i16, _>::new(Cursor::new(bytes))).is_err());
+
+        // A header which ends before the data chunk
+        let mut bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        bytes.truncate(20);
+        assert!(block_on(AsyncWav::<i16, _>::new(Cursor::new(bytes))).is_err());
+    }
+}
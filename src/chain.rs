@@ -0,0 +1,406 @@
+//! Module containing the ``WavChain`` struct, which presents several wav files as a single continuous stream of frames.
+//!
+//! Long recordings are often split across several files, e.g. one file per hour. A ``WavChain`` reads the files one after another without a gap,
+//! so frames are counted, sought and iterated across the whole chain as if it were one file.
+use std::path::Path;
+use std::time::Duration;
+
+use i24::i24;
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::{FormatError, WaversResult};
+use crate::iter::{Frame, FRAME_BUFFER_SIZE};
+
+/// Several wav files with the same sample rate and number of channels, read as one continuous stream of frames.
+/// The files may have different encodings, since every sample is converted to ``T`` as it is read.
+///
+/// Frame numbers are counted from the first frame of the first file, so the first frame of the second file follows directly on from the last frame of the first.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::WavChain;
+///
+/// let mut chain: WavChain<f32> = WavChain::new(["path/to/00.wav", "path/to/01.wav"]).unwrap();
+/// println!("{:?}", chain.duration_time());
+/// // Read ten seconds which may span the boundary between the files
+/// chain.seek_frame(chain.sample_rate() as u64 * 3595).unwrap();
+/// let samples = chain.read_frames(chain.sample_rate() as usize * 10).unwrap();
+/// ```
+pub struct WavChain<T: AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wavs: Vec<Wav<T>>,
+    starts: Vec<u64>,
+    n_frames: u64,
+    current: usize,
+}
+
+impl<T: AudioSample> WavChain<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Opens the files at the given paths and chains them in the order given. See ``from_wavs``.
+    pub fn new<I, P>(paths: I) -> WaversResult<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let wavs = paths
+            .into_iter()
+            .map(Wav::from_path)
+            .collect::<WaversResult<Vec<Wav<T>>>>()?;
+        Self::from_wavs(wavs)
+    }
+
+    /// Chains the Wav structs in the order given, starting from the first frame of the first file.
+    ///
+    /// This function will return an error if there are no files, if the files do not all have the same sample rate and number of channels,
+    /// or if any file is block encoded, since block encoded files, such as MS ADPCM, can only be decoded a whole file at a time.
+    pub fn from_wavs(mut wavs: Vec<Wav<T>>) -> WaversResult<Self> {
+        let first = wavs.first().ok_or(FormatError::EmptyWavChain)?;
+        let (expected_sample_rate, expected_n_channels) = (first.sample_rate(), first.n_channels());
+
+        let mut starts = Vec::with_capacity(wavs.len());
+        let mut n_frames = 0;
+        for (index, wav) in wavs.iter_mut().enumerate() {
+            let wav_type = wav.encoding();
+            if wav_type.is_block_encoded() {
+                return Err(FormatError::BlockEncodedFormat(wav_type).into());
+            }
+            if wav.sample_rate() != expected_sample_rate || wav.n_channels() != expected_n_channels
+            {
+                return Err(FormatError::IncompatibleWavChain {
+                    index,
+                    sample_rate: wav.sample_rate(),
+                    n_channels: wav.n_channels(),
+                    expected_sample_rate,
+                    expected_n_channels,
+                }
+                .into());
+            }
+            wav.to_data()?;
+            starts.push(n_frames);
+            n_frames += wav.n_frames() as u64;
+        }
+
+        Ok(WavChain {
+            wavs,
+            starts,
+            n_frames,
+            current: 0,
+        })
+    }
+
+    /// Returns the Wav structs of the chain, in order.
+    pub fn wavs(&self) -> &[Wav<T>] {
+        &self.wavs
+    }
+
+    /// Consumes the WavChain and returns its Wav structs, in order.
+    pub fn into_inner(self) -> Vec<Wav<T>> {
+        self.wavs
+    }
+
+    /// Returns the number of files in the chain.
+    pub fn n_files(&self) -> usize {
+        self.wavs.len()
+    }
+
+    /// Returns the frame of the chain at which the file with the given index starts, or ``None`` if there is no such file.
+    pub fn file_start(&self, index: usize) -> Option<u64> {
+        self.starts.get(index).copied()
+    }
+
+    /// Returns the sample rate shared by the files of the chain.
+    pub fn sample_rate(&self) -> i32 {
+        self.wavs[0].sample_rate()
+    }
+
+    /// Returns the number of channels shared by the files of the chain.
+    pub fn n_channels(&self) -> u16 {
+        self.wavs[0].n_channels()
+    }
+
+    /// Returns the total number of frames in the chain.
+    pub fn n_frames(&self) -> u64 {
+        self.n_frames
+    }
+
+    /// Returns the total number of samples in the chain, across all channels.
+    pub fn n_samples(&self) -> usize {
+        self.n_frames as usize * self.n_channels() as usize
+    }
+
+    /// Returns the combined duration of the files of the chain, computed from the total number of frames and the sample rate.
+    pub fn duration_time(&self) -> Duration {
+        let sample_rate = self.sample_rate().max(1) as u64;
+        let nanos = (self.n_frames % sample_rate) * 1_000_000_000 / sample_rate;
+        Duration::new(self.n_frames / sample_rate, nanos as u32)
+    }
+
+    /// Moves the reader to the given frame of the chain, counted from the first frame of the first file.
+    /// Seeking to ``n_frames`` positions the reader at the end of the chain.
+    ///
+    /// Returns the frame sought to, or an error if the frame is past the end of the chain.
+    pub fn seek_frame(&mut self, frame: u64) -> WaversResult<u64> {
+        if frame > self.n_frames {
+            return Err(FormatError::FrameOutOfRange {
+                frame,
+                n_frames: self.n_frames,
+            }
+            .into());
+        }
+        // The last file starting at or before the frame, which skips over any empty files
+        let index = self.starts.partition_point(|start| *start <= frame) - 1;
+        self.wavs[index].seek_frame(frame - self.starts[index])?;
+        self.current = index;
+        Ok(frame)
+    }
+
+    /// Returns the frame of the chain the reader is positioned at, counted from the first frame of the first file.
+    pub fn current_frame(&mut self) -> WaversResult<u64> {
+        Ok(self.starts[self.current] + self.wavs[self.current].current_frame()?)
+    }
+
+    /// Reads samples from the current position into ``out``, moving on to the next file whenever the end of a file is reached.
+    /// Returns the number of samples read, which is less than the length of ``out`` only if the end of the chain is reached.
+    ///
+    /// This function will return an error if there is an issue reading a file.
+    pub fn read_into(&mut self, out: &mut [T]) -> WaversResult<usize> {
+        let mut n_read = self.wavs[self.current].read_into(out)?;
+        while n_read < out.len() && self.current + 1 < self.wavs.len() {
+            self.current += 1;
+            self.wavs[self.current].to_data()?;
+            n_read += self.wavs[self.current].read_into(&mut out[n_read..])?;
+        }
+        Ok(n_read)
+    }
+
+    /// Reads up to ``count`` frames from the current position, which may span several files.
+    /// Returns ``count * n_channels`` interleaved samples, or fewer if the end of the chain is reached first.
+    ///
+    /// This function will return an error if there is an issue reading a file.
+    pub fn read_frames(&mut self, count: usize) -> WaversResult<Samples<T>> {
+        let remaining = self.n_frames - self.current_frame()?;
+        let n_samples = count.min(remaining as usize) * self.n_channels() as usize;
+        let mut samples = vec![T::zero(); n_samples];
+        let n_read = self.read_into(&mut samples)?;
+        samples.truncate(n_read);
+        Ok(Samples::from(samples))
+    }
+
+    /// Reads every frame of the chain, starting from the first frame of the first file.
+    /// The reader is moved back to the start of the chain afterwards.
+    ///
+    /// This function will return an error if there is an issue reading a file.
+    pub fn read(&mut self) -> WaversResult<Samples<T>> {
+        self.seek_frame(0)?;
+        let samples = self.read_frames(self.n_frames as usize)?;
+        self.seek_frame(0)?;
+        Ok(samples)
+    }
+
+    /// Returns an iterator over the frames of the chain, starting from the current position. See the ``ChainFrameIterator`` struct for more information.
+    pub fn frames(&mut self) -> ChainFrameIterator<'_, T> {
+        ChainFrameIterator::new(self)
+    }
+}
+
+/// A frame iterator for the WavChain struct, which carries on into the next file whenever the end of a file is reached.
+/// Each frame is returned as a ``Frame``. This should only be used via the ``frames`` function on the WavChain struct.
+/// Frames returned by ``next_frame`` borrow the iterator's buffer, while those returned through ``Iterator`` own their samples.
+///
+/// Frames are decoded ``FRAME_BUFFER_SIZE`` frames at a time, so the memory used does not depend on the length of the chain.
+///
+/// Note: This iterator *should* reset the WavChain struct to the start of the chain when it is done iterating.
+pub struct ChainFrameIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    chain: &'a mut WavChain<T>,
+    buffer: Vec<T>,
+    buffer_pos: usize,
+    finished: bool,
+}
+
+impl<'a, T: 'a + AudioSample> ChainFrameIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    pub fn new(chain: &'a mut WavChain<T>) -> ChainFrameIterator<'a, T> {
+        ChainFrameIterator {
+            chain,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the next frame as a ``Frame`` which borrows the iterator's buffer, so no allocation is made per frame.
+    /// The frame must be dropped before the next call, so this is used in a ``while let`` loop rather than through ``Iterator``.
+    pub fn next_frame(&mut self) -> Option<Frame<'_, T>> {
+        if self.finished {
+            return None;
+        }
+        let n_channels = self.chain.n_channels().max(1) as usize;
+        if self.buffer_pos + n_channels > self.buffer.len() {
+            self.buffer
+                .resize(FRAME_BUFFER_SIZE * n_channels, T::zero());
+            match self.chain.read_into(&mut self.buffer) {
+                Ok(n_read) if n_read >= n_channels => self.buffer.truncate(n_read),
+                Ok(_) => {
+                    self.finish();
+                    return None;
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    self.finish();
+                    return None;
+                }
+            }
+            self.buffer_pos = 0;
+        }
+        let frame = &self.buffer[self.buffer_pos..self.buffer_pos + n_channels];
+        self.buffer_pos += n_channels;
+        Some(Frame::from(frame))
+    }
+
+    /// Stops the iterator and moves the WavChain struct back to the start of the chain.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.buffer = Vec::new();
+        if let Err(e) = self.chain.seek_frame(0) {
+            eprintln!("Error: {}", e);
+        }
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for ChainFrameIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = Frame<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().map(Frame::into_owned)
+    }
+}
+
+#[cfg(test)]
+mod chain_tests {
+    use super::*;
+
+    const ONE_CHANNEL_WAV_I16: &str = "./test_resources/one_channel_i16.wav";
+    const ONE_CHANNEL_WAV_F32: &str = "./test_resources/one_channel_f32.wav";
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn chain_reads_files_back_to_back() {
+        let first = Wav::<f32>::from_path(ONE_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        let second = Wav::<f32>::from_path(ONE_CHANNEL_WAV_F32)
+            .unwrap()
+            .read()
+            .unwrap();
+        let expected: Vec<f32> = first.iter().chain(second.iter()).copied().collect();
+
+        let mut chain: WavChain<f32> =
+            WavChain::new([ONE_CHANNEL_WAV_I16, ONE_CHANNEL_WAV_F32]).unwrap();
+        assert_eq!(chain.n_files(), 2);
+        assert_eq!(chain.n_frames(), expected.len() as u64);
+        assert_eq!(chain.file_start(1), Some(first.len() as u64));
+        let wav_duration = Wav::<f32>::from_path(ONE_CHANNEL_WAV_I16)
+            .unwrap()
+            .duration_time();
+        assert_eq!(chain.duration_time(), wav_duration * 2);
+
+        assert_eq!(&chain.read().unwrap()[..], &expected[..]);
+        let frames: Vec<f32> = chain.frames().map(|frame| frame[0]).collect();
+        assert_eq!(frames, expected);
+        let mut frames = chain.frames();
+        let mut borrowed = Vec::new();
+        while let Some(frame) = frames.next_frame() {
+            borrowed.push(frame[0]);
+        }
+        assert_eq!(borrowed, expected);
+
+        // A read which spans the boundary between the files
+        let boundary = first.len();
+        chain.seek_frame(boundary as u64 - 10).unwrap();
+        assert_eq!(
+            &chain.read_frames(20).unwrap()[..],
+            &expected[boundary - 10..boundary + 10]
+        );
+        assert_eq!(chain.current_frame().unwrap(), boundary as u64 + 10);
+
+        chain.seek_frame(chain.n_frames()).unwrap();
+        assert!(chain.read_frames(10).unwrap().is_empty());
+        assert!(chain.seek_frame(chain.n_frames() + 1).is_err());
+    }
+
+    #[test]
+    fn incompatible_chains_are_rejected() {
+        assert!(WavChain::<i16>::new(Vec::<&str>::new()).is_err());
+        assert!(WavChain::<i16>::new([ONE_CHANNEL_WAV_I16, TWO_CHANNEL_WAV_I16]).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Module containing the ``ChannelLayout`` type, which describes the speaker position of each channel in a wav file.
+//!
+//! The layout is stored in the channel mask of the extensible fmt chunk. Each set bit assigns the next channel in the file to a speaker,
+//! in the order of the bits, e.g. ``FRONT_LEFT | FRONT_RIGHT | LOW_FREQUENCY`` means that channel 0 is front left, channel 1 is front right and channel 2 is the LFE channel.
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+use crate::chunks::fmt::default_channel_mask;
+
+/// A set of speaker positions, as stored in the ``dwChannelMask`` field of the extensible fmt chunk.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::ChannelLayout;
+///
+/// let layout = ChannelLayout::FRONT_LEFT | ChannelLayout::FRONT_RIGHT | ChannelLayout::LOW_FREQUENCY;
+/// assert_eq!(layout.n_channels(), 3);
+/// assert!(layout.contains(ChannelLayout::STEREO));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ChannelLayout(u32);
+
+impl ChannelLayout {
+    pub const FRONT_LEFT: ChannelLayout = ChannelLayout(0x1);
+    pub const FRONT_RIGHT: ChannelLayout = ChannelLayout(0x2);
+    pub const FRONT_CENTER: ChannelLayout = ChannelLayout(0x4);
+    pub const LOW_FREQUENCY: ChannelLayout = ChannelLayout(0x8);
+    pub const BACK_LEFT: ChannelLayout = ChannelLayout(0x10);
+    pub const BACK_RIGHT: ChannelLayout = ChannelLayout(0x20);
+    pub const FRONT_LEFT_OF_CENTER: ChannelLayout = ChannelLayout(0x40);
+    pub const FRONT_RIGHT_OF_CENTER: ChannelLayout = ChannelLayout(0x80);
+    pub const BACK_CENTER: ChannelLayout = ChannelLayout(0x100);
+    pub const SIDE_LEFT: ChannelLayout = ChannelLayout(0x200);
+    pub const SIDE_RIGHT: ChannelLayout = ChannelLayout(0x400);
+    pub const TOP_CENTER: ChannelLayout = ChannelLayout(0x800);
+    pub const TOP_FRONT_LEFT: ChannelLayout = ChannelLayout(0x1000);
+    pub const TOP_FRONT_CENTER: ChannelLayout = ChannelLayout(0x2000);
+    pub const TOP_FRONT_RIGHT: ChannelLayout = ChannelLayout(0x4000);
+    pub const TOP_BACK_LEFT: ChannelLayout = ChannelLayout(0x8000);
+    pub const TOP_BACK_CENTER: ChannelLayout = ChannelLayout(0x10000);
+    pub const TOP_BACK_RIGHT: ChannelLayout = ChannelLayout(0x20000);
+
+    /// Front centre.
+    pub const MONO: ChannelLayout = ChannelLayout(0x4);
+    /// Front left and front right.
+    pub const STEREO: ChannelLayout = ChannelLayout(0x3);
+    /// Front left, front right, back left and back right.
+    pub const QUAD: ChannelLayout = ChannelLayout(0x33);
+    /// Front left, front right, front centre, LFE, back left and back right.
+    pub const SURROUND_5_1: ChannelLayout = ChannelLayout(0x3F);
+    /// 5.1 plus side left and side right.
+    pub const SURROUND_7_1: ChannelLayout = ChannelLayout(0x63F);
+
+    const NAMES: [&'static str; 18] = [
+        "FRONT_LEFT",
+        "FRONT_RIGHT",
+        "FRONT_CENTER",
+        "LOW_FREQUENCY",
+        "BACK_LEFT",
+        "BACK_RIGHT",
+        "FRONT_LEFT_OF_CENTER",
+        "FRONT_RIGHT_OF_CENTER",
+        "BACK_CENTER",
+        "SIDE_LEFT",
+        "SIDE_RIGHT",
+        "TOP_CENTER",
+        "TOP_FRONT_LEFT",
+        "TOP_FRONT_CENTER",
+        "TOP_FRONT_RIGHT",
+        "TOP_BACK_LEFT",
+        "TOP_BACK_CENTER",
+        "TOP_BACK_RIGHT",
+    ];
+
+    /// Creates a layout from a raw channel mask.
+    pub const fn from_bits(bits: u32) -> Self {
+        ChannelLayout(bits)
+    }
+
+    /// Returns the raw channel mask.
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the layout with no speaker positions assigned.
+    pub const fn empty() -> Self {
+        ChannelLayout(0)
+    }
+
+    /// Returns the default layout used when writing the given number of channels, e.g. 5.1 for 6 channels.
+    pub const fn default_for(n_channels: u16) -> Self {
+        ChannelLayout(default_channel_mask(n_channels))
+    }
+
+    /// Returns true if no speaker positions are assigned.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every speaker position of ``other`` is in this layout.
+    pub const fn contains(&self, other: ChannelLayout) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the number of channels which are assigned a speaker position.
+    pub const fn n_channels(&self) -> u16 {
+        self.0.count_ones() as u16
+    }
+
+    /// Returns the speaker position of each assigned channel, in channel order.
+    pub fn speakers(&self) -> Vec<ChannelLayout> {
+        (0..32)
+            .map(|bit| 1 << bit)
+            .filter(|bit| self.0 & bit != 0)
+            .map(ChannelLayout)
+            .collect()
+    }
+}
+
+impl BitOr for ChannelLayout {
+    type Output = ChannelLayout;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        ChannelLayout(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ChannelLayout {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for ChannelLayout {
+    type Output = ChannelLayout;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        ChannelLayout(self.0 & rhs.0)
+    }
+}
+
+impl From<u32> for ChannelLayout {
+    fn from(bits: u32) -> Self {
+        ChannelLayout(bits)
+    }
+}
+
+impl From<ChannelLayout> for u32 {
+    fn from(layout: ChannelLayout) -> Self {
+        layout.0
+    }
+}
+
+impl Debug for ChannelLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ChannelLayout({})", self)
+    }
+}
+
+impl Display for ChannelLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "EMPTY");
+        }
+        let names: Vec<String> = self
+            .speakers()
+            .iter()
+            .map(|speaker| {
+                let bit = speaker.0.trailing_zeros() as usize;
+                match Self::NAMES.get(bit) {
+                    Some(name) => name.to_string(),
+                    None => format!("{:#x}", speaker.0),
+                }
+            })
+            .collect();
+        write!(f, "{}", names.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod channel_layout_tests {
+    use super::*;
+
+    #[test]
+    fn combine_and_inspect_layouts() {
+        let layout = ChannelLayout::STEREO | ChannelLayout::LOW_FREQUENCY;
+        assert_eq!(layout.bits(), 0xB);
+        assert_eq!(layout.n_channels(), 3);
+        assert!(layout.contains(ChannelLayout::FRONT_RIGHT));
+        assert!(!layout.contains(ChannelLayout::SURROUND_5_1));
+        assert_eq!(
+            layout.speakers(),
+            vec![
+                ChannelLayout::FRONT_LEFT,
+                ChannelLayout::FRONT_RIGHT,
+                ChannelLayout::LOW_FREQUENCY
+            ]
+        );
+        assert_eq!(
+            layout.to_string(),
+            "FRONT_LEFT | FRONT_RIGHT | LOW_FREQUENCY"
+        );
+        assert_eq!(ChannelLayout::default_for(6), ChannelLayout::SURROUND_5_1);
+        assert_eq!(ChannelLayout::empty().to_string(), "EMPTY");
+    }
+}
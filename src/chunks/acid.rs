@@ -0,0 +1,208 @@
+//! Contains the AcidChunk struct and its implementation.
+//! The acid chunk stores the tempo, number of beats and root note of a loop, as used by ACID and other loop based software.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, ACID},
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of the acid chunk in bytes, less the size of the ID and size field.
+pub const ACID_SIZE: usize = 24;
+
+/// The file is a one-shot, which is played once rather than looped.
+pub const ACID_ONE_SHOT: u32 = 0x01;
+/// The root note of the file is set.
+pub const ACID_ROOT_NOTE_SET: u32 = 0x02;
+/// The file is time stretched to match the project tempo.
+pub const ACID_STRETCH: u32 = 0x04;
+/// The file is read from disk rather than loaded into memory.
+pub const ACID_DISK_BASED: u32 = 0x08;
+
+/// The acid chunk of a wav file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct AcidChunk {
+    /// A combination of the ``ACID_*`` flags.
+    pub flags: u32,
+    /// The MIDI root note, where 60 is middle C.
+    pub root_note: u16,
+    pub reserved_a: u16,
+    pub reserved_b: f32,
+    pub n_beats: u32,
+    pub meter_denominator: u16,
+    pub meter_numerator: u16,
+    /// The tempo in beats per minute.
+    pub tempo: f32,
+}
+
+impl AcidChunk {
+    /// Creates a new AcidChunk for a stretchable loop in 4/4 time with the given tempo, number of beats and root note.
+    pub fn new(tempo: f32, n_beats: u32, root_note: u16) -> Self {
+        AcidChunk {
+            flags: ACID_ROOT_NOTE_SET | ACID_STRETCH,
+            root_note,
+            n_beats,
+            tempo,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if the file is a one-shot rather than a loop.
+    pub fn is_one_shot(&self) -> bool {
+        self.flags & ACID_ONE_SHOT != 0
+    }
+
+    /// Returns the root note if it is set.
+    pub fn root_note(&self) -> Option<u16> {
+        match self.flags & ACID_ROOT_NOTE_SET {
+            0 => None,
+            _ => Some(self.root_note),
+        }
+    }
+
+    /// Returns true if the file is time stretched to match the project tempo.
+    pub fn is_stretched(&self) -> bool {
+        self.flags & ACID_STRETCH != 0
+    }
+}
+
+impl Default for AcidChunk {
+    fn default() -> Self {
+        AcidChunk {
+            flags: 0,
+            root_note: 60,
+            reserved_a: 0x8000,
+            reserved_b: 0.0,
+            n_beats: 0,
+            meter_denominator: 4,
+            meter_numerator: 4,
+            tempo: 120.0,
+        }
+    }
+}
+
+impl Chunk for AcidChunk {
+    /// Returns the ID of the AcidChunk - "acid".
+    fn id(&self) -> &[u8; 4] {
+        &ACID
+    }
+
+    /// Returns the size of the AcidChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        ACID_SIZE as u32
+    }
+
+    /// Returns the full AcidChunk in bytes.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + ACID_SIZE);
+        bytes.extend_from_slice(&ACID);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&self.flags.to_ne_bytes());
+        bytes.extend_from_slice(&self.root_note.to_ne_bytes());
+        bytes.extend_from_slice(&self.reserved_a.to_ne_bytes());
+        bytes.extend_from_slice(&self.reserved_b.to_ne_bytes());
+        bytes.extend_from_slice(&self.n_beats.to_ne_bytes());
+        bytes.extend_from_slice(&self.meter_denominator.to_ne_bytes());
+        bytes.extend_from_slice(&self.meter_numerator.to_ne_bytes());
+        bytes.extend_from_slice(&self.tempo.to_ne_bytes());
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the AcidChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if (info.size as usize) < ACID_SIZE {
+            return Err(FormatError::InvalidAcidChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = [0; ACID_SIZE];
+        reader.read_exact(&mut bytes)?;
+
+        Ok(AcidChunk {
+            flags: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            root_note: u16::from_ne_bytes([bytes[4], bytes[5]]),
+            reserved_a: u16::from_ne_bytes([bytes[6], bytes[7]]),
+            reserved_b: f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            n_beats: u32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+            meter_denominator: u16::from_ne_bytes([bytes[16], bytes[17]]),
+            meter_numerator: u16::from_ne_bytes([bytes[18], bytes[19]]),
+            tempo: f32::from_ne_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]),
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for AcidChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}\n\t{} {}/{}\n\t{} {}",
+            "AcidChunk: ".white().bold().underline(),
+            "tempo:".green().bold(),
+            self.tempo.to_string().white(),
+            "n_beats:".green().bold(),
+            self.n_beats.to_string().white(),
+            "meter:".green().bold(),
+            self.meter_numerator.to_string().white(),
+            self.meter_denominator.to_string().white(),
+            "root_note:".green().bold(),
+            self.root_note.to_string().white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for AcidChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AcidChunk: tempo: {}, n_beats: {}, meter: {}/{}, root_note: {}",
+            self.tempo, self.n_beats, self.meter_numerator, self.meter_denominator, self.root_note
+        )
+    }
+}
+
+#[cfg(test)]
+mod acid_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let mut acid = AcidChunk::new(128.0, 8, 57);
+        acid.meter_numerator = 3;
+
+        let bytes = acid.as_bytes();
+        assert_eq!(bytes.len(), 8 + ACID_SIZE);
+        assert_eq!(&bytes[0..4], &ACID);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, acid.size() as u64);
+        let read_acid = AcidChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_acid, acid);
+        assert_eq!(read_acid.root_note(), Some(57));
+        assert!(read_acid.is_stretched());
+        assert!(!read_acid.is_one_shot());
+    }
+
+    #[test]
+    fn root_note_is_only_returned_when_set() {
+        let acid = AcidChunk {
+            flags: ACID_ONE_SHOT,
+            ..Default::default()
+        };
+        assert!(acid.is_one_shot());
+        assert_eq!(acid.root_note(), None);
+    }
+}
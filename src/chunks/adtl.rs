@@ -0,0 +1,398 @@
+//! Contains the AdtlChunk, LabeledText and Marker structs and their implementation.
+//! The adtl chunk is a LIST chunk of associated data, which attaches labels, notes and region lengths to the cue points of the cue chunk.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, CuePoint, ADTL, LABL, LIST, LTXT, NOTE},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of a ltxt sub-chunk without its text, less the size of the ID and size field.
+pub const LTXT_BASE_SIZE: usize = 20;
+
+/// The purpose ID used for ltxt sub-chunks which mark a region of the audio, "rgn ".
+pub const REGION_PURPOSE: [u8; 4] = *b"rgn ";
+
+/// A ltxt sub-chunk, which gives a cue point a length in sample frames and optionally some text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledText {
+    /// The ID of the cue point the text belongs to.
+    pub cue_point_id: u32,
+    /// The length of the region starting at the cue point, in sample frames.
+    pub sample_length: u32,
+    /// The purpose of the text, e.g. "rgn " for a region.
+    pub purpose: [u8; 4],
+    pub country: u16,
+    pub language: u16,
+    pub dialect: u16,
+    pub code_page: u16,
+    pub text: String,
+}
+
+impl LabeledText {
+    /// Creates a new region of the given length, in sample frames, for the cue point with the given ID.
+    pub fn new(cue_point_id: u32, sample_length: u32) -> Self {
+        LabeledText {
+            cue_point_id,
+            sample_length,
+            purpose: REGION_PURPOSE,
+            country: 0,
+            language: 0,
+            dialect: 0,
+            code_page: 0,
+            text: String::new(),
+        }
+    }
+}
+
+/// A cue point joined with the label, note and region length which the adtl chunk associates with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct Marker {
+    /// The ID of the cue point.
+    pub id: u32,
+    /// The sample frame, i.e. the sample index per channel, of the marker.
+    pub sample_offset: u32,
+    /// The name of the marker, stored in a labl sub-chunk.
+    pub label: Option<String>,
+    /// A comment about the marker, stored in a note sub-chunk.
+    pub note: Option<String>,
+    /// The length of the region starting at the marker in sample frames, stored in a ltxt sub-chunk.
+    pub length: Option<u32>,
+}
+
+impl Marker {
+    /// Creates a new marker with the given ID and sample frame, without a label, note or length.
+    pub fn new(id: u32, sample_offset: u32) -> Self {
+        Marker {
+            id,
+            sample_offset,
+            label: None,
+            note: None,
+            length: None,
+        }
+    }
+
+    /// Creates a new marker with the given ID, sample frame and label.
+    pub fn with_label(id: u32, sample_offset: u32, label: &str) -> Self {
+        Marker {
+            label: Some(label.to_string()),
+            ..Marker::new(id, sample_offset)
+        }
+    }
+
+    /// Returns the cue point of the marker.
+    pub fn cue_point(&self) -> CuePoint {
+        CuePoint::new(self.id, self.sample_offset)
+    }
+
+    /// Joins the given cue points with the labels, notes and region lengths of the adtl chunk.
+    pub fn from_cue_points(cue_points: &[CuePoint], adtl: &AdtlChunk) -> Vec<Marker> {
+        cue_points
+            .iter()
+            .map(|cue_point| Marker {
+                id: cue_point.id,
+                sample_offset: cue_point.sample_offset,
+                label: adtl.label(cue_point.id).map(str::to_string),
+                note: adtl.note(cue_point.id).map(str::to_string),
+                length: adtl
+                    .labeled_texts
+                    .iter()
+                    .find(|ltxt| ltxt.cue_point_id == cue_point.id)
+                    .map(|ltxt| ltxt.sample_length),
+            })
+            .collect()
+    }
+}
+
+/// The associated data list of a wav file, stored as a LIST chunk with the list type "adtl".
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct AdtlChunk {
+    /// The labl sub-chunks, as the cue point ID and the label.
+    pub labels: Vec<(u32, String)>,
+    /// The note sub-chunks, as the cue point ID and the note.
+    pub notes: Vec<(u32, String)>,
+    /// The ltxt sub-chunks.
+    pub labeled_texts: Vec<LabeledText>,
+}
+
+/// Returns the length of a text sub-chunk, i.e. the cue point ID followed by the null terminated text.
+fn text_size(text: &str) -> usize {
+    4 + text.len() + 1
+}
+
+/// Appends a sub-chunk with the given ID and payload to the bytes, followed by a padding byte if the size is odd.
+fn push_sub_chunk(bytes: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(id);
+    bytes.extend_from_slice(&(data.len() as u32).to_ne_bytes());
+    bytes.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        bytes.push(0);
+    }
+}
+
+/// Reads a null terminated string, ignoring anything after the terminator.
+fn read_text(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+impl AdtlChunk {
+    /// Creates an AdtlChunk containing the labels, notes and region lengths of the given markers.
+    pub fn from_markers(markers: &[Marker]) -> Self {
+        let mut adtl = AdtlChunk::default();
+        for marker in markers.iter() {
+            if let Some(label) = &marker.label {
+                adtl.labels.push((marker.id, label.clone()));
+            }
+            if let Some(note) = &marker.note {
+                adtl.notes.push((marker.id, note.clone()));
+            }
+            if let Some(length) = marker.length {
+                adtl.labeled_texts.push(LabeledText::new(marker.id, length));
+            }
+        }
+        adtl
+    }
+
+    /// Returns the label of the cue point with the given ID, if it has one.
+    pub fn label(&self, cue_point_id: u32) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(id, _)| *id == cue_point_id)
+            .map(|(_, label)| label.as_str())
+    }
+
+    /// Returns the note of the cue point with the given ID, if it has one.
+    pub fn note(&self, cue_point_id: u32) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(id, _)| *id == cue_point_id)
+            .map(|(_, note)| note.as_str())
+    }
+
+    /// Creates a new AdtlChunk from bytes, including the chunk ID and size field.
+    /// Unknown sub-chunks and sub-chunks which run past the end of the bytes are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> WaversResult<Self> {
+        if bytes.len() < 12 || bytes[8..12] != ADTL {
+            return Err(FormatError::InvalidAdtlChunk.into());
+        }
+        let mut adtl = AdtlChunk::default();
+        let mut i = 12;
+        while i + 8 <= bytes.len() {
+            let id = [bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]];
+            let size = u32::from_ne_bytes([bytes[i + 4], bytes[i + 5], bytes[i + 6], bytes[i + 7]])
+                as usize;
+            i += 8;
+            if i + size > bytes.len() {
+                break;
+            }
+            let data = &bytes[i..i + size];
+            // Sub-chunks with an odd size are followed by a padding byte
+            i += size + size % 2;
+
+            let read_u32 =
+                |offset: usize| u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+            let read_u16 =
+                |offset: usize| u16::from_ne_bytes(data[offset..offset + 2].try_into().unwrap());
+            match &id {
+                x if (x == &LABL || x == &NOTE) && size >= 4 => {
+                    let text = (read_u32(0), read_text(&data[4..]));
+                    match x == &LABL {
+                        true => adtl.labels.push(text),
+                        false => adtl.notes.push(text),
+                    }
+                }
+                x if x == &LTXT && size >= LTXT_BASE_SIZE => {
+                    adtl.labeled_texts.push(LabeledText {
+                        cue_point_id: read_u32(0),
+                        sample_length: read_u32(4),
+                        purpose: [data[8], data[9], data[10], data[11]],
+                        country: read_u16(12),
+                        language: read_u16(14),
+                        dialect: read_u16(16),
+                        code_page: read_u16(18),
+                        text: read_text(&data[LTXT_BASE_SIZE..]),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(adtl)
+    }
+}
+
+impl Chunk for AdtlChunk {
+    /// Returns the ID of the AdtlChunk - "LIST".
+    fn id(&self) -> &[u8; 4] {
+        &LIST
+    }
+
+    /// Returns the size of the AdtlChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        let padded = |size: usize| 8 + size + size % 2;
+        let texts: usize = self
+            .labels
+            .iter()
+            .chain(self.notes.iter())
+            .map(|(_, text)| padded(text_size(text)))
+            .sum();
+        let labeled_texts: usize = self
+            .labeled_texts
+            .iter()
+            .map(|ltxt| match ltxt.text.is_empty() {
+                true => padded(LTXT_BASE_SIZE),
+                false => padded(LTXT_BASE_SIZE + ltxt.text.len() + 1),
+            })
+            .sum();
+        (4 + texts + labeled_texts) as u32
+    }
+
+    /// Returns the full AdtlChunk in bytes.
+    /// The labels are written first, followed by the notes and the ltxt sub-chunks.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + self.size() as usize);
+        bytes.extend_from_slice(&LIST);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&ADTL);
+        for (sub_chunk_id, texts) in [(&LABL, &self.labels), (&NOTE, &self.notes)] {
+            for (cue_point_id, text) in texts.iter() {
+                let mut data = Vec::with_capacity(text_size(text));
+                data.extend_from_slice(&cue_point_id.to_ne_bytes());
+                data.extend_from_slice(text.as_bytes());
+                data.push(0);
+                push_sub_chunk(&mut bytes, sub_chunk_id, &data);
+            }
+        }
+        for ltxt in self.labeled_texts.iter() {
+            let mut data = Vec::with_capacity(LTXT_BASE_SIZE + ltxt.text.len() + 1);
+            data.extend_from_slice(&ltxt.cue_point_id.to_ne_bytes());
+            data.extend_from_slice(&ltxt.sample_length.to_ne_bytes());
+            data.extend_from_slice(&ltxt.purpose);
+            for value in [ltxt.country, ltxt.language, ltxt.dialect, ltxt.code_page] {
+                data.extend_from_slice(&value.to_ne_bytes());
+            }
+            if !ltxt.text.is_empty() {
+                data.extend_from_slice(ltxt.text.as_bytes());
+                data.push(0);
+            }
+            push_sub_chunk(&mut bytes, &LTXT, &data);
+        }
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the AdtlChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        reader.seek(SeekFrom::Start(info.offset as u64))?;
+        let mut bytes = alloc_box_buffer(8 + info.size as usize);
+        reader.read_exact(&mut bytes)?;
+        AdtlChunk::from_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for AdtlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", "AdtlChunk: ".white().bold().underline())?;
+        for (id, label) in self.labels.iter() {
+            write!(
+                f,
+                "\n\t{} {} {}",
+                "labl:".green().bold(),
+                id.to_string().white(),
+                label.white()
+            )?;
+        }
+        for (id, note) in self.notes.iter() {
+            write!(
+                f,
+                "\n\t{} {} {}",
+                "note:".green().bold(),
+                id.to_string().white(),
+                note.white()
+            )?;
+        }
+        for ltxt in self.labeled_texts.iter() {
+            write!(
+                f,
+                "\n\t{} {} {}",
+                "ltxt:".green().bold(),
+                ltxt.cue_point_id.to_string().white(),
+                ltxt.sample_length.to_string().white()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for AdtlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AdtlChunk:")?;
+        for (id, label) in self.labels.iter() {
+            write!(f, "\n\tlabl: {} {}", id, label)?;
+        }
+        for (id, note) in self.notes.iter() {
+            write!(f, "\n\tnote: {} {}", id, note)?;
+        }
+        for ltxt in self.labeled_texts.iter() {
+            write!(f, "\n\tltxt: {} {}", ltxt.cue_point_id, ltxt.sample_length)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod adtl_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let mut adtl = AdtlChunk {
+            labels: vec![(1, "Intro".to_string()), (2, "Verse".to_string())],
+            notes: vec![(2, "Needs another take".to_string())],
+            labeled_texts: vec![LabeledText::new(2, 48000)],
+        };
+        adtl.labeled_texts[0].text = "odd".to_string();
+
+        let bytes = adtl.as_bytes();
+        assert_eq!(bytes.len(), 8 + adtl.size() as usize);
+        assert_eq!(&bytes[0..4], &LIST);
+        assert_eq!(&bytes[8..12], &ADTL);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, adtl.size() as u64);
+        let read_adtl = AdtlChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_adtl, adtl);
+    }
+
+    #[test]
+    fn markers_join_cue_points_and_associated_data() {
+        let markers = vec![
+            Marker::with_label(1, 0, "Intro"),
+            Marker {
+                note: Some("Chorus".to_string()),
+                length: Some(1000),
+                ..Marker::new(2, 500)
+            },
+            Marker::new(3, 900),
+        ];
+        let cue_points: Vec<CuePoint> = markers.iter().map(Marker::cue_point).collect();
+        let adtl = AdtlChunk::from_markers(&markers);
+        assert_eq!(Marker::from_cue_points(&cue_points, &adtl), markers);
+    }
+}
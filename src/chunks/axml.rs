@@ -0,0 +1,112 @@
+//! Contains the AxmlChunk struct and its implementation.
+//! The axml chunk stores Audio Definition Model (ITU-R BS.2076) metadata as an XML document, describing the channels, objects and scenes of object-based audio.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, AXML},
+    core::alloc_box_buffer,
+    error::WaversResult,
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The axml chunk of a wav file. Contains the raw ADM XML payload.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct AxmlChunk {
+    pub xml: String,
+}
+
+impl AxmlChunk {
+    /// Creates a new AxmlChunk containing the given ADM XML document.
+    pub fn new(xml: &str) -> Self {
+        AxmlChunk {
+            xml: xml.to_string(),
+        }
+    }
+}
+
+impl Chunk for AxmlChunk {
+    /// Returns the ID of the AxmlChunk - "axml".
+    fn id(&self) -> &[u8; 4] {
+        &AXML
+    }
+
+    /// Returns the size of the AxmlChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        self.xml.len() as u32
+    }
+
+    /// Returns the full AxmlChunk in bytes, including a padding byte if the size is odd.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let size = self.size() as usize;
+        let mut bytes = Vec::with_capacity(8 + size + size % 2);
+        bytes.extend_from_slice(&AXML);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(self.xml.as_bytes());
+        bytes.resize(8 + size + size % 2, 0);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the AxmlChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        // Some writers zero pad the document, e.g. to reserve space for later edits
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(AxmlChunk {
+            xml: String::from_utf8_lossy(&bytes[..end]).to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for AxmlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{}",
+            "AxmlChunk: ".white().bold().underline(),
+            self.xml.white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for AxmlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AxmlChunk: {}", self.xml)
+    }
+}
+
+#[cfg(test)]
+mod axml_tests {
+    use super::*;
+
+    const XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ebuCoreMain><coreMetadata><format><audioFormatExtended><audioProgramme audioProgrammeID=\"APR_1001\" audioProgrammeName=\"Main\"/></audioFormatExtended></format></coreMetadata></ebuCoreMain>";
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let axml = AxmlChunk::new(XML);
+        let bytes = axml.as_bytes();
+        assert_eq!(bytes.len() % 2, 0);
+        assert_eq!(&bytes[0..4], &AXML);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, axml.size() as u64);
+        let read_axml = AxmlChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_axml, axml);
+    }
+}
@@ -0,0 +1,319 @@
+//! Contains the BextChunk struct and its implementation.
+//! The bext chunk is the Broadcast Wave Format (EBU Tech 3285) extension chunk and stores information about the origin of the audio.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+use std::time::Duration;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, BEXT},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of the bext chunk without the coding history, less the size of the ID and size field.
+pub const BEXT_BASE_SIZE: usize = 602;
+
+const DESCRIPTION_SIZE: usize = 256;
+const ORIGINATOR_SIZE: usize = 32;
+const ORIGINATOR_REFERENCE_SIZE: usize = 32;
+const ORIGINATION_DATE_SIZE: usize = 10;
+const ORIGINATION_TIME_SIZE: usize = 8;
+const UMID_SIZE: usize = 64;
+const RESERVED_SIZE: usize = 180;
+
+/// The bext chunk of a Broadcast Wave file.
+///
+/// The text fields are fixed width in the file and are truncated when written if they are too long.
+/// The loudness values are stored as the measured value multiplied by 100, e.g. -23.0 LUFS is stored as -2300.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct BextChunk {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    /// The date of creation in the format "yyyy-mm-dd".
+    pub origination_date: String,
+    /// The time of creation in the format "hh:mm:ss".
+    pub origination_time: String,
+    /// The number of samples since midnight of the first sample.
+    pub time_reference: u64,
+    pub version: u16,
+    pub umid: [u8; UMID_SIZE],
+    pub loudness_value: i16,
+    pub loudness_range: i16,
+    pub max_true_peak_level: i16,
+    pub max_momentary_loudness: i16,
+    pub max_short_term_loudness: i16,
+    pub coding_history: String,
+}
+
+impl BextChunk {
+    /// Creates a new, version 2, BextChunk with the given description, originator and time reference.
+    /// The remaining fields are left empty.
+    pub fn new(description: &str, originator: &str, time_reference: u64) -> Self {
+        BextChunk {
+            description: description.to_string(),
+            originator: originator.to_string(),
+            time_reference,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the time since midnight of the first sample, converting the time reference using the given sample rate.
+    pub fn time_of_day(&self, sample_rate: i32) -> Duration {
+        time_reference_to_time_of_day(self.time_reference, sample_rate)
+    }
+
+    /// Sets the time reference to the given time since midnight, rounded to the nearest sample at the given sample rate.
+    pub fn set_time_of_day(&mut self, time_of_day: Duration, sample_rate: i32) {
+        let sample_rate = sample_rate.max(0) as u128;
+        self.time_reference =
+            ((time_of_day.as_nanos() * sample_rate + 500_000_000) / 1_000_000_000) as u64;
+    }
+}
+
+/// Converts a time reference, i.e. a number of samples since midnight, to the time since midnight at the given sample rate.
+pub(crate) fn time_reference_to_time_of_day(time_reference: u64, sample_rate: i32) -> Duration {
+    let sample_rate = sample_rate.max(1) as u64;
+    let seconds = time_reference / sample_rate;
+    let remainder = time_reference % sample_rate;
+    Duration::new(seconds, (remainder * 1_000_000_000 / sample_rate) as u32)
+}
+
+impl Default for BextChunk {
+    fn default() -> Self {
+        BextChunk {
+            description: String::new(),
+            originator: String::new(),
+            originator_reference: String::new(),
+            origination_date: String::new(),
+            origination_time: String::new(),
+            time_reference: 0,
+            version: 2,
+            umid: [0; UMID_SIZE],
+            loudness_value: 0,
+            loudness_range: 0,
+            max_true_peak_level: 0,
+            max_momentary_loudness: 0,
+            max_short_term_loudness: 0,
+            coding_history: String::new(),
+        }
+    }
+}
+
+/// Appends the string to the bytes, truncated or zero padded to the given width.
+fn extend_fixed_width(bytes: &mut Vec<u8>, value: &str, width: usize) {
+    let value = value.as_bytes();
+    let n_bytes = value.len().min(width);
+    bytes.extend_from_slice(&value[..n_bytes]);
+    bytes.resize(bytes.len() + width - n_bytes, 0);
+}
+
+/// Decodes a zero padded text field.
+fn read_fixed_width(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+impl Chunk for BextChunk {
+    /// Returns the ID of the BextChunk - "bext".
+    fn id(&self) -> &[u8; 4] {
+        &BEXT
+    }
+
+    /// Returns the size of the BextChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        (BEXT_BASE_SIZE + self.coding_history.len()) as u32
+    }
+
+    /// Returns the full BextChunk in bytes, including a padding byte if the size is odd.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let size = self.size() as usize;
+        let mut bytes = Vec::with_capacity(8 + size + size % 2);
+        bytes.extend_from_slice(&BEXT);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        extend_fixed_width(&mut bytes, &self.description, DESCRIPTION_SIZE);
+        extend_fixed_width(&mut bytes, &self.originator, ORIGINATOR_SIZE);
+        extend_fixed_width(
+            &mut bytes,
+            &self.originator_reference,
+            ORIGINATOR_REFERENCE_SIZE,
+        );
+        extend_fixed_width(&mut bytes, &self.origination_date, ORIGINATION_DATE_SIZE);
+        extend_fixed_width(&mut bytes, &self.origination_time, ORIGINATION_TIME_SIZE);
+        bytes.extend_from_slice(&(self.time_reference as u32).to_ne_bytes()); // low 32 bits
+        bytes.extend_from_slice(&((self.time_reference >> 32) as u32).to_ne_bytes()); // high 32 bits
+        bytes.extend_from_slice(&self.version.to_ne_bytes());
+        bytes.extend_from_slice(&self.umid);
+        for loudness in [
+            self.loudness_value,
+            self.loudness_range,
+            self.max_true_peak_level,
+            self.max_momentary_loudness,
+            self.max_short_term_loudness,
+        ] {
+            bytes.extend_from_slice(&loudness.to_ne_bytes());
+        }
+        bytes.resize(bytes.len() + RESERVED_SIZE, 0);
+        bytes.extend_from_slice(self.coding_history.as_bytes());
+        bytes.resize(8 + size + size % 2, 0);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the BextChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if (info.size as usize) < BEXT_BASE_SIZE {
+            return Err(FormatError::InvalidBextChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        let mut offset = 0;
+        let mut next = |n_bytes: usize| {
+            let field = &bytes[offset..offset + n_bytes];
+            offset += n_bytes;
+            field
+        };
+        let description = read_fixed_width(next(DESCRIPTION_SIZE));
+        let originator = read_fixed_width(next(ORIGINATOR_SIZE));
+        let originator_reference = read_fixed_width(next(ORIGINATOR_REFERENCE_SIZE));
+        let origination_date = read_fixed_width(next(ORIGINATION_DATE_SIZE));
+        let origination_time = read_fixed_width(next(ORIGINATION_TIME_SIZE));
+        let time_reference_low = u32::from_ne_bytes(next(4).try_into().unwrap()) as u64;
+        let time_reference_high = u32::from_ne_bytes(next(4).try_into().unwrap()) as u64;
+        let version = u16::from_ne_bytes(next(2).try_into().unwrap());
+        let umid: [u8; UMID_SIZE] = next(UMID_SIZE).try_into().unwrap();
+        let mut next_i16 = || i16::from_ne_bytes(next(2).try_into().unwrap());
+        let loudness_value = next_i16();
+        let loudness_range = next_i16();
+        let max_true_peak_level = next_i16();
+        let max_momentary_loudness = next_i16();
+        let max_short_term_loudness = next_i16();
+        next(RESERVED_SIZE);
+        let coding_history = read_fixed_width(&bytes[BEXT_BASE_SIZE..]);
+
+        Ok(BextChunk {
+            description,
+            originator,
+            originator_reference,
+            origination_date,
+            origination_time,
+            time_reference: time_reference_high << 32 | time_reference_low,
+            version,
+            umid,
+            loudness_value,
+            loudness_range,
+            max_true_peak_level,
+            max_momentary_loudness,
+            max_short_term_loudness,
+            coding_history,
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for BextChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}\n\t{} {}\n\t{} {} {}\n\t{} {}\n\t{} {}",
+            "BextChunk: ".white().bold().underline(),
+            "description:".green().bold(),
+            self.description.white(),
+            "originator:".green().bold(),
+            self.originator.white(),
+            "originator_reference:".green().bold(),
+            self.originator_reference.white(),
+            "origination:".green().bold(),
+            self.origination_date.white(),
+            self.origination_time.white(),
+            "time_reference:".green().bold(),
+            self.time_reference.to_string().white(),
+            "version:".green().bold(),
+            self.version.to_string().white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for BextChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BextChunk: description: {}, originator: {}, originator_reference: {}, origination: {} {}, time_reference: {}, version: {}",
+            self.description,
+            self.originator,
+            self.originator_reference,
+            self.origination_date,
+            self.origination_time,
+            self.time_reference,
+            self.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod bext_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let mut bext = BextChunk::new("A test recording", "wavers", 5_000_000_000);
+        bext.origination_date = "2024-01-31".to_string();
+        bext.origination_time = "12:30:00".to_string();
+        bext.loudness_value = -2300;
+        bext.max_true_peak_level = -100;
+        bext.coding_history = "A=PCM,F=48000,W=24,M=mono,T=wavers\r\n".to_string();
+
+        let bytes = bext.as_bytes();
+        assert_eq!(bytes.len(), 8 + BEXT_BASE_SIZE + bext.coding_history.len());
+        assert_eq!(&bytes[0..4], &BEXT);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, bext.size() as u64);
+        let read_bext = BextChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_bext, bext);
+    }
+
+    #[test]
+    fn time_reference_converts_to_and_from_time_of_day() {
+        // 10:00:00.5 at 48kHz
+        let mut bext = BextChunk::new("", "", 36_000 * 48000 + 24000);
+        assert_eq!(bext.time_of_day(48000), Duration::from_millis(36_000_500));
+
+        bext.set_time_of_day(Duration::from_secs(1), 44100);
+        assert_eq!(bext.time_reference, 44100);
+        // Times between samples are rounded to the nearest sample
+        bext.set_time_of_day(Duration::from_micros(1_000_015), 44100);
+        assert_eq!(bext.time_reference, 44101);
+        assert_eq!(bext.time_of_day(44100).as_micros(), 1_000_022);
+    }
+
+    #[test]
+    fn long_fields_are_truncated_and_odd_sizes_padded() {
+        let mut bext = BextChunk::new(&"a".repeat(300), "wavers", 0);
+        bext.coding_history = "odd".to_string();
+
+        let bytes = bext.as_bytes();
+        assert_eq!(bext.size() as usize, BEXT_BASE_SIZE + 3);
+        assert_eq!(bytes.len(), 8 + BEXT_BASE_SIZE + 4);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, bext.size() as u64);
+        let read_bext = BextChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_bext.description, "a".repeat(DESCRIPTION_SIZE));
+        assert_eq!(read_bext.coding_history, "odd");
+    }
+}
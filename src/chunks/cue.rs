@@ -0,0 +1,194 @@
+//! Contains the CueChunk and CuePoint structs and their implementation.
+//! The cue chunk stores markers, each identifying a sample frame in the data chunk.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, CUE, DATA},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of a single cue point in bytes.
+pub const CUE_POINT_SIZE: usize = 24;
+
+/// A single cue point, or marker, in a wav file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct CuePoint {
+    /// The unique ID of the cue point, used by other chunks such as ``adtl`` to refer to it.
+    pub id: u32,
+    /// The position of the cue point in the play order. For files without a playlist this is the same as the sample offset.
+    pub position: u32,
+    /// The ID of the chunk containing the cue point, "data" for files without a wave list.
+    pub data_chunk_id: [u8; 4],
+    pub chunk_start: u32,
+    pub block_start: u32,
+    /// The sample frame, i.e. the sample index per channel, of the cue point.
+    pub sample_offset: u32,
+}
+
+impl CuePoint {
+    /// Creates a new cue point with the given ID at the given sample frame of the data chunk.
+    pub fn new(id: u32, sample_offset: u32) -> Self {
+        CuePoint {
+            id,
+            position: sample_offset,
+            data_chunk_id: DATA,
+            chunk_start: 0,
+            block_start: 0,
+            sample_offset,
+        }
+    }
+
+    /// Returns the cue point in bytes.
+    pub fn as_bytes(&self) -> [u8; CUE_POINT_SIZE] {
+        let mut bytes = [0; CUE_POINT_SIZE];
+        bytes[0..4].copy_from_slice(&self.id.to_ne_bytes());
+        bytes[4..8].copy_from_slice(&self.position.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.data_chunk_id);
+        bytes[12..16].copy_from_slice(&self.chunk_start.to_ne_bytes());
+        bytes[16..20].copy_from_slice(&self.block_start.to_ne_bytes());
+        bytes[20..24].copy_from_slice(&self.sample_offset.to_ne_bytes());
+        bytes
+    }
+
+    /// Creates a cue point from bytes.
+    pub fn from_bytes(bytes: [u8; CUE_POINT_SIZE]) -> Self {
+        let read_u32 = |offset: usize| {
+            u32::from_ne_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+        CuePoint {
+            id: read_u32(0),
+            position: read_u32(4),
+            data_chunk_id: [bytes[8], bytes[9], bytes[10], bytes[11]],
+            chunk_start: read_u32(12),
+            block_start: read_u32(16),
+            sample_offset: read_u32(20),
+        }
+    }
+}
+
+/// The cue chunk of a wav file. Contains a list of cue points.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct CueChunk {
+    pub cue_points: Vec<CuePoint>,
+}
+
+impl CueChunk {
+    /// Creates a new CueChunk containing the given cue points.
+    pub fn new(cue_points: Vec<CuePoint>) -> Self {
+        CueChunk { cue_points }
+    }
+}
+
+impl Chunk for CueChunk {
+    /// Returns the ID of the CueChunk - "cue ".
+    fn id(&self) -> &[u8; 4] {
+        &CUE
+    }
+
+    /// Returns the size of the CueChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        (4 + CUE_POINT_SIZE * self.cue_points.len()) as u32
+    }
+
+    /// Returns the full CueChunk in bytes.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + self.size() as usize);
+        bytes.extend_from_slice(&CUE);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&(self.cue_points.len() as u32).to_ne_bytes());
+        for cue_point in self.cue_points.iter() {
+            bytes.extend_from_slice(&cue_point.as_bytes());
+        }
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the CueChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if info.size < 4 {
+            return Err(FormatError::InvalidCueChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        let n_cue_points = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let cue_points = bytes[4..]
+            .chunks_exact(CUE_POINT_SIZE)
+            .take(n_cue_points as usize)
+            .map(|cue_point| CuePoint::from_bytes(cue_point.try_into().unwrap()))
+            .collect();
+        Ok(CueChunk { cue_points })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for CueChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", "CueChunk: ".white().bold().underline())?;
+        for cue_point in self.cue_points.iter() {
+            write!(
+                f,
+                "\n\t{} {} {} {}",
+                "id:".green().bold(),
+                cue_point.id.to_string().white(),
+                "sample_offset:".green().bold(),
+                cue_point.sample_offset.to_string().white()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for CueChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CueChunk:")?;
+        for cue_point in self.cue_points.iter() {
+            write!(
+                f,
+                "\n\tid: {} sample_offset: {}",
+                cue_point.id, cue_point.sample_offset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod cue_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let cue = CueChunk::new(vec![CuePoint::new(1, 0), CuePoint::new(2, 16000)]);
+        let bytes = cue.as_bytes();
+        assert_eq!(bytes.len(), 8 + 4 + 2 * CUE_POINT_SIZE);
+        assert_eq!(&bytes[0..4], &CUE);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, cue.size() as u64);
+        let read_cue = CueChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_cue, cue);
+        assert_eq!(read_cue.cue_points[1].data_chunk_id, DATA);
+    }
+}
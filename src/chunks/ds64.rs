@@ -0,0 +1,177 @@
+//! Contains the Ds64Chunk struct and its implementation.
+//! The ds64 chunk is the first chunk of an RF64 or BW64 file and stores the 64-bit sizes of the chunks whose 32-bit size fields are set to ``RF64_PLACEHOLDER_SIZE``.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, DATA, DS64},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The value of a 32-bit size field whose actual size is stored in the ds64 chunk.
+pub const RF64_PLACEHOLDER_SIZE: u32 = 0xFFFFFFFF;
+
+/// The size of the ds64 chunk without any table entries, less the size of the ID and size field.
+pub const DS64_BASE_SIZE: usize = 28;
+
+const DS64_TABLE_ENTRY_SIZE: usize = 12;
+
+/// The ds64 chunk of an RF64 or BW64 wav file.
+/// Contains the 64-bit size of the RIFF and data chunks, the number of samples per channel and a table of 64-bit sizes for any other large chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct Ds64Chunk {
+    pub riff_size: u64,
+    pub data_size: u64,
+    pub sample_count: u64,
+    pub table: Vec<([u8; 4], u64)>,
+}
+
+impl Ds64Chunk {
+    /// Creates a new Ds64Chunk with the given sizes and an empty table.
+    pub fn new(riff_size: u64, data_size: u64, sample_count: u64) -> Self {
+        Self {
+            riff_size,
+            data_size,
+            sample_count,
+            table: Vec::new(),
+        }
+    }
+
+    /// Returns the 64-bit size of the chunk with the given ID, if it is stored in the ds64 chunk.
+    pub fn chunk_size(&self, id: &[u8; 4]) -> Option<u64> {
+        if id == &DATA {
+            return Some(self.data_size);
+        }
+        self.table
+            .iter()
+            .find(|(table_id, _)| table_id == id)
+            .map(|(_, size)| *size)
+    }
+}
+
+impl Chunk for Ds64Chunk {
+    /// Returns the ID of the Ds64Chunk - "ds64".
+    fn id(&self) -> &[u8; 4] {
+        &DS64
+    }
+
+    /// Returns the size of the Ds64Chunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        (DS64_BASE_SIZE + DS64_TABLE_ENTRY_SIZE * self.table.len()) as u32
+    }
+
+    /// Returns the full Ds64Chunk in bytes.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + self.size() as usize);
+        bytes.extend_from_slice(&DS64);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&self.riff_size.to_ne_bytes());
+        bytes.extend_from_slice(&self.data_size.to_ne_bytes());
+        bytes.extend_from_slice(&self.sample_count.to_ne_bytes());
+        bytes.extend_from_slice(&(self.table.len() as u32).to_ne_bytes());
+        for (id, size) in self.table.iter() {
+            bytes.extend_from_slice(id);
+            bytes.extend_from_slice(&size.to_ne_bytes());
+        }
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the Ds64Chunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if (info.size as usize) < DS64_BASE_SIZE {
+            return Err(FormatError::InvalidDs64ChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        let read_u64 = |offset: usize| {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_ne_bytes(buf)
+        };
+        let mut ds64 = Ds64Chunk::new(read_u64(0), read_u64(8), read_u64(16));
+
+        let table_length = u32::from_ne_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]);
+        for entry in bytes[DS64_BASE_SIZE..]
+            .chunks_exact(DS64_TABLE_ENTRY_SIZE)
+            .take(table_length as usize)
+        {
+            let mut id = [0; 4];
+            id.copy_from_slice(&entry[0..4]);
+            let mut size = [0; 8];
+            size.copy_from_slice(&entry[4..12]);
+            ds64.table.push((id, u64::from_ne_bytes(size)));
+        }
+        Ok(ds64)
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for Ds64Chunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}\n\t{} {}\n\t{} {}",
+            "Ds64Chunk: ".white().bold().underline(),
+            "riff_size:".green().bold(),
+            self.riff_size.to_string().white(),
+            "data_size:".green().bold(),
+            self.data_size.to_string().white(),
+            "sample_count:".green().bold(),
+            self.sample_count.to_string().white(),
+            "table_length:".green().bold(),
+            self.table.len().to_string().white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for Ds64Chunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Ds64Chunk: riff_size: {}, data_size: {}, sample_count: {}, table_length: {}",
+            self.riff_size,
+            self.data_size,
+            self.sample_count,
+            self.table.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod ds64_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let mut ds64 = Ds64Chunk::new(5_000_000_036, 5_000_000_000, 2_500_000_000);
+        ds64.table.push((*b"JUNK", 4_294_967_296));
+
+        let bytes = ds64.as_bytes();
+        assert_eq!(bytes.len(), 8 + DS64_BASE_SIZE + 12);
+        assert_eq!(&bytes[0..4], &DS64);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, ds64.size() as u64);
+        let read_ds64 = Ds64Chunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_ds64, ds64);
+        assert_eq!(read_ds64.chunk_size(&DATA), Some(5_000_000_000));
+        assert_eq!(read_ds64.chunk_size(b"JUNK"), Some(4_294_967_296));
+        assert_eq!(read_ds64.chunk_size(b"LIST"), None);
+    }
+}
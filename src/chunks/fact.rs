@@ -13,6 +13,9 @@ use crate::{
     ReadSeek,
 };
 
+/// The size of the fact chunk in bytes, less the size of the ID and size field.
+pub const FACT_SIZE: usize = 4;
+
 /// The fact chunk of a wav file. Contains a single field, ``num_samples``. This field is the number of samples in the wav file per channel.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
@@ -36,14 +39,14 @@ impl Chunk for FactChunk {
 
     /// Returns the size of the FactChunk in bytes less the size of the ID and size field itself.
     fn size(&self) -> u32 {
-        4
+        FACT_SIZE as u32
     }
 
     /// Returns the full FactChunk in bytes.
     fn as_bytes(&self) -> Box<[u8]> {
-        let mut buf = [0; 12];
+        let mut buf = [0; 8 + FACT_SIZE];
         buf[0..4].copy_from_slice(&FACT);
-        buf[4..8].copy_from_slice(&4u32.to_ne_bytes());
+        buf[4..8].copy_from_slice(&self.size().to_ne_bytes());
         buf[8..12].copy_from_slice(&self.num_samples.to_ne_bytes());
         Box::new(buf)
     }
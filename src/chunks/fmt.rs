@@ -2,8 +2,8 @@
 use std::{fmt::Display, io::SeekFrom};
 
 use crate::{
-    chunks::{Chunk, FACT, FMT},
-    error::FormatError,
+    chunks::{Chunk, FMT},
+    error::{FormatError, WaversError},
     log,
     wav_type::{format_info_to_wav_type, wav_type_to_format_info, FormatCode, WavType},
     ReadSeek, WaversResult,
@@ -133,89 +133,111 @@ impl FmtChunk {
         bytes
     }
 
-    fn from_base_bytes(bytes: [u8; FMT_SIZE_BASE_SIZE]) -> Self {
-        let mut buf: [u8; FMT_SIZE_EXTENDED_SIZE] = [0; FMT_SIZE_EXTENDED_SIZE];
+    fn from_base_bytes(bytes: [u8; FMT_SIZE_BASE_SIZE]) -> WaversResult<Self> {
+        let format = FormatCode::try_from(u16::from_ne_bytes([bytes[0], bytes[1]]))?;
+        let bits_per_sample = u16::from_ne_bytes([bytes[14], bytes[15]]);
 
-        let mut info_buf: [u8; 2] = [0; 2];
-        info_buf.copy_from_slice(&bytes[0..2]);
-        let format = FormatCode::try_from(u16::from_ne_bytes(info_buf)).unwrap();
-
-        info_buf.copy_from_slice(&bytes[14..16]);
-        let bits_per_sample = u16::from_ne_bytes(info_buf);
-
-        buf[0..FMT_SIZE_BASE_SIZE].copy_from_slice(&bytes);
-        buf[FMT_SIZE_BASE_SIZE..FMT_SIZE_EXTENDED_SIZE]
-            .copy_from_slice(&ExtFmtChunkInfo::default_bytes(format, bits_per_sample));
-        FmtChunk::from_extended_bytes(buf)
+        Ok(FmtChunk {
+            format,
+            channels: u16::from_ne_bytes([bytes[2], bytes[3]]),
+            sample_rate: i32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            byte_rate: i32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            block_align: u16::from_ne_bytes([bytes[12], bytes[13]]),
+            bits_per_sample,
+            ext_fmt_chunk: ExtFmtChunkInfo::new(CbSize::Base, bits_per_sample, 0, format),
+        })
     }
 
-    fn from_cb_bytes(bytes: [u8; FMT_CB_SIZE]) -> Self {
-        let mut buf: [u8; FMT_SIZE_EXTENDED_SIZE] = [0; FMT_SIZE_EXTENDED_SIZE];
-        buf[0..FMT_CB_SIZE].copy_from_slice(&bytes);
-        let mut info_buf: [u8; 2] = [0; 2];
-
-        info_buf.copy_from_slice(&bytes[0..2]);
-
-        let format = FormatCode::try_from(u16::from_ne_bytes(info_buf)).unwrap();
-
-        info_buf.copy_from_slice(&bytes[14..16]);
-        let bits_per_sample = u16::from_ne_bytes(info_buf);
-        buf[FMT_CB_SIZE - 2..FMT_SIZE_EXTENDED_SIZE]
-            .copy_from_slice(&ExtFmtChunkInfo::default_bytes(format, bits_per_sample));
-        FmtChunk::from_extended_bytes(buf)
+    fn from_cb_bytes(bytes: [u8; FMT_CB_SIZE]) -> WaversResult<Self> {
+        // The cbSize field is ignored, the chunk size has already been used to decide how much of the chunk to read
+        let mut base_bytes: [u8; FMT_SIZE_BASE_SIZE] = [0; FMT_SIZE_BASE_SIZE];
+        base_bytes.copy_from_slice(&bytes[0..FMT_SIZE_BASE_SIZE]);
+        FmtChunk::from_base_bytes(base_bytes)
     }
 
-    fn from_extended_bytes(bytes: [u8; FMT_SIZE_EXTENDED_SIZE]) -> Self {
-        unsafe { std::mem::transmute_copy::<[u8; FMT_SIZE_EXTENDED_SIZE], FmtChunk>(&bytes) }
+    fn from_extended_bytes(bytes: [u8; FMT_SIZE_EXTENDED_SIZE]) -> WaversResult<Self> {
+        let mut base_bytes: [u8; FMT_SIZE_BASE_SIZE] = [0; FMT_SIZE_BASE_SIZE];
+        base_bytes.copy_from_slice(&bytes[0..FMT_SIZE_BASE_SIZE]);
+        let mut fmt_chunk = FmtChunk::from_base_bytes(base_bytes)?;
+
+        let mut ext_bytes: [u8; 24] = [0; 24];
+        ext_bytes.copy_from_slice(&bytes[FMT_SIZE_BASE_SIZE..FMT_SIZE_EXTENDED_SIZE]);
+        fmt_chunk.ext_fmt_chunk = ExtFmtChunkInfo::from_bytes(ext_bytes)?;
+        Ok(fmt_chunk)
     }
 
     #[allow(unused)]
     // This function is only used in the tests
     pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        match FmtChunk::try_from_bytes(bytes) {
+            Ok(fmt_chunk) => fmt_chunk,
+            Err(e) => panic!("Invalid fmt chunk: {}", e),
+        }
+    }
+
+    /// Parses a fmt chunk of 16, 18 or 40 bytes, returning an error if the size, format code or cbSize field is invalid.
+    pub(crate) fn try_from_bytes(bytes: &[u8]) -> WaversResult<Self> {
         match bytes.len() {
             FMT_SIZE_BASE_SIZE => FmtChunk::from_base_bytes(bytes.try_into().unwrap()),
             FMT_CB_SIZE => FmtChunk::from_cb_bytes(bytes.try_into().unwrap()),
             FMT_SIZE_EXTENDED_SIZE => FmtChunk::from_extended_bytes(bytes.try_into().unwrap()),
-            _ => panic!("Invalid fmt chunk size: {}", bytes.len()),
+            _ => Err(FormatError::InvalidFmtChunkSize(bytes.len()).into()),
         }
     }
 }
 
+/// Returns the default speaker positions for a given number of channels, used when writing files in the extensible format.
+/// Returns 0 (no speaker positions) if there is no common layout for the number of channels.
+pub const fn default_channel_mask(n_channels: u16) -> u32 {
+    match n_channels {
+        1 => 0x4,   // front centre
+        2 => 0x3,   // front left, front right
+        3 => 0x7,   // front left, front right, front centre
+        4 => 0x33,  // front left, front right, back left, back right
+        5 => 0x37,  // quad + front centre
+        6 => 0x3F,  // 5.1
+        7 => 0x13F, // 5.1 + back centre
+        8 => 0x63F, // 7.1
+        _ => 0,
+    }
+}
+
 impl Chunk for FmtChunk {
     fn id(&self) -> &[u8; 4] {
         &FMT
     }
 
     fn size(&self) -> u32 {
-        match self.is_extended_format() {
-            true => match self.ext_fmt_chunk.cb_size {
+        match self.format {
+            FormatCode::WAV_FORMAT_PCM | FormatCode::WAV_FORMAT_IEEE_FLOAT => {
+                FMT_SIZE_BASE_SIZE as u32
+            }
+            FormatCode::WAVE_FORMAT_EXTENSIBLE => match self.ext_fmt_chunk.cb_size {
                 CbSize::Base => FMT_CB_SIZE as u32,
                 CbSize::Extended => FMT_SIZE_EXTENDED_SIZE as u32,
             },
-            false => FMT_SIZE_BASE_SIZE as u32,
+            // Non-PCM formats must include the cbSize field, even if there is no extension
+            _ => FMT_CB_SIZE as u32,
         }
     }
 
     fn as_bytes(&self) -> Box<[u8]> {
-        match self.is_extended_format() {
-            true => match self.ext_fmt_chunk.cb_size {
-                CbSize::Base => {
-                    let mut bytes: [u8; FMT_CB_SIZE + 8] = [0; FMT_CB_SIZE + 8];
-                    bytes[0..4].copy_from_slice(&FACT);
-                    bytes[4..8].copy_from_slice(&self.size().to_ne_bytes());
-                    bytes[8..FMT_CB_SIZE + 8].copy_from_slice(&self.cb_bytes());
-                    Box::new(bytes)
-                }
-                CbSize::Extended => {
-                    let mut bytes: [u8; FMT_SIZE_EXTENDED_SIZE + 8] =
-                        [0; FMT_SIZE_EXTENDED_SIZE + 8];
-                    bytes[0..4].copy_from_slice(&FACT);
-                    bytes[4..8].copy_from_slice(&self.size().to_ne_bytes());
-                    bytes[8..FMT_SIZE_EXTENDED_SIZE + 8].copy_from_slice(&self.extended_bytes());
-                    Box::new(bytes)
-                }
-            },
-            false => {
+        match self.size() as usize {
+            FMT_CB_SIZE => {
+                let mut bytes: [u8; FMT_CB_SIZE + 8] = [0; FMT_CB_SIZE + 8];
+                bytes[0..4].copy_from_slice(&FMT);
+                bytes[4..8].copy_from_slice(&self.size().to_ne_bytes());
+                bytes[8..FMT_CB_SIZE + 8].copy_from_slice(&self.cb_bytes());
+                Box::new(bytes)
+            }
+            FMT_SIZE_EXTENDED_SIZE => {
+                let mut bytes: [u8; FMT_SIZE_EXTENDED_SIZE + 8] = [0; FMT_SIZE_EXTENDED_SIZE + 8];
+                bytes[0..4].copy_from_slice(&FMT);
+                bytes[4..8].copy_from_slice(&self.size().to_ne_bytes());
+                bytes[8..FMT_SIZE_EXTENDED_SIZE + 8].copy_from_slice(&self.extended_bytes());
+                Box::new(bytes)
+            }
+            _ => {
                 let mut bytes: [u8; FMT_SIZE_BASE_SIZE + 8] = [0; FMT_SIZE_BASE_SIZE + 8];
                 bytes[0..4].copy_from_slice(&FMT);
                 bytes[4..8].copy_from_slice(&self.size().to_ne_bytes());
@@ -246,27 +268,52 @@ impl Chunk for FmtChunk {
             FormatCode::WAV_FORMAT_PCM => {
                 let mut fmt_buf: [u8; FMT_SIZE_BASE_SIZE] = [0; FMT_SIZE_BASE_SIZE];
                 reader.read_exact(&mut fmt_buf)?;
-                FmtChunk::from_base_bytes(fmt_buf)
+                FmtChunk::from_base_bytes(fmt_buf)?
             }
             FormatCode::WAV_FORMAT_IEEE_FLOAT => {
                 // In theory, the below is the correct approach for non-PCM formats, but so far with testing, it seems that the fmt chunk is always 16 bytes long when the format is set to 3.
                 // let mut fmt_buf: [u8; FMT_CB_SIZE] = [0; FMT_CB_SIZE];
                 let mut fmt_buf: [u8; FMT_SIZE_BASE_SIZE] = [0; FMT_SIZE_BASE_SIZE];
                 reader.read_exact(&mut fmt_buf)?;
-                FmtChunk::from_base_bytes(fmt_buf)
+                FmtChunk::from_base_bytes(fmt_buf)?
+            }
+            FormatCode::WAVE_FORMAT_ADPCM | FormatCode::WAVE_FORMAT_GSM610 => {
+                match total_size_in_bytes {
+                    // Any extension following the cbSize field, such as the ADPCM coefficient table, is read when decoding
+                    n if n >= FMT_CB_SIZE => {
+                        let mut fmt_buf: [u8; FMT_CB_SIZE] = [0; FMT_CB_SIZE];
+                        reader.read_exact(&mut fmt_buf)?;
+                        FmtChunk::from_cb_bytes(fmt_buf)?
+                    }
+                    _ => return Err(FormatError::InvalidFmtChunkSize(total_size_in_bytes).into()),
+                }
+            }
+            FormatCode::WAVE_FORMAT_ALAW | FormatCode::WAVE_FORMAT_MULAW => {
+                match total_size_in_bytes {
+                    // Some encoders omit the cbSize field for companded files
+                    FMT_SIZE_BASE_SIZE => {
+                        let mut fmt_buf: [u8; FMT_SIZE_BASE_SIZE] = [0; FMT_SIZE_BASE_SIZE];
+                        reader.read_exact(&mut fmt_buf)?;
+                        FmtChunk::from_base_bytes(fmt_buf)?
+                    }
+                    n if n >= FMT_CB_SIZE => {
+                        let mut fmt_buf: [u8; FMT_CB_SIZE] = [0; FMT_CB_SIZE];
+                        reader.read_exact(&mut fmt_buf)?;
+                        FmtChunk::from_cb_bytes(fmt_buf)?
+                    }
+                    _ => return Err(FormatError::InvalidFmtChunkSize(total_size_in_bytes).into()),
+                }
             }
-            FormatCode::WAVE_FORMAT_ALAW => todo!(),
-            FormatCode::WAVE_FORMAT_MULAW => todo!(),
             FormatCode::WAVE_FORMAT_EXTENSIBLE => match total_size_in_bytes {
                 FMT_CB_SIZE => {
                     let mut fmt_buf: [u8; FMT_CB_SIZE] = [0; FMT_CB_SIZE];
                     reader.read_exact(&mut fmt_buf)?;
-                    FmtChunk::from_cb_bytes(fmt_buf)
+                    FmtChunk::from_cb_bytes(fmt_buf)?
                 }
                 FMT_SIZE_EXTENDED_SIZE => {
                     let mut fmt_buf: [u8; FMT_SIZE_EXTENDED_SIZE] = [0; FMT_SIZE_EXTENDED_SIZE];
                     reader.read_exact(&mut fmt_buf)?;
-                    FmtChunk::from_extended_bytes(fmt_buf)
+                    FmtChunk::from_extended_bytes(fmt_buf)?
                 }
                 _ => return Err(FormatError::InvalidFmtChunkSize(total_size_in_bytes).into()),
             },
@@ -347,6 +394,18 @@ impl Default for CbSize {
     }
 }
 
+impl TryFrom<u16> for CbSize {
+    type Error = WaversError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CbSize::Base),
+            22 => Ok(CbSize::Extended),
+            _ => Err(FormatError::InvalidFmtChunkSize(FMT_CB_SIZE + value as usize).into()),
+        }
+    }
+}
+
 impl Display for CbSize {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -393,14 +452,49 @@ impl ExtFmtChunkInfo {
         self.valid_bits_per_sample
     }
 
+    pub fn set_valid_bits_per_sample(&mut self, valid_bits_per_sample: u16) {
+        self.valid_bits_per_sample = valid_bits_per_sample;
+    }
+
     pub fn channel_mask(&self) -> u32 {
         self.channel_mask
     }
 
+    pub fn set_channel_mask(&mut self, channel_mask: u32) {
+        self.channel_mask = channel_mask;
+    }
+
     pub fn sub_format(&self) -> FormatCode {
         self.sub_format
     }
 
+    /// Returns the full 16 byte sub-format GUID. The first two bytes are the sub-format code.
+    pub fn guid(&self) -> [u8; 16] {
+        let mut guid = [0; 16];
+        guid[0..2].copy_from_slice(&self.sub_format.to_ne_bytes());
+        guid[2..16].copy_from_slice(&self.guid);
+        guid
+    }
+
+    /// Parses the extensible format fields which follow the base fmt chunk, starting with the cbSize field.
+    pub fn from_bytes(bytes: [u8; 24]) -> WaversResult<Self> {
+        let cb_size = CbSize::try_from(u16::from_ne_bytes([bytes[0], bytes[1]]))?;
+        let valid_bits_per_sample = u16::from_ne_bytes([bytes[2], bytes[3]]);
+        let channel_mask = u32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        let sub_format = FormatCode::try_from(u16::from_ne_bytes([bytes[8], bytes[9]]))?;
+
+        let mut guid = [0; 14];
+        guid.copy_from_slice(&bytes[10..24]);
+
+        Ok(ExtFmtChunkInfo {
+            cb_size,
+            valid_bits_per_sample,
+            channel_mask,
+            sub_format,
+            guid,
+        })
+    }
+
     pub fn as_bytes(&self) -> [u8; 24] {
         let mut bytes = [0; 24];
         let cb_size = self.cb_size as u16;
@@ -0,0 +1,180 @@
+//! Contains the InstChunk struct and its implementation.
+//! The inst chunk stores how a sampler should map the audio onto a keyboard, such as its pitch, gain and key and velocity ranges.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, INST},
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of the inst chunk in bytes, less the size of the ID and size field. The chunk is followed by a padding byte.
+pub const INST_SIZE: usize = 7;
+
+/// The inst chunk of a wav file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct InstChunk {
+    /// The MIDI note which plays the audio at its original pitch, where 60 is middle C.
+    pub unshifted_note: u8,
+    /// The pitch shift in cents, from -50 to +50.
+    pub fine_tune: i8,
+    /// The gain in decibels.
+    pub gain: i8,
+    pub low_note: u8,
+    pub high_note: u8,
+    pub low_velocity: u8,
+    pub high_velocity: u8,
+}
+
+impl InstChunk {
+    /// Creates a new InstChunk with the given unshifted note, which spans all keys and velocities.
+    pub fn new(unshifted_note: u8) -> Self {
+        InstChunk {
+            unshifted_note,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for InstChunk {
+    fn default() -> Self {
+        InstChunk {
+            unshifted_note: 60,
+            fine_tune: 0,
+            gain: 0,
+            low_note: 0,
+            high_note: 127,
+            low_velocity: 1,
+            high_velocity: 127,
+        }
+    }
+}
+
+impl Chunk for InstChunk {
+    /// Returns the ID of the InstChunk - "inst".
+    fn id(&self) -> &[u8; 4] {
+        &INST
+    }
+
+    /// Returns the size of the InstChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        INST_SIZE as u32
+    }
+
+    /// Returns the full InstChunk in bytes, including the padding byte.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + INST_SIZE + 1);
+        bytes.extend_from_slice(&INST);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&[
+            self.unshifted_note,
+            self.fine_tune as u8,
+            self.gain as u8,
+            self.low_note,
+            self.high_note,
+            self.low_velocity,
+            self.high_velocity,
+            0,
+        ]);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the InstChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if (info.size as usize) < INST_SIZE {
+            return Err(FormatError::InvalidInstChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = [0; INST_SIZE];
+        reader.read_exact(&mut bytes)?;
+
+        Ok(InstChunk {
+            unshifted_note: bytes[0],
+            fine_tune: bytes[1] as i8,
+            gain: bytes[2] as i8,
+            low_note: bytes[3],
+            high_note: bytes[4],
+            low_velocity: bytes[5],
+            high_velocity: bytes[6],
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for InstChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}\n\t{} {}\n\t{} {}-{}\n\t{} {}-{}",
+            "InstChunk: ".white().bold().underline(),
+            "unshifted_note:".green().bold(),
+            self.unshifted_note.to_string().white(),
+            "fine_tune:".green().bold(),
+            self.fine_tune.to_string().white(),
+            "gain:".green().bold(),
+            self.gain.to_string().white(),
+            "notes:".green().bold(),
+            self.low_note.to_string().white(),
+            self.high_note.to_string().white(),
+            "velocities:".green().bold(),
+            self.low_velocity.to_string().white(),
+            self.high_velocity.to_string().white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for InstChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "InstChunk: unshifted_note: {}, fine_tune: {}, gain: {}, notes: {}-{}, velocities: {}-{}",
+            self.unshifted_note,
+            self.fine_tune,
+            self.gain,
+            self.low_note,
+            self.high_note,
+            self.low_velocity,
+            self.high_velocity
+        )
+    }
+}
+
+#[cfg(test)]
+mod inst_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let inst = InstChunk {
+            unshifted_note: 48,
+            fine_tune: -12,
+            gain: -6,
+            low_note: 36,
+            high_note: 59,
+            low_velocity: 64,
+            high_velocity: 127,
+        };
+
+        let bytes = inst.as_bytes();
+        assert_eq!(bytes.len(), 8 + INST_SIZE + 1);
+        assert_eq!(&bytes[0..4], &INST);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, inst.size() as u64);
+        let read_inst = InstChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_inst, inst);
+    }
+}
@@ -0,0 +1,157 @@
+//! Contains the IxmlChunk struct and its implementation.
+//! The iXML chunk stores production metadata, such as the project, scene and take, as an XML document.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, IXML},
+    core::alloc_box_buffer,
+    error::WaversResult,
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The iXML chunk of a wav file. Contains the raw XML payload.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct IxmlChunk {
+    pub xml: String,
+}
+
+impl IxmlChunk {
+    /// Creates a new IxmlChunk containing the given XML document.
+    pub fn new(xml: &str) -> Self {
+        IxmlChunk {
+            xml: xml.to_string(),
+        }
+    }
+
+    /// Returns the text of the first element with the given tag, e.g. "SCENE" or "TAKE".
+    /// This is a minimal lookup which does not handle attributes on the element, CDATA sections or escaped characters.
+    pub fn get(&self, tag: &str) -> Option<&str> {
+        let open = format!("<{}>", tag);
+        let close = format!("</{}>", tag);
+        let start = self.xml.find(&open)? + open.len();
+        let end = start + self.xml[start..].find(&close)?;
+        Some(self.xml[start..end].trim())
+    }
+
+    /// Returns the project name.
+    pub fn project(&self) -> Option<&str> {
+        self.get("PROJECT")
+    }
+
+    /// Returns the scene name.
+    pub fn scene(&self) -> Option<&str> {
+        self.get("SCENE")
+    }
+
+    /// Returns the take.
+    pub fn take(&self) -> Option<&str> {
+        self.get("TAKE")
+    }
+
+    /// Returns the tape name.
+    pub fn tape(&self) -> Option<&str> {
+        self.get("TAPE")
+    }
+
+    /// Returns the note.
+    pub fn note(&self) -> Option<&str> {
+        self.get("NOTE")
+    }
+}
+
+impl Chunk for IxmlChunk {
+    /// Returns the ID of the IxmlChunk - "iXML".
+    fn id(&self) -> &[u8; 4] {
+        &IXML
+    }
+
+    /// Returns the size of the IxmlChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        self.xml.len() as u32
+    }
+
+    /// Returns the full IxmlChunk in bytes, including a padding byte if the size is odd.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let size = self.size() as usize;
+        let mut bytes = Vec::with_capacity(8 + size + size % 2);
+        bytes.extend_from_slice(&IXML);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(self.xml.as_bytes());
+        bytes.resize(8 + size + size % 2, 0);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the IxmlChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        // Some writers zero pad the document
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(IxmlChunk {
+            xml: String::from_utf8_lossy(&bytes[..end]).to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for IxmlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{}",
+            "IxmlChunk: ".white().bold().underline(),
+            self.xml.white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for IxmlChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IxmlChunk: {}", self.xml)
+    }
+}
+
+#[cfg(test)]
+mod ixml_tests {
+    use super::*;
+
+    const XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<BWFXML><IXML_VERSION>2.10</IXML_VERSION><PROJECT>Wavers</PROJECT><SCENE>12A</SCENE><TAKE> 3 </TAKE></BWFXML>";
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let ixml = IxmlChunk::new(XML);
+        let bytes = ixml.as_bytes();
+        assert_eq!(bytes.len() % 2, 0);
+        assert_eq!(&bytes[0..4], &IXML);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, ixml.size() as u64);
+        let read_ixml = IxmlChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_ixml, ixml);
+    }
+
+    #[test]
+    fn can_get_elements() {
+        let ixml = IxmlChunk::new(XML);
+        assert_eq!(ixml.project(), Some("Wavers"));
+        assert_eq!(ixml.scene(), Some("12A"));
+        assert_eq!(ixml.take(), Some("3"));
+        assert_eq!(ixml.get("IXML_VERSION"), Some("2.10"));
+        assert_eq!(ixml.tape(), None);
+    }
+}
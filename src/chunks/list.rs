@@ -15,11 +15,92 @@ use pyo3::prelude::*;
 use crate::{
     chunks::{Chunk, LIST},
     core::alloc_box_buffer,
+    error::FormatError,
     ReadSeek, WaversResult,
 };
 
 pub type InfoId = [u8; 4];
 
+/// The tags of the common INFO list sub-chunks, which store metadata such as the artist and title.
+/// Any other tag is stored as ``InfoTag::Other``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InfoTag {
+    /// IART - the artist
+    Artist,
+    /// INAM - the title
+    Name,
+    /// IPRD - the album or product
+    Product,
+    /// ICRD - the creation date
+    CreationDate,
+    /// ICMT - a comment
+    Comment,
+    /// IGNR - the genre
+    Genre,
+    /// ICOP - the copyright
+    Copyright,
+    /// ISFT - the software used to create the file
+    Software,
+    /// ITRK - the track number
+    TrackNumber,
+    /// IKEY - keywords
+    Keywords,
+    /// IENG - the engineer
+    Engineer,
+    /// ITCH - the technician
+    Technician,
+    /// ISBJ - the subject
+    Subject,
+    /// ISRC - the source
+    Source,
+    /// Any other tag
+    Other(InfoId),
+}
+
+impl From<InfoId> for InfoTag {
+    fn from(id: InfoId) -> Self {
+        match &id {
+            b"IART" => InfoTag::Artist,
+            b"INAM" => InfoTag::Name,
+            b"IPRD" => InfoTag::Product,
+            b"ICRD" => InfoTag::CreationDate,
+            b"ICMT" => InfoTag::Comment,
+            b"IGNR" => InfoTag::Genre,
+            b"ICOP" => InfoTag::Copyright,
+            b"ISFT" => InfoTag::Software,
+            b"ITRK" => InfoTag::TrackNumber,
+            b"IKEY" => InfoTag::Keywords,
+            b"IENG" => InfoTag::Engineer,
+            b"ITCH" => InfoTag::Technician,
+            b"ISBJ" => InfoTag::Subject,
+            b"ISRC" => InfoTag::Source,
+            _ => InfoTag::Other(id),
+        }
+    }
+}
+
+impl From<InfoTag> for InfoId {
+    fn from(tag: InfoTag) -> Self {
+        match tag {
+            InfoTag::Artist => *b"IART",
+            InfoTag::Name => *b"INAM",
+            InfoTag::Product => *b"IPRD",
+            InfoTag::CreationDate => *b"ICRD",
+            InfoTag::Comment => *b"ICMT",
+            InfoTag::Genre => *b"IGNR",
+            InfoTag::Copyright => *b"ICOP",
+            InfoTag::Software => *b"ISFT",
+            InfoTag::TrackNumber => *b"ITRK",
+            InfoTag::Keywords => *b"IKEY",
+            InfoTag::Engineer => *b"IENG",
+            InfoTag::Technician => *b"ITCH",
+            InfoTag::Subject => *b"ISBJ",
+            InfoTag::Source => *b"ISRC",
+            InfoTag::Other(id) => id,
+        }
+    }
+}
+
 /// A List Chunk - a chunk that contains a list of other chunks. Each chunk in the list is identified by a 4 byte ID, followed by a 4 byte size, and then the data.
 #[cfg_attr(feature = "pyo3", pyclass)]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +109,12 @@ pub struct ListChunk {
     data: HashMap<InfoId, String>,
 }
 
+/// Returns the length of a sub-chunk value including its null terminator and the padding byte required for odd lengths.
+fn padded_len(value: &str) -> usize {
+    let len = value.len() + 1;
+    len + len % 2
+}
+
 impl Chunk for ListChunk {
     /// Returns the ID of the ListChunk - "LIST".
     fn id(&self) -> &[u8; 4] {
@@ -39,28 +126,31 @@ impl Chunk for ListChunk {
         let n_bytes = 4 + self
             .data
             .iter()
-            .map(|(id, value)| id.len() as u32 + value.len() as u32 + 4)
+            .map(|(id, value)| id.len() as u32 + 4 + padded_len(value) as u32)
             .sum::<u32>();
         n_bytes // 4 bytes for the list_type_id
     }
 
     /// Returns the full ListChunk in bytes.
+    /// Each value is written as a null terminated UTF-8 string, padded to an even length, in order of sub-chunk ID.
     fn as_bytes(&self) -> Box<[u8]> {
-        let mut bytes = alloc_box_buffer(8 + self.size() as usize);
+        let mut bytes = vec![0; 8 + self.size() as usize].into_boxed_slice();
         bytes[0..4].copy_from_slice(&LIST); // Chunk ID
         bytes[4..8].copy_from_slice(&(self.size() as u32).to_ne_bytes()); // Chunk Size
         bytes[8..12].copy_from_slice(&self.list_type_id); // List Type ID
 
         let mut i = 12;
 
-        for (id, value) in &self.data {
+        let mut data: Vec<(&InfoId, &String)> = self.data.iter().collect();
+        data.sort();
+        for (id, value) in data {
             bytes[i..i + 4].copy_from_slice(id);
             i += 4;
-            let size = value.len() as u32;
+            let size = value.len() as u32 + 1; // including the null terminator
             bytes[i..i + 4].copy_from_slice(&size.to_ne_bytes());
             i += 4;
-            bytes[i..i + size as usize].copy_from_slice(value.as_bytes());
-            i += size as usize;
+            bytes[i..i + value.len()].copy_from_slice(value.as_bytes());
+            i += padded_len(value); // the null terminator and padding are left as zeros
         }
 
         bytes
@@ -76,65 +166,71 @@ impl Chunk for ListChunk {
     {
         let offset = info.offset as u64;
         reader.seek(std::io::SeekFrom::Start(offset))?;
-        let mut buf = [0; 4];
-
-        reader.read_exact(&mut buf)?;
-        let chunk_id = buf;
-        println!("{:?}", std::str::from_utf8(&chunk_id).unwrap_or("ERROR"));
-
-        reader.read_exact(&mut buf)?; // Read the chunk size
-        let _chunk_size = u32::from_ne_bytes(buf);
-        log!(log::Level::Debug, "Chunk Size: {}", _chunk_size);
-
-        reader.read_exact(&mut buf)?; // Read the list type id
-        let list_type_id = buf;
-
-        let mut data = HashMap::new();
+        let mut bytes = alloc_box_buffer(8 + info.size as usize);
+        reader.read_exact(&mut bytes)?;
+        log!(log::Level::Debug, "Chunk Size: {}", info.size);
 
-        while (reader.seek(std::io::SeekFrom::Current(0))? as usize)
-            < info.offset + info.size as usize + 8
-        {
-            reader.read_exact(&mut buf)?;
-            let id = buf;
-            reader.read_exact(&mut buf)?;
-            let size = u32::from_ne_bytes(buf) as usize;
-            let mut value = alloc_box_buffer(size);
-            reader.read_exact(&mut value)?;
-            let value = std::str::from_utf8(&value).unwrap_or("[ERROR]").to_string();
-            data.insert(id, value);
-        }
-        log!(log::Level::Debug, "List Chunk Data: {:?}", data);
-        Ok(ListChunk::new(list_type_id, data))
+        let list_chunk = ListChunk::from_bytes(&bytes)?;
+        log!(log::Level::Debug, "List Chunk Data: {:?}", list_chunk.data);
+        Ok(list_chunk)
     }
 }
 
 impl ListChunk {
     /// Creates a new ListChunk.
-    pub(crate) fn new(list_type_id: [u8; 4], data: HashMap<InfoId, String>) -> Self {
+    pub fn new(list_type_id: [u8; 4], data: HashMap<InfoId, String>) -> Self {
         Self { list_type_id, data }
     }
 
-    /// Creates a new ListChunk from bytes.
+    /// Creates a new ListChunk from bytes, including the chunk ID and size field.
+    /// Each value is read up to its null terminator, and any sub-chunks which run past the end of the bytes are ignored.
     pub fn from_bytes(bytes: &[u8]) -> WaversResult<Self> {
-        let list_type_id = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if bytes.len() < 12 {
+            return Err(FormatError::InvalidListChunkSize(bytes.len()).into());
+        }
+        let list_type_id = [bytes[8], bytes[9], bytes[10], bytes[11]];
         let mut data = HashMap::new();
         let mut i: usize = 12;
-        while i < bytes.len() {
-            if i > bytes.len() {
-                break;
-            }
+        while i + 8 <= bytes.len() {
             let id = [bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]];
             i += 4;
             let size =
                 u32::from_ne_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]) as usize;
             i += 4;
-            let value =
-                String::from_utf8(bytes[i..i + size].to_vec()).unwrap_or("[ERROR]".to_string());
-            data.insert(id, value);
-            i += size;
+            if i + size > bytes.len() {
+                break;
+            }
+            let value = &bytes[i..i + size];
+            let end = value.iter().position(|b| *b == 0).unwrap_or(size);
+            data.insert(id, String::from_utf8_lossy(&value[..end]).to_string());
+            // Sub-chunks with an odd size are followed by a padding byte
+            i += size + size % 2;
         }
         Ok(ListChunk::new(list_type_id, data))
     }
+
+    /// Returns the list type ID, e.g. "INFO".
+    pub fn list_type_id(&self) -> &[u8; 4] {
+        &self.list_type_id
+    }
+
+    /// Returns the sub-chunks of the list, keyed by their ID.
+    pub fn data(&self) -> &HashMap<InfoId, String> {
+        &self.data
+    }
+
+    /// Sets the value of the given INFO tag, replacing any existing value.
+    pub fn set(&mut self, tag: InfoTag, value: &str) {
+        self.data.insert(tag.into(), value.to_string());
+    }
+
+    /// Returns the INFO metadata contained in the list, keyed by tag.
+    pub fn info(&self) -> HashMap<InfoTag, String> {
+        self.data
+            .iter()
+            .map(|(id, value)| (InfoTag::from(*id), value.clone()))
+            .collect()
+    }
 }
 
 impl Default for ListChunk {
@@ -206,9 +302,10 @@ mod list_chunk_tests {
         let list_chunk = ListChunk::new(list_type_id, data);
         let bytes = list_chunk.as_bytes();
         let decoded = ListChunk::from_bytes(&bytes).unwrap();
-        // let bytes = list_chunk.as_bytes();
-        // let decoded = ListChunk::from_bytes(&mut bytes.clone(), bytes.len()).unwrap();
-        println!("{}", decoded);
-        // assert_eq!(decoded, list_chunk);
+        assert_eq!(decoded, list_chunk);
+        assert_eq!(
+            decoded.info().get(&InfoTag::Artist),
+            Some(&"Jack Geraghty".to_string())
+        );
     }
 }
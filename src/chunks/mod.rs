@@ -1,14 +1,36 @@
 //! This module contains the ``Chunk`` trait and the constants relating to the different chunks in a wav file.
 
+pub mod acid;
+pub mod adtl;
+pub mod axml;
+pub mod bext;
+pub mod cue;
+pub mod ds64;
 pub mod fact;
 pub mod fmt;
+pub mod inst;
+pub mod ixml;
 pub mod list;
+pub mod plst;
+pub mod raw;
+pub mod smpl;
 
 use std::fmt::Display;
 
+pub use crate::chunks::acid::AcidChunk;
+pub use crate::chunks::adtl::{AdtlChunk, LabeledText, Marker};
+pub use crate::chunks::axml::AxmlChunk;
+pub use crate::chunks::bext::BextChunk;
+pub use crate::chunks::cue::{CueChunk, CuePoint};
+pub use crate::chunks::ds64::Ds64Chunk;
 pub use crate::chunks::fact::FactChunk;
 pub use crate::chunks::fmt::FmtChunk;
-pub use crate::chunks::list::ListChunk;
+pub use crate::chunks::inst::InstChunk;
+pub use crate::chunks::ixml::IxmlChunk;
+pub use crate::chunks::list::{InfoTag, ListChunk};
+pub use crate::chunks::plst::{PlaylistSegment, PlstChunk};
+pub use crate::chunks::raw::RawChunk;
+pub use crate::chunks::smpl::{LoopType, SampleLoop, SmplChunk};
 use crate::{header::HeaderChunkInfo, ReadSeek, WaversResult};
 
 // 100% necessary to have these chunks
@@ -22,11 +44,52 @@ pub const DATA: [u8; 4] = *b"data";
 /// The fmt chunk ID "fmt "
 pub const FMT: [u8; 4] = *b"fmt ";
 
+/// The RIFX chunk ID "RIFX", used instead of "RIFF" by files stored in big-endian byte order
+pub const RIFX: [u8; 4] = *b"RIFX";
+
+// Large file containers, which replace the RIFF chunk ID and store 64-bit sizes in the ds64 chunk
+/// The RF64 chunk ID "RF64"
+pub const RF64: [u8; 4] = *b"RF64";
+/// The BW64 chunk ID "BW64"
+pub const BW64: [u8; 4] = *b"BW64";
+/// The ds64 chunk ID "ds64"
+pub const DS64: [u8; 4] = *b"ds64";
+/// The JUNK chunk ID "JUNK", used to reserve space for a ds64 chunk in files which may need to be promoted to RF64
+pub const JUNK: [u8; 4] = *b"JUNK";
+
 // Optional chunks
-/// The fact chunk ID "fact"
+/// The LIST chunk ID "LIST"
 pub const LIST: [u8; 4] = *b"LIST";
+/// The INFO list type "INFO", used by LIST chunks containing metadata such as the artist and title
+pub const INFO: [u8; 4] = *b"INFO";
+/// The associated data list type "adtl", used by LIST chunks containing the labels and notes of cue points
+pub const ADTL: [u8; 4] = *b"adtl";
+/// The label sub-chunk ID "labl" of an adtl list
+pub const LABL: [u8; 4] = *b"labl";
+/// The note sub-chunk ID "note" of an adtl list
+pub const NOTE: [u8; 4] = *b"note";
+/// The labeled text sub-chunk ID "ltxt" of an adtl list
+pub const LTXT: [u8; 4] = *b"ltxt";
 /// The fact chunk ID "fact"
 pub const FACT: [u8; 4] = *b"fact";
+/// The Broadcast Wave extension chunk ID "bext"
+pub const BEXT: [u8; 4] = *b"bext";
+/// The cue points chunk ID "cue "
+pub const CUE: [u8; 4] = *b"cue ";
+/// The playlist chunk ID "plst"
+pub const PLST: [u8; 4] = *b"plst";
+/// The sampler chunk ID "smpl"
+pub const SMPL: [u8; 4] = *b"smpl";
+/// The ACID loop metadata chunk ID "acid"
+pub const ACID: [u8; 4] = *b"acid";
+/// The instrument chunk ID "inst"
+pub const INST: [u8; 4] = *b"inst";
+/// The Audio Definition Model chunk ID "axml"
+pub const AXML: [u8; 4] = *b"axml";
+/// The ID3v2 tag chunk ID "id3 "
+pub const ID3: [u8; 4] = *b"id3 ";
+/// The iXML production metadata chunk ID "iXML"
+pub const IXML: [u8; 4] = *b"iXML";
 
 /// A trait representing a chunk in a wav file.
 /// Allows for the common creation of several chunks that are found in a wav file.
@@ -0,0 +1,185 @@
+//! Contains the PlstChunk and PlaylistSegment structs and their implementation.
+//! The plst chunk stores a playlist, which plays segments of the audio, each starting at a cue point, in a given order.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, PLST},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of a single playlist segment in bytes.
+pub const PLAYLIST_SEGMENT_SIZE: usize = 12;
+
+/// A single segment of a playlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaylistSegment {
+    /// The ID of the cue point at which the segment starts.
+    pub cue_point_id: u32,
+    /// The length of the segment in sample frames.
+    pub length: u32,
+    /// The number of times to play the segment.
+    pub repeats: u32,
+}
+
+impl PlaylistSegment {
+    /// Creates a new segment of the given length, in sample frames, starting at the cue point with the given ID.
+    pub fn new(cue_point_id: u32, length: u32, repeats: u32) -> Self {
+        PlaylistSegment {
+            cue_point_id,
+            length,
+            repeats,
+        }
+    }
+
+    /// Returns the playlist segment in bytes.
+    pub fn as_bytes(&self) -> [u8; PLAYLIST_SEGMENT_SIZE] {
+        let mut bytes = [0; PLAYLIST_SEGMENT_SIZE];
+        bytes[0..4].copy_from_slice(&self.cue_point_id.to_ne_bytes());
+        bytes[4..8].copy_from_slice(&self.length.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.repeats.to_ne_bytes());
+        bytes
+    }
+
+    /// Creates a playlist segment from bytes.
+    pub fn from_bytes(bytes: [u8; PLAYLIST_SEGMENT_SIZE]) -> Self {
+        PlaylistSegment {
+            cue_point_id: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            length: u32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            repeats: u32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+        }
+    }
+}
+
+/// The plst chunk of a wav file. Contains the segments of the playlist in play order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct PlstChunk {
+    pub segments: Vec<PlaylistSegment>,
+}
+
+impl PlstChunk {
+    /// Creates a new PlstChunk containing the given segments.
+    pub fn new(segments: Vec<PlaylistSegment>) -> Self {
+        PlstChunk { segments }
+    }
+
+    /// Returns the total number of sample frames played by the playlist, including repeats.
+    pub fn total_length(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.length as u64 * segment.repeats as u64)
+            .sum()
+    }
+}
+
+impl Chunk for PlstChunk {
+    /// Returns the ID of the PlstChunk - "plst".
+    fn id(&self) -> &[u8; 4] {
+        &PLST
+    }
+
+    /// Returns the size of the PlstChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        (4 + PLAYLIST_SEGMENT_SIZE * self.segments.len()) as u32
+    }
+
+    /// Returns the full PlstChunk in bytes.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let mut bytes = Vec::with_capacity(8 + self.size() as usize);
+        bytes.extend_from_slice(&PLST);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&(self.segments.len() as u32).to_ne_bytes());
+        for segment in self.segments.iter() {
+            bytes.extend_from_slice(&segment.as_bytes());
+        }
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the PlstChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if info.size < 4 {
+            return Err(FormatError::InvalidPlstChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        let n_segments = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let segments = bytes[4..]
+            .chunks_exact(PLAYLIST_SEGMENT_SIZE)
+            .take(n_segments as usize)
+            .map(|segment| PlaylistSegment::from_bytes(segment.try_into().unwrap()))
+            .collect();
+        Ok(PlstChunk { segments })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for PlstChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", "PlstChunk: ".white().bold().underline())?;
+        for segment in self.segments.iter() {
+            write!(
+                f,
+                "\n\t{} {} {} {} {} {}",
+                "cue_point_id:".green().bold(),
+                segment.cue_point_id.to_string().white(),
+                "length:".green().bold(),
+                segment.length.to_string().white(),
+                "repeats:".green().bold(),
+                segment.repeats.to_string().white()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for PlstChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PlstChunk:")?;
+        for segment in self.segments.iter() {
+            write!(
+                f,
+                "\n\tcue_point_id: {} length: {} repeats: {}",
+                segment.cue_point_id, segment.length, segment.repeats
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod plst_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let plst = PlstChunk::new(vec![
+            PlaylistSegment::new(2, 8000, 1),
+            PlaylistSegment::new(1, 4000, 3),
+        ]);
+        let bytes = plst.as_bytes();
+        assert_eq!(bytes.len(), 8 + 4 + 2 * PLAYLIST_SEGMENT_SIZE);
+        assert_eq!(&bytes[0..4], &PLST);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, plst.size() as u64);
+        let read_plst = PlstChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_plst, plst);
+        assert_eq!(read_plst.total_length(), 20000);
+    }
+}
@@ -0,0 +1,112 @@
+//! Contains the RawChunk struct and its implementation.
+//! A raw chunk stores the identifier and payload of a chunk without interpreting it, so chunks which wavers does not understand can be copied or written.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+use crate::{
+    chunks::Chunk, core::alloc_box_buffer, error::WaversResult, header::HeaderChunkInfo, ReadSeek,
+};
+
+/// A chunk of any type, stored as its identifier and its payload bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawChunk {
+    pub id: [u8; 4],
+    /// The payload of the chunk, without the identifier, size field or padding byte.
+    pub data: Vec<u8>,
+}
+
+impl RawChunk {
+    /// Creates a new RawChunk with the given identifier and payload.
+    pub fn new(id: [u8; 4], data: Vec<u8>) -> Self {
+        RawChunk { id, data }
+    }
+}
+
+impl Chunk for RawChunk {
+    /// Returns the ID of the RawChunk.
+    fn id(&self) -> &[u8; 4] {
+        &self.id
+    }
+
+    /// Returns the size of the RawChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    /// Returns the full RawChunk in bytes, including a padding byte if the size is odd.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let size = self.data.len();
+        let mut bytes = Vec::with_capacity(8 + size + size % 2);
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.resize(8 + size + size % 2, 0);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the RawChunk from a reader. The identifier is read from the file, since some chunks, such as LIST chunks, are stored in the header under a different identifier.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        reader.seek(SeekFrom::Start(info.offset as u64))?;
+        let mut id = [0; 4];
+        reader.read_exact(&mut id)?;
+        reader.seek(SeekFrom::Current(4))?;
+        let mut data = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut data)?;
+        Ok(RawChunk {
+            id,
+            data: data.into_vec(),
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for RawChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}",
+            "RawChunk: ".white().bold().underline(),
+            "id:".green().bold(),
+            String::from_utf8_lossy(&self.id).white(),
+            "size:".green().bold(),
+            self.data.len().to_string().white()
+        )
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for RawChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RawChunk: id: {}, size: {}",
+            String::from_utf8_lossy(&self.id),
+            self.data.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod raw_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let chunk = RawChunk::new(*b"umid", vec![1, 2, 3]);
+        let bytes = chunk.as_bytes();
+        assert_eq!(bytes.len(), 8 + 4);
+        assert_eq!(&bytes[0..4], b"umid");
+        assert_eq!(&bytes[4..8], &3u32.to_ne_bytes());
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, chunk.size() as u64);
+        let read_chunk = RawChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_chunk, chunk);
+    }
+}
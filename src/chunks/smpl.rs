@@ -0,0 +1,309 @@
+//! Contains the SmplChunk and SampleLoop structs and their implementation.
+//! The smpl chunk stores the information a sampler needs to play the audio, such as the MIDI unity note and loop points.
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+
+#[cfg(feature = "colored")]
+use colored::Colorize;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+use crate::{
+    chunks::{Chunk, SMPL},
+    core::alloc_box_buffer,
+    error::{FormatError, WaversResult},
+    header::HeaderChunkInfo,
+    ReadSeek,
+};
+
+/// The size of the smpl chunk without any loops or sampler data, less the size of the ID and size field.
+pub const SMPL_BASE_SIZE: usize = 36;
+/// The size of a single sample loop in bytes.
+pub const SAMPLE_LOOP_SIZE: usize = 24;
+
+/// The direction in which a sample loop is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoopType {
+    Forward,
+    PingPong,
+    Backward,
+    /// A loop type reserved for future standard types or sampler specific types.
+    Other(u32),
+}
+
+impl From<u32> for LoopType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => LoopType::Forward,
+            1 => LoopType::PingPong,
+            2 => LoopType::Backward,
+            _ => LoopType::Other(value),
+        }
+    }
+}
+
+impl From<LoopType> for u32 {
+    fn from(value: LoopType) -> Self {
+        match value {
+            LoopType::Forward => 0,
+            LoopType::PingPong => 1,
+            LoopType::Backward => 2,
+            LoopType::Other(value) => value,
+        }
+    }
+}
+
+/// A single loop in the smpl chunk. The start and end are sample frames and both are included in the loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleLoop {
+    /// The ID of the loop, which may match the ID of a cue point.
+    pub cue_point_id: u32,
+    pub loop_type: LoopType,
+    pub start: u32,
+    pub end: u32,
+    /// The fraction of a sample frame at which to loop, where 0x80000000 is half a sample frame.
+    pub fraction: u32,
+    /// The number of times to play the loop, where 0 is an infinite loop.
+    pub play_count: u32,
+}
+
+impl SampleLoop {
+    /// Creates a new, infinite, forward loop between the given sample frames.
+    pub fn new(cue_point_id: u32, start: u32, end: u32) -> Self {
+        SampleLoop {
+            cue_point_id,
+            loop_type: LoopType::Forward,
+            start,
+            end,
+            fraction: 0,
+            play_count: 0,
+        }
+    }
+
+    /// Returns the sample loop in bytes.
+    pub fn as_bytes(&self) -> [u8; SAMPLE_LOOP_SIZE] {
+        let mut bytes = [0; SAMPLE_LOOP_SIZE];
+        for (i, value) in [
+            self.cue_point_id,
+            self.loop_type.into(),
+            self.start,
+            self.end,
+            self.fraction,
+            self.play_count,
+        ]
+        .iter()
+        .enumerate()
+        {
+            bytes[i * 4..i * 4 + 4].copy_from_slice(&value.to_ne_bytes());
+        }
+        bytes
+    }
+
+    /// Creates a sample loop from bytes.
+    pub fn from_bytes(bytes: [u8; SAMPLE_LOOP_SIZE]) -> Self {
+        let read_u32 = |i: usize| u32::from_ne_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        SampleLoop {
+            cue_point_id: read_u32(0),
+            loop_type: read_u32(1).into(),
+            start: read_u32(2),
+            end: read_u32(3),
+            fraction: read_u32(4),
+            play_count: read_u32(5),
+        }
+    }
+}
+
+/// The smpl chunk of a wav file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct SmplChunk {
+    /// The MIDI Manufacturers Association manufacturer code, 0 if the chunk is not specific to a manufacturer.
+    pub manufacturer: u32,
+    pub product: u32,
+    /// The duration of a single sample in nanoseconds.
+    pub sample_period: u32,
+    /// The MIDI note which plays the audio at its original pitch, where 60 is middle C.
+    pub midi_unity_note: u32,
+    /// The fraction of a semitone above the unity note, where 0x80000000 is half a semitone.
+    pub midi_pitch_fraction: u32,
+    pub smpte_format: u32,
+    pub smpte_offset: u32,
+    pub loops: Vec<SampleLoop>,
+    /// Sampler specific data which follows the loops.
+    pub sampler_data: Vec<u8>,
+}
+
+impl SmplChunk {
+    /// Creates a new SmplChunk for audio with the given sample rate and MIDI unity note, without any loops.
+    pub fn new(sample_rate: i32, midi_unity_note: u32) -> Self {
+        SmplChunk {
+            sample_period: (1_000_000_000.0 / sample_rate as f64).round() as u32,
+            midi_unity_note,
+            ..Default::default()
+        }
+    }
+
+    /// Adds an infinite forward loop between the given sample frames, using the index of the loop as its ID.
+    pub fn add_loop(&mut self, start: u32, end: u32) {
+        let id = self.loops.len() as u32;
+        self.loops.push(SampleLoop::new(id, start, end));
+    }
+}
+
+impl Chunk for SmplChunk {
+    /// Returns the ID of the SmplChunk - "smpl".
+    fn id(&self) -> &[u8; 4] {
+        &SMPL
+    }
+
+    /// Returns the size of the SmplChunk in bytes less the size of the ID and size field itself.
+    fn size(&self) -> u32 {
+        (SMPL_BASE_SIZE + SAMPLE_LOOP_SIZE * self.loops.len() + self.sampler_data.len()) as u32
+    }
+
+    /// Returns the full SmplChunk in bytes, including a padding byte if the size is odd.
+    fn as_bytes(&self) -> Box<[u8]> {
+        let size = self.size() as usize;
+        let mut bytes = Vec::with_capacity(8 + size + size % 2);
+        bytes.extend_from_slice(&SMPL);
+        bytes.extend_from_slice(&self.size().to_ne_bytes());
+        for value in [
+            self.manufacturer,
+            self.product,
+            self.sample_period,
+            self.midi_unity_note,
+            self.midi_pitch_fraction,
+            self.smpte_format,
+            self.smpte_offset,
+            self.loops.len() as u32,
+            self.sampler_data.len() as u32,
+        ] {
+            bytes.extend_from_slice(&value.to_ne_bytes());
+        }
+        for sample_loop in self.loops.iter() {
+            bytes.extend_from_slice(&sample_loop.as_bytes());
+        }
+        bytes.extend_from_slice(&self.sampler_data);
+        bytes.resize(8 + size + size % 2, 0);
+        bytes.into_boxed_slice()
+    }
+
+    /// Reads the SmplChunk from a reader.
+    fn from_reader(reader: &mut Box<dyn ReadSeek>, info: &HeaderChunkInfo) -> WaversResult<Self>
+    where
+        Self: Sized,
+    {
+        if (info.size as usize) < SMPL_BASE_SIZE {
+            return Err(FormatError::InvalidSmplChunkSize(info.size).into());
+        }
+        reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+        let mut bytes = alloc_box_buffer(info.size as usize);
+        reader.read_exact(&mut bytes)?;
+
+        let read_u32 = |i: usize| u32::from_ne_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        let n_loops = read_u32(7) as usize;
+        let sampler_data_size = read_u32(8) as usize;
+        let loops_end = SMPL_BASE_SIZE + n_loops * SAMPLE_LOOP_SIZE;
+        if loops_end + sampler_data_size > bytes.len() {
+            return Err(FormatError::InvalidSmplChunkSize(info.size).into());
+        }
+
+        let loops = bytes[SMPL_BASE_SIZE..loops_end]
+            .chunks_exact(SAMPLE_LOOP_SIZE)
+            .map(|sample_loop| SampleLoop::from_bytes(sample_loop.try_into().unwrap()))
+            .collect();
+        Ok(SmplChunk {
+            manufacturer: read_u32(0),
+            product: read_u32(1),
+            sample_period: read_u32(2),
+            midi_unity_note: read_u32(3),
+            midi_pitch_fraction: read_u32(4),
+            smpte_format: read_u32(5),
+            smpte_offset: read_u32(6),
+            loops,
+            sampler_data: bytes[loops_end..loops_end + sampler_data_size].to_vec(),
+        })
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Display for SmplChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\n\t{} {}\n\t{} {}",
+            "SmplChunk: ".white().bold().underline(),
+            "sample_period:".green().bold(),
+            self.sample_period.to_string().white(),
+            "midi_unity_note:".green().bold(),
+            self.midi_unity_note.to_string().white()
+        )?;
+        for sample_loop in self.loops.iter() {
+            write!(
+                f,
+                "\n\t{} {} {} {}",
+                "loop:".green().bold(),
+                sample_loop.start.to_string().white(),
+                "-".white(),
+                sample_loop.end.to_string().white()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "colored"))]
+impl Display for SmplChunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SmplChunk: sample_period: {}, midi_unity_note: {}",
+            self.sample_period, self.midi_unity_note
+        )?;
+        for sample_loop in self.loops.iter() {
+            write!(f, "\n\tloop: {} - {}", sample_loop.start, sample_loop.end)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod smpl_tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_to_and_from_bytes() {
+        let mut smpl = SmplChunk::new(48000, 60);
+        smpl.add_loop(100, 47999);
+        smpl.loops.push(SampleLoop {
+            cue_point_id: 7,
+            loop_type: LoopType::PingPong,
+            start: 0,
+            end: 10,
+            fraction: 0x80000000,
+            play_count: 2,
+        });
+        smpl.sampler_data = vec![1, 2, 3]; // odd length, so the chunk is padded
+        assert_eq!(smpl.sample_period, 20833);
+
+        let bytes = smpl.as_bytes();
+        assert_eq!(bytes.len(), 8 + SMPL_BASE_SIZE + 2 * SAMPLE_LOOP_SIZE + 4);
+        assert_eq!(&bytes[0..4], &SMPL);
+
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes.to_vec()));
+        let info = HeaderChunkInfo::new(0, smpl.size() as u64);
+        let read_smpl = SmplChunk::from_reader(&mut reader, &info).unwrap();
+        assert_eq!(read_smpl, smpl);
+        assert_eq!(read_smpl.loops[0].loop_type, LoopType::Forward);
+    }
+
+    #[test]
+    fn loop_count_is_validated() {
+        let mut bytes = SmplChunk::new(48000, 60).as_bytes().to_vec();
+        bytes[8 + 28..8 + 32].copy_from_slice(&3u32.to_ne_bytes());
+        let mut reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes));
+        let info = HeaderChunkInfo::new(0, SMPL_BASE_SIZE as u64);
+        assert!(SmplChunk::from_reader(&mut reader, &info).is_err());
+    }
+}
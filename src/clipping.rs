@@ -0,0 +1,229 @@
+//! Module for detecting clipping, so that damaged recordings can be flagged before they are processed any further.
+//!
+//! A sample is at full scale when its absolute value is at least 1.0 once converted to ``f64``, e.g. ``i16::MAX`` and ``i16::MIN``.
+//! A single full-scale sample can be a legitimate peak, so clipping is reported as runs of consecutive full-scale samples in a channel which are at least a minimum length.
+use std::ops::Range;
+
+use i24::i24;
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::WaversResult;
+
+/// The default minimum number of consecutive full-scale samples which count as clipping.
+pub const MIN_CLIPPED_RUN: usize = 3;
+
+/// The clipped runs of each channel of some audio.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClippingReport {
+    /// The runs of full-scale samples of each channel, in order, as ranges of frames.
+    pub runs: Vec<Vec<Range<usize>>>,
+}
+
+impl ClippingReport {
+    /// Returns true if any channel has a clipped run.
+    pub fn is_clipped(&self) -> bool {
+        self.runs.iter().any(|runs| !runs.is_empty())
+    }
+
+    /// Returns the number of clipped runs in the given channel.
+    ///
+    /// Panics if the channel is out of range.
+    pub fn n_runs(&self, channel: usize) -> usize {
+        self.runs[channel].len()
+    }
+
+    /// Returns the number of clipped samples in the given channel, counting only the samples of runs.
+    ///
+    /// Panics if the channel is out of range.
+    pub fn n_clipped(&self, channel: usize) -> usize {
+        self.runs[channel].iter().map(|run| run.len()).sum()
+    }
+}
+
+/// Finds the clipped runs of interleaved samples which are passed to it a block at a time, e.g. as a file is read.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::clipping::ClippingDetector;
+///
+/// let mut detector = ClippingDetector::new(1, 3);
+/// detector.process(&[0.5f32, 1.0, 1.0]);
+/// detector.process(&[1.0f32, -0.2, -1.0, -1.0]);
+/// let report = detector.finish();
+/// assert_eq!(report.runs[0], vec![1..4]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClippingDetector {
+    min_run: usize,
+    n_frames: usize,
+    /// The first frame of the current run of full-scale samples of each channel, if there is one.
+    open: Vec<Option<usize>>,
+    report: ClippingReport,
+}
+
+impl ClippingDetector {
+    /// Creates a detector for samples with the given number of channels,
+    /// which reports runs of at least ``min_run`` consecutive full-scale samples, e.g. ``MIN_CLIPPED_RUN``.
+    ///
+    /// Panics if the number of channels or the minimum run length is zero.
+    pub fn new(n_channels: u16, min_run: usize) -> Self {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        assert!(min_run > 0, "Minimum run length must be positive");
+        ClippingDetector {
+            min_run,
+            n_frames: 0,
+            open: vec![None; n_channels as usize],
+            report: ClippingReport {
+                runs: vec![Vec::new(); n_channels as usize],
+            },
+        }
+    }
+
+    /// Adds interleaved samples to the detection. Any trailing samples which do not make up a whole frame are skipped.
+    pub fn process<T: AudioSample>(&mut self, samples: &[T]) {
+        for frame in samples.chunks_exact(self.open.len()) {
+            for (channel, sample) in frame.iter().enumerate() {
+                let full_scale = ConvertTo::<f64>::convert_to(sample).abs() >= 1.0;
+                match (full_scale, self.open[channel]) {
+                    (true, None) => self.open[channel] = Some(self.n_frames),
+                    (false, Some(start)) => {
+                        self.close_run(channel, start);
+                        self.open[channel] = None;
+                    }
+                    _ => (),
+                }
+            }
+            self.n_frames += 1;
+        }
+    }
+
+    /// Reports the run from ``start`` to the current frame if it is long enough.
+    fn close_run(&mut self, channel: usize, start: usize) {
+        if self.n_frames - start >= self.min_run {
+            self.report.runs[channel].push(start..self.n_frames);
+        }
+    }
+
+    /// Returns the clipped runs found so far, closing any runs which reach the end of the samples.
+    pub fn finish(mut self) -> ClippingReport {
+        for channel in 0..self.open.len() {
+            if let Some(start) = self.open[channel] {
+                self.close_run(channel, start);
+            }
+        }
+        self.report
+    }
+}
+
+impl<T: AudioSample> Samples<T> {
+    /// Returns the runs of at least ``min_run`` consecutive full-scale samples in each channel of interleaved samples.
+    /// See the ``ClippingDetector`` struct for more information.
+    ///
+    /// Panics if the number of channels or the minimum run length is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::clipping::MIN_CLIPPED_RUN;
+    /// use wavers::Samples;
+    ///
+    /// let samples: Samples<i16> = Samples::from(vec![0, i16::MAX, 100, i16::MAX, 200, i16::MAX, 300, 0]);
+    /// let report = samples.detect_clipping(2, MIN_CLIPPED_RUN);
+    /// assert_eq!(report.n_runs(0), 0);
+    /// assert_eq!(report.runs[1], vec![0..3]);
+    /// ```
+    pub fn detect_clipping(&self, n_channels: u16, min_run: usize) -> ClippingReport {
+        let mut detector = ClippingDetector::new(n_channels, min_run);
+        detector.process(&self.samples);
+        detector.finish()
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Returns the runs of at least ``min_run`` consecutive full-scale samples in each channel of the wav file, scanning the file with ``scan_blocks``.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::clipping::MIN_CLIPPED_RUN;
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<i16> = Wav::from_path("path/to/recording.wav").unwrap();
+    ///     let report = wav.detect_clipping(MIN_CLIPPED_RUN).unwrap();
+    ///     if report.is_clipped() {
+    ///         println!("{} clipped samples in the first channel", report.n_clipped(0));
+    ///     }
+    /// }
+    /// ```
+    pub fn detect_clipping(&mut self, min_run: usize) -> WaversResult<ClippingReport> {
+        let mut detector = ClippingDetector::new(self.n_channels(), min_run);
+        self.scan_blocks(|block| detector.process(block))?;
+        Ok(detector.finish())
+    }
+}
+
+#[cfg(test)]
+mod clipping_tests {
+    use super::*;
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    #[test]
+    fn full_scale_runs_are_reported_per_channel() {
+        // The left channel clips for 4 frames and twice briefly, the right channel clips negatively at the end
+        let mut samples = Vec::new();
+        for i in 0..1000usize {
+            let left = match i {
+                100..=103 | 500..=501 | 700 => i16::MAX,
+                _ => (i % 100) as i16,
+            };
+            let right = match i {
+                997.. => i16::MIN,
+                _ => -((i % 100) as i16),
+            };
+            samples.extend([left, right]);
+        }
+        let samples = Samples::from(samples);
+        let report = samples.detect_clipping(2, MIN_CLIPPED_RUN);
+        assert!(report.is_clipped());
+        assert_eq!(report.runs, vec![vec![100..104], vec![997..1000]]);
+        assert_eq!((report.n_runs(0), report.n_clipped(0)), (1, 4));
+        assert_eq!(report.n_clipped(1), 3);
+
+        // Every full-scale sample counts with a minimum run of one
+        let report = samples.detect_clipping(2, 1);
+        assert_eq!(report.runs[0], vec![100..104, 500..502, 700..701]);
+
+        // Float samples beyond full scale are clipped too
+        let floats: Samples<f32> = Samples::from(vec![1.5, 1.0, 1.2, 0.5]);
+        assert_eq!(floats.detect_clipping(1, 3).runs[0], vec![0..3]);
+        assert!(!Samples::<f32>::from(vec![0.99; 10])
+            .detect_clipping(1, 1)
+            .is_clipped());
+
+        let fp = format!("{}detect_clipping.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        crate::write(&fp, &samples, 16000, 2).unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(&fp).unwrap();
+        assert_eq!(
+            wav.detect_clipping(MIN_CLIPPED_RUN).unwrap(),
+            samples.detect_clipping(2, MIN_CLIPPED_RUN)
+        );
+        std::fs::remove_file(&fp).unwrap();
+    }
+}
@@ -0,0 +1,176 @@
+//! Module containing the ``ObjectStoreReader`` struct, which reads wav files from cloud object storage such as S3, GCS and Azure, available with the ``object_store`` feature.
+//!
+//! Files are read using the ``object_store`` crate, with a range request for every read, so an ``AsyncWav`` can seek around a large file, or read only the files it needs of a large dataset,
+//! without downloading anything else.
+use std::sync::Arc;
+
+use i24::i24;
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+use crate::{
+    async_wav::{AsyncWav, AsyncWavReader},
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    error::WaversResult,
+};
+
+/// A reader over an object in an ``ObjectStore``, which fetches each read with a range request.
+///
+/// It implements ``AsyncWavReader``, so it can be read using an ``AsyncWav``.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::async_wav::AsyncWav;
+/// use wavers::cloud::ObjectStoreReader;
+///
+/// async fn open() {
+///     let reader = ObjectStoreReader::from_url("s3://bucket/dataset/speech.wav").await.unwrap();
+///     let mut wav: AsyncWav<f32, _> = AsyncWav::new(reader).await.unwrap();
+///     let first_second = {
+///         let mut buf = vec![0.0; wav.sample_rate() as usize * wav.n_channels() as usize];
+///         let n_frames = wav.read_block(&mut buf, wav.sample_rate() as usize).await.unwrap();
+///         buf.truncate(n_frames * wav.n_channels() as usize);
+///         buf
+///     };
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ObjectStoreReader {
+    store: Arc<dyn ObjectStore>,
+    path: Path,
+    size: u64,
+}
+
+impl ObjectStoreReader {
+    /// Construct a new ObjectStoreReader over the object at ``path`` in ``store``, requesting its metadata to find its size.
+    ///
+    /// This function will return an error if the object does not exist or its metadata cannot be requested.
+    pub async fn new(store: Arc<dyn ObjectStore>, path: Path) -> WaversResult<Self> {
+        let size = store.head(&path).await?.size;
+        Ok(ObjectStoreReader { store, path, size })
+    }
+
+    /// Construct a new ObjectStoreReader from the URL of an object, e.g. ``s3://bucket/path/to/wav.wav``, ``gs://bucket/path/to/wav.wav`` or ``az://container/path/to/wav.wav``.
+    /// The store is configured from the URL alone, so credentials are taken from the environment as far as the ``object_store`` crate supports it.
+    ///
+    /// This function will return an error if the URL is invalid or does not name a supported store, or if the object does not exist.
+    pub async fn from_url(url: &str) -> WaversResult<Self> {
+        let url = url::Url::parse(url)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+        let (store, path) = object_store::parse_url(&url)?;
+        Self::new(Arc::from(store), path).await
+    }
+
+    /// Returns the store the object is read from.
+    pub fn store(&self) -> &Arc<dyn ObjectStore> {
+        &self.store
+    }
+
+    /// Returns the path of the object in its store.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size of the object in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl AsyncWavReader for ObjectStoreReader {
+    async fn read_exact_at(&mut self, pos: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        let end = pos + buf.len() as u64;
+        if end > self.size {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        let bytes = self
+            .store
+            .get_range(&self.path, pos..end)
+            .await
+            .map_err(std::io::Error::other)?;
+        if bytes.len() != buf.len() {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.copy_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+impl<T: AudioSample> AsyncWav<T, ObjectStoreReader>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Opens the object at the given URL, e.g. ``s3://bucket/path/to/wav.wav``, and reads its header. See ``ObjectStoreReader::from_url``.
+    ///
+    /// This function will return an error if the object cannot be opened or if its header is invalid.
+    pub async fn from_url(url: &str) -> WaversResult<Self> {
+        Self::new(ObjectStoreReader::from_url(url).await?).await
+    }
+}
+
+#[cfg(test)]
+mod cloud_tests {
+    use super::*;
+    use crate::core::Wav;
+    use object_store::memory::InMemory;
+    use object_store::PutPayload;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Wake};
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls a future until it completes, which is enough for the in-memory store used here, so the tests do not need a runtime.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Arc::new(NoopWaker).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn stored_wavs_match_local_wavs() {
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = Wav::<i16>::from_vec(bytes.clone()).unwrap().read().unwrap();
+
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("dataset/two_channel_i16.wav");
+        block_on(store.put(&path, PutPayload::from(bytes.clone()))).unwrap();
+
+        let reader = block_on(ObjectStoreReader::new(Arc::clone(&store), path)).unwrap();
+        assert_eq!(reader.size(), bytes.len() as u64);
+        let mut wav: AsyncWav<i16, _> = block_on(AsyncWav::new(reader)).unwrap();
+
+        let mut buf = [0i16; 20];
+        wav.seek_frame(100_000).unwrap();
+        assert_eq!(block_on(wav.read_block(&mut buf, 10)).unwrap(), 10);
+        assert_eq!(&buf[..], &expected[200_000..200_020]);
+        wav.seek_frame(0).unwrap();
+        assert_eq!(block_on(wav.read()).unwrap(), expected);
+
+        let missing = Path::from("dataset/missing.wav");
+        assert!(block_on(ObjectStoreReader::new(store, missing)).is_err());
+    }
+}
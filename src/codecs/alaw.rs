@@ -0,0 +1,102 @@
+//! A-law (ITU-T G.711) companding. Each 8-bit A-law code expands to a 13-bit linear sample, which is returned scaled to the 16-bit range.
+
+const SIGN_BIT: u8 = 0x80;
+const QUANT_MASK: u8 = 0x0F;
+const SEG_SHIFT: u8 = 4;
+const SEG_MASK: u8 = 0x70;
+const EVEN_BIT_MASK: u8 = 0x55;
+
+// The end points of each of the 8 segments, in the 13-bit (>> 3) domain.
+const SEG_END: [i16; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+
+/// Expands a single A-law code to a 16-bit linear PCM sample.
+#[inline(always)]
+pub const fn decode_sample(a_law: u8) -> i16 {
+    let a_law = a_law ^ EVEN_BIT_MASK;
+    let mut t: i16 = ((a_law & QUANT_MASK) as i16) << 4;
+    let seg = (a_law & SEG_MASK) >> SEG_SHIFT;
+    match seg {
+        0 => t += 8,
+        1 => t += 0x108,
+        _ => {
+            t += 0x108;
+            t <<= seg - 1;
+        }
+    }
+    if a_law & SIGN_BIT != 0 {
+        t
+    } else {
+        -t
+    }
+}
+
+/// Compresses a single 16-bit linear PCM sample to an A-law code.
+#[inline(always)]
+pub const fn encode_sample(sample: i16) -> u8 {
+    let mut pcm = sample >> 3;
+    let mask: u8 = if pcm >= 0 {
+        0xD5 // sign (7th) bit = 1
+    } else {
+        pcm = -pcm - 1;
+        EVEN_BIT_MASK // sign bit = 0
+    };
+
+    let mut seg = 0;
+    while seg < SEG_END.len() && pcm > SEG_END[seg] {
+        seg += 1;
+    }
+
+    if seg >= SEG_END.len() {
+        return 0x7F ^ mask;
+    }
+
+    let mut a_law = (seg as u8) << SEG_SHIFT;
+    if seg < 2 {
+        a_law |= ((pcm >> 1) as u8) & QUANT_MASK;
+    } else {
+        a_law |= ((pcm >> seg) as u8) & QUANT_MASK;
+    }
+    a_law ^ mask
+}
+
+/// Expands a buffer of A-law codes to 16-bit linear PCM samples.
+pub fn decode(bytes: &[u8]) -> Box<[i16]> {
+    bytes.iter().map(|b| decode_sample(*b)).collect()
+}
+
+/// Compresses a buffer of 16-bit linear PCM samples to A-law codes.
+pub fn encode(samples: &[i16]) -> Box<[u8]> {
+    samples.iter().map(|s| encode_sample(*s)).collect()
+}
+
+#[cfg(test)]
+mod alaw_tests {
+    use super::*;
+
+    #[test]
+    fn decode_known_values() {
+        assert_eq!(decode_sample(0xD5), 8);
+        assert_eq!(decode_sample(0x55), -8);
+        assert_eq!(decode_sample(0xAA), 32256);
+        assert_eq!(decode_sample(0x2A), -32256);
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for code in 0..=u8::MAX {
+            assert_eq!(
+                encode_sample(decode_sample(code)),
+                code,
+                "A-law code {:#04x} did not survive a round trip",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn encode_clips_to_largest_segment() {
+        assert_eq!(decode_sample(encode_sample(i16::MAX)), 32256);
+        assert_eq!(decode_sample(encode_sample(i16::MIN)), -32256);
+        assert_eq!(encode_sample(0), 0xD5);
+    }
+}
@@ -0,0 +1,400 @@
+//! GSM 6.10 full rate speech decoding.
+//!
+//! Wav files store GSM 6.10 in the Microsoft (WAV49) layout. Each block of ``BLOCK_ALIGN`` bytes holds two 260-bit frames of 160 samples,
+//! with the frame parameters packed least significant bit first. The decoder is a fixed point implementation of the RPE-LTP synthesis described in the GSM 06.10 specification.
+use crate::error::{FormatError, WaversResult};
+
+/// The number of bytes in a block of two frames.
+pub const BLOCK_ALIGN: u16 = 65;
+
+/// The number of samples in a single frame.
+pub const FRAME_SIZE: usize = 160;
+
+/// The number of samples stored in a single block.
+pub const SAMPLES_PER_BLOCK: usize = 2 * FRAME_SIZE;
+
+const SUBFRAME_SIZE: usize = 40;
+const N_SUBFRAMES: usize = 4;
+const N_RPE_PULSES: usize = 13;
+
+// Number of bits used by each of the eight coded log area ratios
+const LAR_BITS: [u32; 8] = [6, 6, 5, 5, 4, 4, 3, 3];
+// (B, MIC, INVA) used to decode each of the log area ratios
+const LAR_DECODING: [(i16, i16, i16); 8] = [
+    (0, -32, 13107),
+    (0, -32, 13107),
+    (2048, -16, 13107),
+    (-2560, -16, 13107),
+    (94, -8, 19223),
+    (-1792, -8, 17476),
+    (-341, -4, 31454),
+    (-1144, -4, 29708),
+];
+// Quantized long term predictor gains
+const QLB: [i16; 4] = [3277, 11469, 21299, 32767];
+// Normalized RPE pulse mantissas
+const FAC: [i16; 8] = [18431, 20479, 22527, 24575, 26623, 28671, 30719, 32767];
+
+/// Returns the number of samples stored in ``data_size`` bytes of encoded data. A trailing partial block is ignored.
+pub const fn n_samples(data_size: usize) -> usize {
+    (data_size / BLOCK_ALIGN as usize) * SAMPLES_PER_BLOCK
+}
+
+/// Decodes a buffer of GSM 6.10 (WAV49) blocks to 16-bit linear PCM samples.
+pub fn decode(bytes: &[u8]) -> WaversResult<Box<[i16]>> {
+    if bytes.len() < BLOCK_ALIGN as usize {
+        return Err(FormatError::InvalidGsmData("no complete blocks").into());
+    }
+    let mut samples: Vec<i16> = Vec::with_capacity(n_samples(bytes.len()));
+    decode_blocks(&mut Decoder::new(), bytes, &mut samples);
+    Ok(samples.into_boxed_slice())
+}
+
+/// Decodes the whole blocks of ``bytes`` using the state in ``decoder``, appending the samples to ``samples``. A trailing partial block is ignored.
+/// The state carries on from the previous call, so a file can be decoded a few blocks at a time.
+pub(crate) fn decode_blocks(decoder: &mut Decoder, bytes: &[u8], samples: &mut Vec<i16>) {
+    for block in bytes.chunks_exact(BLOCK_ALIGN as usize) {
+        let mut reader = BitReader::new(block);
+        for _ in 0..2 {
+            let frame = Frame::from_bits(&mut reader);
+            decoder.decode_frame(&frame, samples);
+        }
+    }
+}
+
+#[inline(always)]
+const fn saturate(x: i32) -> i16 {
+    if x > i16::MAX as i32 {
+        i16::MAX
+    } else if x < i16::MIN as i32 {
+        i16::MIN
+    } else {
+        x as i16
+    }
+}
+
+#[inline(always)]
+const fn add(a: i16, b: i16) -> i16 {
+    saturate(a as i32 + b as i32)
+}
+
+#[inline(always)]
+const fn sub(a: i16, b: i16) -> i16 {
+    saturate(a as i32 - b as i32)
+}
+
+/// Multiplication of two Q15 values with rounding.
+#[inline(always)]
+const fn mult_r(a: i16, b: i16) -> i16 {
+    if a == i16::MIN && b == i16::MIN {
+        i16::MAX
+    } else {
+        ((a as i32 * b as i32 + 16384) >> 15) as i16
+    }
+}
+
+#[inline(always)]
+const fn asr(a: i16, n: i16) -> i16 {
+    if n >= 16 {
+        -((a < 0) as i16)
+    } else if n <= -16 {
+        0
+    } else if n < 0 {
+        a.wrapping_shl(-n as u32)
+    } else {
+        a >> n
+    }
+}
+
+#[inline(always)]
+const fn asl(a: i16, n: i16) -> i16 {
+    if n >= 16 {
+        0
+    } else if n <= -16 {
+        -((a < 0) as i16)
+    } else if n < 0 {
+        asr(a, -n)
+    } else {
+        a.wrapping_shl(n as u32)
+    }
+}
+
+/// Reads the frame parameters, least significant bit first.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0 }
+    }
+
+    fn read(&mut self, n_bits: u32) -> i16 {
+        let mut value = 0;
+        for i in 0..n_bits {
+            let bit = (self.bytes[self.position / 8] >> (self.position % 8)) & 1;
+            value |= (bit as i16) << i;
+            self.position += 1;
+        }
+        value
+    }
+}
+
+#[derive(Default)]
+struct SubFrame {
+    lag: i16,
+    gain: i16,
+    grid: i16,
+    block_amplitude: i16,
+    pulses: [i16; N_RPE_PULSES],
+}
+
+struct Frame {
+    log_area_ratios: [i16; 8],
+    sub_frames: [SubFrame; N_SUBFRAMES],
+}
+
+impl Frame {
+    fn from_bits(reader: &mut BitReader) -> Self {
+        let mut log_area_ratios = [0; 8];
+        for (lar, n_bits) in log_area_ratios.iter_mut().zip(LAR_BITS) {
+            *lar = reader.read(n_bits);
+        }
+        let mut sub_frames: [SubFrame; N_SUBFRAMES] = Default::default();
+        for sub_frame in sub_frames.iter_mut() {
+            sub_frame.lag = reader.read(7);
+            sub_frame.gain = reader.read(2);
+            sub_frame.grid = reader.read(2);
+            sub_frame.block_amplitude = reader.read(6);
+            for pulse in sub_frame.pulses.iter_mut() {
+                *pulse = reader.read(3);
+            }
+        }
+        Frame {
+            log_area_ratios,
+            sub_frames,
+        }
+    }
+}
+
+/// The state carried between frames.
+pub(crate) struct Decoder {
+    // Reconstructed long term residual, the last 120 samples are the history used by the long term predictor
+    dp: [i16; 160],
+    lar_pp: [[i16; 8]; 2],
+    j: usize,
+    nrp: i16,
+    v: [i16; 9],
+    msr: i16,
+}
+
+impl Decoder {
+    pub(crate) fn new() -> Self {
+        Decoder {
+            dp: [0; 160],
+            lar_pp: [[0; 8]; 2],
+            j: 0,
+            nrp: 40,
+            v: [0; 9],
+            msr: 0,
+        }
+    }
+
+    fn decode_frame(&mut self, frame: &Frame, samples: &mut Vec<i16>) {
+        let mut wt = [0i16; FRAME_SIZE];
+        for (sub_frame, wt) in frame
+            .sub_frames
+            .iter()
+            .zip(wt.chunks_exact_mut(SUBFRAME_SIZE))
+        {
+            let erp = rpe_decoding(sub_frame);
+            self.long_term_synthesis(sub_frame.lag, sub_frame.gain, &erp);
+            wt.copy_from_slice(&self.dp[120..160]);
+        }
+
+        let mut s = [0i16; FRAME_SIZE];
+        self.short_term_synthesis(&frame.log_area_ratios, &wt, &mut s);
+
+        // De-emphasis filtering, followed by truncation and upscaling
+        for sample in s {
+            let temp = mult_r(self.msr, 28180);
+            self.msr = add(sample, temp);
+            samples.push(add(self.msr, self.msr) & !0x7);
+        }
+    }
+
+    fn long_term_synthesis(&mut self, lag: i16, gain: i16, erp: &[i16; SUBFRAME_SIZE]) {
+        // Out of range lags repeat the previous lag
+        let nr = if (40..=120).contains(&lag) {
+            lag
+        } else {
+            self.nrp
+        };
+        self.nrp = nr;
+        let brp = QLB[gain as usize];
+        for (k, erp) in erp.iter().enumerate() {
+            let drpp = mult_r(brp, self.dp[120 + k - nr as usize]);
+            self.dp[120 + k] = add(*erp, drpp);
+        }
+        self.dp.copy_within(40..160, 0);
+    }
+
+    fn short_term_synthesis(
+        &mut self,
+        lar_c: &[i16; 8],
+        wt: &[i16; FRAME_SIZE],
+        s: &mut [i16; FRAME_SIZE],
+    ) {
+        let lar_pp_j = decode_log_area_ratios(lar_c);
+        let lar_pp_j_1 = self.lar_pp[self.j ^ 1];
+        self.lar_pp[self.j] = lar_pp_j;
+        self.j ^= 1;
+
+        // The reflection coefficients are interpolated with the previous frame for the first 40 samples
+        for (start, end) in [(0, 13), (13, 27), (27, 40), (40, 160)] {
+            let mut lar_p = [0i16; 8];
+            for i in 0..8 {
+                lar_p[i] = match start {
+                    0 => add(
+                        add(lar_pp_j_1[i] >> 2, lar_pp_j[i] >> 2),
+                        lar_pp_j_1[i] >> 1,
+                    ),
+                    13 => add(lar_pp_j_1[i] >> 1, lar_pp_j[i] >> 1),
+                    27 => add(add(lar_pp_j_1[i] >> 2, lar_pp_j[i] >> 2), lar_pp_j[i] >> 1),
+                    _ => lar_pp_j[i],
+                };
+            }
+            let rrp = lar_p_to_rp(lar_p);
+
+            for k in start..end {
+                let mut sri = wt[k];
+                for i in (0..8).rev() {
+                    sri = sub(sri, mult_r(rrp[i], self.v[i]));
+                    self.v[i + 1] = add(self.v[i], mult_r(rrp[i], sri));
+                }
+                self.v[0] = sri;
+                s[k] = sri;
+            }
+        }
+    }
+}
+
+fn decode_log_area_ratios(lar_c: &[i16; 8]) -> [i16; 8] {
+    let mut lar_pp = [0; 8];
+    for ((lar, coded), (b, mic, inva)) in lar_pp.iter_mut().zip(lar_c).zip(LAR_DECODING) {
+        let temp = add(*coded, mic) << 10;
+        let temp = sub(temp, b << 1);
+        let temp = mult_r(inva, temp);
+        *lar = add(temp, temp);
+    }
+    lar_pp
+}
+
+fn lar_p_to_rp(lar_p: [i16; 8]) -> [i16; 8] {
+    lar_p.map(|lar| {
+        let temp = if lar == i16::MIN { i16::MAX } else { lar.abs() };
+        let rp = if temp < 11059 {
+            temp << 1
+        } else if temp < 20070 {
+            temp + 11059
+        } else {
+            add(temp >> 2, 26112)
+        };
+        if lar < 0 {
+            -rp
+        } else {
+            rp
+        }
+    })
+}
+
+fn rpe_decoding(sub_frame: &SubFrame) -> [i16; SUBFRAME_SIZE] {
+    // Split the block amplitude into an exponent and mantissa
+    let xmaxc = sub_frame.block_amplitude;
+    let mut exp = if xmaxc > 15 { (xmaxc >> 3) - 1 } else { 0 };
+    let mut mant = xmaxc - (exp << 3);
+    if mant == 0 {
+        exp = -4;
+        mant = 7;
+    } else {
+        while mant <= 7 {
+            mant = mant << 1 | 1;
+            exp -= 1;
+        }
+        mant -= 8;
+    }
+
+    let temp1 = FAC[mant as usize];
+    let temp2 = sub(6, exp);
+    let temp3 = asl(1, sub(temp2, 1));
+
+    // Place the pulses on the selected grid
+    let mut erp = [0; SUBFRAME_SIZE];
+    for (i, pulse) in sub_frame.pulses.iter().enumerate() {
+        let temp = ((pulse << 1) - 7) << 12;
+        let temp = add(mult_r(temp1, temp), temp3);
+        erp[sub_frame.grid as usize + 3 * i] = asr(temp, temp2);
+    }
+    erp
+}
+
+#[cfg(test)]
+mod gsm610_tests {
+    use super::*;
+
+    #[test]
+    fn block_sizes() {
+        assert_eq!(n_samples(65 * 3), 960);
+        assert_eq!(n_samples(65 * 3 + 64), 960);
+    }
+
+    #[test]
+    fn reads_bits_lsb_first() {
+        let bytes = [0b1010_1100, 0b0000_0001];
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read(2), 0b00);
+        assert_eq!(reader.read(3), 0b011);
+        assert_eq!(reader.read(4), 0b1101);
+    }
+
+    #[test]
+    fn silent_block_decodes_to_near_silence() {
+        // Zero log area ratios and pulses at the centre of the quantizer with the smallest amplitude
+        let mut block = [0u8; BLOCK_ALIGN as usize];
+        let mut bits: Vec<(i16, u32)> = Vec::new();
+        for _ in 0..2 {
+            bits.extend(
+                LAR_BITS
+                    .iter()
+                    .zip(LAR_DECODING)
+                    .map(|(n, (_, mic, _))| (-mic, *n)),
+            );
+            for _ in 0..N_SUBFRAMES {
+                bits.extend([(40, 7), (0, 2), (0, 2), (0, 6)]);
+                bits.extend([(4, 3); N_RPE_PULSES]);
+            }
+        }
+        let mut position = 0;
+        for (value, n_bits) in bits {
+            for i in 0..n_bits {
+                block[position / 8] |= (((value >> i) & 1) as u8) << (position % 8);
+                position += 1;
+            }
+        }
+        assert_eq!(position, 520);
+
+        let samples = decode(&block).unwrap();
+        assert_eq!(samples.len(), SAMPLES_PER_BLOCK);
+        assert!(
+            samples.iter().all(|s| s.abs() <= 64),
+            "Expected near silence, got {:?}",
+            samples
+        );
+    }
+
+    #[test]
+    fn incomplete_block_is_an_error() {
+        assert!(decode(&[0u8; 64]).is_err());
+    }
+}
@@ -0,0 +1,6 @@
+//! Module containing the codecs used to decode and encode wav encodings which are not stored as linear PCM or IEEE float samples.
+//! Each codec converts between its encoded byte representation and 16-bit linear PCM, which can then be converted to any ``AudioSample``.
+pub mod alaw;
+pub mod gsm610;
+pub mod ms_adpcm;
+pub mod mulaw;
@@ -0,0 +1,214 @@
+//! Microsoft ADPCM decoding.
+//!
+//! The samples are stored in blocks of ``block_align`` bytes. Each block begins with a header holding the predictor index, the initial delta and the first two samples of every channel,
+//! followed by 4-bit codes which are interleaved between the channels.
+use crate::error::{FormatError, WaversResult};
+
+/// The number of bytes in a block header for each channel. (1 byte predictor, 2 byte delta and two 2 byte samples)
+pub const BLOCK_HEADER_SIZE: usize = 7;
+
+/// The standard set of predictor coefficients. Encoders are required to include these in the fmt chunk, in this order.
+pub const DEFAULT_COEFFICIENTS: [(i16, i16); 7] = [
+    (256, 0),
+    (512, -256),
+    (0, 0),
+    (192, 64),
+    (240, 0),
+    (460, -208),
+    (392, -232),
+];
+
+const ADAPTATION_TABLE: [i32; 16] = [
+    230, 230, 230, 230, 307, 409, 512, 614, 768, 614, 512, 409, 307, 230, 230, 230,
+];
+
+const MIN_DELTA: i32 = 16;
+
+/// Returns the number of samples, per channel, stored in a single block.
+pub const fn samples_per_block(block_align: u16, n_channels: u16) -> usize {
+    let header_size = BLOCK_HEADER_SIZE * n_channels as usize;
+    if n_channels == 0 || (block_align as usize) < header_size {
+        return 0;
+    }
+    ((block_align as usize - header_size) * 2) / n_channels as usize + 2
+}
+
+/// Returns the total number of samples, across all channels, stored in ``data_size`` bytes of encoded data.
+/// A trailing partial block is included provided it contains a complete block header.
+pub const fn n_samples(data_size: usize, block_align: u16, n_channels: u16) -> usize {
+    if block_align == 0 || n_channels == 0 {
+        return 0;
+    }
+    let n_channels_usize = n_channels as usize;
+    let header_size = BLOCK_HEADER_SIZE * n_channels_usize;
+    let remainder = data_size % block_align as usize;
+
+    let mut n_frames =
+        (data_size / block_align as usize) * samples_per_block(block_align, n_channels);
+    if remainder >= header_size {
+        n_frames += ((remainder - header_size) * 2) / n_channels_usize + 2;
+    }
+    n_frames * n_channels_usize
+}
+
+/// Parses the predictor coefficients from the extension of the fmt chunk (the bytes following the cbSize field).
+/// Falls back to the default coefficients if the extension is not present.
+pub fn coefficients_from_extension(extension: &[u8]) -> WaversResult<Vec<(i16, i16)>> {
+    if extension.len() < 4 {
+        return Ok(DEFAULT_COEFFICIENTS.to_vec());
+    }
+    // The first two bytes are the samples per block, which can be calculated from the block align
+    let n_coefficients = u16::from_le_bytes([extension[2], extension[3]]) as usize;
+    let coefficient_bytes = &extension[4..];
+    if n_coefficients == 0 || coefficient_bytes.len() < n_coefficients * 4 {
+        return Err(FormatError::InvalidAdpcmData("incomplete coefficient table").into());
+    }
+
+    Ok(coefficient_bytes
+        .chunks_exact(4)
+        .take(n_coefficients)
+        .map(|c| {
+            (
+                i16::from_le_bytes([c[0], c[1]]),
+                i16::from_le_bytes([c[2], c[3]]),
+            )
+        })
+        .collect())
+}
+
+/// Decodes a buffer of MS ADPCM blocks to interleaved 16-bit linear PCM samples.
+pub fn decode(
+    bytes: &[u8],
+    n_channels: u16,
+    block_align: u16,
+    coefficients: &[(i16, i16)],
+) -> WaversResult<Box<[i16]>> {
+    let mut samples: Vec<i16> = Vec::with_capacity(n_samples(bytes.len(), block_align, n_channels));
+    decode_into(bytes, n_channels, block_align, coefficients, &mut samples)?;
+    Ok(samples.into_boxed_slice())
+}
+
+/// Decodes a buffer of MS ADPCM blocks in the same way as ``decode``, appending the samples to ``samples``.
+/// Every block is decoded on its own, so a file can be decoded a few blocks at a time.
+pub(crate) fn decode_into(
+    bytes: &[u8],
+    n_channels: u16,
+    block_align: u16,
+    coefficients: &[(i16, i16)],
+    samples: &mut Vec<i16>,
+) -> WaversResult<()> {
+    let n_channels_usize = n_channels as usize;
+    let header_size = BLOCK_HEADER_SIZE * n_channels_usize;
+    if n_channels == 0 || (block_align as usize) < header_size {
+        return Err(
+            FormatError::InvalidAdpcmData("block align is smaller than the block header").into(),
+        );
+    }
+
+    for block in bytes.chunks(block_align as usize) {
+        // A truncated final block without a full header cannot be decoded
+        if block.len() < header_size {
+            break;
+        }
+        decode_block(block, n_channels_usize, coefficients, samples)?;
+    }
+    Ok(())
+}
+
+struct ChannelState {
+    coefficient_1: i32,
+    coefficient_2: i32,
+    delta: i32,
+    sample_1: i32,
+    sample_2: i32,
+}
+
+impl ChannelState {
+    #[inline(always)]
+    fn expand(&mut self, code: u8) -> i16 {
+        let signed_code = ((code << 4) as i8 >> 4) as i32;
+        let predictor =
+            (self.sample_1 * self.coefficient_1 + self.sample_2 * self.coefficient_2) >> 8;
+        let sample = (predictor + signed_code * self.delta).clamp(i16::MIN as i32, i16::MAX as i32);
+
+        self.sample_2 = self.sample_1;
+        self.sample_1 = sample;
+        self.delta = ((ADAPTATION_TABLE[code as usize] * self.delta) >> 8).max(MIN_DELTA);
+        sample as i16
+    }
+}
+
+fn decode_block(
+    block: &[u8],
+    n_channels: usize,
+    coefficients: &[(i16, i16)],
+    samples: &mut Vec<i16>,
+) -> WaversResult<()> {
+    let read_i16 = |offset: usize| i16::from_le_bytes([block[offset], block[offset + 1]]) as i32;
+
+    let mut states: Vec<ChannelState> = Vec::with_capacity(n_channels);
+    for (channel, predictor) in block[..n_channels].iter().enumerate() {
+        let (coefficient_1, coefficient_2) =
+            *coefficients
+                .get(*predictor as usize)
+                .ok_or(FormatError::InvalidAdpcmData(
+                    "predictor index out of range",
+                ))?;
+        states.push(ChannelState {
+            coefficient_1: coefficient_1 as i32,
+            coefficient_2: coefficient_2 as i32,
+            delta: read_i16(n_channels + 2 * channel),
+            sample_1: read_i16(3 * n_channels + 2 * channel),
+            sample_2: read_i16(5 * n_channels + 2 * channel),
+        });
+    }
+
+    // The header samples are stored most recent first
+    samples.extend(states.iter().map(|s| s.sample_2 as i16));
+    samples.extend(states.iter().map(|s| s.sample_1 as i16));
+
+    let mut channel = 0;
+    for byte in &block[BLOCK_HEADER_SIZE * n_channels..] {
+        for code in [byte >> 4, byte & 0x0F] {
+            samples.push(states[channel].expand(code));
+            channel = (channel + 1) % n_channels;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod ms_adpcm_tests {
+    use super::*;
+
+    #[test]
+    fn block_sizes() {
+        assert_eq!(samples_per_block(256, 1), 500);
+        assert_eq!(samples_per_block(512, 2), 500);
+        assert_eq!(samples_per_block(2048, 2), 2036);
+        assert_eq!(n_samples(256 * 3, 256, 1), 1500);
+        // partial block with 10 bytes of codes after the header
+        assert_eq!(n_samples(256 + 17, 256, 1), 500 + 22);
+        // partial block without a complete header
+        assert_eq!(n_samples(256 + 3, 256, 1), 500);
+    }
+
+    #[test]
+    fn decodes_block_header_and_codes() {
+        // predictor 0, delta 16, sample_1 = 100, sample_2 = 50, codes +1 and -1
+        let mut block = vec![0u8];
+        block.extend_from_slice(&16i16.to_le_bytes());
+        block.extend_from_slice(&100i16.to_le_bytes());
+        block.extend_from_slice(&50i16.to_le_bytes());
+        block.push(0x1F);
+
+        let samples = decode(&block, 1, block.len() as u16, &DEFAULT_COEFFICIENTS).unwrap();
+        assert_eq!(&samples[..], &[50, 100, 116, 100]);
+    }
+
+    #[test]
+    fn invalid_predictor_is_an_error() {
+        let block = [7u8, 16, 0, 0, 0, 0, 0, 0x00];
+        assert!(decode(&block, 1, block.len() as u16, &DEFAULT_COEFFICIENTS).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! µ-law (ITU-T G.711) companding. Each 8-bit µ-law code expands to a 14-bit linear sample, which is returned scaled to the 16-bit range.
+
+const SIGN_BIT: u8 = 0x80;
+const QUANT_MASK: u8 = 0x0F;
+const SEG_SHIFT: u8 = 4;
+const SEG_MASK: u8 = 0x70;
+const BIAS: i16 = 0x84;
+const CLIP: i16 = 8159;
+
+// The end points of each of the 8 segments, in the 14-bit (>> 2) domain.
+const SEG_END: [i16; 8] = [0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF, 0x1FFF];
+
+/// Expands a single µ-law code to a 16-bit linear PCM sample.
+#[inline(always)]
+pub const fn decode_sample(mu_law: u8) -> i16 {
+    let mu_law = !mu_law;
+    let mut t: i16 = (((mu_law & QUANT_MASK) as i16) << 3) + BIAS;
+    t <<= (mu_law & SEG_MASK) >> SEG_SHIFT;
+    if mu_law & SIGN_BIT != 0 {
+        BIAS - t
+    } else {
+        t - BIAS
+    }
+}
+
+/// Compresses a single 16-bit linear PCM sample to a µ-law code.
+#[inline(always)]
+pub const fn encode_sample(sample: i16) -> u8 {
+    let mut pcm = sample >> 2;
+    let mask: u8 = if pcm < 0 {
+        pcm = -pcm;
+        0x7F
+    } else {
+        0xFF
+    };
+
+    if pcm > CLIP {
+        pcm = CLIP;
+    }
+    pcm += BIAS >> 2;
+
+    let mut seg = 0;
+    while seg < SEG_END.len() && pcm > SEG_END[seg] {
+        seg += 1;
+    }
+
+    if seg >= SEG_END.len() {
+        return 0x7F ^ mask;
+    }
+
+    let mu_law = ((seg as u8) << SEG_SHIFT) | (((pcm >> (seg + 1)) as u8) & QUANT_MASK);
+    mu_law ^ mask
+}
+
+/// Expands a buffer of µ-law codes to 16-bit linear PCM samples.
+pub fn decode(bytes: &[u8]) -> Box<[i16]> {
+    bytes.iter().map(|b| decode_sample(*b)).collect()
+}
+
+/// Compresses a buffer of 16-bit linear PCM samples to µ-law codes.
+pub fn encode(samples: &[i16]) -> Box<[u8]> {
+    samples.iter().map(|s| encode_sample(*s)).collect()
+}
+
+#[cfg(test)]
+mod mulaw_tests {
+    use super::*;
+
+    #[test]
+    fn decode_known_values() {
+        assert_eq!(decode_sample(0xFF), 0);
+        assert_eq!(decode_sample(0x7F), 0);
+        assert_eq!(decode_sample(0x80), 32124);
+        assert_eq!(decode_sample(0x00), -32124);
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for code in 0..=u8::MAX {
+            // 0x7F is negative zero, which is encoded as positive zero
+            if code == 0x7F {
+                continue;
+            }
+            assert_eq!(
+                encode_sample(decode_sample(code)),
+                code,
+                "µ-law code {:#04x} did not survive a round trip",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn encode_clips_to_largest_segment() {
+        assert_eq!(decode_sample(encode_sample(i16::MAX)), 32124);
+        assert_eq!(decode_sample(encode_sample(i16::MIN)), -32124);
+        assert_eq!(encode_sample(0), 0xFF);
+    }
+}
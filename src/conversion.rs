@@ -2,16 +2,126 @@
 use std::fmt::Debug;
 
 use bytemuck::Pod;
+#[cfg(feature = "half")]
+use half::f16;
 use i24::i24;
 use num_traits::Num;
 
 use crate::core::alloc_sample_buffer;
+use crate::sampler::splitmix64;
+
+// The largest and smallest values representable by a signed 24-bit integer.
+const I24_MAX: i32 = 8_388_607;
+const I24_MIN: i32 = -8_388_608;
+
+/// A source of triangular probability density function (TPDF) dither, which is added to samples as their bit depth is reduced
+/// so that the quantization error becomes uncorrelated noise rather than distortion which follows the signal.
+///
+/// The noise is drawn from a seeded pseudo-random number generator, so the same seed always gives the same output.
+/// With noise shaping, the quantization error of each channel is fed back through the filter of the ``NoiseShaping`` curve.
+#[derive(Debug, Clone)]
+pub(crate) struct Dither {
+    state: u64,
+    shaping: NoiseShaping,
+    n_channels: usize,
+    /// The channel of the next sample.
+    channel: usize,
+    /// The most recent quantization errors of each channel in steps of the encoding, the latest first.
+    errors: Vec<f64>,
+}
+
+/// A noise shaping curve, which moves the quantization noise of dithered samples towards frequencies where it is less audible.
+///
+/// Noise shaping is used alongside dither when reducing the bit depth, e.g. writing 16-bit files from 24-bit or float masters, via ``WriteOptions::noise_shaping``.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoiseShaping {
+    /// Plain TPDF dither, whose noise is spread evenly across all frequencies.
+    #[default]
+    None,
+    /// A simple second-order highpass shaper, which suits any sample rate.
+    SecondOrder,
+    /// The five-tap E-weighted shaper of Lipshitz et al., which follows the sensitivity of the ear and is designed for 44.1 kHz audio.
+    Lipshitz,
+}
+
+impl NoiseShaping {
+    /// Returns the coefficients of the error feedback filter, applied to the most recent quantization error first.
+    pub fn coefficients(&self) -> &'static [f64] {
+        match self {
+            NoiseShaping::None => &[],
+            NoiseShaping::SecondOrder => &[2.0, -1.0],
+            NoiseShaping::Lipshitz => &[2.033, -2.165, 1.959, -1.590, 0.6149],
+        }
+    }
+}
+
+impl Dither {
+    /// Creates a source of dither seeded with ``seed``, which is shaped by ``shaping`` and added to interleaved samples of ``n_channels`` channels.
+    pub(crate) fn new(seed: u64, shaping: NoiseShaping, n_channels: u16) -> Self {
+        let n_channels = n_channels.max(1) as usize;
+        Dither {
+            state: seed,
+            shaping,
+            n_channels,
+            channel: 0,
+            errors: vec![0.0; n_channels * shaping.coefficients().len()],
+        }
+    }
+
+    /// Returns a uniformly distributed value in ``[0, 1)``.
+    fn next_uniform(&mut self) -> f64 {
+        (splitmix64(&mut self.state) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns ``samples`` as ``f64`` with TPDF dither of one least significant bit added and rounded to the resolution of an integer encoding
+    /// of ``n_bits`` bits, the lowest ``padding_bits`` of which are unused. The result is converted to the encoding without any further rounding.
+    pub(crate) fn apply<T: AudioSample>(
+        &mut self,
+        samples: &[T],
+        n_bits: u16,
+        padding_bits: u16,
+    ) -> Vec<f64> {
+        // The conversions from f64 scale by the largest value of the encoding, so this is the step between two values of the encoding
+        let max = (1u64 << (n_bits - 1)) - 1;
+        let lsb = (1u64 << padding_bits) as f64 / max as f64;
+        let (min, max) = (
+            -((1u64 << (n_bits - 1 - padding_bits)) as f64),
+            (max >> padding_bits) as f64,
+        );
+        let coefficients = self.shaping.coefficients();
+        let n_taps = coefficients.len();
+        samples
+            .iter()
+            .map(|sample| {
+                let sample: f64 = sample.convert_to();
+                // The difference of two uniform values has a triangular distribution over (-1, 1)
+                let noise = self.next_uniform() - self.next_uniform();
+                let errors = &mut self.errors[self.channel * n_taps..(self.channel + 1) * n_taps];
+                let feedback: f64 = coefficients
+                    .iter()
+                    .zip(errors.iter())
+                    .map(|(c, e)| c * e)
+                    .sum();
+                let shaped = sample / lsb - feedback;
+                let quantized = (shaped + noise).round().clamp(min, max);
+                if n_taps > 0 {
+                    // The error is at most 1.5 steps unless the sample clipped, which must not be fed back or the filter could become unstable
+                    errors.rotate_right(1);
+                    errors[0] = (quantized - shaped).clamp(-1.5, 1.5);
+                }
+                self.channel = (self.channel + 1) % self.n_channels;
+                quantized * lsb
+            })
+            .collect()
+    }
+}
 
 /// Trait used to indicate that a type is an audio sample and can be treated as such.
 pub trait AudioSample:
     Copy
     + Pod
     + Num
+    + ConvertTo<u8>
     + ConvertTo<i16>
     + ConvertTo<i32>
     + ConvertTo<i24>
@@ -23,6 +133,7 @@ pub trait AudioSample:
 {
 }
 
+impl AudioSample for u8 {}
 impl AudioSample for i16 {}
 impl AudioSample for i24 {}
 impl AudioSample for i32 {}
@@ -56,7 +167,58 @@ where
     }
 }
 
+// u8 //
+// 8-bit wav files are stored as unsigned offset binary, 128 represents silence.
+impl ConvertTo<u8> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        *self
+    }
+}
+
+impl ConvertTo<i16> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> i16 {
+        (*self as i16 - 128) << 8
+    }
+}
+
+impl ConvertTo<i24> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> i24 {
+        i24::from_i32((*self as i32 - 128) << 16)
+    }
+}
+
+impl ConvertTo<i32> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> i32 {
+        (*self as i32 - 128) << 24
+    }
+}
+
+impl ConvertTo<f32> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> f32 {
+        ((*self as f32 - 128.0) / (i8::MAX as f32)).clamp(-1.0, 1.0)
+    }
+}
+
+impl ConvertTo<f64> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> f64 {
+        ((*self as f64 - 128.0) / (i8::MAX as f64)).clamp(-1.0, 1.0)
+    }
+}
+
 // i16 //
+impl ConvertTo<u8> for i16 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        ((*self >> 8) + 128) as u8
+    }
+}
+
 impl ConvertTo<i16> for i16 {
     #[inline(always)]
     fn convert_to(&self) -> i16 {
@@ -93,6 +255,12 @@ impl ConvertTo<f64> for i16 {
 }
 
 // i24 //
+impl ConvertTo<u8> for i24 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        ((self.to_i32() >> 16) + 128) as u8
+    }
+}
 
 impl ConvertTo<i16> for i24 {
     #[inline(always)]
@@ -118,18 +286,25 @@ impl ConvertTo<i32> for i24 {
 impl ConvertTo<f32> for i24 {
     #[inline(always)]
     fn convert_to(&self) -> f32 {
-        (self.to_i32() as f32) / (i32::MAX as f32)
+        ((self.to_i32() as f32) / (I24_MAX as f32)).clamp(-1.0, 1.0)
     }
 }
 
 impl ConvertTo<f64> for i24 {
     #[inline(always)]
     fn convert_to(&self) -> f64 {
-        (self.to_i32() as f64) / (i32::MAX as f64)
+        ((self.to_i32() as f64) / (I24_MAX as f64)).clamp(-1.0, 1.0)
     }
 }
 
 // i32 //
+impl ConvertTo<u8> for i32 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        ((*self >> 24) + 128) as u8
+    }
+}
+
 impl ConvertTo<i16> for i32 {
     #[inline(always)]
     fn convert_to(&self) -> i16 {
@@ -166,6 +341,13 @@ impl ConvertTo<f64> for i32 {
 }
 
 // f32 //
+impl ConvertTo<u8> for f32 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        (((*self * (i8::MAX as f32)).clamp(i8::MIN as f32, i8::MAX as f32)).round() + 128.0) as u8
+    }
+}
+
 impl ConvertTo<i16> for f32 {
     #[inline(always)]
     fn convert_to(&self) -> i16 {
@@ -177,7 +359,7 @@ impl ConvertTo<i24> for f32 {
     #[inline(always)]
     fn convert_to(&self) -> i24 {
         i24::from_i32(
-            ((*self * (i32::MAX as f32)).clamp(i32::MIN as f32, i32::MAX as f32)).round() as i32,
+            ((*self * (I24_MAX as f32)).clamp(I24_MIN as f32, I24_MAX as f32)).round() as i32,
         )
     }
 }
@@ -204,6 +386,13 @@ impl ConvertTo<f64> for f32 {
 }
 
 // f64 //
+impl ConvertTo<u8> for f64 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        (((*self * (i8::MAX as f64)).clamp(i8::MIN as f64, i8::MAX as f64)).round() + 128.0) as u8
+    }
+}
+
 impl ConvertTo<i16> for f64 {
     #[inline(always)]
     fn convert_to(&self) -> i16 {
@@ -215,7 +404,7 @@ impl ConvertTo<i24> for f64 {
     #[inline(always)]
     fn convert_to(&self) -> i24 {
         i24::from_i32(
-            ((*self * (i32::MAX as f64)).clamp(i32::MIN as f64, i32::MAX as f64)).round() as i32,
+            ((*self * (I24_MAX as f64)).clamp(I24_MIN as f64, I24_MAX as f64)).round() as i32,
         )
     }
 }
@@ -241,6 +430,115 @@ impl ConvertTo<f64> for f64 {
     }
 }
 
+// f16 //
+// Half-precision samples are converted via f32, which can represent every f16 value exactly.
+#[cfg(feature = "half")]
+impl AudioSample for f16 {}
+
+#[cfg(feature = "half")]
+impl ConvertTo<u8> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> u8 {
+        self.to_f32().convert_to()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<i16> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> i16 {
+        self.to_f32().convert_to()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<i24> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> i24 {
+        self.to_f32().convert_to()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<i32> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> i32 {
+        self.to_f32().convert_to()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f32> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> f32 {
+        self.to_f32()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f64> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> f64 {
+        self.to_f64()
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for f16 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        *self
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for u8 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f32(self.convert_to())
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for i16 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f32(self.convert_to())
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for i24 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f32(self.convert_to())
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for i32 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f32(self.convert_to())
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for f32 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f32(*self)
+    }
+}
+
+#[cfg(feature = "half")]
+impl ConvertTo<f16> for f64 {
+    #[inline(always)]
+    fn convert_to(&self) -> f16 {
+        f16::from_f64(*self)
+    }
+}
+
 #[cfg(test)]
 mod conversion_tests {
 
@@ -305,6 +603,93 @@ mod conversion_tests {
         }
     }
 
+    #[test]
+    fn i24_to_f32_and_back() {
+        let expected: [i32; 5] = [-I24_MAX, -4_194_304, 0, 4_194_304, I24_MAX];
+        for sample in expected {
+            let as_f32: f32 = i24::from_i32(sample).convert_to();
+            assert!(
+                (-1.0..=1.0).contains(&as_f32),
+                "{} converted to {} which is out of range",
+                sample,
+                as_f32
+            );
+            let back: i24 = as_f32.convert_to();
+            assert_eq!(sample, back.to_i32(), "Failed to round trip {}", sample);
+        }
+
+        let negative_full_scale: f32 = i24::from_i32(I24_MIN).convert_to();
+        assert_eq!(negative_full_scale, -1.0, "The i24 min should map to -1.0");
+
+        let full_scale: i24 = 1.0f64.convert_to();
+        assert_eq!(
+            full_scale.to_i32(),
+            I24_MAX,
+            "1.0 should map to the i24 max"
+        );
+    }
+
+    #[test]
+    fn u8_to_i16_and_back() {
+        let u8_samples: Vec<u8> =
+            read_text_to_vec(Path::new("./test_resources/one_channel_u8.txt")).unwrap();
+        let i16_samples: Vec<i16> =
+            read_text_to_vec(Path::new("./test_resources/one_channel_i16.txt")).unwrap();
+
+        for (expected_sample, actual_sample) in u8_samples.iter().zip(&i16_samples) {
+            let converted_sample: u8 = actual_sample.convert_to();
+            assert_eq!(
+                *expected_sample, converted_sample,
+                "Failed to convert sample {} to u8",
+                actual_sample
+            );
+
+            let widened_sample: i16 = converted_sample.convert_to();
+            assert!(
+                (*actual_sample as i32 - widened_sample as i32).abs() < 256,
+                "{} widened to {} which is not within one u8 step of {}",
+                converted_sample,
+                widened_sample,
+                actual_sample
+            );
+        }
+    }
+
+    #[test]
+    fn u8_silence_and_full_scale() {
+        let silence: f32 = 128u8.convert_to();
+        assert_eq!(silence, 0.0, "128 should be silence");
+
+        let full_scale: u8 = 1.0f32.convert_to();
+        assert_eq!(full_scale, 255, "1.0 should map to 255");
+
+        let negative_full_scale: u8 = (-1.0f64).convert_to();
+        assert_eq!(negative_full_scale, 1, "-1.0 should map to 1");
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn i16_to_f16_and_back() {
+        let i16_samples: Vec<i16> =
+            read_text_to_vec(Path::new("./test_resources/one_channel_i16.txt")).unwrap();
+        let f32_samples: Vec<f32> =
+            read_text_to_vec(Path::new("./test_resources/one_channel_f32.txt")).unwrap();
+
+        for (expected_sample, sample) in f32_samples.iter().zip(&i16_samples) {
+            let half_sample: f16 = sample.convert_to();
+            // f16 has an 11-bit significand, so allow for a relative error of 2^-11
+            assert_approx_eq!(*expected_sample as f64, half_sample.to_f64(), 1e-3);
+
+            let round_trip: i16 = half_sample.convert_to();
+            assert!(
+                (round_trip as i32 - *sample as i32).abs() <= 16,
+                "{} != {}",
+                round_trip,
+                sample
+            );
+        }
+    }
+
     #[cfg(test)]
     fn read_lines<P>(filename: P) -> std::io::Result<std::io::Lines<std::io::BufReader<File>>>
     where
@@ -1,14 +1,19 @@
 /// Module contains the core structs, ``Wav`` and ``Samples`` for working working with wav files.
 use std::alloc::Layout;
 use std::any::TypeId;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use std::fs::File;
-use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
-use std::ops::{Deref, DerefMut};
-use std::path::Path;
+use std::io::{BufWriter, Cursor, IoSlice, Read, Seek, SeekFrom, Write};
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
-use bytemuck::cast_slice;
+use bytemuck::{cast_slice, cast_slice_mut};
 use i24::i24;
 
 #[cfg(feature = "ndarray")]
@@ -20,22 +25,394 @@ use colored::*;
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
-use crate::chunks::{read_chunk, Chunk, ListChunk};
-use crate::chunks::{DATA, FACT, LIST};
+use crate::chunks::bext::time_reference_to_time_of_day;
+use crate::chunks::fmt::{ExtFmtChunkInfo, FMT_CB_SIZE};
+use crate::chunks::{read_chunk, Chunk, InfoTag, ListChunk};
+use crate::chunks::{
+    AcidChunk, AdtlChunk, AxmlChunk, BextChunk, CueChunk, CuePoint, InstChunk, IxmlChunk, Marker,
+    PlaylistSegment, PlstChunk, RawChunk, SmplChunk, ACID, ADTL, AXML, BEXT, CUE, DATA, DS64, FACT,
+    FMT, ID3, INST, IXML, LIST, PLST, SMPL,
+};
+use crate::codecs::{alaw, gsm610, ms_adpcm, mulaw};
 use crate::conversion::ConvertSlice;
 
 use crate::conversion::{AudioSample, ConvertTo};
 use crate::error::{FormatError, WaversError, WaversResult};
-use crate::header::{read_header, ChunkIdentifier, HeaderChunkInfo, WavHeader};
-use crate::iter::{BlockIterator, ChannelIterator, FrameIterator};
+use crate::gain::{db_to_gain, is_integer, peak_gain, scale_samples};
+use crate::header::{
+    header_identifier, read_header, read_stream_header, ChunkIdentifier, HeaderChunkInfo, WavHeader,
+};
+use crate::iter::{
+    BlockIterator, ChannelIterator, Frame, FrameIterator, LoopIterator, SampleIterator,
+    SampleWindowIterator, Segment, SegmentIterator, SingleChannelIterator, WindowIterator,
+};
+use crate::mix::Matrix;
+use crate::transcode::Resampler;
 use crate::wav_type::WavType;
-use crate::{log, FactChunk, FmtChunk, FormatCode};
+use crate::writer::{Endianness, WavSpec, WavWriter, WriteOptions, ITER_BLOCK_FRAMES};
+use crate::{log, write_all_vectored, ChannelLayout, FactChunk, FmtChunk, FormatCode};
+
+/// The number of bytes decoded at a time when reading into a caller-provided buffer, such as by ``Wav::read_block``.
+pub const DECODE_BUFFER_SIZE: usize = 8192;
+
+/// The capacity of the buffers used to read and write files, unless another size is given, e.g. by ``Wav::from_path_with_buffer_size``.
+pub const DEFAULT_BUFFER_SIZE: usize = 8192;
+
+/// The number of bytes of samples read or written between calls to a progress callback.
+pub const PROGRESS_INTERVAL: usize = 1 << 20;
+
+/// A progress callback, which is called with the number of bytes done so far and the total number of bytes.
+pub type ProgressFn = fn(u64, u64);
+
+/// A handle used to cancel a long-running read, write or transcode, e.g. from a UI thread.
+///
+/// Clones of a token share the same flag, so one clone can be passed to the operation while another is kept to cancel it.
+/// The flag is checked every ``PROGRESS_INTERVAL`` bytes, and a cancelled operation returns ``WaversError::Cancelled``.
+///
+/// # Example
+/// ```no_run
+/// use wavers::{CancelToken, Wav};
+///
+/// fn main() {
+///     let token = CancelToken::new();
+///     let canceller = token.clone();
+///     std::thread::spawn(move || {
+///         std::thread::sleep(std::time::Duration::from_secs(1));
+///         canceller.cancel();
+///     });
+///     let mut wav: Wav<f32> = Wav::from_path("path/to/huge.wav").unwrap();
+///     match wav.read_cancellable(&token) {
+///         Ok(samples) => println!("Read {} samples", samples.len()),
+///         Err(e) => println!("{}", e),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Constructs a new token which has not been cancelled.
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// Cancels the operations using this token, or any of its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Returns ``WaversError::Cancelled`` if the token has been cancelled.
+    pub(crate) fn check(&self) -> WaversResult<()> {
+        match self.is_cancelled() {
+            true => Err(WaversError::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        CancelToken(flag)
+    }
+}
+
+// Tokens are equal if they share the same flag
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
 
 /// Trait representing a type that can be used to read and seek.
 pub trait ReadSeek: Read + Seek {}
 
 impl<T: Read + Seek> ReadSeek for T {}
 
+/// Opens a new reader over the file a Wav struct was read from, so that it can be cloned by ``Wav::try_clone``.
+type Reopen = Arc<dyn Fn() -> std::io::Result<Box<dyn ReadSeek + Send>> + Send + Sync>;
+
+/// A buffer shared by the readers of a Wav struct read from memory and its clones.
+struct SharedBuffer<B>(Arc<B>);
+
+impl<B: AsRef<[u8]>> AsRef<[u8]> for SharedBuffer<B> {
+    fn as_ref(&self) -> &[u8] {
+        (*self.0).as_ref()
+    }
+}
+
+/// Reader which shifts every position by ``offset`` bytes, so that headerless PCM, or part of a data chunk read by other means,
+/// is found where the data chunk of the header says it is.
+struct RawPcmReader {
+    inner: Box<dyn ReadSeek>,
+    offset: i64,
+}
+
+impl Read for RawPcmReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for RawPcmReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => {
+                let pos = pos as i64 - self.offset;
+                if pos < 0 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Cannot seek before the start of the raw PCM data",
+                    ));
+                }
+                SeekFrom::Start(pos as u64)
+            }
+            pos => pos,
+        };
+        Ok((self.inner.seek(pos)? as i64 + self.offset) as u64)
+    }
+}
+
+/// Reader for sources which cannot seek, such as pipes, stdin and network streams.
+///
+/// Seeking only records the new position. Reading from a later position skips the bytes in between,
+/// and an error is only returned when reading from a position in the data chunk which has already been passed.
+/// Every byte before the start of the data chunk is kept, so the header chunks can still be read in any order.
+struct ForwardReader {
+    inner: Box<dyn Read>,
+    /// The bytes of the stream before the start of the data chunk, or every byte read so far while the header is being read.
+    header: Vec<u8>,
+    /// Bytes of the data chunk which have been taken from the stream but not yet read, the first of which is at ``buffer_start``.
+    buffer: Vec<u8>,
+    buffer_start: u64,
+    /// The number of bytes taken from the stream so far.
+    stream_pos: u64,
+    pos: u64,
+    /// The offset of the samples of the data chunk, which is set once the header has been read.
+    data_start: Rc<Cell<Option<u64>>>,
+    split: bool,
+}
+
+impl ForwardReader {
+    fn new(inner: Box<dyn Read>, data_start: Rc<Cell<Option<u64>>>) -> Self {
+        ForwardReader {
+            inner,
+            header: Vec::new(),
+            buffer: Vec::new(),
+            buffer_start: 0,
+            stream_pos: 0,
+            pos: 0,
+            data_start,
+            split: false,
+        }
+    }
+
+    /// Once the header has been read, moves the bytes of the data chunk which were read with it out of the header.
+    fn split_header(&mut self) {
+        if self.split {
+            return;
+        }
+        if let Some(data_start) = self.data_start.get() {
+            self.split = true;
+            match self.header.len() as u64 > data_start {
+                true => {
+                    self.buffer = self.header.split_off(data_start as usize);
+                    self.buffer_start = data_start;
+                }
+                false => self.buffer_start = self.stream_pos,
+            }
+        }
+    }
+}
+
+impl Read for ForwardReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.split_header();
+        if self.split && self.pos > self.buffer_start {
+            // Bytes of the data chunk which have been passed are no longer needed
+            let n_passed = ((self.pos - self.buffer_start) as usize).min(self.buffer.len());
+            self.buffer.drain(..n_passed);
+            self.buffer_start += n_passed as u64;
+        }
+
+        let n_read = if self.pos < self.header.len() as u64 {
+            let header = &self.header[self.pos as usize..];
+            let n_read = header.len().min(buf.len());
+            buf[..n_read].copy_from_slice(&header[..n_read]);
+            n_read
+        } else if self.pos < self.buffer_start {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Cannot go back to samples which have already been read from a stream which cannot seek",
+            ));
+        } else if self.pos < self.stream_pos {
+            let n_read = self.buffer.len().min(buf.len());
+            buf[..n_read].copy_from_slice(&self.buffer[..n_read]);
+            n_read
+        } else {
+            if self.stream_pos < self.pos {
+                let n_skip = self.pos - self.stream_pos;
+                let mut skipped = (&mut self.inner).take(n_skip);
+                let n_skipped = match self.split {
+                    true => std::io::copy(&mut skipped, &mut std::io::sink())?,
+                    false => skipped.read_to_end(&mut self.header)? as u64,
+                };
+                self.stream_pos += n_skipped;
+                if self.split {
+                    self.buffer_start = self.stream_pos;
+                }
+                if n_skipped < n_skip {
+                    return Ok(0);
+                }
+            }
+            let n_read = self.inner.read(buf)?;
+            match self.split {
+                true => self.buffer_start += n_read as u64,
+                false => self.header.extend_from_slice(&buf[..n_read]),
+            }
+            self.stream_pos += n_read as u64;
+            n_read
+        };
+        self.pos += n_read as u64;
+        Ok(n_read)
+    }
+}
+
+impl Seek for ForwardReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            SeekFrom::End(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "Cannot seek from the end of a stream which cannot seek",
+                ))
+            }
+        };
+        self.pos = pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot seek before the start of the stream",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// A handle to a wav file which can be sent to other threads, returned by ``Wav::handle``.
+/// Each call to ``open`` returns a new Wav struct with its own reader and position, sharing the already parsed header,
+/// so that several threads can decode different regions of the same file at the same time.
+///
+/// # Example
+/// ```no_run
+/// use wavers::Wav;
+///
+/// fn main() {
+///     let wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+///     let handle = wav.handle().unwrap();
+///     let n_frames = wav.n_frames() as u64;
+///     let second_half = std::thread::spawn(move || {
+///         let mut wav: Wav<f32> = handle.open().unwrap();
+///         wav.seek_frame(n_frames / 2).unwrap();
+///         wav.read().unwrap()
+///     });
+///     println!("{}", second_half.join().unwrap().len());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct WavHandle {
+    reopen: Reopen,
+    wav_info: WavInfo,
+    path: Option<PathBuf>,
+    target_sample_rate: Option<i32>,
+    time_reference: Option<u64>,
+}
+
+impl WavHandle {
+    /// Opens a new Wav struct over the file, positioned at the beginning of the data chunk.
+    ///
+    /// This function will return an error if the file cannot be opened again.
+    pub fn open<T: AudioSample>(&self) -> WaversResult<Wav<T>>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        let mut reader = (self.reopen)()?;
+        let (data_offset, _) = self.wav_info.wav_header.data().into();
+        reader.seek(SeekFrom::Start(data_offset as u64 + 8))?;
+
+        Ok(Wav {
+            _phantom: std::marker::PhantomData,
+            reader,
+            wav_info: self.wav_info.clone(),
+            reopen: Some(Arc::clone(&self.reopen)),
+            path: self.path.clone(),
+            target_sample_rate: self.target_sample_rate,
+            time_reference: self.time_reference,
+        })
+    }
+}
+
+/// Decodes a block encoded wav file, such as MS ADPCM, a few blocks at a time, so that the whole file is never held in memory.
+/// Returned by ``Wav::block_decoder`` and used with ``Wav::read_encoded_blocks``.
+pub(crate) struct BlockDecoder {
+    wav_type: WavType,
+    n_channels: u16,
+    block_align: u16,
+    /// The number of blocks read at a time, which hold at least ``ITER_BLOCK_FRAMES`` frames.
+    n_blocks: usize,
+    /// The predictor coefficients of an MS ADPCM file.
+    coefficients: Vec<(i16, i16)>,
+    /// The state of the GSM 6.10 decoder, which carries on from one block to the next.
+    gsm: gsm610::Decoder,
+    encoded: Vec<u8>,
+    /// The number of bytes of the data chunk which have not been read yet.
+    bytes_left: u64,
+    /// The number of samples recorded in the fact chunk which have not been decoded yet.
+    samples_left: usize,
+}
+
+impl BlockDecoder {
+    /// Reads the next blocks from ``reader`` and decodes them, replacing the contents of ``out`` with their samples.
+    fn decode_next(&mut self, reader: &mut dyn Read, out: &mut Vec<i16>) -> WaversResult<usize> {
+        out.clear();
+        let n_bytes = (self.n_blocks as u64 * self.block_align as u64).min(self.bytes_left);
+        self.encoded.resize(n_bytes as usize, 0);
+        reader.read_exact(&mut self.encoded)?;
+        self.bytes_left -= n_bytes;
+
+        match self.wav_type {
+            WavType::MsAdpcm => ms_adpcm::decode_into(
+                &self.encoded,
+                self.n_channels,
+                self.block_align,
+                &self.coefficients,
+                out,
+            )?,
+            _ => gsm610::decode_blocks(&mut self.gsm, &self.encoded, out),
+        }
+        // The final block is padded, so only the number of samples recorded in the fact chunk are kept
+        out.truncate(self.samples_left);
+        self.samples_left -= out.len();
+        Ok(out.len())
+    }
+}
+
 /// Struct representing a wav file.
 /// The struct contains a boxed reader and the header information of the wav file.
 ///
@@ -43,11 +420,13 @@ impl<T: Read + Seek> ReadSeek for T {}
 /// The header information is available after instantiating the struct and can be used to inspect the wav file.
 pub struct Wav<T: AudioSample>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -57,15 +436,25 @@ where
     _phantom: std::marker::PhantomData<T>,
     reader: Box<dyn ReadSeek>,
     pub wav_info: WavInfo,
+    /// Opens another reader over the same file, if the file can be opened again.
+    reopen: Option<Reopen>,
+    /// The path of the file, if it was read from one, which is opened for writing to edit the file in place.
+    path: Option<PathBuf>,
+    /// The sample rate which ``read`` and ``frames`` resample the file to, if it differs from the sample rate of the file.
+    target_sample_rate: Option<i32>,
+    /// The time reference set with ``set_time_reference``, which replaces that of the bext chunk when the file is written.
+    time_reference: Option<u64>,
 }
 
 impl<T: AudioSample> Wav<T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -84,15 +473,385 @@ where
             _phantom: std::marker::PhantomData,
             reader,
             wav_info,
+            reopen: None,
+            path: None,
+            target_sample_rate: None,
+            time_reference: None,
         })
     }
 
     /// Construct a new Wav struct from a path.
-    /// Uses a BufReader to read the file.
+    /// Uses a BufReader with a capacity of ``DEFAULT_BUFFER_SIZE`` bytes to read the file.
     pub fn from_path<P: AsRef<Path>>(path: P) -> WaversResult<Self> {
-        let f = std::fs::File::open(path)?;
-        let buf_reader: Box<dyn ReadSeek> = Box::new(std::io::BufReader::new(f));
-        Self::new(buf_reader)
+        Self::from_path_with_buffer_size(path, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Construct a new Wav struct from a path, reading the file through a BufReader with a capacity of ``buffer_size`` bytes.
+    /// Larger buffers make fewer, larger reads, e.g. for network file systems or spinning disks, while smaller buffers suit targets with little memory.
+    /// Readers configured in other ways, such as a pre-built BufReader, can be used with ``new``.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path_with_buffer_size("path/on/nfs.wav", 1 << 20).unwrap();
+    ///     let samples = wav.read().unwrap();
+    /// }
+    /// ```
+    pub fn from_path_with_buffer_size<P: AsRef<Path>>(
+        path: P,
+        buffer_size: usize,
+    ) -> WaversResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let reopen_path = path.clone();
+        let mut wav = Self::from_reopen(Arc::new(
+            move || -> std::io::Result<Box<dyn ReadSeek + Send>> {
+                let f = std::fs::File::open(&reopen_path)?;
+                Ok(Box::new(std::io::BufReader::with_capacity(buffer_size, f)))
+            },
+        ))?;
+        wav.path = Some(path);
+        Ok(wav)
+    }
+
+    /// Construct a new Wav struct from the reader opened by ``reopen``, which is kept so that the Wav struct can be cloned.
+    fn from_reopen(reopen: Reopen) -> WaversResult<Self> {
+        let mut wav = Self::new(reopen()?)?;
+        wav.reopen = Some(reopen);
+        Ok(wav)
+    }
+
+    /// Construct a new Wav struct from a reader which cannot seek, such as ``std::io::stdin()``, a pipe or a network stream.
+    /// Only the bytes before the data chunk are kept in memory, after which the samples are streamed from the reader as they are read.
+    ///
+    /// The samples can only be read once and in order, so going back to samples which have already been read, e.g. by reading the file a second time, returns an error.
+    /// Chunks which follow the data chunk are not read, and the size of the data chunk must be known when the header is written.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_stream(std::io::stdin()).unwrap();
+    ///     for frame in wav.frames() {
+    ///         println!("{:?}", &frame[..]);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_stream<R: Read + 'static>(reader: R) -> WaversResult<Self> {
+        let data_start = Rc::new(Cell::new(None));
+        let mut reader: Box<dyn ReadSeek> =
+            Box::new(ForwardReader::new(Box::new(reader), Rc::clone(&data_start)));
+        let wav_info = read_stream_header(&mut reader)?;
+
+        let (data_offset, _) = wav_info.wav_header.data().into();
+        let data_offset = data_offset + 8;
+        data_start.set(Some(data_offset as u64));
+        reader.seek(SeekFrom::Start(data_offset as u64))?;
+
+        Ok(Self {
+            _phantom: std::marker::PhantomData,
+            reader,
+            wav_info,
+            reopen: None,
+            path: None,
+            target_sample_rate: None,
+            time_reference: None,
+        })
+    }
+
+    /// Construct a new Wav struct from the bytes of a wav file which live for the rest of the program, such as those embedded with ``include_bytes!``.
+    /// The bytes are read in place, without being copied. Use ``from_vec`` for bytes which are only in memory for a while, such as a downloaded file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_bytes(include_bytes!("path/to/click.wav")).unwrap();
+    ///     let samples = wav.read().unwrap();
+    /// }
+    /// ```
+    pub fn from_bytes(bytes: &'static [u8]) -> WaversResult<Self> {
+        Self::from_cursor(Cursor::new(bytes))
+    }
+
+    /// Construct a new Wav struct from the bytes of a wav file held in a ``Vec``, which the Wav struct takes ownership of, so the bytes are not copied.
+    pub fn from_vec(bytes: Vec<u8>) -> WaversResult<Self> {
+        Self::from_cursor(Cursor::new(bytes))
+    }
+
+    /// Construct a new Wav struct from a cursor over the bytes of a wav file held in any owned buffer, such as a ``Box<[u8]>`` or an ``Arc<[u8]>`` shared with other readers.
+    /// The wav file is read from the start of the buffer, regardless of the position of the cursor.
+    pub fn from_cursor<B: AsRef<[u8]> + Send + Sync + 'static>(
+        cursor: Cursor<B>,
+    ) -> WaversResult<Self> {
+        let buffer = Arc::new(cursor.into_inner());
+        Self::from_reopen(Arc::new(
+            move || -> std::io::Result<Box<dyn ReadSeek + Send>> {
+                Ok(Box::new(Cursor::new(SharedBuffer(Arc::clone(&buffer)))))
+            },
+        ))
+    }
+
+    /// Construct a new Wav struct from headerless PCM, such as a telephony ``.raw`` or ``.pcm`` capture.
+    /// The samples are assumed to be interleaved, little-endian and stored with the given encoding.
+    /// Block encoded formats, such as MS ADPCM, are not supported.
+    ///
+    /// The whole reader is treated as sample data. Any trailing bytes which do not make up a whole sample are ignored.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{Wav, WavType};
+    ///
+    /// fn main() {
+    ///     let f = std::fs::File::open("path/to/capture.raw").unwrap();
+    ///     let mut wav: Wav<f32> = Wav::from_raw_pcm(Box::new(f), 8000, 1, WavType::MuLaw).unwrap();
+    ///     wav.write::<i16, _>("path/to/capture.wav").unwrap();
+    /// }
+    /// ```
+    pub fn from_raw_pcm(
+        reader: Box<dyn ReadSeek>,
+        sample_rate: i32,
+        n_channels: u16,
+        wav_type: WavType,
+    ) -> WaversResult<Self> {
+        Self::from_raw_parts(
+            reader,
+            sample_rate,
+            n_channels,
+            wav_type,
+            Endianness::Little,
+            None,
+        )
+    }
+
+    /// Construct a new Wav struct from PCM samples starting at the current position of the reader, stored with the given byte order.
+    /// At most ``max_samples`` samples are read if given, otherwise the rest of the reader is treated as sample data.
+    pub(crate) fn from_raw_parts(
+        mut reader: Box<dyn ReadSeek>,
+        sample_rate: i32,
+        n_channels: u16,
+        wav_type: WavType,
+        endianness: Endianness,
+        max_samples: Option<usize>,
+    ) -> WaversResult<Self> {
+        let start = reader.stream_position()?;
+        let n_bytes = reader.seek(SeekFrom::End(0))? - start;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let n_samples = match wav_type.n_bytes() {
+            0 => return Err(FormatError::BlockEncodedFormat(wav_type).into()),
+            sample_size => n_bytes as usize / sample_size,
+        };
+        let n_samples = n_samples.min(max_samples.unwrap_or(usize::MAX));
+        let n_samples = n_samples - n_samples % n_channels.max(1) as usize;
+        let mut wav_header =
+            WavHeader::new_header_with_type(wav_type, sample_rate, n_channels, n_samples)?;
+        if endianness == Endianness::Big {
+            wav_header.set_rifx();
+        }
+
+        // Positions in the raw reader are offset so that the samples appear at the data chunk of the generated header
+        let data_offset = wav_header.data().offset as u64 + 8;
+        let reader: Box<dyn ReadSeek> = Box::new(RawPcmReader {
+            inner: reader,
+            offset: data_offset as i64 - start as i64,
+        });
+
+        Ok(Self {
+            _phantom: std::marker::PhantomData,
+            reader,
+            wav_info: WavInfo {
+                wav_type,
+                wav_header,
+                fact_chunk: None,
+            },
+            reopen: None,
+            path: None,
+            target_sample_rate: None,
+            time_reference: None,
+        })
+    }
+
+    /// Construct a new Wav struct with the same header which reads ``bytes`` as if they were found at ``position`` in the file,
+    /// so that part of the data chunk which was read by other means, e.g. asynchronously, can be decoded.
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    pub(crate) fn with_bytes_at(&self, bytes: Vec<u8>, position: u64) -> WaversResult<Self> {
+        let mut reader: Box<dyn ReadSeek> = Box::new(RawPcmReader {
+            inner: Box::new(Cursor::new(bytes)),
+            offset: position as i64,
+        });
+        reader.seek(SeekFrom::Start(position))?;
+
+        Ok(Self {
+            _phantom: std::marker::PhantomData,
+            reader,
+            wav_info: self.wav_info.clone(),
+            reopen: None,
+            path: None,
+            target_sample_rate: None,
+            time_reference: None,
+        })
+    }
+
+    /// Returns a new Wav struct over the same file, with its own reader and position, which shares the already parsed header rather than reading it again.
+    /// The clone starts at the beginning of the data chunk, so the original and the clone can read different regions of the file independently.
+    /// The clone stays on the thread it was made on, use ``handle`` to read the file from other threads.
+    ///
+    /// Only Wav structs read from a path or from memory, e.g. using ``from_path``, ``from_vec`` or ``from_cursor``, can be cloned.
+    /// A file read from a path is opened again, so it should not be replaced while it is being read.
+    ///
+    /// This function will return an error if the Wav struct was read from any other reader, such as a stream, or if the file cannot be opened again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let mut second_half = wav.try_clone().unwrap();
+    ///     second_half.seek_frame(wav.n_frames() as u64 / 2).unwrap();
+    ///     for (a, b) in wav.frames().zip(second_half.frames()) {
+    ///         println!("{:?} {:?}", &a[..], &b[..]);
+    ///     }
+    /// }
+    /// ```
+    pub fn try_clone(&self) -> WaversResult<Self> {
+        self.handle()?.open()
+    }
+
+    /// Returns a ``WavHandle`` over the same file which can be sent to other threads, where ``WavHandle::open`` returns Wav structs with their own reader and position.
+    ///
+    /// Only Wav structs read from a path or from memory, e.g. using ``from_path``, ``from_vec`` or ``from_cursor``, have a handle.
+    ///
+    /// This function will return an error if the Wav struct was read from any other reader, such as a stream.
+    pub fn handle(&self) -> WaversResult<WavHandle> {
+        let Some(reopen) = &self.reopen else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Only wav files read from a path or from memory can be cloned",
+            )
+            .into());
+        };
+
+        Ok(WavHandle {
+            reopen: Arc::clone(reopen),
+            wav_info: self.wav_info.clone(),
+            path: self.path.clone(),
+            target_sample_rate: self.target_sample_rate,
+            time_reference: self.time_reference,
+        })
+    }
+
+    /// Returns the Wav struct reading its samples as ``F`` rather than ``T``, keeping its reader, its position and the parsed header.
+    pub(crate) fn into_sample_type<F: AudioSample>(self) -> Wav<F>
+    where
+        u8: ConvertTo<F>,
+        i16: ConvertTo<F>,
+        i24: ConvertTo<F>,
+        i32: ConvertTo<F>,
+        f32: ConvertTo<F>,
+        f64: ConvertTo<F>,
+        Box<[u8]>: ConvertSlice<F>,
+        Box<[i16]>: ConvertSlice<F>,
+        Box<[i24]>: ConvertSlice<F>,
+        Box<[i32]>: ConvertSlice<F>,
+        Box<[f32]>: ConvertSlice<F>,
+        Box<[f64]>: ConvertSlice<F>,
+    {
+        Wav {
+            _phantom: std::marker::PhantomData,
+            reader: self.reader,
+            wav_info: self.wav_info,
+            reopen: self.reopen,
+            path: self.path,
+            target_sample_rate: self.target_sample_rate,
+            time_reference: self.time_reference,
+        }
+    }
+
+    /// Overwrites the samples of one channel of the wav file in place, encoding them in the same way as the rest of the file.
+    /// Only the data chunk is rewritten, a block of frames at a time, so a stem can be replaced without decoding and re-encoding every channel.
+    ///
+    /// The Wav struct must have been constructed using ``from_path``, and ``samples`` must hold one sample for every frame of the file.
+    /// Block encoded files, such as MS ADPCM, cannot be overwritten in place.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/mix.wav").unwrap();
+    ///     let vocals: Vec<f32> = vec![0.0; wav.n_frames()];
+    ///     wav.overwrite_channel(1, &vocals).unwrap();
+    /// }
+    /// ```
+    pub fn overwrite_channel(&mut self, channel: usize, samples: &[T]) -> WaversResult<()> {
+        let Some(path) = &self.path else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Only wav files read from a path can be overwritten in place",
+            )
+            .into());
+        };
+        let n_channels = self.n_channels();
+        if channel >= n_channels as usize {
+            return Err(FormatError::InvalidChannel {
+                channel,
+                n_channels,
+            }
+            .into());
+        }
+        let wav_type = self.encoding();
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_frames = self.n_frames();
+        if samples.len() != n_frames {
+            return Err(FormatError::PlanarChannelLength {
+                channel,
+                len: samples.len(),
+                expected: n_frames,
+            }
+            .into());
+        }
+
+        let mut encoded =
+            crate::encode_samples(samples, wav_type, self.padding_bits(), None)?.into_owned();
+        if self.header().is_rifx() {
+            // Reversing the bytes of each sample converts them back to big-endian as well
+            rifx_to_native(&mut encoded, wav_type);
+        }
+
+        let sample_size = wav_type.n_bytes();
+        let frame_size = sample_size * n_channels as usize;
+        let frames_per_block = (DECODE_BUFFER_SIZE / frame_size).max(1);
+        let mut block = vec![0u8; frames_per_block * frame_size];
+        let (data_offset, _) = self.header().data().into();
+        let mut f = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        f.seek(SeekFrom::Start(data_offset as u64 + 8))?;
+        for encoded in encoded.chunks(frames_per_block * sample_size) {
+            let block = &mut block[..encoded.len() / sample_size * frame_size];
+            f.read_exact(block)?;
+            for (frame, sample) in block
+                .chunks_exact_mut(frame_size)
+                .zip(encoded.chunks_exact(sample_size))
+            {
+                frame[channel * sample_size..(channel + 1) * sample_size].copy_from_slice(sample);
+            }
+            f.seek(SeekFrom::Current(-(block.len() as i64)))?;
+            f.write_all(block)?;
+        }
+
+        // Seeking discards any samples the reader has buffered from before they were overwritten
+        let position = self.reader.stream_position()?;
+        self.reader.seek(SeekFrom::Start(position))?;
+        Ok(())
     }
 
     /// Read the audio samples from the wav file.
@@ -113,12 +872,23 @@ where
     ///
     #[inline(always)]
     pub fn read(&mut self) -> WaversResult<Samples<T>> {
+        let samples = self.read_at_file_rate()?;
+        Ok(self.resample_to_target(samples))
+    }
+
+    /// Reads the remaining samples of the wav file in the same way as ``read``, without resampling them to the target sample rate.
+    fn read_at_file_rate(&mut self) -> WaversResult<Samples<T>> {
         let (data_offset, data_size_bytes) = self.header().data().into();
         log!(log::Level::Debug, "Data offset: {}", data_offset);
         log!(log::Level::Debug, "Data size: {}", data_size_bytes);
         let data_offset = data_offset + 8;
         let native_type = self.wav_info.wav_type;
         log!(log::Level::Debug, "Native type: {:?}", native_type);
+        if native_type.is_block_encoded() {
+            let samples = self.read_blocks()?;
+            self.reader.seek(SeekFrom::Start(data_offset as u64))?;
+            return Ok(samples);
+        }
         let native_size_bytes: usize = native_type.n_bytes();
         log!(
             log::Level::Debug,
@@ -146,6 +916,70 @@ where
         Ok(samples)
     }
 
+    /// Reads the remaining samples of the wav file in the same way as ``read``, calling ``progress`` with the number of bytes of the data chunk read so far and the total number of bytes to read.
+    /// The callback is called every ``PROGRESS_INTERVAL`` bytes, so that tools wrapping wavers can show the progress of reading very large files.
+    /// Block encoded files, such as MS ADPCM, are decoded in one go, so the callback is only called once they have been read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/huge.wav").unwrap();
+    ///     let samples = wav
+    ///         .read_with_progress(|done, total| println!("{:.1}%", 100.0 * done as f64 / total as f64))
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn read_with_progress(&mut self, progress: ProgressFn) -> WaversResult<Samples<T>> {
+        let samples = self.read_reporting(&mut |done, total| {
+            progress(done, total);
+            Ok(())
+        })?;
+        Ok(self.resample_to_target(samples))
+    }
+
+    /// Reads the remaining samples of the wav file in the same way as ``read``, checking ``token`` every ``PROGRESS_INTERVAL`` bytes
+    /// and returning ``WaversError::Cancelled`` once it has been cancelled. See ``CancelToken``.
+    ///
+    /// Block encoded files, such as MS ADPCM, are decoded in one go, so they can only be cancelled before or after they have been read.
+    /// A cancelled read leaves the position of the reader unspecified, so ``seek_frame`` should be used before reading again.
+    pub fn read_cancellable(&mut self, token: &CancelToken) -> WaversResult<Samples<T>> {
+        token.check()?;
+        let samples = self.read_reporting(&mut |_, _| token.check())?;
+        Ok(self.resample_to_target(samples))
+    }
+
+    /// Reads the remaining samples of the wav file in the same way as ``read``, reporting the progress to ``progress``.
+    /// The read stops with the error of ``progress`` if it returns one.
+    fn read_reporting(
+        &mut self,
+        progress: &mut dyn FnMut(u64, u64) -> WaversResult<()>,
+    ) -> WaversResult<Samples<T>> {
+        let native_type = self.wav_info.wav_type;
+        if native_type.is_block_encoded() {
+            let (_, data_size_bytes) = self.header().data().into();
+            let samples = self.read_at_file_rate()?;
+            progress(data_size_bytes, data_size_bytes)?;
+            return Ok(samples);
+        }
+
+        let n_bytes = native_type.n_bytes();
+        let mut samples = vec![T::zero(); self.n_samples_left()?];
+        let total = (samples.len() * n_bytes) as u64;
+        let mut n_read = 0;
+        for chunk in samples.chunks_mut((PROGRESS_INTERVAL / n_bytes).max(1)) {
+            self.decode_into(chunk)?;
+            n_read += chunk.len();
+            progress((n_read * n_bytes) as u64, total)?;
+        }
+        if samples.is_empty() {
+            progress(0, 0)?;
+        }
+        self.to_data()?;
+        Ok(Samples::from(samples))
+    }
+
     /// Read n_samples from the wav file.
     /// The function will read n_samples from the current position in the file.
     ///
@@ -155,22 +989,32 @@ where
     #[inline(always)]
     pub fn read_samples(&mut self, n_samples: usize) -> WaversResult<Samples<T>> {
         let native_type = self.wav_info.wav_type;
+        if n_samples == 0 && !native_type.is_block_encoded() {
+            // An empty buffer is not aligned for the sample type, so it cannot be cast
+            return Ok(Samples::from(Vec::new()));
+        }
 
         let native_size_bytes: usize = native_type.n_bytes();
         let n_native_bytes: usize = n_samples * native_size_bytes;
 
         let mut samples = alloc_box_buffer(n_native_bytes);
         self.reader.read_exact(&mut samples)?;
+        if self.header().is_rifx() {
+            rifx_to_native(&mut samples, native_type);
+        }
+        mask_padding_bits(&mut samples, native_type, self.padding_bits());
 
         let wav_type_from_file = self.wav_info.wav_type;
 
-        let desired_type = WavType::try_from(TypeId::of::<T>())?;
+        // Not every sample type has a wav encoding (e.g. f16), in which case the samples are always converted
+        let desired_type = WavType::try_from(TypeId::of::<T>()).ok();
         log!(log::Level::Debug, "Desired type: {:?}", desired_type);
-        if wav_type_from_file == desired_type {
+        if Some(wav_type_from_file) == desired_type {
             return Ok(Samples::from(cast_slice::<u8, T>(&samples)));
         }
 
         match wav_type_from_file {
+            WavType::Pcm8 | WavType::EPcm8 => Ok(Samples::<u8>::from(&samples[..]).convert()),
             WavType::Pcm16 | WavType::EPcm16 => {
                 let samples: &[i16] = cast_slice::<u8, i16>(&samples);
                 Ok(Samples::from(samples).convert())
@@ -192,7 +1036,161 @@ where
                 let samples: &[f64] = cast_slice::<u8, f64>(&samples);
                 Ok(Samples::from(samples).convert())
             }
+            WavType::ALaw => {
+                // Expand to 16-bit linear PCM and then convert
+                let samples: Box<[i16]> = alaw::decode(&samples);
+                Ok(Samples::from(samples).convert())
+            }
+            WavType::MuLaw => {
+                let samples: Box<[i16]> = mulaw::decode(&samples);
+                Ok(Samples::from(samples).convert())
+            }
+            WavType::MsAdpcm | WavType::Gsm610 => {
+                Err(FormatError::BlockEncodedFormat(wav_type_from_file).into())
+            }
+        }
+    }
+
+    /// Reads up to ``n_frames`` frames from the current position in the wav file into the start of ``buf``, converting them to ``T``.
+    /// Fewer frames are read if ``buf`` cannot hold ``n_frames`` frames or if the end of the data chunk is reached.
+    /// No memory is allocated, so the same buffer can be reused for every block of a streaming loop.
+    ///
+    /// Reading can later be resumed. Returns the number of frames read, which is zero once the end of the data chunk is reached,
+    /// or an error if there is an issue reading the file or the file is block encoded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let mut buf = vec![0.0; 1024 * wav.n_channels() as usize];
+    ///     loop {
+    ///         let n_frames = wav.read_block(&mut buf, 1024).unwrap();
+    ///         if n_frames == 0 {
+    ///             break;
+    ///         }
+    ///         let block = &buf[..n_frames * wav.n_channels() as usize];
+    ///     }
+    /// }
+    /// ```
+    pub fn read_block(&mut self, buf: &mut [T], n_frames: usize) -> WaversResult<usize> {
+        let n_channels = self.n_channels().max(1) as usize;
+        let frames_left = self.n_samples_left()? / n_channels;
+
+        let n_frames = n_frames.min(buf.len() / n_channels).min(frames_left);
+        self.decode_into(&mut buf[..n_frames * n_channels])?;
+        Ok(n_frames)
+    }
+
+    /// Reads samples from the current position in the wav file into ``out``, converting them to ``T``, until it is full or the end of the data chunk is reached.
+    /// No memory is allocated, so the same buffer can be reused for every read.
+    ///
+    /// Reading can later be resumed. Returns the number of samples read, or an error if there is an issue reading the file or the file is block encoded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let mut samples = [0.0; 4096];
+    ///     let n_samples = wav.read_into(&mut samples).unwrap();
+    /// }
+    /// ```
+    pub fn read_into(&mut self, out: &mut [T]) -> WaversResult<usize> {
+        let n_samples = out.len().min(self.n_samples_left()?);
+        self.decode_into(&mut out[..n_samples])?;
+        Ok(n_samples)
+    }
+
+    /// Reads the remaining samples of the wav file into ``out``, replacing its contents, in the same way as ``read``.
+    /// The capacity of ``out`` is reused, so reading into the same ``Vec`` again only allocates if the samples do not fit.
+    ///
+    /// Returns the number of samples read, or an error if there is an issue reading the file.
+    pub fn read_into_vec(&mut self, out: &mut Vec<T>) -> WaversResult<usize> {
+        out.clear();
+        if self.wav_info.wav_type.is_block_encoded() {
+            // Block encoded samples can only be decoded a whole file at a time
+            out.extend_from_slice(&self.read_at_file_rate()?);
+            return Ok(out.len());
+        }
+
+        out.resize(self.n_samples_left()?, T::zero());
+        self.decode_into(out)?;
+        self.to_data()?;
+        Ok(out.len())
+    }
+
+    /// Returns the number of samples between the current position and the end of the data chunk.
+    ///
+    /// This function will return an error if the file is block encoded, since its samples cannot be counted from their position.
+    fn n_samples_left(&mut self) -> WaversResult<usize> {
+        let native_type = self.wav_info.wav_type;
+        if native_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(native_type).into());
+        }
+        let n_bytes_left = self.max_data_pos().saturating_sub(self.current_pos()?);
+        Ok(n_bytes_left as usize / native_type.n_bytes())
+    }
+
+    /// Reads ``out.len()`` samples from the current position into ``out``, converting them to ``T``.
+    /// Samples which are stored as ``T`` are read straight into ``out``, while others are decoded through a buffer on the stack.
+    fn decode_into(&mut self, out: &mut [T]) -> WaversResult<()> {
+        let native_type = self.wav_info.wav_type;
+        let is_rifx = self.header().is_rifx();
+        let padding_bits = self.padding_bits();
+
+        let desired_type = WavType::try_from(TypeId::of::<T>()).ok();
+        if Some(native_type) == desired_type {
+            let bytes: &mut [u8] = cast_slice_mut::<T, u8>(out);
+            self.reader.read_exact(bytes)?;
+            if is_rifx {
+                rifx_to_native(bytes, native_type);
+            }
+            mask_padding_bits(bytes, native_type, padding_bits);
+            return Ok(());
+        }
+
+        // A u64 buffer keeps the bytes aligned for every sample type
+        let mut buffer = [0u64; DECODE_BUFFER_SIZE / 8];
+        let buffer: &mut [u8] = cast_slice_mut::<u64, u8>(&mut buffer);
+        let native_size_bytes = native_type.n_bytes();
+        for out in out.chunks_mut(DECODE_BUFFER_SIZE / native_size_bytes) {
+            let bytes = &mut buffer[..out.len() * native_size_bytes];
+            self.reader.read_exact(bytes)?;
+            if is_rifx {
+                rifx_to_native(bytes, native_type);
+            }
+            mask_padding_bits(bytes, native_type, padding_bits);
+
+            match native_type {
+                WavType::Pcm8 | WavType::EPcm8 => convert_into::<u8, T>(bytes, out),
+                WavType::Pcm16 | WavType::EPcm16 => convert_into::<i16, T>(cast_slice(bytes), out),
+                WavType::Pcm24 | WavType::EPcm24 => convert_into::<i24, T>(cast_slice(bytes), out),
+                WavType::Pcm32 | WavType::EPcm32 => convert_into::<i32, T>(cast_slice(bytes), out),
+                WavType::Float32 | WavType::EFloat32 => {
+                    convert_into::<f32, T>(cast_slice(bytes), out)
+                }
+                WavType::Float64 | WavType::EFloat64 => {
+                    convert_into::<f64, T>(cast_slice(bytes), out)
+                }
+                WavType::ALaw => {
+                    for (sample, byte) in out.iter_mut().zip(bytes.iter()) {
+                        *sample = alaw::decode_sample(*byte).convert_to();
+                    }
+                }
+                WavType::MuLaw => {
+                    for (sample, byte) in out.iter_mut().zip(bytes.iter()) {
+                        *sample = mulaw::decode_sample(*byte).convert_to();
+                    }
+                }
+                WavType::MsAdpcm | WavType::Gsm610 => {
+                    return Err(FormatError::BlockEncodedFormat(native_type).into())
+                }
+            }
         }
+        Ok(())
     }
 
     #[inline(always)]
@@ -203,6 +1201,10 @@ where
 
         let mut samples = alloc_box_buffer(native_size_bytes);
         self.reader.read_exact(&mut samples)?;
+        if self.header().is_rifx() {
+            rifx_to_native(&mut samples, native_type);
+        }
+        mask_padding_bits(&mut samples, native_type, self.padding_bits());
 
         let wav_type_from_file = self.wav_info.wav_type;
         log!(
@@ -211,6 +1213,7 @@ where
             wav_type_from_file
         );
         match wav_type_from_file {
+            WavType::Pcm8 | WavType::EPcm8 => Ok(samples[0].convert_to()),
             // file is encoded as i16 but we want T
             WavType::Pcm16 | WavType::EPcm16 => {
                 let buf: [u8; 2] = [samples[0], samples[1]];
@@ -235,7 +1238,79 @@ where
                 ];
                 Ok(f64::from_ne_bytes(buf).convert_to())
             }
+            WavType::ALaw => Ok(alaw::decode_sample(samples[0]).convert_to()),
+            WavType::MuLaw => Ok(mulaw::decode_sample(samples[0]).convert_to()),
+            WavType::MsAdpcm | WavType::Gsm610 => {
+                Err(FormatError::BlockEncodedFormat(wav_type_from_file).into())
+            }
+        }
+    }
+
+    /// Reads and decodes the remaining blocks of a block encoded wav file, such as MS ADPCM.
+    fn read_blocks(&mut self) -> WaversResult<Samples<T>> {
+        let mut decoder = self.block_decoder()?;
+        let mut samples: Vec<i16> = Vec::with_capacity(decoder.samples_left);
+        let mut block = Vec::new();
+        while self.read_encoded_blocks(&mut decoder, &mut block)? > 0 {
+            samples.extend_from_slice(&block);
         }
+        Ok(Samples::from(samples.into_boxed_slice()).convert())
+    }
+
+    /// Returns a ``BlockDecoder`` which decodes the blocks of a block encoded wav file, such as MS ADPCM, from the current position,
+    /// which must be at the start of a block. The reader is left at the current position.
+    ///
+    /// This function will return an error if the file is not block encoded, or if its blocks cannot be decoded.
+    pub(crate) fn block_decoder(&mut self) -> WaversResult<BlockDecoder> {
+        let current_pos = self.current_pos()?;
+        let fmt_chunk = self.header().fmt_chunk;
+        let fmt_info = *self.header().fmt();
+        let samples_before = self.current_frame()? as usize * fmt_chunk.channels as usize;
+
+        let coefficients = match self.wav_info.wav_type {
+            WavType::MsAdpcm => {
+                // The coefficient table is stored in the fmt chunk after the cbSize field
+                let extension_size = (fmt_info.size as usize).saturating_sub(FMT_CB_SIZE);
+                let mut extension = alloc_box_buffer(extension_size);
+                self.reader
+                    .seek(SeekFrom::Start((fmt_info.offset + 8 + FMT_CB_SIZE) as u64))?;
+                self.reader.read_exact(&mut extension)?;
+                self.reader.seek(SeekFrom::Start(current_pos))?;
+                ms_adpcm::coefficients_from_extension(&extension)?
+            }
+            WavType::Gsm610 => {
+                if fmt_chunk.channels != 1 || fmt_chunk.block_align != gsm610::BLOCK_ALIGN {
+                    return Err(FormatError::InvalidGsmData(
+                        "only mono files with 65 byte blocks are supported",
+                    )
+                    .into());
+                }
+                Vec::new()
+            }
+            wav_type => return Err(FormatError::InvalidWavType(wav_type).into()),
+        };
+
+        Ok(BlockDecoder {
+            wav_type: self.wav_info.wav_type,
+            n_channels: fmt_chunk.channels,
+            block_align: fmt_chunk.block_align,
+            n_blocks: (ITER_BLOCK_FRAMES as u64).div_ceil(self.frames_per_block()) as usize,
+            coefficients,
+            gsm: gsm610::Decoder::new(),
+            encoded: Vec::new(),
+            bytes_left: self.max_data_pos().saturating_sub(current_pos),
+            samples_left: self.n_samples().saturating_sub(samples_before),
+        })
+    }
+
+    /// Reads and decodes the next blocks of a block encoded wav file using ``decoder``, replacing the contents of ``out`` with their samples.
+    /// Returns the number of samples decoded, which is zero once every block has been decoded.
+    pub(crate) fn read_encoded_blocks(
+        &mut self,
+        decoder: &mut BlockDecoder,
+        out: &mut Vec<i16>,
+    ) -> WaversResult<usize> {
+        decoder.decode_next(&mut self.reader, out)
     }
 
     /// Write the audio samples contained within this wav file to a new wav file.
@@ -248,6 +1323,26 @@ where
         Box<[T]>: ConvertSlice<F>,
     {
         log!(log::Level::Debug, "Writing to file: {:?}", p.as_ref());
+        let f = std::fs::File::create(&p)?;
+        let mut buf_writer: BufWriter<File> = BufWriter::new(f);
+        self.write_to_writer::<F, _>(&mut buf_writer)?;
+        buf_writer.flush()?;
+        log!(
+            log::Level::Debug,
+            "Finished writing to file: {:?}",
+            p.as_ref()
+        );
+        Ok(())
+    }
+
+    /// Write the audio samples contained within this wav file to ``writer`` as a new wav file, in the same way as ``write``,
+    /// so that it can be written to a socket, a compressed stream, an archive or an in-memory buffer rather than to a path.
+    /// The function will return an error if there is an issue writing to the writer.
+    pub fn write_to_writer<F: AudioSample, W: Write>(&mut self, writer: &mut W) -> WaversResult<()>
+    where
+        T: ConvertTo<F>,
+        Box<[T]>: ConvertSlice<F>,
+    {
         let samples = self.read()?.convert::<F>();
         log!(
             log::Level::Debug,
@@ -257,48 +1352,245 @@ where
 
         let sample_bytes = samples.as_bytes();
         let fmt_chunk = self.wav_info.wav_header.fmt_chunk;
-        self.wav_info.wav_header =
-            WavHeader::new_header::<F>(fmt_chunk.sample_rate, fmt_chunk.channels, samples.len())?;
+        self.wav_info.wav_header = WavHeader::new_header::<F>(
+            self.target_sample_rate(),
+            fmt_chunk.channels,
+            samples.len(),
+        )?;
+
+        // The header, the data id and size and the data are written in a single vectored write
+        let header_bytes = self.wav_info.wav_header.as_bytes();
+        let data_chunk_bytes = self.wav_info.wav_header.data_chunk_bytes();
+        write_all_vectored(
+            writer,
+            &mut [
+                IoSlice::new(&header_bytes),
+                IoSlice::new(&data_chunk_bytes),
+                IoSlice::new(sample_bytes),
+            ],
+        )?;
+        Ok(())
+    }
 
-        let f = std::fs::File::create(&p)?;
-        let mut buf_writer: BufWriter<File> = BufWriter::new(f);
-        let data_size_bytes = sample_bytes.len() as u32; // write up to the data size
+    /// Write the audio samples contained within this wav file to the specified path using the given options.
+    ///
+    /// The samples are stored using the encoding set in the options, or the encoding of this file if none is set.
+    /// Files encoded with MS ADPCM or GSM 6.10, which cannot be written, are stored as 16-bit PCM instead.
+    /// If ``WriteOptions::preserve_chunks`` is set, the other chunks of this file are copied to the written file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{Wav, WavType, WriteOptions};
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/tagged.wav").unwrap();
+    ///     let options = WriteOptions::new().encoding(WavType::Pcm16).preserve_chunks(true);
+    ///     wav.write_with_options("path/to/tagged_i16.wav", &options).unwrap();
+    /// }
+    /// ```
+    pub fn write_with_options<P: AsRef<Path>>(
+        &mut self,
+        p: P,
+        options: &WriteOptions,
+    ) -> WaversResult<()> {
+        let options = self.output_options(options)?;
+        crate::check_write_options::<T>(self.target_sample_rate(), self.n_channels(), &options)?;
+        crate::write_file(p, &options, |f| self.write_output(f, &options))
+    }
 
-        match self.format() {
-            (FormatCode::WAV_FORMAT_PCM, FormatCode::WAV_FORMAT_PCM) => {
-                let header_bytes = self.wav_info.wav_header.as_base_bytes();
-                buf_writer.write_all(&header_bytes)?;
-            }
-            (FormatCode::WAV_FORMAT_IEEE_FLOAT, _) => {
-                let header_bytes = self.wav_info.wav_header.as_extended_bytes(); // need to check if the format should always be marked as extended
-                buf_writer.write_all(&header_bytes)?;
-            }
-            (FormatCode::WAVE_FORMAT_EXTENSIBLE, FormatCode::WAV_FORMAT_PCM) => {
-                let header_bytes = self.wav_info.wav_header.as_cb_bytes();
-                buf_writer.write_all(&header_bytes)?;
-            }
-            (FormatCode::WAVE_FORMAT_EXTENSIBLE, _) => {
-                let header_bytes = self.wav_info.wav_header.as_extended_bytes();
-                buf_writer.write_all(&header_bytes)?;
-            }
-            _ => {
-                return Err(FormatError::UnsupportedWriteFormat {
-                    main: self.format().0,
-                    sub: self.format().1,
+    /// Write the audio samples contained within this wav file to ``writer`` using the given options, in the same way as ``write_with_options``.
+    /// See ``write_to_writer``.
+    pub fn write_to_writer_with_options<W: Write>(
+        &mut self,
+        writer: &mut W,
+        options: &WriteOptions,
+    ) -> WaversResult<()> {
+        let options = self.output_options(options)?;
+        self.write_output(writer, &options)
+    }
+
+    /// Returns ``options`` with the encoding of this file filled in if none is set, along with the chunks and chunk order of this file if they are to be preserved.
+    fn output_options(&mut self, options: &WriteOptions) -> WaversResult<WriteOptions> {
+        let mut options = options.clone();
+        options.encoding = match (options.encoding, self.encoding()) {
+            (Some(encoding), _) => Some(encoding),
+            (None, WavType::MsAdpcm | WavType::Gsm610) => Some(WavType::Pcm16),
+            (None, encoding) => Some(encoding),
+        };
+
+        if let (Some(time_reference), None) = (self.time_reference, &options.bext) {
+            let mut bext = self.bext()?.unwrap_or_default();
+            bext.time_reference = time_reference;
+            options.bext = Some(bext);
+        }
+
+        if options.preserve_chunks {
+            let replaced: Vec<ChunkIdentifier> = options
+                .metadata_chunks()
+                .iter()
+                .map(|chunk| header_identifier(*chunk))
+                .collect();
+            let regenerated: [ChunkIdentifier; 4] =
+                [DS64.into(), FMT.into(), FACT.into(), DATA.into()];
+            for (id, _) in self.header().chunks() {
+                if regenerated.contains(&id) || replaced.contains(&id) {
+                    continue;
+                }
+                if let Some(chunk) = self.get_chunk::<RawChunk>(id)? {
+                    options.chunks.push(chunk);
                 }
-                .into());
             }
+        }
+        if options.preserve_chunk_order {
+            options.chunk_order = self
+                .header()
+                .chunks()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+        }
+        Ok(options)
+    }
+
+    /// Writes the samples of this file to ``writer`` with options returned by ``output_options``.
+    fn write_output<W: Write>(
+        &mut self,
+        writer: &mut W,
+        options: &WriteOptions,
+    ) -> WaversResult<()> {
+        let sample_rate = self.target_sample_rate();
+        let n_channels = self.n_channels();
+        if options.progress.is_none() && options.cancel.is_none() {
+            let samples = self.read()?;
+            return crate::write_to_writer_with_options(
+                writer,
+                &samples,
+                sample_rate,
+                n_channels,
+                options,
+            );
+        }
+
+        // Reading the source and writing the new file are reported as one operation
+        let (_, read_total) = self.header().data().into();
+        let n_frames =
+            (self.n_frames() as u64 * sample_rate as u64).div_ceil(self.sample_rate() as u64);
+        let write_total =
+            n_frames * n_channels as u64 * options.encoding.map_or(0, |x| x.n_bytes()) as u64;
+        let total = read_total + write_total;
+        let samples =
+            self.read_reporting(&mut |done, _| options.report(done.min(read_total), total))?;
+        let samples = self.resample_to_target(samples);
+        crate::write_to(
+            writer,
+            &samples,
+            sample_rate,
+            n_channels,
+            options,
+            &mut |done, _| options.report(read_total + done, total),
+        )
+    }
+
+    /// Returns the audio samples contained within this wav file as a complete wav file in memory, stored with the encoding of this file,
+    /// in the same way as ``write_with_options`` with the default options. The header of this Wav struct is left unchanged.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let body: Vec<u8> = wav.to_bytes().unwrap();
+    /// }
+    /// ```
+    pub fn to_bytes(&mut self) -> WaversResult<Vec<u8>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.write_to_writer_with_options(&mut bytes, &WriteOptions::default())?;
+        Ok(bytes)
+    }
+
+    /// Streams the audio samples contained within this wav file through ``process`` and writes the result to ``dst``,
+    /// keeping the sample rate, number of channels and encoding of this file. Files encoded with MS ADPCM or GSM 6.10 are written as 16-bit PCM instead.
+    ///
+    /// The samples are read from the start of the data chunk and passed to ``process`` in blocks of whole interleaved frames,
+    /// at most ``ITER_BLOCK_FRAMES`` frames at a time, so the memory used does not depend on the length of the file.
+    /// Block encoded files are the exception, as they are decoded as a whole before being processed.
+    /// ``dst`` is written atomically, so it can be the file this Wav struct was read from, see ``WriteOptions::atomic``.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     // Halve the volume of the file
+    ///     wav.process_to("path/to/quiet.wav", |block| {
+    ///         block.iter_mut().for_each(|sample| *sample *= 0.5);
+    ///     })
+    ///     .unwrap();
+    /// }
+    /// ```
+    pub fn process_to<P: AsRef<Path>, F: FnMut(&mut [T])>(
+        &mut self,
+        dst: P,
+        mut process: F,
+    ) -> WaversResult<()> {
+        let mut options = WriteOptions::new().atomic(true);
+        options = match self.encoding() {
+            WavType::MsAdpcm | WavType::Gsm610 => options.encoding(WavType::Pcm16),
+            encoding => options.encoding(encoding),
         };
+        if self.padding_bits() > 0 {
+            options = options.valid_bits_per_sample(self.valid_bits_per_sample());
+        }
+        let sample_rate = self.sample_rate();
+        let n_channels = self.n_channels();
+        let block_len = ITER_BLOCK_FRAMES * n_channels as usize;
+
+        if self.encoding().is_block_encoded() {
+            let mut samples = self.read_at_file_rate()?;
+            samples.chunks_mut(block_len).for_each(&mut process);
+            return crate::write_with_options(dst, &samples, sample_rate, n_channels, &options);
+        }
+
+        self.to_data()?;
+        crate::check_write_options::<T>(sample_rate, n_channels, &options)?;
+        crate::write_file(dst, &options, |f| {
+            let mut writer: WavWriter<T, _> =
+                WavWriter::new(options.buf_writer(f), sample_rate, n_channels, &options)?;
+            let mut buf = vec![T::zero(); block_len];
+            loop {
+                let n_frames = self.read_block(&mut buf, ITER_BLOCK_FRAMES)?;
+                if n_frames == 0 {
+                    break;
+                }
+                let block = &mut buf[..n_frames * n_channels as usize];
+                process(block);
+                writer.write_frames(block)?;
+            }
+            writer.finalize()?;
+            Ok(())
+        })
+    }
 
-        buf_writer.write_all(&DATA)?;
-        buf_writer.write_all(&data_size_bytes.to_ne_bytes())?; // write the data size
-        buf_writer.write_all(&sample_bytes)?; // write the data
+    /// Write the audio samples contained within this wav file to the specified path as headerless PCM.
+    /// The samples are converted to the type ``F`` and written with the given byte order.
+    /// The function will return an error if there is an issue writing the file.
+    pub fn write_raw<F: AudioSample, P: AsRef<Path>>(
+        &mut self,
+        p: P,
+        endianness: Endianness,
+    ) -> WaversResult<()>
+    where
+        T: ConvertTo<F>,
+        Box<[T]>: ConvertSlice<F>,
+    {
         log!(
             log::Level::Debug,
-            "Finished writing to file: {:?}",
+            "Writing raw PCM to file: {:?}",
             p.as_ref()
         );
-        Ok(())
+        self.read()?.convert::<F>().write_raw(p, endianness)
     }
 
     /// Returns the Fact chunk of the wav file if it is present.
@@ -308,6 +1600,29 @@ where
         self.get_chunk(FACT.into())
     }
 
+    /// Returns the metadata stored in the LIST INFO chunk of the wav file, such as the artist and title, keyed by tag.
+    /// The map is empty if the file has no metadata.
+    /// This function will return an error if there is an issue loading the chunk.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{InfoTag, Wav};
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<i16> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let metadata = wav.metadata().unwrap();
+    ///     if let Some(artist) = metadata.get(&InfoTag::Artist) {
+    ///         println!("Artist: {}", artist);
+    ///     }
+    /// }
+    /// ```
+    pub fn metadata(&mut self) -> WaversResult<HashMap<InfoTag, String>> {
+        Ok(self
+            .get_list_chunk()?
+            .map(|list_chunk| list_chunk.info())
+            .unwrap_or_default())
+    }
+
     /// Returns the List chunk of the wav file if it is present.
     /// This function will return an error if there is an issue loading the chunk.
     /// This function will return as Some if the chunk is present and None if it is not.
@@ -315,17 +1630,187 @@ where
         self.get_chunk(LIST.into())
     }
 
+    /// Returns the acid chunk of the wav file, containing the tempo, number of beats and root note of a loop, if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn acid(&mut self) -> WaversResult<Option<AcidChunk>> {
+        self.get_chunk(ACID.into())
+    }
+
+    /// Returns the axml chunk of the wav file, containing Audio Definition Model metadata for object-based audio, if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn axml(&mut self) -> WaversResult<Option<AxmlChunk>> {
+        self.get_chunk(AXML.into())
+    }
+
+    /// Returns the Broadcast Wave bext chunk of the wav file if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn bext(&mut self) -> WaversResult<Option<BextChunk>> {
+        self.get_chunk(BEXT.into())
+    }
+
+    /// Returns the time reference of the bext chunk, i.e. the number of samples since midnight of the first sample, if the chunk is present,
+    /// or the time reference set with ``set_time_reference``.
+    /// This function will return an error if there is an issue loading the chunk.
+    pub fn time_reference(&mut self) -> WaversResult<Option<u64>> {
+        if self.time_reference.is_some() {
+            return Ok(self.time_reference);
+        }
+        Ok(self.bext()?.map(|bext| bext.time_reference))
+    }
+
+    /// Sets the time reference, i.e. the number of samples since midnight of the first sample, which ``time_reference`` and ``time_of_day`` return.
+    /// The file is not modified, but ``write_with_options`` writes the time reference in the bext chunk of the written file,
+    /// which is a copy of the bext chunk of this file if it has one, unless the options set a bext chunk of their own.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{Wav, WriteOptions};
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/take.wav").unwrap();
+    ///     // 10:00:00 at 48 kHz
+    ///     wav.set_time_reference(10 * 3600 * 48000);
+    ///     wav.write_with_options("path/to/synced_take.wav", &WriteOptions::new()).unwrap();
+    /// }
+    /// ```
+    pub fn set_time_reference(&mut self, time_reference: u64) {
+        self.time_reference = Some(time_reference);
+    }
+
+    /// Returns the time since midnight of the first sample, converted from the time reference using the sample rate of the file.
+    /// Files recorded on several devices can be aligned by comparing their time of day.
+    /// This function will return an error if there is an issue loading the chunk.
+    pub fn time_of_day(&mut self) -> WaversResult<Option<Duration>> {
+        let sample_rate = self.sample_rate();
+        Ok(self
+            .time_reference()?
+            .map(|time_reference| time_reference_to_time_of_day(time_reference, sample_rate)))
+    }
+
+    /// Returns the cue points, or markers, of the wav file. The list is empty if the file has no cue chunk.
+    /// This function will return an error if there is an issue loading the chunk.
+    pub fn cue_points(&mut self) -> WaversResult<Vec<CuePoint>> {
+        Ok(self
+            .get_chunk::<CueChunk>(CUE.into())?
+            .map(|cue| cue.cue_points)
+            .unwrap_or_default())
+    }
+
+    /// Returns the cue points of the wav file joined with their labels, notes and region lengths from the adtl LIST chunk.
+    /// The list is empty if the file has no cue chunk, and the markers have no labels if the file has no adtl chunk.
+    /// This function will return an error if there is an issue loading either chunk.
+    pub fn markers(&mut self) -> WaversResult<Vec<Marker>> {
+        let cue_points = self.cue_points()?;
+        let adtl: AdtlChunk = self.get_chunk(ADTL.into())?.unwrap_or_default();
+        Ok(Marker::from_cue_points(&cue_points, &adtl))
+    }
+
+    /// Returns the segments of the playlist of the wav file, in play order. The list is empty if the file has no plst chunk.
+    /// Each segment starts at a cue point, which can be found using ``Wav::cue_points`` or ``Wav::markers``.
+    /// This function will return an error if there is an issue loading the chunk.
+    pub fn playlist(&mut self) -> WaversResult<Vec<PlaylistSegment>> {
+        Ok(self
+            .get_chunk::<PlstChunk>(PLST.into())?
+            .map(|plst| plst.segments)
+            .unwrap_or_default())
+    }
+
+    /// Returns the inst chunk of the wav file, containing the pitch, gain and key and velocity ranges of an instrument sample, if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn inst(&mut self) -> WaversResult<Option<InstChunk>> {
+        self.get_chunk(INST.into())
+    }
+
+    /// Returns the ID3v2 tag stored in the "id3 " chunk of the wav file, as raw bytes, if it is present.
+    /// Some software writes the chunk as "ID3 ", which is also checked.
+    pub fn id3_bytes(&mut self) -> WaversResult<Option<Box<[u8]>>> {
+        match self.read_chunk_bytes(ID3.into())? {
+            Some(bytes) => Ok(Some(bytes)),
+            None => self.read_chunk_bytes((*b"ID3 ").into()),
+        }
+    }
+
+    /// Returns the ID3v2 tag of the wav file, parsed using the id3 crate, if it is present.
+    /// This function will return an error if there is an issue loading or parsing the tag.
+    #[cfg(feature = "id3")]
+    pub fn id3_tag(&mut self) -> WaversResult<Option<id3::Tag>> {
+        match self.id3_bytes()? {
+            Some(bytes) => Ok(Some(id3::Tag::read_from2(std::io::Cursor::new(bytes))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the iXML chunk of the wav file if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn ixml(&mut self) -> WaversResult<Option<IxmlChunk>> {
+        self.get_chunk(IXML.into())
+    }
+
+    /// Returns the smpl chunk of the wav file, containing the MIDI unity note and loop points, if it is present.
+    /// This function will return an error if there is an issue loading the chunk.
+    /// This function will return as Some if the chunk is present and None if it is not.
+    pub fn smpl(&mut self) -> WaversResult<Option<SmplChunk>> {
+        self.get_chunk(SMPL.into())
+    }
+
     /// Returns a Result containing an optional so as to allow the possibility of a missing fact chunk, but also to allow for the possibility of an error in reading that chunk.
+    /// The position of the reader is unchanged, so reading samples can continue afterwards.
     pub fn get_chunk<C: Chunk>(&mut self, id: ChunkIdentifier) -> WaversResult<Option<C>> {
         let header: &WavHeader = self.header();
         let chunk_info: HeaderChunkInfo = match header.get_chunk_info(id) {
             Some(info) => info.clone(),
             None => return Ok(None),
         };
+        let current_pos = self.reader.stream_position()?;
         let chunk = read_chunk::<C>(&mut self.reader, &chunk_info)?;
+        self.reader.seek(SeekFrom::Start(current_pos))?;
         Ok(Some(chunk))
     }
 
+    /// Returns an iterator over the identifier, offset and size of every chunk in the wav file, ordered by their offset.
+    /// The size does not include the identifier and size fields of the chunk.
+    /// Chunks which wavers does not understand, such as vendor specific chunks, are included and can be read using ``read_chunk_bytes``.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkIdentifier, usize, u64)> {
+        self.header()
+            .chunks()
+            .into_iter()
+            .map(|(id, info)| (id, info.offset, info.size))
+    }
+
+    /// Returns the raw bytes of the chunk with the given identifier, without the identifier and size fields, if it is present.
+    /// The position of the reader is unchanged, so reading samples can continue afterwards.
+    pub fn read_chunk_bytes(&mut self, id: ChunkIdentifier) -> WaversResult<Option<Box<[u8]>>> {
+        let chunk_info: HeaderChunkInfo = match self.header().get_chunk_info(id) {
+            Some(info) => *info,
+            None => return Ok(None),
+        };
+        let current_pos = self.reader.stream_position()?;
+        self.reader
+            .seek(SeekFrom::Start(chunk_info.offset as u64 + 8))?;
+        // The size comes from the file, so only as many bytes as the file holds are allocated, rather than trusting it up front
+        let mut bytes = Vec::new();
+        Read::take(&mut self.reader, chunk_info.size).read_to_end(&mut bytes)?;
+        self.reader.seek(SeekFrom::Start(current_pos))?;
+        if (bytes.len() as u64) < chunk_info.size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "The {} chunk claims {} bytes, but the file ends after {}",
+                    id,
+                    chunk_info.size,
+                    bytes.len()
+                ),
+            )
+            .into());
+        }
+        Ok(Some(bytes.into_boxed_slice()))
+    }
+
     /// Returns a reference to the fmt chunk of the wav file.
     pub fn get_fmt_chunk(&self) -> &FmtChunk {
         &self.wav_info.wav_header.fmt_chunk
@@ -345,19 +1830,215 @@ where
         &self.wav_info.wav_header
     }
 
-    /// Returns a mutable reference to the header of the wav file.
-    pub fn header_mut(&mut self) -> &mut WavHeader {
-        &mut self.wav_info.wav_header
+    /// Returns a mutable reference to the header of the wav file.
+    pub fn header_mut(&mut self) -> &mut WavHeader {
+        &mut self.wav_info.wav_header
+    }
+
+    /// Returns the encoding of the wav file.
+    pub fn encoding(&self) -> WavType {
+        self.wav_info.wav_type
+    }
+
+    /// Returns true if the fmt chunk of the wav file is stored in the extensible format.
+    pub fn is_extensible(&self) -> bool {
+        self.header().fmt_chunk.is_extended_format()
+    }
+
+    /// Returns the extensible format fields (valid bits per sample, channel mask and sub-format) if the wav file uses the extensible format.
+    pub fn ext_fmt_chunk(&self) -> Option<&ExtFmtChunkInfo> {
+        match self.is_extensible() {
+            true => Some(&self.header().fmt_chunk.ext_fmt_chunk),
+            false => None,
+        }
+    }
+
+    /// Returns the number of bits per sample which contain audio data.
+    /// This is the same as the bits per sample unless the file is extensible and declares fewer valid bits.
+    pub fn valid_bits_per_sample(&self) -> u16 {
+        match self.ext_fmt_chunk() {
+            Some(ext) if ext.valid_bits_per_sample() != 0 => ext.valid_bits_per_sample(),
+            _ => self.header().fmt_chunk.bits_per_sample,
+        }
+    }
+
+    /// Returns the number of padding bits in each integer PCM sample, e.g. 4 for 20-bit samples stored in a 24-bit container.
+    pub(crate) fn padding_bits(&self) -> u16 {
+        match self.wav_info.wav_type {
+            WavType::Pcm16
+            | WavType::EPcm16
+            | WavType::Pcm24
+            | WavType::EPcm24
+            | WavType::Pcm32
+            | WavType::EPcm32 => self
+                .header()
+                .fmt_chunk
+                .bits_per_sample
+                .saturating_sub(self.valid_bits_per_sample()),
+            _ => 0,
+        }
+    }
+
+    /// Returns the speaker positions of the channels, if the wav file uses the extensible format.
+    pub fn channel_mask(&self) -> Option<u32> {
+        self.ext_fmt_chunk().map(|ext| ext.channel_mask())
+    }
+
+    /// Returns the speaker positions of the channels as a ``ChannelLayout``, if the wav file uses the extensible format.
+    pub fn channel_layout(&self) -> Option<ChannelLayout> {
+        self.channel_mask().map(ChannelLayout::from_bits)
+    }
+
+    /// Returns the sample rate of the wav file.
+    pub fn sample_rate(&self) -> i32 {
+        self.header().fmt_chunk.sample_rate
+    }
+
+    /// Sets the sample rate which ``read``, ``read_with_progress``, ``read_cancellable`` and ``frames`` resample the file to,
+    /// so that e.g. every file of a dataset is read at 16 kHz without an intermediate buffer at the rate of the file.
+    /// The samples are resampled using the same windowed sinc filter as ``transcode``, and ``frames`` resamples them a block at a time as it reads them.
+    ///
+    /// Functions which address the file by position, such as ``read_samples``, ``read_block``, ``read_frames`` and the seek functions,
+    /// as well as ``sample_rate``, ``n_frames`` and the other iterators, still work at the sample rate of the file.
+    /// Writing the file with ``write`` or ``write_with_options`` writes the resampled samples at the target sample rate.
+    ///
+    /// This function will return an error if the sample rate is not positive.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/48k.wav").unwrap().with_target_sample_rate(16000).unwrap();
+    ///     let samples_16k = wav.read().unwrap();
+    /// }
+    /// ```
+    pub fn with_target_sample_rate(mut self, sample_rate: i32) -> WaversResult<Self> {
+        if sample_rate <= 0 {
+            return Err(FormatError::InvalidSampleRate(sample_rate).into());
+        }
+        self.target_sample_rate = (sample_rate != self.sample_rate()).then_some(sample_rate);
+        Ok(self)
+    }
+
+    /// Returns the sample rate of the samples returned by ``read`` and ``frames``,
+    /// which is the sample rate of the file unless another was set with ``with_target_sample_rate``.
+    pub fn target_sample_rate(&self) -> i32 {
+        self.target_sample_rate.unwrap_or(self.sample_rate())
+    }
+
+    /// Returns a resampler from the sample rate of the file to the target sample rate, if they differ.
+    pub(crate) fn target_resampler(&self) -> Option<Resampler> {
+        self.target_sample_rate
+            .map(|target| Resampler::new(self.sample_rate(), target, self.n_channels()))
+    }
+
+    /// Resamples samples read at the sample rate of the file to the target sample rate.
+    fn resample_to_target(&self, samples: Samples<T>) -> Samples<T> {
+        match self.target_resampler() {
+            Some(mut resampler) => Samples::from(resampler.process_samples(&samples, true)),
+            None => samples,
+        }
+    }
+
+    /// Reads the remaining samples of the wav file in the same way as ``read`` and averages the channels of each frame into a single channel.
+    /// See ``Samples::to_mono_weighted`` for mixing the channels with other weights.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/stereo.wav").unwrap();
+    ///     let mono = wav.read_mono().unwrap();
+    /// }
+    /// ```
+    pub fn read_mono(&mut self) -> WaversResult<Samples<T>> {
+        let samples = self.read()?;
+        Ok(samples.to_mono(self.n_channels()))
+    }
+
+    /// Reads the remaining samples of the wav file and maps their channels to new channels with the gain matrix ``matrix``.
+    /// The file is read and remixed ``ITER_BLOCK_FRAMES`` frames at a time, so only the remixed samples are held in memory,
+    /// and the samples are resampled to the target sample rate as with ``read``. The returned samples have ``matrix.n_outputs()`` channels.
+    ///
+    /// This function will return an error if the number of input channels of the matrix is not the number of channels of the file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{Matrix, Wav};
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/surround.wav").unwrap();
+    ///     let stereo = wav.read_remixed(&Matrix::surround_to_stereo()).unwrap();
+    /// }
+    /// ```
+    pub fn read_remixed(&mut self, matrix: &Matrix) -> WaversResult<Samples<T>> {
+        let n_channels = self.n_channels() as usize;
+        if matrix.n_inputs() != n_channels {
+            return Err(FormatError::MatrixChannelMismatch {
+                expected: matrix.n_inputs(),
+                found: n_channels,
+            }
+            .into());
+        }
+        let mut resampler = self
+            .target_sample_rate
+            .map(|target| Resampler::new(self.sample_rate(), target, matrix.n_outputs() as u16));
+        if self.encoding().is_block_encoded() {
+            let remixed = self.read_at_file_rate()?.remix(matrix);
+            return Ok(match resampler.as_mut() {
+                Some(resampler) => Samples::from(resampler.process_samples(&remixed, true)),
+                None => remixed,
+            });
+        }
+
+        let mut buf = vec![T::zero(); ITER_BLOCK_FRAMES * n_channels];
+        let mut block = Vec::new();
+        let mut out = Vec::new();
+        loop {
+            let n_frames = self.read_block(&mut buf, ITER_BLOCK_FRAMES)?;
+            block.clear();
+            matrix.remix_into(&buf[..n_frames * n_channels], &mut block);
+            match resampler.as_mut() {
+                Some(resampler) => out.extend(resampler.process_samples(&block, n_frames == 0)),
+                None => out.extend_from_slice(&block),
+            }
+            if n_frames == 0 {
+                return Ok(Samples::from(out));
+            }
+        }
     }
 
-    /// Returns the encoding of the wav file.
-    pub fn encoding(&self) -> WavType {
-        self.wav_info.wav_type
+    /// Returns the largest absolute value of the samples of the wav file, where 1.0 is full scale, scanning the file with ``scan_blocks``.
+    pub fn peak(&mut self) -> WaversResult<f64> {
+        let mut peak: f64 = 0.0;
+        self.scan_blocks(|block| {
+            peak = block.iter().fold(peak, |peak, sample| {
+                peak.max(ConvertTo::<f64>::convert_to(sample).abs())
+            })
+        })?;
+        Ok(peak)
     }
 
-    /// Returns the sample rate of the wav file.
-    pub fn sample_rate(&self) -> i32 {
-        self.header().fmt_chunk.sample_rate
+    /// Passes every sample of the wav file to ``f`` in blocks of whole frames. Block encoded files are decoded and passed as a single block.
+    /// The whole file is scanned regardless of the current position, after which the reader is moved back to the start of the data chunk.
+    pub(crate) fn scan_blocks<F: FnMut(&[T])>(&mut self, mut f: F) -> WaversResult<()> {
+        self.to_data()?;
+        if self.encoding().is_block_encoded() {
+            f(&self.read_at_file_rate()?);
+        } else {
+            let n_channels = self.n_channels() as usize;
+            let mut buf = vec![T::zero(); ITER_BLOCK_FRAMES * n_channels];
+            loop {
+                let n_frames = self.read_block(&mut buf, ITER_BLOCK_FRAMES)?;
+                if n_frames == 0 {
+                    break;
+                }
+                f(&buf[..n_frames * n_channels]);
+            }
+        }
+        self.to_data()
     }
 
     /// Returns the number of channels of the wav file.
@@ -365,22 +2046,65 @@ where
         self.header().fmt_chunk.channels
     }
 
-    /// Returns the number of samples in the wav file.
+    /// Returns the sample rate, number of channels and encoding of the wav file as a ``WavSpec``, e.g. to write other files in the same format.
+    pub fn spec(&self) -> WavSpec {
+        WavSpec::new(self.sample_rate(), self.n_channels()).wav_type(self.encoding())
+    }
+
+    /// Returns the number of samples in the wav file, across all channels. Computed from the header, so no samples are read.
+    ///
+    /// For block encoded files, such as MS ADPCM, the final block may be padded, so the length of the data chunk overstates the number of samples.
+    /// The number of samples recorded in the fact chunk is used instead, falling back to an estimate from the number of blocks if the file has no fact chunk.
+    /// Every other encoding stores each sample in a fixed number of bytes, so its number of samples is exact.
     pub fn n_samples(&self) -> usize {
         let (_, native_data_size_bytes) = self.header().data().into();
-        let size_of_native_bytes = self.header().fmt_chunk.bits_per_sample as usize / 8;
-        native_data_size_bytes as usize / size_of_native_bytes
+        let fmt_chunk = self.header().fmt_chunk;
+        let n_encoded_samples = match self.wav_info.wav_type {
+            WavType::MsAdpcm => ms_adpcm::n_samples(
+                native_data_size_bytes as usize,
+                fmt_chunk.block_align,
+                fmt_chunk.channels,
+            ),
+            WavType::Gsm610 => {
+                gsm610::n_samples(native_data_size_bytes as usize) * fmt_chunk.channels as usize
+            }
+            wav_type => {
+                // The container size of the encoding, which is what the samples are read with
+                return native_data_size_bytes as usize / wav_type.n_bytes().max(1);
+            }
+        };
+
+        // The number of samples in block encoded files is given by the fact chunk, since the final block may be padded
+        match self.wav_info.fact_chunk {
+            Some(fact_chunk) => {
+                n_encoded_samples.min(fact_chunk.num_samples as usize * fmt_chunk.channels as usize)
+            }
+            None => n_encoded_samples,
+        }
     }
 
-    /// Returns the duration of the wav file in seconds.
-    pub fn duration(&self) -> u32 {
-        let data_size = self.header().data().size;
+    /// Returns the number of frames in the wav file, where a frame holds one sample for each channel.
+    /// Computed from the header, so no samples are read.
+    pub fn n_frames(&self) -> usize {
+        self.n_samples() / self.n_channels().max(1) as usize
+    }
 
+    /// Returns the duration of the wav file, computed from the number of frames and the sample rate.
+    /// Unlike ``duration``, the duration is not truncated to whole seconds.
+    /// For block encoded files the number of frames is taken from the fact chunk rather than the length of the data chunk, see ``n_samples``.
+    pub fn duration_time(&self) -> Duration {
+        let sample_rate = self.sample_rate().max(1) as u64;
+        let n_frames = self.n_frames() as u64;
+        let nanos = (n_frames % sample_rate) * 1_000_000_000 / sample_rate;
+        Duration::new(n_frames / sample_rate, nanos as u32)
+    }
+
+    /// Returns the duration of the wav file in whole seconds. See ``duration_time`` for the exact duration.
+    pub fn duration(&self) -> u32 {
         let sample_rate = self.sample_rate() as u32;
         let n_channels = self.n_channels() as u32;
-        let bytes_per_sample = (self.header().fmt_chunk.bits_per_sample / 8) as u32;
 
-        data_size / (sample_rate * n_channels * bytes_per_sample)
+        self.n_samples() as u32 / (sample_rate * n_channels)
     }
 
     /// Returns the sample rate, number of channels, duration and encoding of a wav file.
@@ -391,8 +2115,13 @@ where
 
     #[inline(always)]
     /// From the current seek position, seek forward by n samples. If the number of samples goes beyond the max number of samples in the DATA chunk, the function will return an error.
+    /// Block encoded files, such as MS ADPCM and GSM 6.10, cannot be sought by samples, see ``seek_frame`` instead.
     pub fn seek_by_samples(&mut self, n_samples: u64) -> WaversResult<u64> {
-        let n_sample_bytes = n_samples as usize * self.wav_info.wav_type.n_bytes();
+        let wav_type = self.wav_info.wav_type;
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_sample_bytes = n_samples as usize * wav_type.n_bytes();
 
         self.seek_by_bytes(n_sample_bytes as i64)
     }
@@ -419,6 +2148,159 @@ where
         Ok(self.reader.seek(SeekFrom::Current(n_bytes))?)
     }
 
+    /// Moves the reader to the start of the given frame, counted from the start of the data chunk, so that reading resumes from that frame.
+    /// Seeking to the frame after the last frame moves the reader to the end of the data chunk.
+    /// Block encoded files, such as MS ADPCM, can only be sought to the first frame of a block, since each block is decoded as a whole.
+    ///
+    /// Returns the frame the reader was moved to, or an error if the frame is out of range or is not at the start of a block.
+    pub fn seek_frame(&mut self, frame: u64) -> WaversResult<u64> {
+        let n_frames = self.n_frames() as u64;
+        if frame > n_frames {
+            return Err(FormatError::FrameOutOfRange { frame, n_frames }.into());
+        }
+        let frames_per_block = self.frames_per_block();
+        if !frame.is_multiple_of(frames_per_block) {
+            return Err(FormatError::UnalignedFrame {
+                frame,
+                frames_per_block,
+            }
+            .into());
+        }
+
+        let (data_offset, _) = self.header().data().into();
+        let offset = frame / frames_per_block * self.block_size();
+        self.reader
+            .seek(SeekFrom::Start(data_offset as u64 + 8 + offset))?;
+        Ok(frame)
+    }
+
+    /// Moves the reader to the frame at the given time from the start of the data chunk, rounded to the nearest frame. See ``seek_frame``.
+    ///
+    /// Returns the frame the reader was moved to, or an error if the time is past the end of the file.
+    pub fn seek_time(&mut self, time: Duration) -> WaversResult<u64> {
+        self.seek_frame(self.duration_to_frames(time))
+    }
+
+    /// Reads the frames spanning the given duration, rounded to the nearest frame, from the current position in the wav file.
+    /// Fewer frames are read if the end of the data chunk is reached first.
+    ///
+    /// Reading can later be resumed. Returns the samples read, or an error if there is an issue reading the file or the file is block encoded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     // 30 seconds starting at 1:02:17
+    ///     wav.seek_time(Duration::from_secs(3737)).unwrap();
+    ///     let excerpt = wav.read_duration(Duration::from_secs(30)).unwrap();
+    /// }
+    /// ```
+    pub fn read_duration(&mut self, duration: Duration) -> WaversResult<Samples<T>> {
+        let n_channels = self.n_channels().max(1) as usize;
+        let frames_left = self.n_samples_left()? / n_channels;
+        let n_frames = (self.duration_to_frames(duration) as usize).min(frames_left);
+        self.read_samples(n_frames * n_channels)
+    }
+
+    /// Reads the given range of frames, counted from the start of the data chunk, seeking directly to the first frame of the range
+    /// so that only the requested frames are read. Unbounded ranges extend to the start or end of the data chunk.
+    ///
+    /// The reader is left after the last frame of the range, so reading can later be resumed.
+    /// Returns the samples of the frames, or an error if the range is out of range or the file is block encoded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let excerpt = wav.read_range(48000..96000).unwrap();
+    /// }
+    /// ```
+    pub fn read_range<R: RangeBounds<u64>>(&mut self, range: R) -> WaversResult<Samples<T>> {
+        let n_channels = self.n_channels().max(1) as usize;
+        let n_frames = self.n_frames() as u64;
+        // Bounds of u64::MAX cannot be made exclusive, but are out of range anyway
+        let out_of_range = |frame: u64| FormatError::FrameOutOfRange { frame, n_frames };
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.checked_add(1).ok_or(out_of_range(*start))?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.checked_add(1).ok_or(out_of_range(*end))?,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => n_frames,
+        };
+        if start > end {
+            return Err(FormatError::InvalidFrameRange { start, end }.into());
+        }
+        if end > n_frames {
+            return Err(FormatError::FrameOutOfRange {
+                frame: end,
+                n_frames,
+            }
+            .into());
+        }
+
+        let wav_type = self.wav_info.wav_type;
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        self.seek_frame(start)?;
+        self.read_samples((end - start) as usize * n_channels)
+    }
+
+    /// Reads ``count`` frames starting at the frame ``offset``, counted from the start of the data chunk.
+    /// Returns ``count * n_channels`` interleaved samples, or fewer if the end of the data chunk is reached first.
+    ///
+    /// The reader is left after the last frame read, so reading can later be resumed.
+    /// Returns an error if the offset is past the end of the data chunk or the file is block encoded.
+    pub fn read_frames(&mut self, offset: u64, count: usize) -> WaversResult<Samples<T>> {
+        let n_frames = self.n_frames() as u64;
+        let end = offset
+            .saturating_add(count as u64)
+            .min(n_frames.max(offset));
+        self.read_range(offset..end)
+    }
+
+    /// Converts a duration to a number of frames at the sample rate of the file, rounded to the nearest frame.
+    fn duration_to_frames(&self, duration: Duration) -> u64 {
+        (duration.as_secs_f64() * self.sample_rate() as f64).round() as u64
+    }
+
+    /// Returns the frame the reader is positioned at, counted from the start of the data chunk.
+    /// For block encoded files this is the first frame of the block the reader is positioned at.
+    pub fn current_frame(&mut self) -> WaversResult<u64> {
+        let (data_offset, _) = self.header().data().into();
+        let offset = self.current_pos()?.saturating_sub(data_offset as u64 + 8);
+        Ok(offset / self.block_size() * self.frames_per_block())
+    }
+
+    /// Returns the number of frames stored in each block of the data chunk, which is one unless the file is block encoded.
+    fn frames_per_block(&self) -> u64 {
+        let fmt_chunk = self.header().fmt_chunk;
+        match self.wav_info.wav_type {
+            WavType::MsAdpcm => {
+                ms_adpcm::samples_per_block(fmt_chunk.block_align, fmt_chunk.channels).max(1) as u64
+            }
+            WavType::Gsm610 => gsm610::SAMPLES_PER_BLOCK as u64,
+            _ => 1,
+        }
+    }
+
+    /// Returns the size of each block of the data chunk in bytes, which is the size of a frame unless the file is block encoded.
+    fn block_size(&self) -> u64 {
+        let wav_type = self.wav_info.wav_type;
+        match wav_type.is_block_encoded() {
+            true => self.header().fmt_chunk.block_align.max(1) as u64,
+            false => (wav_type.n_bytes() * self.n_channels().max(1) as usize) as u64,
+        }
+    }
+
     /// Returns the maximum position of the data chunk in the wav file.
     pub fn max_data_pos(&self) -> u64 {
         let info = self
@@ -448,26 +2330,85 @@ where
     }
 
     /// Returns an iterator over the frames of the wav file. See the ``FrameIterator`` struct for more information.
+    /// The frames are read from the current position of the reader, which is the start of the data chunk unless samples have already been read.
     pub fn frames(&mut self) -> FrameIterator<T> {
-        let info = self
-            .wav_info
-            .wav_header
-            .get_chunk_info(DATA.into())
-            .unwrap();
-
-        let max_pos = info.offset as u64 + info.size as u64;
-
+        let max_pos = self.max_data_pos();
         FrameIterator::new(max_pos, self)
     }
 
-    /// Returns an iterator over the channels of the wav file. See the ``ChannelIterator`` struct for more information.
+    /// Returns an iterator which yields a ``SingleChannelIterator`` for each channel of the wav file. See the ``ChannelIterator`` struct for more information.
+    /// The samples of each channel are read lazily from the start of the data chunk, and the channel iterators can be read side by side.
     pub fn channels(&mut self) -> ChannelIterator<T> {
         ChannelIterator::new(self)
     }
 
+    /// Returns an iterator over the samples of a single channel of the wav file, starting from the start of the data chunk.
+    /// The samples are read lazily, a buffer of frames at a time. See the ``SingleChannelIterator`` struct for more information.
+    ///
+    /// This function will return an error if the channel is out of range or if there is an issue seeking to the data chunk.
+    pub fn channel(&mut self, channel: usize) -> WaversResult<SingleChannelIterator<'_, T>> {
+        let n_channels = self.n_channels();
+        if channel >= n_channels as usize {
+            return Err(FormatError::InvalidChannel {
+                channel,
+                n_channels,
+            }
+            .into());
+        }
+        self.to_data()?;
+        Ok(SingleChannelIterator::new(self, channel))
+    }
+
+    /// Returns an iterator which plays the frames of the wav file ``n_loops`` times, wrapping from the last frame back to the first without a gap.
+    /// The file is decoded as it is played, so the repeated samples are never held in memory. See the ``LoopIterator`` struct for more information.
+    ///
+    /// This function will return an error if the file is block encoded, or if there is an issue seeking to the data chunk.
+    pub fn looped(&mut self, n_loops: usize) -> WaversResult<LoopIterator<'_, T>> {
+        self.to_data()?;
+        LoopIterator::new(self, Some(n_loops))
+    }
+
+    /// Returns an iterator which plays the frames of the wav file indefinitely. See ``looped``.
+    pub fn looped_forever(&mut self) -> WaversResult<LoopIterator<'_, T>> {
+        self.to_data()?;
+        LoopIterator::new(self, None)
+    }
+
     pub fn blocks(&mut self, block_size: usize, overlap: usize) -> BlockIterator<T> {
         BlockIterator::new(self, block_size, overlap)
     }
+
+    /// Returns an iterator over windows of ``win_len`` frames which start every ``hop`` frames, beginning at the start of the data chunk.
+    /// The final window is padded with zeros by default, see ``WindowIterator::padding`` to drop it instead. See the ``WindowIterator`` struct for more information.
+    ///
+    /// Panics if the window length or the hop size is zero.
+    pub fn windows(&mut self, win_len: usize, hop: usize) -> WindowIterator<'_, T> {
+        WindowIterator::new(self, win_len, hop)
+    }
+
+    /// Returns an iterator over the label and samples of each segment of the wav file, in the order they appear in the file.
+    ///
+    /// The segments are given by the markers of the file: a marker with a region length spans that many sample frames,
+    /// otherwise it spans up to the next marker or the end of the file. Files without cue points use the loops of their smpl chunk instead.
+    /// Each segment is only read from the file when it is reached. See the ``SegmentIterator`` struct for more information.
+    ///
+    /// This function will return an error if the file is block encoded or if there is an issue loading the cue, adtl or smpl chunks.
+    pub fn segments(&mut self) -> WaversResult<SegmentIterator<'_, T>> {
+        let wav_type = self.wav_info.wav_type;
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_frames = self.n_frames() as u64;
+        let markers = self.markers()?;
+        let segments = match markers.is_empty() {
+            false => Segment::from_markers(&markers, n_frames),
+            true => match self.smpl()? {
+                Some(smpl) => Segment::from_loops(&smpl.loops, n_frames),
+                None => Vec::new(),
+            },
+        };
+        Ok(SegmentIterator::new(self, segments))
+    }
 }
 
 /// Returns the sample rate, number of channels, duration and encoding of a wav file.
@@ -483,15 +2424,56 @@ pub fn wav_spec<P: AsRef<Path>>(p: P) -> WaversResult<(u32, WavHeader)> {
 pub struct WavInfo {
     pub wav_type: WavType, // the type of the wav file
     pub wav_header: WavHeader,
+    pub fact_chunk: Option<FactChunk>, // the fact chunk, if present, which records the number of samples per channel
+}
+
+/// Converts the Wav struct into an iterator over its samples, which are read lazily from the current position.
+/// See the ``SampleIterator`` struct for more information.
+///
+/// # Example
+/// ```no_run
+/// use wavers::{Wav, WaversResult};
+///
+/// fn main() -> WaversResult<()> {
+///     let wav: Wav<f32> = Wav::from_path("path/to/wav.wav")?;
+///     let peak = wav
+///         .into_iter()
+///         .try_fold(0.0f32, |peak, sample| Ok::<_, wavers::WaversError>(peak.max(sample?.abs())))?;
+///     Ok(())
+/// }
+/// ```
+impl<T: AudioSample> IntoIterator for Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = WaversResult<T>;
+    type IntoIter = SampleIterator<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SampleIterator::new(self)
+    }
 }
 
 impl<T: AudioSample> Debug for Wav<T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -516,11 +2498,13 @@ where
 
 impl<T: AudioSample> Display for Wav<T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -575,6 +2559,7 @@ use crate::conversion::{AsNdarray, IntoNdarray};
 #[cfg(feature = "ndarray")]
 impl<T: AudioSample> IntoNdarray for Wav<T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
@@ -591,13 +2576,14 @@ where
         let shape = (length / n_channels, n_channels); // correct format (as per everyone else) is n_channels, n_samples
 
         let arr: Array2<T> = Array::from_shape_vec(shape, copied_data.to_vec())?;
-        Ok((arr, self.sample_rate()))
+        Ok((arr, self.target_sample_rate()))
     }
 }
 
 #[cfg(feature = "ndarray")]
 impl<T: AudioSample> AsNdarray for Wav<T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
@@ -614,7 +2600,7 @@ where
 
         let shape = (length / n_channels, n_channels); // correct format (as per everyone else) is n_channels, n_samples
         let arr: Array2<T> = Array::from_shape_vec(shape, copied_data.to_vec())?;
-        Ok((arr, self.sample_rate()))
+        Ok((arr, self.target_sample_rate()))
     }
 }
 
@@ -690,6 +2676,23 @@ where
     }
 }
 
+// From u8 Buffer
+// Implemented per sample type, since a generic implementation would overlap with ``From<&[T]>`` now that ``u8`` is a sample type.
+// For ``Samples<u8>`` the slice implementation above gives the same result.
+macro_rules! impl_from_bytes {
+    ($($sample:ty),*) => {
+        $(
+            impl From<&[u8]> for Samples<$sample> {
+                fn from(bytes: &[u8]) -> Self {
+                    Samples::from_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_bytes!(i16, i24, i32, f32, f64);
+
 // From boxed slice
 impl<T> From<Box<[T]>> for Samples<T>
 where
@@ -700,19 +2703,6 @@ where
     }
 }
 
-// From u8 Buffer
-impl<T> From<&[u8]> for Samples<T>
-where
-    T: AudioSample,
-{
-    fn from(bytes: &[u8]) -> Self {
-        let casted_samples: &[T] = cast_slice::<u8, T>(bytes);
-        Samples {
-            samples: Box::from(casted_samples),
-        }
-    }
-}
-
 #[cfg(feature = "ndarray")]
 impl<T> From<Array2<T>> for Samples<T>
 where
@@ -764,10 +2754,234 @@ where
         }
     }
 
+    /// Returns an iterator which converts each sample to the specified type ``F`` as it is reached.
+    /// Unlike ``convert``, no converted buffer is allocated, which suits converted samples that are only used once, e.g. when summing or writing them.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::Samples;
+    ///
+    /// let samples: Samples<i16> = Samples::from(vec![i16::MAX, 0, i16::MIN]);
+    /// let energy: f32 = samples.iter_as::<f32>().map(|sample| sample * sample).sum();
+    /// assert!((energy - 2.0).abs() < 1e-4);
+    /// ```
+    pub fn iter_as<F: AudioSample>(&self) -> impl ExactSizeIterator<Item = F> + '_
+    where
+        T: ConvertTo<F>,
+    {
+        self.samples.iter().map(|sample| sample.convert_to())
+    }
+
     /// Converts the boxed slice of samples to the corresponding bytes.
     pub fn as_bytes(&self) -> &[u8] {
         cast_slice::<T, u8>(&self.samples)
     }
+
+    /// Writes the samples to the specified path as headerless PCM with the given byte order.
+    /// The function will return an error if there is an issue writing the file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{Endianness, Samples};
+    ///
+    /// fn main() {
+    ///     let samples: Samples<i16> = Samples::from(vec![0i16; 16000]);
+    ///     samples.write_raw("path/to/capture.pcm", Endianness::Big).unwrap();
+    /// }
+    /// ```
+    pub fn write_raw<P: AsRef<Path>>(&self, p: P, endianness: Endianness) -> WaversResult<()> {
+        let f = std::fs::File::create(&p)?;
+        let mut buf_writer: BufWriter<File> = BufWriter::new(f);
+        match endianness == Endianness::native() {
+            true => buf_writer.write_all(self.as_bytes())?,
+            false => {
+                let mut bytes = self.as_bytes().to_vec();
+                for sample in bytes.chunks_exact_mut(std::mem::size_of::<T>()) {
+                    sample.reverse();
+                }
+                buf_writer.write_all(&bytes)?;
+            }
+        }
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Construct a new Samples struct by reinterpreting a buffer of native endian bytes as samples of type ``T``.
+    /// Since ``u8`` is itself a sample type, this is the generic counterpart of the ``From<&[u8]>`` implementations, which only exist for concrete sample types.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let casted_samples: &[T] = cast_slice::<u8, T>(bytes);
+        Samples {
+            samples: Box::from(casted_samples),
+        }
+    }
+
+    /// Returns an iterator over the frames of the samples, where each frame is ``n_channels`` interleaved samples.
+    /// The frames borrow the samples, see the ``Frame`` struct for more information. Any trailing samples which do not make up a whole frame are skipped.
+    ///
+    /// Panics if the number of channels is zero.
+    pub fn frames(&self, n_channels: u16) -> impl ExactSizeIterator<Item = Frame<'_, T>> + '_ {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        self.samples
+            .chunks_exact(n_channels as usize)
+            .map(Frame::from)
+    }
+
+    /// Returns an iterator over windows of ``win_len`` frames which start every ``hop`` frames, where each frame is ``n_channels`` interleaved samples.
+    /// The final window is padded with zeros by default, see ``SampleWindowIterator::padding`` to drop it instead.
+    ///
+    /// Panics if the window length, the hop size or the number of channels is zero.
+    pub fn windows(
+        &self,
+        win_len: usize,
+        hop: usize,
+        n_channels: u16,
+    ) -> SampleWindowIterator<'_, T> {
+        SampleWindowIterator::new(&self.samples, win_len, hop, n_channels)
+    }
+
+    /// Mixes interleaved samples with ``n_channels`` channels down to a single channel by averaging the samples of each frame.
+    /// The samples are averaged as ``f64`` and converted back to ``T``. Any trailing samples which do not make up a whole frame are skipped.
+    ///
+    /// Panics if the number of channels is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::Samples;
+    ///
+    /// let stereo: Samples<f32> = Samples::from(vec![0.5, 0.25, -1.0, 0.0]);
+    /// assert_eq!(&stereo.to_mono(2)[..], &[0.375, -0.5]);
+    /// ```
+    pub fn to_mono(&self, n_channels: u16) -> Samples<T>
+    where
+        T: ConvertTo<f64>,
+        f64: ConvertTo<T>,
+    {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        let weights = vec![1.0 / n_channels as f64; n_channels as usize];
+        self.to_mono_weighted(&weights)
+    }
+
+    /// Mixes interleaved samples down to a single channel, multiplying each channel by its weight before summing the samples of each frame.
+    /// The number of channels is the number of weights, e.g. ``&[1.0, 0.0]`` keeps only the left channel of a stereo signal.
+    /// Any trailing samples which do not make up a whole frame are skipped.
+    ///
+    /// Panics if there are no weights.
+    pub fn to_mono_weighted(&self, weights: &[f64]) -> Samples<T>
+    where
+        T: ConvertTo<f64>,
+        f64: ConvertTo<T>,
+    {
+        assert!(!weights.is_empty(), "Number of channels must be positive");
+        self.samples
+            .chunks_exact(weights.len())
+            .map(|frame| {
+                let mixed: f64 = frame
+                    .iter()
+                    .zip(weights)
+                    .map(|(sample, weight)| ConvertTo::<f64>::convert_to(sample) * weight)
+                    .sum();
+                mixed.convert_to()
+            })
+            .collect::<Vec<T>>()
+            .into()
+    }
+
+    /// Maps the channels of the interleaved samples to new channels with the gain matrix ``matrix``,
+    /// e.g. ``Matrix::surround_to_stereo`` mixes 5.1 surround down to stereo. The samples must have ``matrix.n_inputs()`` channels.
+    /// Any trailing samples which do not make up a whole frame are skipped. See the ``Matrix`` struct for more information.
+    pub fn remix(&self, matrix: &Matrix) -> Samples<T>
+    where
+        T: ConvertTo<f64>,
+        f64: ConvertTo<T>,
+    {
+        let mut out = Vec::new();
+        matrix.remix_into(&self.samples, &mut out);
+        Samples::from(out)
+    }
+
+    /// Copies each sample of a mono signal to ``n_channels`` interleaved channels, e.g. so that a test tone or synthesised speech can be written as stereo.
+    /// Use ``remix`` with a ``Matrix`` with a single input channel to give the channels different gains.
+    ///
+    /// Panics if the number of channels is zero.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::{write, Samples};
+    ///
+    /// fn main() {
+    ///     let tone: Samples<f32> = (0..16000)
+    ///         .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 16000.0).sin())
+    ///         .collect::<Vec<f32>>()
+    ///         .into();
+    ///     write("path/to/stereo_tone.wav", &tone.upmix(2), 16000, 2).unwrap();
+    /// }
+    /// ```
+    pub fn upmix(&self, n_channels: u16) -> Samples<T> {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        let mut out = Vec::with_capacity(self.samples.len() * n_channels as usize);
+        for &sample in self.samples.iter() {
+            out.resize(out.len() + n_channels as usize, sample);
+        }
+        Samples::from(out)
+    }
+
+    /// Returns the largest absolute value of the samples, where 1.0 is the full scale of the type, or 0.0 if there are no samples.
+    pub fn peak(&self) -> f64 {
+        self.iter_as::<f64>()
+            .fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    /// Scales the samples so that their absolute peak is at ``target_dbfs``, returning the linear gain which was applied.
+    /// Silent samples are left unchanged with a gain of 1.0.
+    ///
+    /// The samples are scaled as ``f64`` and converted back to ``T``, so a target above 0 dBFS clips integer samples at full scale,
+    /// while float samples are scaled beyond 1.0. See ``normalize_peak`` for normalizing a file without loading it into memory.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::Samples;
+    ///
+    /// let mut samples: Samples<f32> = Samples::from(vec![0.25, -0.5, 0.1]);
+    /// let gain = samples.normalize_peak(0.0);
+    /// assert_eq!(gain, 2.0);
+    /// assert_eq!(&samples[..], &[0.5, -1.0, 0.2]);
+    /// ```
+    pub fn normalize_peak(&mut self, target_dbfs: f64) -> f64
+    where
+        f64: ConvertTo<T>,
+    {
+        let gain = peak_gain(self.peak(), target_dbfs);
+        scale_samples(&mut self.samples, gain);
+        gain
+    }
+
+    /// Multiplies the samples by a gain of ``gain_db`` dB, returning the number of samples which were clamped to full scale.
+    /// Float samples are never clamped, while integer samples pushed beyond full scale are, see ``apply_gain`` for rejecting gains which would clip a file.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::Samples;
+    ///
+    /// let mut samples: Samples<i16> = Samples::from(vec![1000, -20000]);
+    /// let n_clipped = samples.apply_gain_db(6.0);
+    /// assert_eq!(n_clipped, 1);
+    /// assert_eq!(&samples[..], &[1995, i16::MIN]);
+    /// ```
+    pub fn apply_gain_db(&mut self, gain_db: f64) -> usize
+    where
+        f64: ConvertTo<T>,
+    {
+        let gain = db_to_gain(gain_db);
+        let n_clipped = match is_integer::<T>() {
+            true => self
+                .iter_as::<f64>()
+                .filter(|sample| (sample * gain).abs() > 1.0)
+                .count(),
+            false => 0,
+        };
+        scale_samples(&mut self.samples, gain);
+        n_clipped
+    }
 }
 
 impl Samples<i16> {}
@@ -776,6 +2990,52 @@ impl Samples<i32> {}
 impl Samples<f32> {}
 impl Samples<f64> {}
 
+/// Clears the padding bits of integer PCM samples which are stored in a larger container, such as 20-bit samples in a 24-bit container.
+/// The valid bits are left-justified within the container, so shifting right and back left zeroes the padding while preserving the sign.
+pub(crate) fn mask_padding_bits(bytes: &mut [u8], wav_type: WavType, padding_bits: u16) {
+    if padding_bits == 0 || padding_bits >= wav_type.n_bits() {
+        return;
+    }
+    let padding_bits = padding_bits as u32;
+    match wav_type {
+        WavType::Pcm16 | WavType::EPcm16 => {
+            for sample in cast_slice_mut::<u8, i16>(bytes) {
+                *sample = (*sample >> padding_bits) << padding_bits;
+            }
+        }
+        WavType::Pcm24 | WavType::EPcm24 => {
+            for sample in cast_slice_mut::<u8, i24>(bytes) {
+                *sample = i24::from_i32((sample.to_i32() >> padding_bits) << padding_bits);
+            }
+        }
+        WavType::Pcm32 | WavType::EPcm32 => {
+            for sample in cast_slice_mut::<u8, i32>(bytes) {
+                *sample = (*sample >> padding_bits) << padding_bits;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Converts each sample of ``samples`` to ``T``, writing the results into ``out``.
+fn convert_into<F: AudioSample + ConvertTo<T>, T: AudioSample>(samples: &[F], out: &mut [T]) {
+    for (sample, converted) in samples.iter().zip(out.iter_mut()) {
+        *converted = sample.convert_to();
+    }
+}
+
+/// Converts big-endian samples, as stored in RIFX files, to native byte order in place.
+/// Single byte and block encoded formats are left unchanged.
+pub(crate) fn rifx_to_native(bytes: &mut [u8], wav_type: WavType) {
+    let n_bytes = wav_type.n_bytes();
+    if cfg!(target_endian = "big") || n_bytes <= 1 {
+        return;
+    }
+    for sample in bytes.chunks_exact_mut(n_bytes) {
+        sample.reverse();
+    }
+}
+
 /// Helper function to allocate a fixed sized, heap allocated buffer of bytes.
 pub(crate) fn alloc_box_buffer(len: usize) -> Box<[u8]> {
     if len == 0 {
@@ -813,6 +3073,11 @@ where
 #[cfg(test)]
 mod core_tests {
     use super::*;
+    use crate::chunks::{
+        ds64::RF64_PLACEHOLDER_SIZE,
+        fmt::{CbSize, FMT_SIZE_EXTENDED_SIZE},
+        Ds64Chunk, BW64, DATA, FMT, RF64, RIFX, WAVE,
+    };
     use std::{io::BufRead, str::FromStr};
 
     use approx_eq::assert_approx_eq;
@@ -829,6 +3094,15 @@ mod core_tests {
     const ONE_CHANNEL_EXPECTED_I16: &str = "./test_resources/one_channel_i16.txt";
     const ONE_CHANNEL_EXPECTED_F32: &str = "./test_resources/one_channel_f32.txt";
 
+    const ONE_CHANNEL_WAV_MS_ADPCM: &str = "./test_resources/one_channel_ms_adpcm.wav";
+    const ONE_CHANNEL_EXPECTED_MS_ADPCM: &str = "./test_resources/one_channel_ms_adpcm.txt";
+    const TWO_CHANNEL_WAV_MS_ADPCM: &str = "./test_resources/two_channel_ms_adpcm.wav";
+    const TWO_CHANNEL_EXPECTED_MS_ADPCM: &str = "./test_resources/two_channel_ms_adpcm.txt";
+
+    const ONE_CHANNEL_WAV_GSM: &str = "./test_resources/one_channel_gsm.wav";
+    const ONE_CHANNEL_EXPECTED_GSM: &str = "./test_resources/one_channel_gsm.txt";
+    const TWO_CHANNEL_WAV_EXTENSIBLE_I16: &str = "./test_resources/two_channel_extensible_i16.wav";
+
     const MULTI_CHANNEL_WAV: &str = "./test_resources/multi_channel.wav";
     const SIN_WAVE: &str = "./test_resources/sin_wave.wav";
     const TEST_OUTPUT: &str = "./test_resources/tmp/";
@@ -840,6 +3114,25 @@ mod core_tests {
         assert_eq!(duration, 10, "Expected duration of 10 seconds");
     }
 
+    #[test]
+    fn frame_count_and_exact_duration() {
+        let wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        assert_eq!(wav.n_frames(), wav.n_samples() / 2);
+        assert_eq!(wav.duration_time(), Duration::from_secs(10));
+
+        // 1.5 seconds of mono audio at 44.1 kHz, plus one frame
+        let fp = "./test_resources/tmp/duration_time.wav";
+        crate::write(fp, &vec![0i16; 66151], 44100, 1).unwrap();
+        let wav: Wav<i16> = Wav::from_path(fp).unwrap();
+        assert_eq!(wav.n_frames(), 66151);
+        assert_eq!(wav.duration(), 1);
+        assert_eq!(
+            wav.duration_time(),
+            Duration::from_millis(1500) + Duration::from_nanos(22675)
+        );
+        std::fs::remove_file(fp).unwrap();
+    }
+
     #[test]
     pub fn duration_two_channel() {
         let wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
@@ -847,6 +3140,104 @@ mod core_tests {
         assert_eq!(duration, 10, "Expected duration of 10 seconds");
     }
 
+    #[test]
+    fn seek_frame_and_current_frame() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+        let n_frames = (expected.len() / 2) as u64;
+
+        assert_eq!(wav.seek_frame(1000).unwrap(), 1000);
+        assert_eq!(wav.current_frame().unwrap(), 1000);
+        assert_eq!(&wav.read_samples(4).unwrap()[..], &expected[2000..2004]);
+        assert_eq!(wav.current_frame().unwrap(), 1002);
+
+        assert_eq!(wav.seek_frame(n_frames).unwrap(), n_frames);
+        assert_eq!(wav.current_pos().unwrap(), wav.max_data_pos());
+        assert!(wav.seek_frame(n_frames + 1).is_err());
+
+        // MS ADPCM can only be sought to the start of a block, after which the remaining blocks are decoded
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        let expected = wav.read().unwrap();
+        let fmt_chunk = wav.header().fmt_chunk;
+        let frames_per_block = ms_adpcm::samples_per_block(fmt_chunk.block_align, 1) as u64;
+
+        assert!(wav.seek_frame(1).is_err());
+        wav.seek_frame(2 * frames_per_block).unwrap();
+        assert_eq!(wav.current_frame().unwrap(), 2 * frames_per_block);
+        let remaining = wav.read().unwrap();
+        assert_eq!(&remaining[..], &expected[2 * frames_per_block as usize..]);
+    }
+
+    #[test]
+    fn seek_time_and_read_duration() {
+        // The file is sampled at 16 kHz
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+
+        assert_eq!(wav.seek_time(Duration::from_millis(1500)).unwrap(), 24000);
+        let excerpt = wav.read_duration(Duration::from_micros(250)).unwrap();
+        assert_eq!(&excerpt[..], &expected[48000..48008]);
+
+        // Rounds to the nearest frame
+        assert_eq!(wav.seek_time(Duration::from_micros(40)).unwrap(), 1);
+
+        let duration = Duration::from_secs(wav.duration() as u64);
+        wav.seek_time(duration - Duration::from_millis(1)).unwrap();
+        let tail = wav.read_duration(Duration::from_secs(1)).unwrap();
+        assert_eq!(&tail[..], &expected[expected.len() - 32..]);
+        assert!(wav.seek_time(duration + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn read_frame_ranges() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+        let n_frames = (expected.len() / 2) as u64;
+
+        assert_eq!(&wav.read_range(100..150).unwrap()[..], &expected[200..300]);
+        assert_eq!(wav.current_frame().unwrap(), 150);
+        assert_eq!(&wav.read_range(10..=10).unwrap()[..], &expected[20..22]);
+        assert_eq!(&wav.read_range(..3).unwrap()[..], &expected[..6]);
+        assert_eq!(
+            &wav.read_range(n_frames - 5..).unwrap()[..],
+            &expected[expected.len() - 10..]
+        );
+        assert!(wav.read_range(5..5).unwrap().is_empty());
+
+        assert!(wav.read_range(0..n_frames + 1).is_err());
+        assert!(matches!(
+            wav.read_range(..=u64::MAX),
+            Err(WaversError::Format(FormatError::FrameOutOfRange {
+                frame: u64::MAX,
+                ..
+            }))
+        ));
+        assert!(wav
+            .read_range((Bound::Excluded(u64::MAX), Bound::Unbounded))
+            .is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = wav.read_range(10..5);
+        assert!(reversed.is_err());
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        assert!(wav.read_range(0..10).is_err());
+    }
+
+    #[test]
+    fn read_frames_at_offset() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+        let n_frames = (expected.len() / 2) as u64;
+
+        assert_eq!(&wav.read_frames(7, 3).unwrap()[..], &expected[14..20]);
+        assert_eq!(
+            &wav.read_frames(n_frames - 2, 10).unwrap()[..],
+            &expected[expected.len() - 4..]
+        );
+        assert!(wav.read_frames(n_frames, 10).unwrap().is_empty());
+        assert!(wav.read_frames(n_frames + 1, 10).is_err());
+    }
+
     #[test]
     pub fn seek_by_samples() {
         let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
@@ -889,6 +3280,17 @@ mod core_tests {
         for (expected, actual) in expected_samples.iter().zip(actual.iter()) {
             assert_eq!(*expected, *actual, "{} != {}", expected, actual);
         }
+
+        // The samples of a block encoded file have no fixed size, so they cannot be skipped over
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        assert!(wav.seek_by_samples(100).is_err());
+        assert!(wav.seek_by_duration(Duration::from_secs(1)).is_err());
+        assert_eq!(wav.current_frame().unwrap(), 0);
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_GSM).unwrap();
+        assert!(wav.seek_by_samples(100).is_err());
+        assert!(wav.seek_by_duration(Duration::from_secs(1)).is_err());
+        assert_eq!(wav.current_frame().unwrap(), 0);
     }
 
     #[test]
@@ -965,6 +3367,17 @@ mod core_tests {
         }
     }
 
+    #[test]
+    fn iter_as_matches_convert() {
+        let samples: Samples<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap().read().unwrap();
+        let converted: Vec<f32> = samples.iter_as::<f32>().collect();
+        assert_eq!(samples.iter_as::<f32>().len(), samples.len());
+        assert_eq!(&converted[..], &samples.clone().convert::<f32>()[..]);
+
+        let converted: Vec<i24> = samples.iter_as::<i24>().collect();
+        assert_eq!(&converted[..], &samples.convert::<i24>()[..]);
+    }
+
     #[cfg(feature = "ndarray")]
     #[test]
     fn wav_as_ndarray() {
@@ -1087,6 +3500,19 @@ mod core_tests {
         }
     }
 
+    #[test]
+    fn u8_slice_to_primitive() {
+        let test_data: Vec<i16> = (0..8).collect();
+        let bytes: Vec<u8> = test_data.iter().flat_map(|s| s.to_ne_bytes()).collect();
+
+        let samples: Samples<i16> = Samples::from(bytes.as_slice());
+        assert_eq!(&samples[..], &test_data[..]);
+        assert_eq!(samples, Samples::<i16>::from_bytes(&bytes));
+
+        let bytes_as_samples: Samples<u8> = Samples::from(bytes.as_slice());
+        assert_eq!(&bytes_as_samples[..], &bytes[..]);
+    }
+
     #[test]
     fn read_and_convert() {
         let expected_samples =
@@ -1108,15 +3534,21 @@ mod core_tests {
         }
 
         let mut og_wav: Wav<f32> = Wav::<f32>::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let og_file_size = og_wav.header().current_file_size;
         let out_fp = format!("{}{}", TEST_OUTPUT, "convert_write_read.wav");
         og_wav.write::<f32, _>(Path::new(&out_fp)).unwrap();
 
         let mut wav: Wav<f32> = Wav::<f32>::from_path(&out_fp).unwrap();
         let actual_samples: &[f32] = &wav.read().unwrap();
 
+        assert_eq!(
+            wav.header().current_file_size as u64,
+            std::fs::metadata(&out_fp).unwrap().len(),
+            "File size does not match the size on disk"
+        );
         assert_ne!(
             wav.header().current_file_size,
-            og_wav.header().current_file_size,
+            og_file_size,
             "File sizes are equal and they shouldn't be"
         );
 
@@ -1169,6 +3601,65 @@ mod core_tests {
         assert_eq!(all_samples.len(), wav.n_samples());
     }
 
+    #[test]
+    fn read_blocks_into_reused_buffer() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+
+        let mut buf = vec![0; 2 * 1000];
+        let mut samples = Vec::new();
+        loop {
+            let n_frames = wav.read_block(&mut buf, 1000).unwrap();
+            if n_frames == 0 {
+                break;
+            }
+            samples.extend_from_slice(&buf[..n_frames * 2]);
+        }
+        assert_eq!(&samples[..], &expected[..], "Samples do not match");
+
+        // Converted samples are decoded through a smaller buffer, so use a block spanning several of them
+        let mut wav: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I24).unwrap();
+        let expected = wav.read().unwrap();
+        let mut buf = vec![0.0; 5000];
+        assert_eq!(wav.read_block(&mut buf, 10_000).unwrap(), 5000);
+        assert_eq!(&buf[..], &expected[..5000]);
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        assert!(wav.read_block(&mut [0; 16], 16).is_err());
+    }
+
+    #[test]
+    fn read_into_caller_storage() {
+        let mut wav: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected = wav.read().unwrap();
+
+        let mut first = [0.0; 100];
+        assert_eq!(wav.read_into(&mut first).unwrap(), 100);
+        assert_eq!(&first[..], &expected[..100]);
+
+        // Reads the remainder and resets, reusing the capacity of the vec
+        let mut samples = Vec::with_capacity(expected.len());
+        let capacity = samples.capacity();
+        assert_eq!(
+            wav.read_into_vec(&mut samples).unwrap(),
+            expected.len() - 100
+        );
+        assert_eq!(&samples[..], &expected[100..]);
+        assert_eq!(wav.read_into_vec(&mut samples).unwrap(), expected.len());
+        assert_eq!(&samples[..], &expected[..]);
+        assert_eq!(samples.capacity(), capacity);
+
+        let mut rest = vec![0.0; expected.len() + 10];
+        assert_eq!(wav.read_into(&mut rest).unwrap(), expected.len());
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        let expected = wav.read().unwrap();
+        let mut samples = Vec::new();
+        wav.read_into_vec(&mut samples).unwrap();
+        assert_eq!(&samples[..], &expected[..]);
+        assert!(wav.read_into(&mut [0; 16]).is_err());
+    }
+
     #[test]
     fn read_i24_correctly() {
         let mut wav: Wav<i24> = Wav::from_path(ONE_CHANNEL_WAV_I24).expect("Failed to open file");
@@ -1189,6 +3680,731 @@ mod core_tests {
         }
     }
 
+    #[test]
+    fn read_i24_as_f32() {
+        let mut wav: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I24).expect("Failed to open file");
+        let samples: &[f32] = &wav.read().unwrap();
+
+        let expected_samples =
+            read_text_to_vec::<f32>(Path::new(ONE_CHANNEL_EXPECTED_F32)).unwrap();
+
+        assert_eq!(samples.len(), expected_samples.len(), "Lengths not equal");
+        for (expected, actual) in expected_samples.iter().zip(samples) {
+            assert_approx_eq!(*expected as f64, *actual as f64, 1e-4);
+        }
+    }
+
+    #[test]
+    fn read_ms_adpcm() {
+        for (wav_fp, expected_fp, n_channels) in [
+            (ONE_CHANNEL_WAV_MS_ADPCM, ONE_CHANNEL_EXPECTED_MS_ADPCM, 1),
+            (TWO_CHANNEL_WAV_MS_ADPCM, TWO_CHANNEL_EXPECTED_MS_ADPCM, 2),
+        ] {
+            let mut wav: Wav<i16> = Wav::from_path(wav_fp).expect("Failed to open file");
+            assert_eq!(wav.encoding(), WavType::MsAdpcm, "Encoding is not MS ADPCM");
+            assert_eq!(
+                wav.n_channels(),
+                n_channels,
+                "Number of channels do not match"
+            );
+
+            let expected_samples = read_text_to_vec::<i16>(Path::new(expected_fp)).unwrap();
+            assert_eq!(
+                wav.n_samples(),
+                expected_samples.len(),
+                "Number of samples do not match"
+            );
+
+            let samples: &[i16] = &wav.read().unwrap();
+            assert_eq!(samples, &expected_samples[..], "Samples do not match");
+
+            // the reader is reset, so the file can be read again as another type
+            let mut wav: Wav<f32> = Wav::from_path(wav_fp).expect("Failed to open file");
+            let samples: &[f32] = &wav.read().unwrap();
+            for (expected, actual) in expected_samples.iter().zip(samples) {
+                assert_approx_eq!(*expected as f64 / i16::MAX as f64, *actual as f64, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn duration_of_block_encoded_files_comes_from_fact_chunk() {
+        for fp in [ONE_CHANNEL_WAV_GSM, ONE_CHANNEL_WAV_MS_ADPCM] {
+            let mut wav: Wav<i16> = Wav::from_path(fp).unwrap();
+            let n_frames = wav.wav_info.fact_chunk.unwrap().num_samples as u64;
+            let sample_rate = wav.sample_rate() as u64;
+            assert_eq!(wav.n_frames() as u64, n_frames);
+            assert_eq!(
+                wav.duration_time(),
+                Duration::from_nanos(n_frames * 1_000_000_000 / sample_rate)
+            );
+
+            // Without a fact chunk, the padding of the final block is counted
+            wav.wav_info.fact_chunk = None;
+            let estimate = wav.n_frames() as u64;
+            assert!(estimate >= n_frames);
+            assert!(estimate - n_frames < wav.frames_per_block());
+        }
+    }
+
+    #[test]
+    fn read_from_memory() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let mut wav: Wav<i16> =
+            Wav::from_bytes(include_bytes!("../test_resources/two_channel_i16.wav")).unwrap();
+        assert_eq!(wav.n_channels(), 2);
+        assert_eq!(wav.read().unwrap(), expected);
+        assert_eq!(&wav.read_frames(100, 10).unwrap()[..], &expected[200..220]);
+
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let mut wav: Wav<i16> = Wav::from_vec(bytes.clone()).unwrap();
+        assert_eq!(wav.read().unwrap(), expected);
+
+        let mut cursor = Cursor::new(std::sync::Arc::<[u8]>::from(bytes));
+        cursor.set_position(100);
+        let mut wav: Wav<i16> = Wav::from_cursor(cursor).unwrap();
+        assert_eq!(wav.read().unwrap(), expected);
+
+        assert!(Wav::<i16>::from_vec(vec![0; 16]).is_err());
+    }
+
+    #[test]
+    fn channels_are_overwritten_in_place() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_fp = format!("{}overwrite_channel.wav", TEST_OUTPUT);
+        std::fs::copy(TWO_CHANNEL_WAV_I16, &out_fp).unwrap();
+        let expected = Wav::<i16>::from_path(&out_fp).unwrap().read().unwrap();
+
+        let mut wav: Wav<f32> = Wav::from_path(&out_fp).unwrap();
+        let silence = vec![0.0; wav.n_frames()];
+        wav.overwrite_channel(1, &silence).unwrap();
+        assert!(wav.overwrite_channel(2, &silence).is_err());
+        assert!(wav.overwrite_channel(0, &silence[1..]).is_err());
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        let samples = wav.read().unwrap();
+        for (frame, expected) in samples.chunks(2).zip(expected.chunks(2)) {
+            assert_eq!(frame, &[expected[0], 0]);
+        }
+
+        // The Wav struct reads the new samples, rather than any it had buffered
+        let left: Vec<i16> = expected.iter().step_by(2).copied().collect();
+        wav.overwrite_channel(1, &left).unwrap();
+        for frame in wav.read().unwrap().chunks(2) {
+            assert_eq!(frame[0], frame[1]);
+        }
+
+        let mut wav: Wav<i16> = Wav::from_vec(std::fs::read(&out_fp).unwrap()).unwrap();
+        assert!(wav.overwrite_channel(0, &left).is_err());
+        std::fs::remove_file(Path::new(&out_fp)).unwrap();
+    }
+
+    #[test]
+    fn buffer_sizes_do_not_change_samples() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        for buffer_size in [1, 64, 1 << 20] {
+            let wav: Wav<i16> =
+                Wav::from_path_with_buffer_size(TWO_CHANNEL_WAV_I16, buffer_size).unwrap();
+            let mut clone = wav.try_clone().unwrap();
+            assert_eq!(clone.read().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn blocks_are_processed_into_the_destination() {
+        let out_fp = format!("{}process_to.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let samples = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        // Reading has already moved the reader, but processing starts from the first frame
+        wav.read_samples(100).unwrap();
+        let mut n_samples = 0;
+        wav.process_to(&out_fp, |block| {
+            assert_eq!(block.len() % 2, 0);
+            assert!(block.len() <= ITER_BLOCK_FRAMES * 2);
+            n_samples += block.len();
+            block
+                .iter_mut()
+                .for_each(|sample| *sample = sample.saturating_neg());
+        })
+        .unwrap();
+        assert_eq!(n_samples, samples.len());
+        let expected: Vec<i16> = samples.iter().map(|s| s.saturating_neg()).collect();
+        let mut processed: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(processed.spec(), wav.spec());
+        assert_eq!(&processed.read().unwrap()[..], &expected[..]);
+
+        // Processing a file into itself undoes the negation
+        processed
+            .process_to(&out_fp, |block| {
+                block
+                    .iter_mut()
+                    .for_each(|sample| *sample = sample.saturating_neg())
+            })
+            .unwrap();
+        let mut restored: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        let expected: Vec<i16> = samples.iter().map(|s| (*s).max(-i16::MAX)).collect();
+        assert_eq!(&restored.read().unwrap()[..], &expected[..]);
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+
+    #[test]
+    fn channels_are_mixed_down_to_mono() {
+        let mut wav: Wav<f32> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let samples = wav.read().unwrap();
+        wav.to_data().unwrap();
+        let mono = wav.read_mono().unwrap();
+        assert_eq!(mono.len(), wav.n_frames());
+        for (frame, sample) in samples.frames(2).zip(mono.iter()) {
+            assert!((sample - (frame[0] + frame[1]) / 2.0).abs() < 1e-6);
+        }
+
+        let left = samples.to_mono_weighted(&[1.0, 0.0]);
+        let expected: Vec<f32> = samples.frames(2).map(|frame| frame[0]).collect();
+        assert_eq!(&left[..], &expected[..]);
+
+        // Integer samples are averaged without overflowing
+        let samples: Samples<i16> = Samples::from(vec![i16::MAX, i16::MAX, -100, 100, 7]);
+        assert_eq!(&samples.to_mono(2)[..], &[i16::MAX, 0]);
+        assert_eq!(samples.to_mono(1), samples);
+    }
+
+    #[test]
+    fn mono_samples_are_copied_to_each_channel() {
+        let mono: Samples<i16> = Samples::from(vec![1, -2, 3]);
+        assert_eq!(&mono.upmix(2)[..], &[1, 1, -2, -2, 3, 3]);
+        assert_eq!(mono.upmix(1), mono);
+
+        let surround = mono.upmix(6);
+        assert_eq!(surround.len(), 18);
+        assert!(surround
+            .frames(6)
+            .zip(mono.iter())
+            .all(|(frame, &sample)| frame.iter().all(|&s| s == sample)));
+        assert_eq!(surround.to_mono(6), mono);
+    }
+
+    #[test]
+    fn channels_are_remixed_with_a_matrix() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let samples = wav.read().unwrap();
+        wav.to_data().unwrap();
+
+        let swapped = wav.read_remixed(&Matrix::swap_stereo()).unwrap();
+        let expected: Vec<i16> = samples
+            .frames(2)
+            .flat_map(|frame| [frame[1], frame[0]])
+            .collect();
+        assert_eq!(&swapped[..], &expected[..]);
+        assert_eq!(swapped, samples.remix(&Matrix::swap_stereo()));
+
+        wav.to_data().unwrap();
+        assert!(wav.read_remixed(&Matrix::surround_to_stereo()).is_err());
+
+        wav.to_data().unwrap();
+        let mono = Matrix::new(vec![vec![0.5, 0.5]]).unwrap();
+        let remixed = wav.read_remixed(&mono).unwrap();
+        assert_eq!(remixed.len(), wav.n_frames());
+    }
+
+    #[test]
+    fn reads_and_frames_are_resampled_to_the_target_sample_rate() {
+        let wav: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let n_frames = wav.n_frames();
+        let sample_rate = wav.sample_rate();
+        let target = sample_rate / 2;
+
+        assert!(Wav::<f32>::from_path(ONE_CHANNEL_WAV_I16)
+            .unwrap()
+            .with_target_sample_rate(0)
+            .is_err());
+        let mut wav = wav.with_target_sample_rate(target).unwrap();
+        assert_eq!(wav.sample_rate(), sample_rate);
+        assert_eq!(wav.target_sample_rate(), target);
+
+        let expected_frames =
+            (n_frames as u64 * target as u64).div_ceil(sample_rate as u64) as usize;
+        let samples = wav.read().unwrap();
+        assert_eq!(samples.len(), expected_frames);
+
+        wav.to_data().unwrap();
+        let frames: Vec<f32> = wav.frames().map(|frame| frame[0]).collect();
+        assert_eq!(frames.len(), expected_frames);
+        assert_eq!(&frames[..], &samples[..]);
+
+        // Positional reads still address the samples of the file
+        wav.to_data().unwrap();
+        assert_eq!(wav.read_samples(10).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn channels_are_read_at_the_sample_rate_of_the_file() {
+        let wav: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let n_frames = wav.n_frames();
+        let target = wav.sample_rate() / 2;
+        let mut wav = wav.with_target_sample_rate(target).unwrap();
+
+        assert_eq!(wav.channel(0).unwrap().count(), n_frames);
+        let channels: Vec<usize> = wav.channels().map(|channel| channel.count()).collect();
+        assert_eq!(channels, vec![n_frames]);
+    }
+
+    #[test]
+    fn clones_read_independently() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let wavs: [Wav<i16>; 2] = [
+            Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap(),
+            Wav::from_vec(bytes.clone()).unwrap(),
+        ];
+        for mut wav in wavs {
+            wav.seek_frame(100).unwrap();
+            let mut clone = wav.try_clone().unwrap();
+            assert_eq!(clone.current_frame().unwrap(), 0);
+            assert_eq!(clone.wav_info, wav.wav_info);
+
+            // Reading from one does not move the other
+            clone.seek_frame(1000).unwrap();
+            assert_eq!(&wav.read_samples(20).unwrap()[..], &expected[200..220]);
+            assert_eq!(&clone.read_samples(20).unwrap()[..], &expected[2000..2020]);
+            assert_eq!(&wav.read_samples(20).unwrap()[..], &expected[220..240]);
+
+            drop(wav);
+            clone.seek_frame(0).unwrap();
+            assert_eq!(clone.read().unwrap(), expected);
+        }
+
+        let stream: Wav<i16> = Wav::from_stream(Cursor::new(bytes)).unwrap();
+        assert!(stream.try_clone().is_err());
+        assert!(stream.handle().is_err());
+    }
+
+    #[test]
+    fn handles_read_on_other_threads() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected: Vec<Vec<i16>> = wav.frames().map(|frame| frame.to_vec()).collect();
+
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let handle = wav.handle().unwrap();
+                let start = worker * expected.len() / 4;
+                std::thread::spawn(move || {
+                    let mut clone: Wav<i16> = handle.open().unwrap();
+                    clone.seek_frame(start as u64).unwrap();
+                    let frames: Vec<Vec<i16>> =
+                        clone.frames().map(|frame| frame.to_vec()).collect();
+                    (start, frames)
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            let (start, frames) = worker.join().unwrap();
+            assert_eq!(frames, expected[start..]);
+        }
+    }
+
+    #[test]
+    fn read_from_stream() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        // Take is not seekable, so it stands in for a pipe
+        let stream = std::fs::File::open(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .take(u64::MAX);
+        let mut wav: Wav<i16> = Wav::from_stream(stream).unwrap();
+        assert_eq!(wav.n_channels(), 2);
+        assert_eq!(wav.n_samples(), expected.len());
+
+        assert_eq!(&wav.read_samples(10).unwrap()[..], &expected[..10]);
+        // The chunks before the data chunk are kept, so they can still be read
+        let fmt = wav.get_chunk::<crate::RawChunk>(FMT.into()).unwrap();
+        assert!(fmt.is_some());
+        // Skipping forward only needs to read
+        wav.seek_frame(100).unwrap();
+        assert_eq!(&wav.read_samples(10).unwrap()[..], &expected[200..210]);
+        assert_eq!(wav.current_frame().unwrap(), 105);
+        assert_eq!(&wav.read().unwrap()[..], &expected[210..]);
+
+        // Reading again needs to go back to the start of the data chunk
+        assert!(wav.read().is_err());
+
+        let stream = std::fs::File::open(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .take(u64::MAX);
+        let mut wav: Wav<i16> = Wav::from_stream(stream).unwrap();
+        let frames: Vec<i16> = wav.frames().flat_map(|frame| frame.to_vec()).collect();
+        assert_eq!(&frames[..], &expected[..]);
+    }
+
+    #[test]
+    fn read_samples_ms_adpcm_is_an_error() {
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_MS_ADPCM).unwrap();
+        assert!(wav.read_samples(10).is_err());
+    }
+
+    #[test]
+    fn read_gsm_610() {
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_GSM).expect("Failed to open file");
+        assert_eq!(wav.encoding(), WavType::Gsm610, "Encoding is not GSM 6.10");
+
+        // The final block is padded, the number of samples comes from the fact chunk
+        let expected_samples =
+            read_text_to_vec::<i16>(Path::new(ONE_CHANNEL_EXPECTED_GSM)).unwrap();
+        assert_eq!(wav.wav_info.fact_chunk.unwrap().num_samples, 16250);
+        assert_eq!(
+            wav.n_samples(),
+            expected_samples.len(),
+            "Number of samples do not match"
+        );
+        assert_eq!(wav.duration(), 1);
+
+        assert!(wav.read_samples(10).is_err());
+        let samples: &[i16] = &wav.read().unwrap();
+        assert_eq!(samples, &expected_samples[..], "Samples do not match");
+    }
+
+    #[test]
+    fn read_rf64() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        let (fmt_bytes, data_bytes) = (&wav_bytes[12..36], &wav_bytes[44..]);
+
+        let mut expected: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected_samples: Samples<i16> = expected.read().unwrap();
+
+        for riff_id in [RF64, BW64] {
+            let out_fp = format!(
+                "{}read_{}.wav",
+                TEST_OUTPUT,
+                String::from_utf8_lossy(&riff_id)
+            );
+
+            // Both the RIFF and data sizes are placeholders, the real sizes are in the ds64 chunk
+            let mut ds64 =
+                Ds64Chunk::new(0, data_bytes.len() as u64, expected_samples.len() as u64);
+            let riff_size = 4 + ds64.as_bytes().len() + fmt_bytes.len() + 8 + data_bytes.len();
+            ds64.riff_size = riff_size as u64;
+
+            let mut bytes = Vec::with_capacity(riff_size + 8);
+            bytes.extend_from_slice(&riff_id);
+            bytes.extend_from_slice(&RF64_PLACEHOLDER_SIZE.to_ne_bytes());
+            bytes.extend_from_slice(&WAVE);
+            bytes.extend_from_slice(&ds64.as_bytes());
+            bytes.extend_from_slice(fmt_bytes);
+            bytes.extend_from_slice(&DATA);
+            bytes.extend_from_slice(&RF64_PLACEHOLDER_SIZE.to_ne_bytes());
+            bytes.extend_from_slice(data_bytes);
+            std::fs::write(&out_fp, &bytes).unwrap();
+
+            let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+            assert_eq!(wav.header().data().size, data_bytes.len() as u64);
+            assert_eq!(wav.n_samples(), expected_samples.len());
+            assert_eq!(wav.duration(), expected.duration());
+            let samples: Samples<i16> = wav.read().unwrap();
+            assert_eq!(&samples[..], &expected_samples[..], "Samples do not match");
+            std::fs::remove_file(Path::new(&out_fp)).unwrap();
+        }
+    }
+
+    #[test]
+    fn read_raw_pcm() {
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        // Include a trailing partial sample, which should be ignored
+        let raw_bytes = wav_bytes[44..]
+            .iter()
+            .copied()
+            .chain([0u8])
+            .collect::<Vec<u8>>();
+
+        let mut expected: Wav<f32> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected_samples: Samples<f32> = expected.read().unwrap();
+
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(raw_bytes));
+        let mut wav: Wav<f32> = Wav::from_raw_pcm(reader, 16000, 1, WavType::Pcm16).unwrap();
+        assert_eq!(wav.n_samples(), expected_samples.len());
+        assert_eq!(wav.sample_rate(), 16000);
+        assert_eq!(wav.duration(), expected.duration());
+
+        let first: Samples<f32> = wav.read_samples(10).unwrap();
+        assert_eq!(&first[..], &expected_samples[..10]);
+        let samples: Samples<f32> = wav.read().unwrap();
+        assert_eq!(
+            &samples[..],
+            &expected_samples[10..],
+            "Samples do not match"
+        );
+
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(vec![0u8; 65]));
+        assert!(Wav::<i16>::from_raw_pcm(reader, 8000, 1, WavType::Gsm610).is_err());
+    }
+
+    #[test]
+    fn write_raw_pcm() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_fp = format!("{}write_raw_pcm.pcm", TEST_OUTPUT);
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        wav.write_raw::<i16, _>(&out_fp, Endianness::Little)
+            .unwrap();
+        assert_eq!(std::fs::read(&out_fp).unwrap(), &wav_bytes[44..]);
+
+        wav.write_raw::<i16, _>(&out_fp, Endianness::Big).unwrap();
+        let raw_bytes = std::fs::read(&out_fp).unwrap();
+        for (actual, expected) in raw_bytes.chunks(2).zip(wav_bytes[44..].chunks(2)) {
+            assert_eq!(actual, [expected[1], expected[0]]);
+        }
+
+        // Converted samples can be read back as raw PCM of the new type
+        wav.write_raw::<f32, _>(&out_fp, Endianness::Little)
+            .unwrap();
+        let expected_samples: Samples<f32> = wav.read().unwrap().convert();
+        let reader: Box<dyn ReadSeek> = Box::new(File::open(&out_fp).unwrap());
+        let mut raw_wav: Wav<f32> = Wav::from_raw_pcm(reader, 16000, 1, WavType::Float32).unwrap();
+        let samples: Samples<f32> = raw_wav.read().unwrap();
+        assert_eq!(&samples[..], &expected_samples[..]);
+        std::fs::remove_file(Path::new(&out_fp)).unwrap();
+    }
+
+    #[test]
+    fn read_metadata() {
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/n_samples_ext_test.wav").unwrap();
+        let metadata = wav.metadata().unwrap();
+        assert_eq!(
+            metadata.get(&InfoTag::Software),
+            Some(&"Lavf58.76.100".to_string())
+        );
+
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        assert!(wav.metadata().unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_metadata_with_several_lists() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+
+        // An INFO list with an odd length, and so padded, title followed by an associated data list
+        let mut lists = Vec::new();
+        lists.extend_from_slice(b"LIST");
+        lists.extend_from_slice(&30u32.to_ne_bytes());
+        lists.extend_from_slice(b"INFOINAM");
+        lists.extend_from_slice(&5u32.to_ne_bytes());
+        lists.extend_from_slice(b"Title\0IART");
+        lists.extend_from_slice(&4u32.to_ne_bytes());
+        lists.extend_from_slice(b"Jack");
+        lists.extend_from_slice(b"LIST");
+        lists.extend_from_slice(&4u32.to_ne_bytes());
+        lists.extend_from_slice(b"adtl");
+
+        let mut bytes = wav_bytes[..36].to_vec();
+        bytes.extend_from_slice(&lists);
+        bytes.extend_from_slice(&wav_bytes[36..]);
+        let riff_size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&riff_size.to_ne_bytes());
+        let out_fp = format!("{}read_metadata_with_several_lists.wav", TEST_OUTPUT);
+        std::fs::write(&out_fp, &bytes).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        let metadata = wav.metadata().unwrap();
+        assert_eq!(metadata.get(&InfoTag::Name), Some(&"Title".to_string()));
+        assert_eq!(metadata.get(&InfoTag::Artist), Some(&"Jack".to_string()));
+        assert!(wav.header().get_chunk_info((*b"adtl").into()).is_some());
+
+        let mut expected: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected_samples: Samples<i16> = expected.read().unwrap();
+        let samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&samples[..], &expected_samples[..]);
+        std::fs::remove_file(Path::new(&out_fp)).unwrap();
+    }
+
+    #[test]
+    fn enumerate_and_read_unknown_chunks() {
+        // Insert a vendor specific chunk with an odd size between the fmt and data chunks
+        let mut bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        let mut vendor_chunk = b"umid".to_vec();
+        vendor_chunk.extend_from_slice(&5u32.to_le_bytes());
+        vendor_chunk.extend_from_slice(&[1, 2, 3, 4, 5, 0]);
+        bytes.splice(36..36, vendor_chunk);
+        let riff_size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(bytes));
+        let mut wav: Wav<i16> = Wav::new(reader).unwrap();
+        let chunks: Vec<(ChunkIdentifier, usize, u64)> = wav.chunks().collect();
+        assert_eq!(
+            chunks,
+            vec![
+                (FMT.into(), 12, 16),
+                ((*b"umid").into(), 36, 5),
+                (DATA.into(), 50, wav.header().data().size)
+            ]
+        );
+
+        let first: Samples<i16> = wav.read_samples(10).unwrap();
+        let vendor_bytes = wav.read_chunk_bytes((*b"umid").into()).unwrap();
+        assert_eq!(vendor_bytes.as_deref(), Some(&[1u8, 2, 3, 4, 5][..]));
+        assert_eq!(wav.read_chunk_bytes((*b"junk").into()).unwrap(), None);
+
+        // Reading the chunk does not move the reader
+        let rest: Samples<i16> = wav.read_samples(10).unwrap();
+        let mut expected: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected: Samples<i16> = expected.read_samples(20).unwrap();
+        assert_eq!(&first[..], &expected[..10]);
+        assert_eq!(&rest[..], &expected[10..]);
+
+        // A chunk which claims to be larger than the rest of the file is an error, rather than an allocation of its claimed size
+        let mut bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        bytes.extend_from_slice(b"huge");
+        bytes.extend_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        let mut wav: Wav<i16> = Wav::from_vec(bytes).unwrap();
+        assert!(wav.read_chunk_bytes((*b"huge").into()).is_err());
+        assert_eq!(&wav.read_samples(10).unwrap()[..], &expected[..10]);
+    }
+
+    #[test]
+    fn read_rifx() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+
+        for (fp, n_bytes) in [(ONE_CHANNEL_WAV_I16, 2), (ONE_CHANNEL_WAV_I24, 3)] {
+            // Convert the little-endian file to a big-endian RIFX file
+            let mut bytes = std::fs::read(fp).unwrap();
+            bytes[0..4].copy_from_slice(&RIFX);
+            for (start, size) in [(4, 4), (16, 4), (20, 2), (22, 2), (24, 4), (28, 4), (32, 2)] {
+                bytes[start..start + size].reverse();
+            }
+            bytes[34..36].reverse();
+            bytes[40..44].reverse();
+            for sample in bytes[44..].chunks_exact_mut(n_bytes) {
+                sample.reverse();
+            }
+            let out_fp = format!("{}read_rifx_{}.wav", TEST_OUTPUT, n_bytes);
+            std::fs::write(&out_fp, &bytes).unwrap();
+
+            let mut expected: Wav<f32> = Wav::from_path(fp).unwrap();
+            let mut wav: Wav<f32> = Wav::from_path(&out_fp).unwrap();
+            assert!(wav.header().is_rifx());
+            assert_eq!(wav.header().fmt_chunk, expected.header().fmt_chunk);
+            assert_eq!(wav.n_samples(), expected.n_samples());
+
+            let expected_samples: Samples<f32> = expected.read().unwrap();
+            let samples: Samples<f32> = wav.read().unwrap();
+            assert_eq!(&samples[..], &expected_samples[..], "Samples do not match");
+
+            wav.to_data().unwrap();
+            let sample: f32 = wav.read_sample().unwrap();
+            assert_eq!(sample, expected_samples[0]);
+            std::fs::remove_file(Path::new(&out_fp)).unwrap();
+        }
+    }
+
+    #[test]
+    fn read_extensible_fields() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_EXTENSIBLE_I16).unwrap();
+        assert!(wav.is_extensible(), "File should be extensible");
+        assert_eq!(wav.encoding(), WavType::EPcm16);
+        assert_eq!(wav.valid_bits_per_sample(), 16);
+        assert_eq!(wav.channel_mask(), Some(0x3));
+        let ext = wav.ext_fmt_chunk().unwrap();
+        assert_eq!(ext.cb_size(), CbSize::Extended);
+        assert_eq!(ext.sub_format(), FormatCode::WAV_FORMAT_PCM);
+        assert_eq!(
+            ext.guid(),
+            *b"\x01\x00\x00\x00\x00\x00\x10\x00\x80\x00\x00\xAA\x00\x38\x9B\x71"
+        );
+
+        // The same samples as the first second of the non-extensible file
+        let samples: Samples<i16> = wav.read().unwrap();
+        let mut expected: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = expected.read_samples(samples.len()).unwrap();
+        assert_eq!(&samples[..], &expected[..], "Samples do not match");
+
+        let wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        assert!(!wav.is_extensible(), "File should not be extensible");
+        assert_eq!(wav.channel_mask(), None);
+        assert_eq!(wav.valid_bits_per_sample(), 16);
+    }
+
+    #[test]
+    fn read_masks_padding_bits() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_fp = format!("{}{}", TEST_OUTPUT, "read_masks_padding_bits.wav");
+
+        // Declare 12 valid bits in the 16-bit container, the valid bits field follows the cbSize field
+        let mut bytes = std::fs::read(TWO_CHANNEL_WAV_EXTENSIBLE_I16).unwrap();
+        let valid_bits_offset = 12 + 8 + FMT_CB_SIZE;
+        bytes[valid_bits_offset..valid_bits_offset + 2].copy_from_slice(&12u16.to_ne_bytes());
+        std::fs::write(&out_fp, &bytes).unwrap();
+
+        let mut expected: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_EXTENSIBLE_I16).unwrap();
+        let expected: Samples<i16> = expected.read().unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(wav.valid_bits_per_sample(), 12);
+        assert_eq!(wav.read_sample().unwrap(), expected[0] & !0xF);
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        let samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(samples.len(), expected.len());
+        for (exp, act) in expected.iter().zip(samples.iter()) {
+            assert_eq!(*act, *exp & !0xF, "Padding bits were not masked");
+        }
+        std::fs::remove_file(Path::new(&out_fp)).unwrap();
+    }
+
+    #[test]
+    fn write_multi_channel_as_extensible() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_fp = format!("{}{}", TEST_OUTPUT, "write_multi_channel_as_extensible.wav");
+        let mut wav: Wav<i16> = Wav::from_path(MULTI_CHANNEL_WAV).unwrap();
+        let n_channels = wav.n_channels();
+        let samples = wav.read().unwrap();
+        wav.write::<i16, _>(&out_fp).unwrap();
+
+        let mut new_wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert!(new_wav.is_extensible(), "File should be extensible");
+        assert_eq!(new_wav.encoding(), WavType::EPcm16);
+        assert_eq!(new_wav.n_channels(), n_channels);
+        assert_eq!(new_wav.header().fmt().size, FMT_SIZE_EXTENDED_SIZE as u64);
+        assert_eq!(new_wav.ext_fmt_chunk().unwrap().cb_size(), CbSize::Extended);
+        assert_eq!(&new_wav.read().unwrap()[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_fp)).unwrap();
+    }
+
     #[test]
     fn write_i24_correctly() {
         let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
@@ -1215,12 +4431,12 @@ mod core_tests {
     #[test]
     fn channels_iter_correct() {
         let mut wav: Wav<f32> = Wav::from_path(MULTI_CHANNEL_WAV).unwrap();
-        let channels: Vec<Samples<f32>> = wav.channels().collect();
+        let channels: Vec<Samples<f32>> = wav
+            .channels()
+            .map(|channel| Samples::from(channel.collect::<Vec<f32>>()))
+            .collect();
         assert_eq!(channels.len(), 69, "Expected 69 channels");
 
-        let mut wav: Wav<f32> = Wav::from_path(MULTI_CHANNEL_WAV).unwrap();
-        let channels = wav.channels();
-
         let mut reference: Wav<f32> = Wav::from_path(SIN_WAVE).unwrap();
 
         let reference: &[f32] = &reference.read().unwrap();
@@ -0,0 +1,225 @@
+//! Module for measuring and removing the DC offset of audio, which cheap capture hardware often adds to recordings.
+//!
+//! The offset of each channel is the mean of its samples, where 1.0 is full scale. It can be subtracted from samples which are held in memory,
+//! while ``DcBlocker`` removes it from a stream a block at a time using a one-pole high-pass filter, without knowing the mean up front.
+use std::f64::consts::PI;
+
+use i24::i24;
+
+use crate::check_sample_rate_and_channels;
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::WaversResult;
+
+/// The default cutoff frequency of a ``DcBlocker`` in Hz, which is below the audible range.
+pub const DC_BLOCKER_CUTOFF_HZ: f64 = 10.0;
+
+/// Sums each channel of interleaved samples into ``sums``, returning the number of whole frames.
+fn sum_channels<T: AudioSample>(samples: &[T], sums: &mut [f64]) -> usize {
+    let mut n_frames = 0;
+    for frame in samples.chunks_exact(sums.len()) {
+        for (sum, sample) in sums.iter_mut().zip(frame) {
+            *sum += ConvertTo::<f64>::convert_to(sample);
+        }
+        n_frames += 1;
+    }
+    n_frames
+}
+
+/// Divides the sums of each channel by the number of frames, giving 0.0 if there are none.
+fn means(mut sums: Vec<f64>, n_frames: usize) -> Vec<f64> {
+    if n_frames > 0 {
+        sums.iter_mut().for_each(|sum| *sum /= n_frames as f64);
+    }
+    sums
+}
+
+/// Removes the DC offset from interleaved samples which are passed to it a block at a time, using a one-pole high-pass filter on each channel.
+///
+/// The filter is ``y[n] = x[n] - x[n - 1] + r * y[n - 1]``, where ``r`` is set by the cutoff frequency. It settles within a few periods of the cutoff,
+/// so the start of a stream may still carry some of the offset, while frequencies well above the cutoff pass unchanged.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::dc::DcBlocker;
+/// use wavers::Wav;
+///
+/// fn main() {
+///     let mut wav: Wav<f32> = Wav::from_path("path/to/recording.wav").unwrap();
+///     let mut blocker = DcBlocker::new(wav.sample_rate(), wav.n_channels()).unwrap();
+///     wav.process_to("path/to/recording_no_dc.wav", |block| blocker.process(block))
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DcBlocker {
+    r: f64,
+    /// The previous input and output of each channel.
+    states: Vec<(f64, f64)>,
+}
+
+impl DcBlocker {
+    /// Creates a filter with a cutoff of ``DC_BLOCKER_CUTOFF_HZ`` for samples with the given sample rate and number of channels. See ``with_cutoff``.
+    pub fn new(sample_rate: i32, n_channels: u16) -> WaversResult<Self> {
+        Self::with_cutoff(sample_rate, n_channels, DC_BLOCKER_CUTOFF_HZ)
+    }
+
+    /// Creates a filter with the given cutoff frequency in Hz. A higher cutoff settles faster but also attenuates more of the low frequencies.
+    ///
+    /// This function will return an error if the sample rate or the number of channels is not positive.
+    pub fn with_cutoff(sample_rate: i32, n_channels: u16, cutoff_hz: f64) -> WaversResult<Self> {
+        check_sample_rate_and_channels(sample_rate, n_channels)?;
+        Ok(DcBlocker {
+            r: (-2.0 * PI * cutoff_hz / sample_rate as f64).exp(),
+            states: vec![(0.0, 0.0); n_channels as usize],
+        })
+    }
+
+    /// Filters a block of interleaved samples in place. Any trailing samples which do not make up a whole frame are left unchanged.
+    pub fn process<T: AudioSample>(&mut self, samples: &mut [T])
+    where
+        f64: ConvertTo<T>,
+    {
+        let n_channels = self.states.len();
+        for frame in samples.chunks_exact_mut(n_channels) {
+            for (sample, (x1, y1)) in frame.iter_mut().zip(self.states.iter_mut()) {
+                let x: f64 = ConvertTo::<f64>::convert_to(sample);
+                let y = x - *x1 + self.r * *y1;
+                (*x1, *y1) = (x, y);
+                *sample = y.convert_to();
+            }
+        }
+    }
+}
+
+impl<T: AudioSample> Samples<T> {
+    /// Returns the DC offset of each channel of interleaved samples, which is the mean of the samples of the channel, where 1.0 is full scale.
+    /// Any trailing samples which do not make up a whole frame are skipped.
+    ///
+    /// Panics if the number of channels is zero.
+    pub fn dc_offset(&self, n_channels: u16) -> Vec<f64> {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        let mut sums = vec![0.0; n_channels as usize];
+        let n_frames = sum_channels(&self.samples, &mut sums);
+        means(sums, n_frames)
+    }
+
+    /// Subtracts the DC offset of each channel from its samples, returning the offsets which were removed. See ``dc_offset``.
+    /// Use a ``DcBlocker`` for samples which are streamed rather than held in memory.
+    ///
+    /// Panics if the number of channels is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::Samples;
+    ///
+    /// let mut samples: Samples<f32> = Samples::from(vec![0.25, -0.5, 0.75, -0.5]);
+    /// assert_eq!(samples.remove_dc(2), vec![0.5, -0.5]);
+    /// assert_eq!(&samples[..], &[-0.25, 0.0, 0.25, 0.0]);
+    /// ```
+    pub fn remove_dc(&mut self, n_channels: u16) -> Vec<f64>
+    where
+        f64: ConvertTo<T>,
+    {
+        let offsets = self.dc_offset(n_channels);
+        for frame in self.samples.chunks_exact_mut(n_channels as usize) {
+            for (sample, offset) in frame.iter_mut().zip(&offsets) {
+                let x: f64 = ConvertTo::<f64>::convert_to(sample) - offset;
+                *sample = x.convert_to();
+            }
+        }
+        offsets
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Returns the DC offset of each channel of the wav file, scanning the file with ``scan_blocks``. See ``Samples::dc_offset``.
+    pub fn dc_offset(&mut self) -> WaversResult<Vec<f64>> {
+        let mut sums = vec![0.0; self.n_channels() as usize];
+        let mut n_frames = 0;
+        self.scan_blocks(|block| n_frames += sum_channels(block, &mut sums))?;
+        Ok(means(sums, n_frames))
+    }
+}
+
+#[cfg(test)]
+mod dc_tests {
+    use super::*;
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    /// A stereo 1 kHz tone at 16 kHz with an offset of 0.1 on the left channel and -0.05 on the right.
+    fn offset_tone(n_frames: usize) -> Vec<f64> {
+        (0..n_frames)
+            .flat_map(|i| {
+                let x = 0.5 * (2.0 * PI * 1000.0 * i as f64 / 16000.0).sin();
+                [x + 0.1, x - 0.05]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn offsets_are_measured_and_removed() {
+        let mut samples: Samples<i16> = Samples::from(offset_tone(16000)).convert();
+        let offsets = samples.dc_offset(2);
+        assert!((offsets[0] - 0.1).abs() < 1e-4 && (offsets[1] + 0.05).abs() < 1e-4);
+        assert_eq!(samples.remove_dc(2), offsets);
+        assert!(samples
+            .dc_offset(2)
+            .iter()
+            .all(|offset| offset.abs() < 1e-4));
+        assert!(Samples::<f32>::from(vec![]).dc_offset(1) == vec![0.0]);
+
+        let fp = format!("{}dc_offset.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let samples: Samples<f32> = Samples::from(offset_tone(16000)).convert();
+        crate::write(&fp, &samples, 16000, 2).unwrap();
+        let mut wav: Wav<f32> = Wav::from_path(&fp).unwrap();
+        let file_offsets = wav.dc_offset().unwrap();
+        for (file_offset, offset) in file_offsets.iter().zip(samples.dc_offset(2)) {
+            assert!((file_offset - offset).abs() < 1e-9);
+        }
+        std::fs::remove_file(&fp).unwrap();
+    }
+
+    #[test]
+    fn blocker_removes_the_offset_of_a_stream() {
+        let mut samples = offset_tone(32000);
+        let mut blocker = DcBlocker::new(16000, 2).unwrap();
+        samples
+            .chunks_mut(1000)
+            .for_each(|block| blocker.process(block));
+
+        // Once the filter has settled the offset is gone and the tone is unchanged
+        let settled = Samples::from(&samples[16000..]);
+        assert!(settled
+            .dc_offset(2)
+            .iter()
+            .all(|offset| offset.abs() < 1e-3));
+        let tone: Vec<f64> = offset_tone(32000)
+            .chunks_exact(2)
+            .map(|frame| frame[0] - 0.1)
+            .collect();
+        for (frame, x) in settled.frames(2).zip(&tone[8000..]) {
+            assert!((frame[0] - x).abs() < 0.01);
+        }
+
+        assert!(DcBlocker::new(0, 2).is_err());
+        assert!(DcBlocker::new(16000, 0).is_err());
+    }
+}
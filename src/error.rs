@@ -1,7 +1,7 @@
 //! Module containing the error types for Wavers
 use thiserror::Error;
 
-use crate::{FormatCode, WavType};
+use crate::{ChannelLayout, FormatCode, WavType};
 
 /// Result type for Wavers
 pub type WaversResult<T> = Result<T, WaversError>;
@@ -29,6 +29,30 @@ pub enum WaversError {
         attempted: u64,
     },
 
+    /// The operation was cancelled using a ``CancelToken``
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    /// ID3 tag error (when 'id3' feature is enabled)
+    #[cfg(feature = "id3")]
+    #[error("ID3 error: {0}")]
+    Id3Error(#[from] id3::Error),
+
+    /// Object store error (when 'object_store' feature is enabled)
+    #[cfg(feature = "object_store")]
+    #[error("Object store error: {0}")]
+    ObjectStoreError(#[from] object_store::Error),
+
+    /// Resampling error (when 'resampling' feature is enabled)
+    #[cfg(feature = "resampling")]
+    #[error("Resampling error: {0}")]
+    ResampleError(#[from] rubato::ResampleError),
+
+    /// Resampler construction error (when 'resampling' feature is enabled)
+    #[cfg(feature = "resampling")]
+    #[error("Resampler construction error: {0}")]
+    ResamplerConstructionError(#[from] rubato::ResamplerConstructionError),
+
     /// NdArray error (when 'ndarray' feature is enabled)
     #[cfg(feature = "ndarray")]
     #[error("NdArray error: {0}")]
@@ -52,6 +76,66 @@ pub enum FormatError {
     #[error("Invalid FMT chunk size: {0}")]
     InvalidFmtChunkSize(usize),
 
+    /// Invalid ds64 chunk size
+    #[error("Invalid ds64 chunk size: {0}")]
+    InvalidDs64ChunkSize(u64),
+
+    /// Invalid AIFF or AIFF-C data
+    #[error("Invalid AIFF data: {0}")]
+    InvalidAiffData(&'static str),
+
+    /// Unsupported AIFF-C compression type
+    #[error("Unsupported AIFF-C compression type: {0}")]
+    UnsupportedAiffCompression(String),
+
+    /// Invalid NIST SPHERE header
+    #[error("Invalid SPHERE header: {0}")]
+    InvalidSphereHeader(&'static str),
+
+    /// Unsupported NIST SPHERE sample coding, such as shorten compression
+    #[error("Unsupported SPHERE sample coding: {0}")]
+    UnsupportedSphereCoding(String),
+
+    /// Invalid LIST chunk size
+    #[error("Invalid LIST chunk size: {0}")]
+    InvalidListChunkSize(usize),
+
+    /// Invalid cue chunk size
+    #[error("Invalid cue chunk size: {0}")]
+    InvalidCueChunkSize(u64),
+
+    /// Invalid plst chunk size
+    #[error("Invalid plst chunk size: {0}")]
+    InvalidPlstChunkSize(u64),
+
+    /// A line of an Audacity label track does not start with a start and end time
+    #[error("Invalid Audacity label: {0}")]
+    InvalidAudacityLabel(String),
+
+    /// The LIST chunk is too short or is not an adtl list
+    #[error("Invalid adtl chunk")]
+    InvalidAdtlChunk,
+
+    /// Invalid smpl chunk size
+    #[error("Invalid smpl chunk size: {0}")]
+    InvalidSmplChunkSize(u64),
+
+    /// Invalid acid chunk size
+    #[error("Invalid acid chunk size: {0}")]
+    InvalidAcidChunkSize(u64),
+
+    /// Invalid inst chunk size
+    #[error("Invalid inst chunk size: {0}")]
+    InvalidInstChunkSize(u64),
+
+    /// A chunk identifier which is written by wavers itself, such as "fmt " or "data", was used for a custom chunk
+    #[error("Reserved chunk identifier used for a custom chunk: {0}")]
+    ReservedChunkIdentifier(String),
+
+    /// Invalid bext chunk size
+    #[error("Invalid bext chunk size: {0}")]
+    InvalidBextChunkSize(u64),
+
     /// Invalid number of bits for a Wav file to have for each sample.
     #[error("Invalid number of bits per sample: {0}")]
     InvalidBitsPerSample(u16),
@@ -67,4 +151,129 @@ pub enum FormatError {
     /// Unsupported write format
     #[error("Unsupported write format: main format {main}, sub-format {sub}")]
     UnsupportedWriteFormat { main: FormatCode, sub: FormatCode },
+
+    /// Invalid or corrupt ADPCM encoded data
+    #[error("Invalid ADPCM data: {0}")]
+    InvalidAdpcmData(&'static str),
+
+    /// Invalid or corrupt GSM 6.10 encoded data
+    #[error("Invalid GSM 6.10 data: {0}")]
+    InvalidGsmData(&'static str),
+
+    /// Block encoded formats can only be decoded a whole block at a time
+    #[error(
+        "Cannot read individual samples from block encoded format {0}, read the whole file instead"
+    )]
+    BlockEncodedFormat(WavType),
+
+    /// The valid bits per sample do not fit the container of the encoding
+    #[error("Invalid valid bits per sample {valid_bits_per_sample} for encoding {wav_type}")]
+    InvalidValidBitsPerSample {
+        valid_bits_per_sample: u16,
+        wav_type: WavType,
+    },
+
+    /// The channel layout assigns more speaker positions than there are channels
+    #[error(
+        "Channel layout {layout} has more speakers than the {n_channels} channels being written"
+    )]
+    InvalidChannelLayout {
+        layout: ChannelLayout,
+        n_channels: u16,
+    },
+
+    /// The encoding cannot be stored in the extensible format, which is required to record a channel layout
+    #[error("Cannot write a channel layout for encoding {0}")]
+    ChannelLayoutNotSupported(WavType),
+
+    /// The channel index is out of range for the number of channels in the file
+    #[error("Invalid channel {channel} for a file with {n_channels} channels")]
+    InvalidChannel { channel: usize, n_channels: u16 },
+
+    /// The maximum buffer size of a streaming reader cannot hold a single frame
+    #[error(
+        "Streaming buffer of {max_buffer_size} bytes cannot hold a frame of {frame_size} bytes"
+    )]
+    StreamingBufferTooSmall {
+        max_buffer_size: usize,
+        frame_size: usize,
+    },
+
+    /// The frame is past the end of the data chunk
+    #[error("Frame {frame} is out of range for a file with {n_frames} frames")]
+    FrameOutOfRange { frame: u64, n_frames: u64 },
+
+    /// Block encoded files can only be sought to the first frame of a block
+    #[error("Frame {frame} is not at the start of a block of {frames_per_block} frames")]
+    UnalignedFrame { frame: u64, frames_per_block: u64 },
+
+    /// The number of samples written is not a multiple of the number of channels
+    #[error(
+        "{n_samples} samples do not make up a whole number of frames of {n_channels} channels"
+    )]
+    IncompleteFrames { n_samples: usize, n_channels: u16 },
+
+    /// The channels of planar samples must all have the same number of samples
+    #[error("Channel {channel} has {len} samples, expected {expected} samples")]
+    PlanarChannelLength {
+        channel: usize,
+        len: usize,
+        expected: usize,
+    },
+
+    /// The header of an unfinalized wav file cannot be repaired
+    #[error("Cannot recover wav file: {0}")]
+    Unrecoverable(&'static str),
+
+    /// A stream of known length was given a different number of frames
+    #[error("Stream of {expected} frames was given {written} frames")]
+    StreamLengthMismatch { expected: u64, written: u64 },
+
+    /// The start of the frame range is after its end
+    #[error("Invalid frame range {start}..{end}")]
+    InvalidFrameRange { start: u64, end: u64 },
+
+    /// The window length is zero or longer than the file
+    #[error("Invalid window length {win_len} for a file with {n_frames} frames")]
+    InvalidWindowLength { win_len: usize, n_frames: u64 },
+
+    /// A chain of wav files needs at least one file
+    #[error("Cannot create a chain of zero wav files")]
+    EmptyWavChain,
+
+    /// Every file of a chain must have the same sample rate and number of channels
+    #[error("Wav {index} of the chain has a sample rate of {sample_rate} and {n_channels} channels, expected a sample rate of {expected_sample_rate} and {expected_n_channels} channels")]
+    IncompatibleWavChain {
+        index: usize,
+        sample_rate: i32,
+        n_channels: u16,
+        expected_sample_rate: i32,
+        expected_n_channels: u16,
+    },
+
+    /// The sample rate must be positive
+    #[error("Invalid sample rate: {0}")]
+    InvalidSampleRate(i32),
+
+    /// The number of channels must be positive and fit in the header of a wav file
+    #[error("Invalid number of channels: {0}")]
+    InvalidNumberOfChannels(usize),
+
+    /// Channels can only be mixed down to mono, copied from mono, or kept as they are
+    #[error("Cannot convert {from} channels to {to} channels")]
+    ChannelConversion { from: u16, to: u16 },
+
+    /// A mixing matrix needs at least one row, and every row needs the same, non-zero number of gains
+    #[error("Invalid mixing matrix, the rows must be non-empty and of equal length")]
+    InvalidMatrix,
+
+    /// The number of input channels of a mixing matrix must match the number of channels of the samples
+    #[error(
+        "Mixing matrix expects {expected} input channels, but the samples have {found} channels"
+    )]
+    MatrixChannelMismatch { expected: usize, found: usize },
+
+    /// Applying a gain with ``ClipPolicy::Error`` would push integer samples beyond full scale
+    #[error("A gain of {0} dB would clip the samples")]
+    Clipping(f64),
 }
@@ -0,0 +1,185 @@
+//! Module containing functions which change the level of a wav file, such as ``normalize_peak`` and ``apply_gain``.
+//!
+//! Levels are given in dBFS, where 0 dBFS is the full scale of the encoding, and gains in dB.
+//! Samples are scaled as ``f64`` and converted back to their type, so integer samples are clamped to full scale while float samples may exceed it.
+use std::any::TypeId;
+use std::path::Path;
+
+use crate::conversion::{AudioSample, ConvertTo};
+use crate::core::Wav;
+use crate::error::{FormatError, WaversResult};
+use crate::wav_type::WavType;
+
+/// What to do when a gain pushes integer samples beyond full scale.
+/// Float samples can hold values beyond full scale, so they are never clipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClipPolicy {
+    /// Clamp the samples to full scale.
+    #[default]
+    Clamp,
+    /// Return an error, without writing anything, if any sample would be clipped.
+    Error,
+}
+
+/// Converts a level in dB to a linear gain.
+pub(crate) fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+/// Returns the gain which brings a signal with the absolute peak ``peak`` to ``target_dbfs``, or 1.0 if the signal is silent.
+pub(crate) fn peak_gain(peak: f64, target_dbfs: f64) -> f64 {
+    match peak > 0.0 {
+        true => db_to_gain(target_dbfs) / peak,
+        false => 1.0,
+    }
+}
+
+/// Returns true if samples of type ``T`` are clamped to full scale, which is the case for every sample type other than floats.
+pub(crate) fn is_integer<T: 'static>() -> bool {
+    WavType::try_from(TypeId::of::<T>()).is_ok_and(|wav_type| !wav_type.is_float())
+}
+
+/// Multiplies each sample by ``gain``, converting the samples to ``f64`` and back.
+pub(crate) fn scale_samples<T: AudioSample>(samples: &mut [T], gain: f64)
+where
+    f64: ConvertTo<T>,
+{
+    for sample in samples.iter_mut() {
+        let scaled: f64 = ConvertTo::<f64>::convert_to(sample) * gain;
+        *sample = scaled.convert_to();
+    }
+}
+
+/// Scales the wav file at ``src`` so that its absolute peak is at ``target_dbfs`` and writes it to ``dst``, returning the linear gain which was applied.
+///
+/// The file is read twice, a block of frames at a time: once to find the peak and once to scale and write the samples, so long recordings are never loaded into memory as a whole.
+/// The encoding of ``src`` is kept and ``dst`` can be ``src``, see ``Wav::process_to``. A silent file is copied unchanged with a gain of 1.0.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::normalize_peak;
+///
+/// fn main() {
+///     let gain = normalize_peak("./take.wav", "./take_normalized.wav", -1.0).unwrap();
+///     println!("Applied a gain of {:.2} dB", 20.0 * gain.log10());
+/// }
+/// ```
+pub fn normalize_peak<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    target_dbfs: f64,
+) -> WaversResult<f64> {
+    let mut wav: Wav<f64> = Wav::from_path(src)?;
+    let gain = peak_gain(wav.peak()?, target_dbfs);
+    wav.process_to(dst, |block| {
+        block.iter_mut().for_each(|sample| *sample *= gain)
+    })?;
+    Ok(gain)
+}
+
+/// Multiplies the samples of the wav file at ``src`` by a gain of ``gain_db`` dB and writes them to ``dst``, returning the number of samples which were clamped to full scale.
+///
+/// The file is streamed a block of frames at a time, the encoding of ``src`` is kept and ``dst`` can be ``src``, see ``Wav::process_to``.
+/// Float encodings are never clipped. For the other encodings ``policy`` decides whether samples pushed beyond full scale are clamped,
+/// or whether an error is returned, in which case the peak of the file is checked in a first pass so that ``dst`` is left untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{apply_gain, ClipPolicy};
+///
+/// fn main() {
+///     // Fails rather than distorting the take if it is too loud to be raised by 6 dB
+///     apply_gain("./take.wav", "./take_louder.wav", 6.0, ClipPolicy::Error).unwrap();
+/// }
+/// ```
+pub fn apply_gain<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    gain_db: f64,
+    policy: ClipPolicy,
+) -> WaversResult<usize> {
+    let mut wav: Wav<f64> = Wav::from_path(src)?;
+    let gain = db_to_gain(gain_db);
+    let clips = !wav.encoding().is_float() && gain > 1.0;
+    if clips && policy == ClipPolicy::Error && wav.peak()? * gain > 1.0 {
+        return Err(FormatError::Clipping(gain_db).into());
+    }
+    let mut n_clipped = 0;
+    wav.process_to(dst, |block| {
+        for sample in block.iter_mut() {
+            *sample *= gain;
+            n_clipped += (clips && sample.abs() > 1.0) as usize;
+        }
+    })?;
+    Ok(n_clipped)
+}
+
+#[cfg(test)]
+mod gain_tests {
+    use super::*;
+    use crate::core::Samples;
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn files_are_normalized_to_the_target_peak() {
+        let out_fp = format!("{}normalize_peak.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let samples = wav.read().unwrap();
+
+        let gain = normalize_peak(TWO_CHANNEL_WAV_I16, &out_fp, -6.0).unwrap();
+        let mut normalized: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(normalized.spec(), wav.spec());
+        let peak = normalized.peak().unwrap();
+        assert!((20.0 * peak.log10() + 6.0).abs() < 0.01);
+
+        // The same gain is applied by the in-memory variant
+        let mut expected: Samples<i16> = samples.clone();
+        assert!((expected.normalize_peak(-6.0) - gain).abs() < 1e-12);
+        assert_eq!(normalized.read().unwrap(), expected);
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+
+    #[test]
+    fn gains_clamp_or_reject_clipped_samples() {
+        let out_fp = format!("{}apply_gain.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let samples = wav.read().unwrap();
+        let headroom_db = -20.0 * wav.peak().unwrap().log10();
+
+        // Lowering the level never clips
+        assert_eq!(
+            apply_gain(TWO_CHANNEL_WAV_I16, &out_fp, -6.0, ClipPolicy::Error).unwrap(),
+            0
+        );
+        let mut expected = samples.clone();
+        assert_eq!(expected.apply_gain_db(-6.0), 0);
+        let mut quieter: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(quieter.read().unwrap(), expected);
+
+        // Raising the level past the headroom of the file clips its peaks
+        let gain_db = headroom_db + 6.0;
+        assert!(apply_gain(TWO_CHANNEL_WAV_I16, &out_fp, gain_db, ClipPolicy::Error).is_err());
+        let mut expected = samples.clone();
+        let n_clipped = expected.apply_gain_db(gain_db);
+        assert!(n_clipped > 0);
+        assert_eq!(
+            apply_gain(TWO_CHANNEL_WAV_I16, &out_fp, gain_db, ClipPolicy::Clamp).unwrap(),
+            n_clipped
+        );
+        let mut louder: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(louder.read().unwrap(), expected);
+        assert_eq!(louder.peak().unwrap(), 1.0);
+
+        // Float samples are scaled beyond full scale
+        let mut floats: Samples<f32> = Samples::from(vec![0.5, -0.75]);
+        assert_eq!(floats.apply_gain_db(20.0 * 2f64.log10()), 0);
+        assert!((floats[1] + 1.5).abs() < 1e-6);
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+}
@@ -14,12 +14,17 @@ use colored::Colorize;
 
 use crate::{
     chunks::{
-        fmt::{CbSize, ExtFmtChunkInfo, FMT_CB_SIZE, FMT_SIZE_BASE_SIZE, FMT_SIZE_EXTENDED_SIZE},
-        list::InfoId,
-        read_chunk, Chunk, FmtChunk, ListChunk, DATA, FACT, FMT, LIST, RIFF, WAVE,
+        ds64::{DS64_BASE_SIZE, RF64_PLACEHOLDER_SIZE},
+        fact::FACT_SIZE,
+        fmt::{
+            default_channel_mask, CbSize, ExtFmtChunkInfo, FMT_CB_SIZE, FMT_SIZE_BASE_SIZE,
+            FMT_SIZE_EXTENDED_SIZE,
+        },
+        read_chunk, Chunk, Ds64Chunk, FmtChunk, BW64, DATA, DS64, FACT, FMT, INFO, LIST, RF64,
+        RIFF, RIFX, WAVE,
     },
     conversion::AudioSample,
-    core::{ReadSeek, WavInfo},
+    core::{alloc_box_buffer, ReadSeek, WavInfo},
     error::{FormatError, WaversError, WaversResult},
     log,
     wav_type::{format_info_to_wav_type, FormatCode, WavType},
@@ -34,7 +39,7 @@ const HEADER_FMT_EXTENDED_SIZE: usize = 60;
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct HeaderChunkInfo {
     pub offset: usize,
-    pub size: u32,
+    pub size: u64,
 }
 
 impl Display for HeaderChunkInfo {
@@ -45,19 +50,19 @@ impl Display for HeaderChunkInfo {
 
 impl HeaderChunkInfo {
     /// Constructs a new HeaderEntryInfo struct with a given offset and size.
-    pub fn new(offset: usize, size: u32) -> Self {
+    pub fn new(offset: usize, size: u64) -> Self {
         HeaderChunkInfo { offset, size }
     }
 }
 
-impl Into<(usize, u32)> for HeaderChunkInfo {
-    fn into(self) -> (usize, u32) {
+impl Into<(usize, u64)> for HeaderChunkInfo {
+    fn into(self) -> (usize, u64) {
         (self.offset, self.size)
     }
 }
 
-impl Into<(usize, u32)> for &HeaderChunkInfo {
-    fn into(self) -> (usize, u32) {
+impl Into<(usize, u64)> for &HeaderChunkInfo {
+    fn into(self) -> (usize, u64) {
         (self.offset, self.size)
     }
 }
@@ -134,24 +139,41 @@ impl WavHeader {
     where
         T: AudioSample,
     {
+        let wav_type: WavType = TypeId::of::<T>().try_into()?;
+        WavHeader::new_header_with_type(wav_type, sample_rate, n_channels, n_samples)
+    }
+
+    /// Creates a new WavHeader for samples stored with the given encoding, sample rate, number of channels and number of samples.
+    /// Unlike ``new_header``, the encoding does not have to match a native sample type, for example ``WavType::ALaw``.
+    pub fn new_header_with_type(
+        wav_type: WavType,
+        sample_rate: i32,
+        n_channels: u16,
+        n_samples: usize,
+    ) -> WaversResult<Self> {
         log!(
             log::Level::Debug,
-            "Creating new header with sample rate: {}, channels: {}, samples: {}",
+            "Creating new {} header with sample rate: {}, channels: {}, samples: {}",
+            wav_type,
             sample_rate,
             n_channels,
             n_samples
         );
-        let wav_type: WavType = TypeId::of::<T>().try_into()?;
 
         let (main_format, sub_format) = match wav_type {
-            WavType::Pcm16 | WavType::Pcm24 | WavType::Pcm32 => {
+            // The extensible format is required when there are more than two channels
+            WavType::Pcm8 | WavType::Pcm16 | WavType::Pcm24 | WavType::Pcm32 if n_channels > 2 => (
+                FormatCode::WAVE_FORMAT_EXTENSIBLE,
+                FormatCode::WAV_FORMAT_PCM,
+            ),
+            WavType::Pcm8 | WavType::Pcm16 | WavType::Pcm24 | WavType::Pcm32 => {
                 (FormatCode::WAV_FORMAT_PCM, FormatCode::WAV_FORMAT_PCM)
             }
             WavType::Float32 | WavType::Float64 => (
                 FormatCode::WAVE_FORMAT_EXTENSIBLE,
                 FormatCode::WAV_FORMAT_IEEE_FLOAT,
             ),
-            WavType::EPcm16 | WavType::EPcm24 | WavType::EPcm32 => (
+            WavType::EPcm8 | WavType::EPcm16 | WavType::EPcm24 | WavType::EPcm32 => (
                 FormatCode::WAVE_FORMAT_EXTENSIBLE,
                 FormatCode::WAV_FORMAT_PCM,
             ),
@@ -159,6 +181,12 @@ impl WavHeader {
                 FormatCode::WAVE_FORMAT_EXTENSIBLE,
                 FormatCode::WAV_FORMAT_IEEE_FLOAT,
             ),
+            WavType::ALaw => (FormatCode::WAVE_FORMAT_ALAW, FormatCode::WAVE_FORMAT_ALAW),
+            WavType::MuLaw => (FormatCode::WAVE_FORMAT_MULAW, FormatCode::WAVE_FORMAT_MULAW),
+            // Encoding to MS ADPCM or GSM 6.10 is not supported
+            WavType::MsAdpcm | WavType::Gsm610 => {
+                return Err(FormatError::InvalidWavType(wav_type).into())
+            }
         };
 
         let bits_per_sample = wav_type.n_bits();
@@ -167,17 +195,19 @@ impl WavHeader {
             (FormatCode::WAV_FORMAT_PCM, FormatCode::WAV_FORMAT_PCM) => {
                 ExtFmtChunkInfo::new(CbSize::Base, bits_per_sample, 0, FormatCode::WAV_FORMAT_PCM)
             }
-            (FormatCode::WAVE_FORMAT_EXTENSIBLE, FormatCode::WAV_FORMAT_PCM) => {
-                ExtFmtChunkInfo::new(CbSize::Base, bits_per_sample, 0, FormatCode::WAV_FORMAT_PCM)
-            }
-            (FormatCode::WAVE_FORMAT_EXTENSIBLE, FormatCode::WAV_FORMAT_IEEE_FLOAT) => {
+            (FormatCode::WAVE_FORMAT_EXTENSIBLE, FormatCode::WAV_FORMAT_PCM)
+            | (FormatCode::WAVE_FORMAT_EXTENSIBLE, FormatCode::WAV_FORMAT_IEEE_FLOAT) => {
                 ExtFmtChunkInfo::new(
-                    CbSize::Base,
+                    CbSize::Extended,
                     bits_per_sample,
-                    0,
-                    FormatCode::WAV_FORMAT_IEEE_FLOAT,
+                    default_channel_mask(n_channels),
+                    sub_format,
                 )
             }
+            (FormatCode::WAVE_FORMAT_ALAW, FormatCode::WAVE_FORMAT_ALAW)
+            | (FormatCode::WAVE_FORMAT_MULAW, FormatCode::WAVE_FORMAT_MULAW) => {
+                ExtFmtChunkInfo::new(CbSize::Base, bits_per_sample, 0, sub_format)
+            }
             _ => return Err(FormatError::InvalidWavType(wav_type).into()),
         };
 
@@ -189,12 +219,6 @@ impl WavHeader {
             ext_fmt_chunk,
         );
 
-        let fact_chunk = FactChunk::new((n_samples / n_channels as usize) as u32); // number of samples per channel
-
-        let mut list_chunk_data: HashMap<InfoId, String> = HashMap::new();
-        list_chunk_data.insert(*b"ISFT", "Wavers".to_string()); // Software used to create the file
-        let list_chunk = ListChunk::new(*b"INFO", list_chunk_data);
-
         let mut header_info: HashMap<ChunkIdentifier, HeaderChunkInfo> = HashMap::new();
 
         let header_offset = match main_format {
@@ -202,42 +226,56 @@ impl WavHeader {
             FormatCode::WAV_FORMAT_IEEE_FLOAT | FormatCode::WAVE_FORMAT_EXTENSIBLE => {
                 FMT_SIZE_EXTENDED_SIZE
             }
+            FormatCode::WAVE_FORMAT_ALAW | FormatCode::WAVE_FORMAT_MULAW => FMT_CB_SIZE,
             _ => {
                 return Err(FormatError::InvalidWavType(wav_type).into());
             }
         };
 
-        header_info.insert(RIFF.into(), HeaderChunkInfo::new(0, RIFF_SIZE as u32));
-        // insert fmt
-        header_info.insert(FMT.into(), HeaderChunkInfo::new(12, header_offset as u32));
+        // Every encoding other than integer PCM requires a fact chunk, recording the number of samples per channel
+        let fact_size = match sub_format {
+            FormatCode::WAV_FORMAT_PCM => 0,
+            _ => 8 + FACT_SIZE,
+        };
 
-        header_info.insert(
-            FACT.into(),
-            HeaderChunkInfo::new(12 + header_offset, fact_chunk.size() as u32 + 8),
-        );
+        let data_size_bytes = n_samples as u64 * (bits_per_sample / 8) as u64;
+        let riff_size =
+            RIFF_SIZE as u64 + 8 + header_offset as u64 + fact_size as u64 + 8 + data_size_bytes;
 
+        // Files too large for the 32-bit RIFF size are promoted to RF64, which stores the sizes in a ds64 chunk placed before the fmt chunk
+        let ds64_size = match riff_size > u32::MAX as u64 {
+            true => {
+                header_info.insert(DS64.into(), HeaderChunkInfo::new(12, DS64_BASE_SIZE as u64));
+                DS64_BASE_SIZE + 8
+            }
+            false => 0,
+        };
+        let riff_size = riff_size + ds64_size as u64;
+
+        header_info.insert(RIFF.into(), HeaderChunkInfo::new(0, riff_size));
+        // insert fmt
         header_info.insert(
-            LIST.into(),
-            HeaderChunkInfo::new(
-                12 + header_offset + fact_chunk.size() as usize + 8,
-                list_chunk.size() as u32 + 8,
-            ),
+            FMT.into(),
+            HeaderChunkInfo::new(12 + ds64_size, header_offset as u64),
         );
 
-        let data_size_bytes = n_samples * (bits_per_sample / 8) as usize;
+        if fact_size > 0 {
+            header_info.insert(
+                FACT.into(),
+                HeaderChunkInfo::new(12 + ds64_size + 8 + header_offset, FACT_SIZE as u64),
+            );
+        }
 
         // insert data
         header_info.insert(
             DATA.into(),
-            HeaderChunkInfo::new(12 + header_offset, data_size_bytes as u32),
+            HeaderChunkInfo::new(
+                12 + ds64_size + 8 + header_offset + fact_size,
+                data_size_bytes,
+            ),
         );
 
-        let current_file_size = header_info
-            .iter()
-            .filter(|(k, _)| !buf_eq(k.as_ref(), &RIFF))
-            .map(|(_, v)| v.size + 8)
-            .sum::<u32>() as usize
-            + 8; // Go through each chunk found and sum the size field, +8 for each chunk identifier and size field
+        let current_file_size = riff_size as usize + 8; // +8 for the RIFF identifier and size field
 
         log!(
             log::Level::Debug,
@@ -258,11 +296,100 @@ impl WavHeader {
         self.current_file_size
     }
 
+    /// Returns true if the header is for a RIFX file, i.e. the chunk sizes and samples are stored in big-endian byte order.
+    pub fn is_rifx(&self) -> bool {
+        self.header_info.contains_key(&RIFX.into())
+    }
+
+    /// Marks the header as being for a RIFX file, so that samples are read in big-endian byte order.
+    pub(crate) fn set_rifx(&mut self) {
+        if let Some(riff_info) = self.header_info.remove(&RIFF.into()) {
+            self.header_info.insert(RIFX.into(), riff_info);
+        }
+    }
+
+    /// Returns true if the header is for an RF64 file, i.e. the file is too large for 32-bit chunk sizes.
+    pub fn is_rf64(&self) -> bool {
+        self.header_info.contains_key(&DS64.into())
+    }
+
+    /// Returns the ds64 chunk describing this header if it is for an RF64 file.
+    pub fn ds64_chunk(&self) -> Option<Ds64Chunk> {
+        match self.is_rf64() {
+            true => {
+                let data_size = self.data().size;
+                Some(Ds64Chunk::new(
+                    self.file_size() as u64 - 8,
+                    data_size,
+                    data_size / self.fmt_chunk.block_align as u64,
+                ))
+            }
+            false => None,
+        }
+    }
+
+    /// Returns the fact chunk describing this header if it has one, i.e. the samples are not stored as integer PCM.
+    /// The number of samples is derived from the size of the data chunk, so this is only valid for encodings without blocks.
+    fn fact_chunk(&self) -> Option<FactChunk> {
+        match self.header_info.contains_key(&FACT.into()) {
+            true => Some(FactChunk::new(
+                (self.data().size / self.fmt_chunk.block_align as u64) as u32,
+            )),
+            false => None,
+        }
+    }
+
+    /// Returns the value written to the 32-bit RIFF size field.
+    fn riff_size(&self) -> u32 {
+        match self.is_rf64() {
+            true => RF64_PLACEHOLDER_SIZE,
+            false => (self.file_size() - 8) as u32,
+        }
+    }
+
+    /// Returns the header in bytes, using the size of the FmtChunk to decide whether it is written in the base, cb or extensible format.
+    /// If the header is for an RF64 file, the RF64 identifier and the ds64 chunk are written instead of the RIFF identifier.
+    /// The fact chunk is written after the fmt chunk if the header has one.
+    pub fn as_bytes(&self) -> Box<[u8]> {
+        let fmt_bytes = self.fmt_chunk.as_bytes();
+        let ds64_bytes = self.ds64_chunk().map(|ds64| ds64.as_bytes());
+        let ds64_bytes: &[u8] = ds64_bytes.as_deref().unwrap_or_default();
+
+        let fact_bytes = self.fact_chunk().map(|fact| fact.as_bytes());
+        let fact_bytes: &[u8] = fact_bytes.as_deref().unwrap_or_default();
+
+        let mut bytes =
+            Vec::with_capacity(12 + ds64_bytes.len() + fmt_bytes.len() + fact_bytes.len());
+        bytes.extend_from_slice(match self.is_rf64() {
+            true => &RF64,
+            false => &RIFF,
+        });
+        bytes.extend_from_slice(&self.riff_size().to_ne_bytes());
+        bytes.extend_from_slice(&WAVE);
+        bytes.extend_from_slice(ds64_bytes);
+        bytes.extend_from_slice(&fmt_bytes);
+        bytes.extend_from_slice(fact_bytes);
+        bytes.into_boxed_slice()
+    }
+
+    /// Returns the identifier and size field of the data chunk in bytes.
+    /// The size field is set to ``RF64_PLACEHOLDER_SIZE`` if the header is for an RF64 file.
+    pub fn data_chunk_bytes(&self) -> [u8; 8] {
+        let data_size = match self.is_rf64() {
+            true => RF64_PLACEHOLDER_SIZE,
+            false => self.data().size as u32,
+        };
+        let mut bytes = [0; 8];
+        bytes[0..4].copy_from_slice(&DATA);
+        bytes[4..8].copy_from_slice(&data_size.to_ne_bytes());
+        bytes
+    }
+
     /// Returns the header in bytes, assuming that the FmtChunk is in the base format.
     pub fn as_base_bytes(&self) -> [u8; HEADER_FMT_BASE_SIZE] {
         let mut bytes = [0; HEADER_FMT_BASE_SIZE];
         bytes[0..4].copy_from_slice(&RIFF);
-        let size = self.file_size() as u32;
+        let size = self.riff_size();
         bytes[4..8].copy_from_slice(&size.to_ne_bytes());
         bytes[8..12].copy_from_slice(&WAVE);
 
@@ -281,7 +408,7 @@ impl WavHeader {
     pub fn as_cb_bytes(&self) -> [u8; HEADER_FMT_CB_SIZE] {
         let mut bytes = [0; HEADER_FMT_CB_SIZE];
         bytes[0..4].copy_from_slice(&RIFF);
-        let size = self.file_size() as u32;
+        let size = self.riff_size();
         bytes[4..8].copy_from_slice(&size.to_ne_bytes());
         bytes[8..12].copy_from_slice(&WAVE);
 
@@ -301,7 +428,7 @@ impl WavHeader {
     pub fn as_extended_bytes(&self) -> [u8; HEADER_FMT_EXTENDED_SIZE] {
         let mut bytes = [0; HEADER_FMT_EXTENDED_SIZE];
         bytes[0..4].copy_from_slice(&RIFF);
-        let size = self.file_size() as u32;
+        let size = self.riff_size();
         bytes[4..8].copy_from_slice(&size.to_ne_bytes());
         bytes[8..12].copy_from_slice(&WAVE);
         bytes[12..16].copy_from_slice(&FMT);
@@ -311,6 +438,47 @@ impl WavHeader {
         bytes
     }
 
+    /// Adds a chunk of the given size to the header, placed directly before the data chunk.
+    /// The data chunk offset and the file size are updated to account for the new chunk and any padding byte.
+    pub fn add_chunk_info(&mut self, chunk_identifier: ChunkIdentifier, size: u64) {
+        let data_info = *self.data();
+        let padded_size = 8 + size + size % 2;
+        self.header_info.insert(
+            chunk_identifier,
+            HeaderChunkInfo::new(data_info.offset, size),
+        );
+        self.header_info.insert(
+            DATA.into(),
+            HeaderChunkInfo::new(data_info.offset + padded_size as usize, data_info.size),
+        );
+        self.current_file_size += padded_size as usize;
+    }
+
+    /// Adds the information of a chunk which is written after the data chunk, updating the size of the file.
+    /// A data chunk with an odd size is followed by a padding byte, which is included in the size of the file when the first such chunk is added.
+    pub fn add_trailing_chunk_info(&mut self, chunk_identifier: ChunkIdentifier, size: u64) {
+        let data_info = *self.data();
+        let data_end = data_info.offset + 8 + (data_info.size + data_info.size % 2) as usize;
+        let offset = self.current_file_size.max(data_end);
+        self.header_info
+            .insert(chunk_identifier, HeaderChunkInfo::new(offset, size));
+        self.current_file_size = offset + 8 + (size + size % 2) as usize;
+    }
+
+    /// Returns the identifier and information of every chunk in the file, ordered by their offset.
+    /// The RIFF, or RIFX, chunk containing the other chunks is not included.
+    /// LIST chunks other than INFO are identified by their list type, e.g. "adtl".
+    pub fn chunks(&self) -> Vec<(ChunkIdentifier, HeaderChunkInfo)> {
+        let mut chunks: Vec<(ChunkIdentifier, HeaderChunkInfo)> = self
+            .header_info
+            .iter()
+            .filter(|(id, _)| **id != RIFF.into() && **id != RIFX.into())
+            .map(|(id, info)| (*id, *info))
+            .collect();
+        chunks.sort_by_key(|(_, info)| info.offset);
+        chunks
+    }
+
     /// Attempt to get some chunk information from the header. Returns None if the chunk is not found.
     pub fn get_chunk_info(&self, chunk_identifier: ChunkIdentifier) -> Option<&HeaderChunkInfo> {
         self.header_info.get(&chunk_identifier)
@@ -428,14 +596,87 @@ impl Debug for ChunkIdentifier {
     }
 }
 
+/// Returns the identifier a chunk is stored under in the header. This is the chunk ID, except for LIST chunks other than INFO, which are identified by their list type.
+pub(crate) fn header_identifier(chunk: &dyn Chunk) -> ChunkIdentifier {
+    if chunk.id() == &LIST {
+        let bytes = chunk.as_bytes();
+        if bytes.len() >= 12 && bytes[8..12] != INFO {
+            return [bytes[8], bytes[9], bytes[10], bytes[11]].into();
+        }
+    }
+    (*chunk.id()).into()
+}
+
 /// Reads the header of a wav file and returns a tuple containing the header information and the wav encoding.
 /// Mostly for convenience, but can also be used to inspect a wav file without reading the data.
 pub(crate) fn read_header(readable: &mut Box<dyn ReadSeek>) -> WaversResult<WavInfo> {
+    read_header_until(readable, false)
+}
+
+/// Reads the header of a wav file from a stream, in the same way as ``read_header``, but stops at the data chunk so that the samples are not skipped over.
+/// Any chunks following the data chunk are not discovered.
+pub(crate) fn read_stream_header(readable: &mut Box<dyn ReadSeek>) -> WaversResult<WavInfo> {
+    read_header_until(readable, true)
+}
+
+/// Returns the number of bytes which must follow ``prefix``, the first bytes of a wav file, before it holds every chunk up to the id and size of the data chunk,
+/// i.e. every byte ``read_stream_header`` reads. Returns zero once it does.
+/// This allows the header to be read from sources which cannot be read with ``Read``, such as asynchronous readers, without reading past the data chunk.
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub(crate) fn header_bytes_needed(prefix: &[u8]) -> WaversResult<usize> {
+    if prefix.len() < 12 {
+        return Ok(12 - prefix.len());
+    }
+    let riff_id = [prefix[0], prefix[1], prefix[2], prefix[3]];
+    let is_rifx = buf_eq(&RIFX, &riff_id);
+    match buf_eq(&RIFF, &riff_id) || is_rifx || buf_eq(&RF64, &riff_id) || buf_eq(&BW64, &riff_id) {
+        true => (),
+        false => {
+            return Err(WaversError::from(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "File is not a valid RIFF, RIFX, RF64 or BW64 file",
+            )));
+        }
+    }
+
+    let mut offset = 12;
+    loop {
+        if prefix.len() < offset + 8 {
+            return Ok(offset + 8 - prefix.len());
+        }
+        let id = [
+            prefix[offset],
+            prefix[offset + 1],
+            prefix[offset + 2],
+            prefix[offset + 3],
+        ];
+        if buf_eq(&DATA, &id) {
+            return Ok(0);
+        }
+        let size = [
+            prefix[offset + 4],
+            prefix[offset + 5],
+            prefix[offset + 6],
+            prefix[offset + 7],
+        ];
+        let size = match is_rifx {
+            true => u32::from_be_bytes(size),
+            false => u32::from_le_bytes(size),
+        } as usize;
+        // Chunks with an odd size are followed by a padding byte
+        offset += 8 + size + size % 2;
+    }
+}
+
+fn read_header_until(
+    readable: &mut Box<dyn ReadSeek>,
+    stop_at_data: bool,
+) -> WaversResult<WavInfo> {
     // reset the buffer reader to the start of the file
     readable.seek(SeekFrom::Start(0))?;
 
     let header_info: HashMap<ChunkIdentifier, HeaderChunkInfo> =
-        discover_all_header_chunks(readable)?;
+        discover_all_header_chunks(readable, stop_at_data)?;
 
     match header_info.contains_key(&FMT.into()) {
         true => (),
@@ -447,8 +688,13 @@ pub(crate) fn read_header(readable: &mut Box<dyn ReadSeek>) -> WaversResult<WavI
         }
     }
 
+    let is_rifx = header_info.contains_key(&RIFX.into());
+
     let fmt_entry = header_info.get(&FMT.into()).unwrap(); // Safe since we just checked that the key exists
-    let fmt_chunk: FmtChunk = read_chunk::<FmtChunk>(readable, fmt_entry)?;
+    let fmt_chunk: FmtChunk = match is_rifx {
+        true => read_rifx_fmt_chunk(readable, fmt_entry)?,
+        false => read_chunk::<FmtChunk>(readable, fmt_entry)?,
+    };
 
     let wav_type = format_info_to_wav_type((
         fmt_chunk.format,
@@ -456,57 +702,165 @@ pub(crate) fn read_header(readable: &mut Box<dyn ReadSeek>) -> WaversResult<WavI
         fmt_chunk.format(),
     ))?;
 
-    let total_size = header_info.get(&RIFF.into()).unwrap().size as usize + 8;
+    let fact_chunk = match header_info.get(&FACT.into()) {
+        Some(fact_entry) => {
+            let fact_chunk = read_chunk::<FactChunk>(readable, fact_entry)?;
+            match is_rifx {
+                true => Some(FactChunk::new(u32::from_be(fact_chunk.num_samples))),
+                false => Some(fact_chunk),
+            }
+        }
+        None => None,
+    };
+
+    let riff_id = match is_rifx {
+        true => RIFX,
+        false => RIFF,
+    };
+    let total_size = header_info.get(&riff_id.into()).unwrap().size as usize + 8;
     let wav_header = WavHeader::new(header_info, fmt_chunk, total_size);
 
     Ok(WavInfo {
         wav_type,
         wav_header,
+        fact_chunk,
     })
 }
 
+/// Reads the fmt chunk of a RIFX file, converting each of its big-endian fields to native byte order before decoding it.
+fn read_rifx_fmt_chunk(
+    reader: &mut Box<dyn ReadSeek>,
+    info: &HeaderChunkInfo,
+) -> WaversResult<FmtChunk> {
+    // Any extension beyond the extensible format fields, such as the ADPCM coefficient table, is ignored
+    let fmt_size = match info.size as usize {
+        n if n >= FMT_SIZE_EXTENDED_SIZE => FMT_SIZE_EXTENDED_SIZE,
+        n if n >= FMT_CB_SIZE => FMT_CB_SIZE,
+        n if n >= FMT_SIZE_BASE_SIZE => FMT_SIZE_BASE_SIZE,
+        n => return Err(FormatError::InvalidFmtChunkSize(n).into()),
+    };
+    reader.seek(SeekFrom::Start(info.offset as u64 + 8))?;
+    let mut bytes = alloc_box_buffer(fmt_size);
+    reader.read_exact(&mut bytes)?;
+
+    // format, channels, sample rate, byte rate, block align, bits per sample, cb size, valid bits, channel mask and the first three fields of the sub format GUID
+    let field_sizes: [usize; 12] = [2, 2, 4, 4, 2, 2, 2, 2, 4, 4, 2, 2];
+    let mut offset = 0;
+    for field_size in field_sizes {
+        if offset + field_size > fmt_size {
+            break;
+        }
+        if cfg!(target_endian = "little") {
+            bytes[offset..offset + field_size].reverse();
+        }
+        offset += field_size;
+    }
+    FmtChunk::try_from_bytes(&bytes)
+}
+
 // This shouldn't cause too many performance issues. Would wager than there is only ever the core header chunks and maybe a handful more.
 // Each iteration is simply just a read of 8 (4+4) bytes.
 fn discover_all_header_chunks(
     reader: &mut Box<dyn ReadSeek>,
+    stop_at_data: bool,
 ) -> WaversResult<HashMap<ChunkIdentifier, HeaderChunkInfo>> {
     let mut entries: HashMap<ChunkIdentifier, HeaderChunkInfo> = HashMap::new();
 
     // create a reusable buffer for reading header chunks
     let mut buf: [u8; 4] = [0; 4];
-    // The first 4 bytes of the file should be the RIFF chunk
+    // The first 4 bytes of the file should be the RIFF chunk, the RIFX chunk for big-endian files or the RF64/BW64 chunk for files larger than 4 GiB
     reader.read_exact(&mut buf)?;
-    match buf_eq(&RIFF, &buf) {
+    let is_rf64 = buf_eq(&RF64, &buf) || buf_eq(&BW64, &buf);
+    let is_rifx = buf_eq(&RIFX, &buf);
+    match buf_eq(&RIFF, &buf) || is_rifx || is_rf64 {
         true => (),
         false => {
             return Err(WaversError::from(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
-                "File is not a valid RIFF file",
+                "File is not a valid RIFF, RIFX, RF64 or BW64 file",
             )));
         }
     }
+    // Reads a 32-bit size field, which is big-endian in RIFX files and little-endian otherwise
+    let read_size = |buf: [u8; 4]| match is_rifx {
+        true => u32::from_be_bytes(buf),
+        false => u32::from_le_bytes(buf),
+    };
 
     reader.read_exact(&mut buf)?; // read the next 4 bytes which should be the size of the file
 
-    let file_size: u32 =
-        buf[0] as u32 | (buf[1] as u32) << 8 | (buf[2] as u32) << 16 | (buf[3] as u32) << 24;
-    entries.insert(RIFF.into(), HeaderChunkInfo::new(0, file_size as u32));
+    let file_size: u32 = read_size(buf);
 
     // The next 4 bytes should be the RIFF type id
     reader.read_exact(&mut buf)?;
     let _: ChunkIdentifier = buf.into();
 
+    // The ds64 chunk must be the first chunk of an RF64 file
+    let ds64_chunk: Option<Ds64Chunk> = match is_rf64 {
+        true => {
+            reader.read_exact(&mut buf)?;
+            if !buf_eq(&DS64, &buf) {
+                return Err(WaversError::from(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "RF64 file does not start with a ds64 chunk",
+                )));
+            }
+            reader.read_exact(&mut buf)?;
+            let ds64_info = HeaderChunkInfo::new(12, u32::from_ne_bytes(buf) as u64);
+            let ds64_chunk = read_chunk::<Ds64Chunk>(reader, &ds64_info)?;
+            reader.seek(SeekFrom::Start(12))?;
+            Some(ds64_chunk)
+        }
+        false => None,
+    };
+
+    let file_size: u64 = match &ds64_chunk {
+        Some(ds64_chunk) if file_size == RF64_PLACEHOLDER_SIZE => ds64_chunk.riff_size,
+        _ => file_size as u64,
+    };
+    // RF64 and BW64 files are otherwise identical to RIFF files, so only RIFX is recorded separately
+    let riff_id = match is_rifx {
+        true => RIFX,
+        false => RIFF,
+    };
+    entries.insert(riff_id.into(), HeaderChunkInfo::new(0, file_size));
+
     while let Ok(_) = reader.read_exact(&mut buf) {
         let chunk_identifier: ChunkIdentifier = buf.into();
+        let id = buf;
         reader.read_exact(&mut buf)?;
-        let chunk_size: u32 =
-            buf[0] as u32 | (buf[1] as u32) << 8 | (buf[2] as u32) << 16 | (buf[3] as u32) << 24;
+        let chunk_size: u32 = read_size(buf);
+        // Chunks too large for a 32-bit size have their size stored in the ds64 chunk
+        let chunk_size: u64 = match &ds64_chunk {
+            Some(ds64_chunk) if chunk_size == RF64_PLACEHOLDER_SIZE => {
+                ds64_chunk.chunk_size(&id).unwrap_or(chunk_size as u64)
+            }
+            _ => chunk_size as u64,
+        };
+        let chunk_offset = reader.stream_position()? as usize - 8;
+
+        // A file can contain several LIST chunks, so any list other than INFO is stored under its list type, e.g. "adtl"
+        let chunk_identifier = match buf_eq(&LIST, &id) && chunk_size >= 4 {
+            true => {
+                reader.read_exact(&mut buf)?;
+                reader.seek(SeekFrom::Current(-4))?;
+                match buf_eq(&INFO, &buf) {
+                    true => chunk_identifier,
+                    false => buf.into(),
+                }
+            }
+            false => chunk_identifier,
+        };
         entries.insert(
             chunk_identifier,
-            HeaderChunkInfo::new(reader.stream_position()? as usize - 8, chunk_size),
+            HeaderChunkInfo::new(chunk_offset, chunk_size),
         );
+        if stop_at_data && buf_eq(&DATA, &id) {
+            break;
+        }
 
-        reader.seek(SeekFrom::Current(chunk_size as i64))?;
+        // Chunks with an odd size are followed by a padding byte
+        reader.seek(SeekFrom::Current((chunk_size + chunk_size % 2) as i64))?;
     }
 
     Ok(entries)
@@ -559,6 +913,90 @@ mod header_tests {
         );
     }
 
+    #[test]
+    fn multi_channel_header_is_extensible() {
+        let header = WavHeader::new_header::<i16>(16000, 6, 6 * 16000).unwrap();
+        let fmt_chunk = header.fmt_chunk;
+        assert_eq!(fmt_chunk.format, FormatCode::WAVE_FORMAT_EXTENSIBLE);
+        assert_eq!(fmt_chunk.format(), FormatCode::WAV_FORMAT_PCM);
+        assert_eq!(fmt_chunk.ext_fmt_chunk.cb_size(), CbSize::Extended);
+        assert_eq!(fmt_chunk.ext_fmt_chunk.channel_mask(), 0x3F);
+        assert_eq!(fmt_chunk.size(), FMT_SIZE_EXTENDED_SIZE as u32);
+
+        let new_fmt = FmtChunk::from_bytes(&fmt_chunk.extended_bytes());
+        assert_eq!(fmt_chunk, new_fmt, "Fmt chunk does not match");
+
+        let header = WavHeader::new_header::<i16>(16000, 2, 2 * 16000).unwrap();
+        assert_eq!(header.fmt_chunk.format, FormatCode::WAV_FORMAT_PCM);
+    }
+
+    #[test]
+    fn new_header_sizes_match_written_bytes() {
+        let header = WavHeader::new_header::<i16>(16000, 2, 2 * 16000).unwrap();
+        assert!(!header.is_rf64());
+        let header_bytes = header.as_bytes();
+        assert_eq!(
+            header_bytes.len() + 8 + header.data().size as usize,
+            header.file_size()
+        );
+        assert_eq!(header.data().offset, header_bytes.len());
+        assert_eq!(
+            &header_bytes[4..8],
+            &((header.file_size() - 8) as u32).to_ne_bytes()
+        );
+    }
+
+    #[test]
+    fn non_pcm_headers_have_a_fact_chunk() {
+        let header = WavHeader::new_header::<i16>(16000, 1, 16000).unwrap();
+        assert!(header.get_chunk_info(FACT.into()).is_none());
+
+        for wav_type in [WavType::ALaw, WavType::Float32] {
+            let header = WavHeader::new_header_with_type(wav_type, 16000, 2, 32000).unwrap();
+            let header_bytes = header.as_bytes();
+            assert_eq!(header.data().offset, header_bytes.len());
+            assert_eq!(
+                header_bytes.len() + 8 + header.data().size as usize,
+                header.file_size()
+            );
+
+            let fact_offset = header.get_chunk_info(FACT.into()).unwrap().offset;
+            assert_eq!(&header_bytes[fact_offset..fact_offset + 4], &FACT);
+            assert_eq!(
+                &header_bytes[fact_offset + 8..fact_offset + 12],
+                &16000u32.to_ne_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn large_header_is_promoted_to_rf64() {
+        let n_samples = 3_000_000_000;
+        let header = WavHeader::new_header::<i16>(48000, 2, n_samples).unwrap();
+        assert!(header.is_rf64());
+
+        let mut bytes = header.as_bytes().to_vec();
+        assert_eq!(&bytes[0..4], &RF64);
+        assert_eq!(&bytes[4..8], &RF64_PLACEHOLDER_SIZE.to_ne_bytes());
+        assert_eq!(&bytes[12..16], &DS64);
+        assert_eq!(header.data().offset, bytes.len());
+        bytes.extend_from_slice(&header.data_chunk_bytes());
+        assert_eq!(
+            &bytes[bytes.len() - 4..],
+            &RF64_PLACEHOLDER_SIZE.to_ne_bytes()
+        );
+
+        let ds64_chunk = header.ds64_chunk().unwrap();
+        assert_eq!(ds64_chunk.data_size, n_samples as u64 * 2);
+        assert_eq!(ds64_chunk.sample_count, n_samples as u64 / 2);
+
+        let mut reader = Box::new(std::io::Cursor::new(bytes)) as Box<dyn ReadSeek>;
+        let wav_info = read_header(&mut reader).expect("Failed to read header");
+        assert_eq!(wav_info.wav_header.data().size, n_samples as u64 * 2);
+        assert_eq!(wav_info.wav_header.file_size(), header.file_size());
+        assert_eq!(wav_info.wav_header.fmt_chunk, header.fmt_chunk);
+    }
+
     #[test]
     fn test_printing() {
         let file = File::open(TEST_FILE).unwrap();
@@ -0,0 +1,298 @@
+//! Module containing the ``HttpReader`` struct, which reads a remote file over HTTP byte-range requests, available with the ``http`` feature.
+//!
+//! Only the bytes which are read are requested from the server, using the ``ureq`` crate, so the header of a wav file on a web server or CDN can be inspected,
+//! and part of its samples decoded, without downloading the whole file. The server must support range requests, which most static file servers and CDNs do.
+use std::io::{Read, Seek, SeekFrom};
+
+use i24::i24;
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::Wav,
+    error::WaversResult,
+};
+
+/// The default minimum number of bytes requested at a time, so that the small reads made while reading a header do not each need a request.
+pub const HTTP_READ_AHEAD: usize = 1 << 16;
+
+/// A reader over a remote file which fetches the bytes it reads using HTTP byte-range requests.
+///
+/// Each request fetches at least ``read_ahead`` bytes, which are kept until a position outside of them is read.
+/// Seeking never makes a request, so a wav file can be read from any frame after its header has been read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::http::HttpReader;
+/// use wavers::Wav;
+///
+/// let reader = HttpReader::new("https://example.com/speech.wav").unwrap();
+/// let mut wav: Wav<f32> = Wav::new(Box::new(reader)).unwrap();
+/// // Only the header and the first second are downloaded
+/// let first_second = wav.read_frames(0, wav.sample_rate() as usize).unwrap();
+/// ```
+pub struct HttpReader {
+    agent: ureq::Agent,
+    url: String,
+    len: u64,
+    pos: u64,
+    buffer: Vec<u8>,
+    buffer_start: u64,
+    read_ahead: usize,
+}
+
+impl HttpReader {
+    /// Construct a new HttpReader over the file at the given URL, requesting its first bytes to find its length.
+    ///
+    /// This function will return an error if the request fails, or if the server does not support range requests.
+    pub fn new(url: &str) -> WaversResult<Self> {
+        Self::with_agent(ureq::Agent::new(), url)
+    }
+
+    /// Construct a new HttpReader which makes its requests using the given agent, e.g. one configured with timeouts or a proxy. See ``new``.
+    pub fn with_agent(agent: ureq::Agent, url: &str) -> WaversResult<Self> {
+        let mut reader = HttpReader {
+            agent,
+            url: url.to_string(),
+            len: 0,
+            pos: 0,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            read_ahead: HTTP_READ_AHEAD,
+        };
+        reader.fetch(0, 0)?;
+        Ok(reader)
+    }
+
+    /// Sets the minimum number of bytes requested at a time. Larger values make fewer requests when reading sequentially,
+    /// while smaller values download less when reading small parts of the file. Defaults to ``HTTP_READ_AHEAD``.
+    pub fn read_ahead(mut self, n_bytes: usize) -> Self {
+        self.read_ahead = n_bytes.max(1);
+        self
+    }
+
+    /// Returns the URL of the file.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the length of the file in bytes, as reported by the server.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Requests at least ``read_ahead`` bytes starting at ``start``, and at least ``n_bytes`` if more are wanted, replacing the buffered bytes.
+    fn fetch(&mut self, start: u64, n_bytes: usize) -> std::io::Result<()> {
+        let n_bytes = n_bytes.max(self.read_ahead) as u64;
+        let range = format!("bytes={}-{}", start, start + n_bytes - 1);
+        let response = match self.agent.get(&self.url).set("Range", &range).call() {
+            Ok(response) => response,
+            // The range starts past the end of the file, which is still reported
+            Err(ureq::Error::Status(416, response)) => {
+                self.len = content_range_len(response.header("Content-Range"))?;
+                self.buffer.clear();
+                self.buffer_start = start;
+                return Ok(());
+            }
+            Err(e) => return Err(std::io::Error::other(e)),
+        };
+        if response.status() != 206 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "The server does not support HTTP range requests",
+            ));
+        }
+        self.len = content_range_len(response.header("Content-Range"))?;
+
+        self.buffer.clear();
+        response
+            .into_reader()
+            .take(n_bytes)
+            .read_to_end(&mut self.buffer)?;
+        self.buffer_start = start;
+        Ok(())
+    }
+}
+
+/// Returns the length of the file from the ``Content-Range`` header of a response, e.g. "bytes 0-65535/1048576" or "bytes */1048576".
+fn content_range_len(content_range: Option<&str>) -> std::io::Result<u64> {
+    content_range
+        .and_then(|content_range| content_range.rsplit_once('/'))
+        .and_then(|(_, len)| len.trim().parse().ok())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "The server did not report the length of the file in a Content-Range header",
+            )
+        })
+}
+
+impl Read for HttpReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let buffer_end = self.buffer_start + self.buffer.len() as u64;
+        if self.pos < self.buffer_start || self.pos >= buffer_end {
+            self.fetch(self.pos, buf.len())?;
+        }
+
+        let buffered = &self.buffer[(self.pos - self.buffer_start) as usize..];
+        let n_read = buffered.len().min(buf.len());
+        buf[..n_read].copy_from_slice(&buffered[..n_read]);
+        self.pos += n_read as u64;
+        Ok(n_read)
+    }
+}
+
+impl Seek for HttpReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Cannot seek before the start of the file",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new Wav struct from the URL of a wav file on a web server, which is read using HTTP byte-range requests.
+    /// Only the parts of the file which are read are downloaded. See ``HttpReader``.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let wav: Wav<f32> = Wav::from_url("https://example.com/speech.wav").unwrap();
+    ///     println!("{} Hz, {} channels", wav.sample_rate(), wav.n_channels());
+    /// }
+    /// ```
+    pub fn from_url(url: &str) -> WaversResult<Self> {
+        Self::new(Box::new(HttpReader::new(url)?))
+    }
+}
+
+#[cfg(test)]
+mod http_tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    /// Serves ``bytes`` over HTTP on a local port, answering range requests, and counts the requests made.
+    fn serve(bytes: Vec<u8>, supports_ranges: bool) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file.wav", listener.local_addr().unwrap());
+        let n_requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&n_requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut range = None;
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("Range: bytes=") {
+                        let (start, end) = value.trim().split_once('-').unwrap();
+                        range = Some((
+                            start.parse::<usize>().unwrap(),
+                            end.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+
+                let (status, headers, body) = match range.filter(|_| supports_ranges) {
+                    Some((start, _)) if start >= bytes.len() => (
+                        "416 Range Not Satisfiable",
+                        format!("Content-Range: bytes */{}\r\n", bytes.len()),
+                        &bytes[..0],
+                    ),
+                    Some((start, end)) => {
+                        let end = end.min(bytes.len() - 1);
+                        (
+                            "206 Partial Content",
+                            format!("Content-Range: bytes {}-{}/{}\r\n", start, end, bytes.len()),
+                            &bytes[start..=end],
+                        )
+                    }
+                    None => ("200 OK", String::new(), &bytes[..]),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    headers,
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        (url, n_requests)
+    }
+
+    #[test]
+    fn remote_reads_match_local_reads() {
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let mut expected_wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected = expected_wav.read().unwrap();
+        let (url, n_requests) = serve(bytes.clone(), true);
+
+        let reader = HttpReader::new(&url).unwrap();
+        assert_eq!(reader.len(), bytes.len() as u64);
+        let mut wav: Wav<i16> = Wav::new(Box::new(reader.read_ahead(4096))).unwrap();
+        assert_eq!(wav.n_frames(), expected_wav.n_frames());
+
+        // Only the requested frames are downloaded
+        let n_before = n_requests.load(Ordering::SeqCst);
+        assert_eq!(
+            &wav.read_frames(100_000, 10).unwrap()[..],
+            &expected[200_000..200_020]
+        );
+        assert_eq!(n_requests.load(Ordering::SeqCst), n_before + 1);
+
+        let mut wav: Wav<i16> = Wav::from_url(&url).unwrap();
+        assert_eq!(wav.read().unwrap(), expected);
+    }
+
+    #[test]
+    fn servers_without_range_support_are_rejected() {
+        let bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let (url, _) = serve(bytes, false);
+        assert!(HttpReader::new(&url).is_err());
+    }
+}
@@ -1,24 +1,120 @@
 //! Module containing the different type of iterators for the Wav struct.
 //! - The FrameIterator iterates over the frames of the Wav file
+//! - The SampleIterator iterates over the samples of the Wav file, taking ownership of it.
 //! - The ChannelIterator iterates over the channels of the Wav file.
+//! - The SingleChannelIterator iterates over the samples of one channel of the Wav file.
 //! - The BlockIterator iterates over blocks of the Wav file with an optional overlap.
+//! - The WindowIterator iterates over fixed-length windows of the Wav file which start every hop frames.
+//! - The LoopIterator iterates over the frames of the Wav file repeatedly, wrapping from the end back to the start.
+//! - The SegmentIterator iterates over the segments of the Wav file given by its markers or sample loops.
 
-use crate::{core::alloc_sample_buffer, i24, AudioSample, ConvertSlice, ConvertTo, Samples, Wav};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::error::FormatError;
+use crate::transcode::Resampler;
+use crate::{
+    i24, AudioSample, ConvertSlice, ConvertTo, Marker, SampleLoop, Samples, Wav, WaversResult,
+};
+
+/// The number of frames the FrameIterator reads from the file at a time.
+pub const FRAME_BUFFER_SIZE: usize = 4096;
+
+/// A single frame of audio, holding one sample for each channel.
+/// Frames returned by ``Samples::frames`` borrow the samples, while frames returned by ``FrameIterator::next_frame`` borrow the iterator's buffer.
+/// A frame dereferences to a slice of its samples in channel order.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::Samples;
+///
+/// let samples = Samples::from(vec![1, -1, 2, -2]);
+/// let right: Vec<i32> = samples.frames(2).filter_map(|frame| frame.channel(1)).collect();
+/// assert_eq!(right, vec![-1, -2]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame<'a, T: AudioSample> {
+    samples: Cow<'a, [T]>,
+}
+
+impl<'a, T: AudioSample> Frame<'a, T> {
+    /// Returns the sample of the given channel, or ``None`` if the frame has no such channel.
+    pub fn channel(&self, channel: usize) -> Option<T> {
+        self.samples.get(channel).copied()
+    }
+
+    /// Returns the samples of the frame in channel order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.samples
+    }
+
+    /// Returns the number of channels in the frame.
+    pub fn n_channels(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns a frame which owns its samples, so that it can outlive the samples it was borrowed from.
+    pub fn into_owned(self) -> Frame<'static, T> {
+        Frame {
+            samples: Cow::Owned(self.samples.into_owned()),
+        }
+    }
+}
+
+impl<'a, T: AudioSample> From<&'a [T]> for Frame<'a, T> {
+    fn from(samples: &'a [T]) -> Self {
+        Frame {
+            samples: Cow::Borrowed(samples),
+        }
+    }
+}
+
+impl<T: AudioSample> From<Vec<T>> for Frame<'_, T> {
+    fn from(samples: Vec<T>) -> Self {
+        Frame {
+            samples: Cow::Owned(samples),
+        }
+    }
+}
+
+impl<T: AudioSample> From<Frame<'_, T>> for Samples<T> {
+    fn from(frame: Frame<'_, T>) -> Self {
+        Samples::from(frame.samples.into_owned())
+    }
+}
+
+impl<T: AudioSample> Deref for Frame<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.samples
+    }
+}
 
 /// A frame iterator for the Wav struct.
 /// WaveRs defines a frame as a collection of samples, where each sample is a single value from a single channel.
-/// So one frame contains n_channel samples and there are n_samples frames in the Wav file.
+/// So one frame contains n_channel samples and there are n_samples frames in the Wav file. Each frame is returned as ``Samples``, or lent as a ``Frame`` by ``next_frame``.
 /// The FrameIterator takes a max_pos value which is used to limit where in the file the iterator should stop.
 /// This should only be used via the ``frames`` function on the Wav struct.
 ///
+/// Frames are decoded from the file ``FRAME_BUFFER_SIZE`` frames at a time, so the memory used does not depend on the length of the file,
+/// except for block encoded files such as MS ADPCM, which are decoded in one go.
+///
+/// If a target sample rate was set with ``with_target_sample_rate``, each block of frames is resampled as it is read.
+///
 /// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
 pub struct FrameIterator<'a, T: 'a + AudioSample>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -27,15 +123,23 @@ where
 {
     max_pos: u64,
     wav: &'a mut Wav<T>,
+    buffer: Samples<T>,
+    buffer_pos: usize,
+    finished: bool,
+    /// Whether the samples of a block encoded file have been decoded into the buffer.
+    block_decoded: bool,
+    resampler: Option<Resampler>,
 }
 
 impl<'a, T: 'a + AudioSample> FrameIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -43,17 +147,138 @@ where
     Box<[f64]>: ConvertSlice<T>,
 {
     pub fn new(max_pos: u64, wav: &'a mut Wav<T>) -> FrameIterator<'a, T> {
-        FrameIterator { max_pos, wav }
+        let resampler = wav.target_resampler();
+        FrameIterator {
+            max_pos,
+            wav,
+            buffer: Samples::from(Vec::new()),
+            buffer_pos: 0,
+            finished: false,
+            block_decoded: false,
+            resampler,
+        }
+    }
+
+    /// Reads the next frames from the file into the buffer, resampling them if a target sample rate was set.
+    /// Returns false if there are no frames left.
+    fn fill_buffer(&mut self) -> crate::WaversResult<bool> {
+        if self.resampler.is_none() {
+            return self.read_buffer();
+        }
+        loop {
+            let more = self.read_buffer()?;
+            let Some(resampler) = self.resampler.as_mut() else {
+                return Ok(false);
+            };
+            let frames = if more { &self.buffer[..] } else { &[] };
+            let resampled = resampler.process_samples(frames, !more);
+            if !more {
+                // The filter has been flushed, so nothing is left once these frames are returned.
+                self.resampler = None;
+            }
+            self.buffer = Samples::from(resampled);
+            self.buffer_pos = 0;
+            if !self.buffer.is_empty() {
+                return Ok(true);
+            }
+            if !more {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Reads the next frames from the file into the buffer. Returns false if there are no whole frames left to read.
+    fn read_buffer(&mut self) -> crate::WaversResult<bool> {
+        if self.wav.encoding().is_block_encoded() {
+            // Block encoded samples can only be decoded a whole file at a time
+            if std::mem::replace(&mut self.block_decoded, true) {
+                return Ok(false);
+            }
+            let mut samples = Vec::new();
+            self.wav.read_into_vec(&mut samples)?;
+            self.buffer = Samples::from(samples);
+            self.buffer_pos = 0;
+            return Ok(!self.buffer.is_empty());
+        }
+        let n_channels = self.wav.n_channels() as usize;
+        let frame_size = (n_channels * self.wav.encoding().n_bytes()) as u64;
+        let current_pos = self.wav.current_pos()?;
+        let n_frames = match frame_size {
+            0 => 0,
+            _ => (self.max_pos.saturating_sub(current_pos) / frame_size) as usize,
+        };
+        if n_frames == 0 {
+            return Ok(false);
+        }
+        self.buffer = self
+            .wav
+            .read_samples(n_frames.min(FRAME_BUFFER_SIZE) * n_channels)?;
+        self.buffer_pos = 0;
+        Ok(true)
+    }
+
+    /// Returns the next frame, reading more frames from the file once the buffer is exhausted.
+    pub(crate) fn next_slice(&mut self) -> Option<&[T]> {
+        if self.finished {
+            return None;
+        }
+        if self.buffer_pos >= self.buffer.len() {
+            match self.fill_buffer() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.finish();
+                    return None;
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    self.finish();
+                    return None;
+                }
+            }
+        }
+        let n_channels = self.wav.n_channels() as usize;
+        let frame = &self.buffer[self.buffer_pos..self.buffer_pos + n_channels];
+        self.buffer_pos += n_channels;
+        Some(frame)
+    }
+
+    /// Returns the next frame as a ``Frame`` which borrows the iterator's buffer, so no allocation is made per frame.
+    /// The frame must be dropped before the next call, so this is used in a ``while let`` loop rather than through ``Iterator``.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// let mut wav: Wav<i16> = Wav::from_path("path/to/two_channel.wav").unwrap();
+    /// let mut frames = wav.frames();
+    /// while let Some(frame) = frames.next_frame() {
+    ///     println!("{:?} {:?}", frame.channel(0), frame.channel(1));
+    /// }
+    /// ```
+    pub fn next_frame(&mut self) -> Option<Frame<'_, T>> {
+        self.next_slice().map(Frame::from)
+    }
+
+    /// Stops the iterator and moves the Wav struct back to the start of the data chunk.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.buffer = Samples::from(Vec::new());
+        if let Err(e) = self.wav.to_data() {
+            eprintln!("Error: {}", e);
+        }
     }
 }
 
 impl<'a, T: 'a + AudioSample> Iterator for FrameIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -63,68 +288,157 @@ where
     type Item = Samples<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current_pos = match self.wav.current_pos() {
-            Ok(pos) => pos,
-            Err(_) => {
-                match self.wav.to_data() {
-                    Ok(_) => (),
-                    Err(e) => {
-                        eprintln!("Error: {}", e);
-                    }
-                }
+        self.next_slice().map(Samples::from)
+    }
+}
+
+/// An iterator over the samples of a Wav struct, which takes ownership of the Wav struct.
+/// The samples are decoded and converted ``FRAME_BUFFER_SIZE`` at a time into a buffer which is reused, so a whole file is never loaded at once,
+/// except for block encoded files such as MS ADPCM, which are decoded in one go.
+/// Each sample is returned as a ``WaversResult``. If reading fails, the error is returned and the iterator ends.
+/// This should only be used via the ``IntoIterator`` implementation of the Wav struct.
+pub struct SampleIterator<T: AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: Wav<T>,
+    buffer: Vec<T>,
+    buffer_pos: usize,
+    finished: bool,
+}
+
+impl<T: AudioSample> SampleIterator<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates an iterator over the samples of the Wav struct, starting from its current position.
+    pub fn new(wav: Wav<T>) -> SampleIterator<T> {
+        SampleIterator {
+            wav,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the Wav struct, positioned after the samples which have been read into the buffer.
+    pub fn into_inner(self) -> Wav<T> {
+        self.wav
+    }
+
+    /// Reads the next samples from the file into the buffer. Returns false if there are no samples left to read.
+    fn fill_buffer(&mut self) -> crate::WaversResult<bool> {
+        if self.wav.encoding().is_block_encoded() {
+            self.finished = true;
+            self.wav.read_into_vec(&mut self.buffer)?;
+        } else {
+            self.buffer.resize(FRAME_BUFFER_SIZE, T::zero());
+            let n_samples = self.wav.read_into(&mut self.buffer)?;
+            self.buffer.truncate(n_samples);
+        }
+        self.buffer_pos = 0;
+        Ok(!self.buffer.is_empty())
+    }
+}
+
+impl<T: AudioSample> Iterator for SampleIterator<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = crate::WaversResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer_pos >= self.buffer.len() {
+            if self.finished {
                 return None;
             }
-        };
-
-        if current_pos >= self.max_pos {
-            match self.wav.to_data() {
-                Ok(_) => (),
+            match self.fill_buffer() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.finished = true;
+                    return None;
+                }
                 Err(e) => {
-                    eprintln!("Error: {}", e);
+                    self.finished = true;
+                    self.buffer.clear();
+                    return Some(Err(e));
                 }
             }
-            return None;
         }
-        let n_samples = self.wav.n_channels() as usize;
-        let frame = match self.wav.read_samples(n_samples) {
-            Ok(frame) => frame,
-            Err(_) => return None,
-        };
-        Some(frame)
+        let sample = self.buffer[self.buffer_pos];
+        self.buffer_pos += 1;
+        Some(Ok(sample))
     }
 }
 
 /// A channel iterator for the Wav struct.
-/// The ChannelItertor returns an iterator over the channels of the Wav file.
-/// The ChannelIterator takes a max_pos value which is used to limit where in the file the iterator should stop.
+/// The ChannelIterator returns a ``SingleChannelIterator`` for each channel of the Wav file in turn.
+/// The channel iterators share the reader of the Wav struct and each seeks to its own position before reading, so they can be read side by side.
 /// This should only be used via the ``channels`` function on the Wav struct.
 ///
 /// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
 pub struct ChannelIterator<'a, T: 'a + AudioSample>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
     Box<[f32]>: ConvertSlice<T>,
     Box<[f64]>: ConvertSlice<T>,
 {
-    wav: &'a mut Wav<T>,
+    wav: Rc<RefCell<&'a mut Wav<T>>>,
+    n_channels: usize,
     current_channel: usize,
-    n_samples_per_channel: usize,
 }
 
 impl<'a, T: 'a + AudioSample> ChannelIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -132,76 +446,182 @@ where
     Box<[f64]>: ConvertSlice<T>,
 {
     pub fn new(wav: &'a mut Wav<T>) -> ChannelIterator<'a, T> {
-        let n_samples_per_channel = wav.n_samples() as usize / wav.n_channels() as usize;
+        let n_channels = wav.n_channels() as usize;
         ChannelIterator {
-            wav,
+            wav: Rc::new(RefCell::new(wav)),
+            n_channels,
             current_channel: 0,
-            n_samples_per_channel: n_samples_per_channel,
         }
     }
 }
 
 impl<'a, T: 'a + AudioSample> Iterator for ChannelIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
     Box<[f32]>: ConvertSlice<T>,
     Box<[f64]>: ConvertSlice<T>,
 {
-    type Item = Samples<T>;
+    type Item = SingleChannelIterator<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let n_channels = self.wav.n_channels() as u64;
-        let current_channel = self.current_channel as u64;
-
-        if current_channel >= n_channels {
-            match self.wav.to_data() {
-                Ok(_) => (),
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                }
+        if self.current_channel >= self.n_channels {
+            if let Err(e) = self.wav.borrow_mut().to_data() {
+                eprintln!("Error: {}", e);
             }
             return None;
         }
 
-        // Seek to the start of the channel.
-        // Since we just seeked to the start, we can seek by the current channel (samples) to get to the next channel.
-        match self.wav.seek_by_samples(current_channel) {
-            Ok(_) => (),
-            Err(_) => return None,
+        let channel = SingleChannelIterator::shared(Rc::clone(&self.wav), self.current_channel);
+        self.current_channel += 1;
+        Some(channel)
+    }
+}
+
+/// An iterator over the samples of a single channel of the Wav struct.
+/// The frames of the file are read ``FRAME_BUFFER_SIZE`` at a time, and the samples of the channel are picked out of each frame,
+/// so the memory used does not depend on the length of the file, except for block encoded files, which are decoded in one go.
+/// The iterator seeks to the frame after the last one it read before reading more, so several can share the reader of a Wav struct.
+/// This should only be used via the ``channel`` and ``channels`` functions on the Wav struct.
+///
+/// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
+pub struct SingleChannelIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: Rc<RefCell<&'a mut Wav<T>>>,
+    channel: usize,
+    n_channels: usize,
+    next_frame: u64,
+    buffer: Vec<T>,
+    buffer_pos: usize,
+    finished: bool,
+}
+
+impl<'a, T: 'a + AudioSample> SingleChannelIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates an iterator over the given channel, starting from the start of the data chunk.
+    pub fn new(wav: &'a mut Wav<T>, channel: usize) -> SingleChannelIterator<'a, T> {
+        SingleChannelIterator::shared(Rc::new(RefCell::new(wav)), channel)
+    }
+
+    /// Creates an iterator over the given channel which shares the reader of the Wav struct with other iterators.
+    fn shared(wav: Rc<RefCell<&'a mut Wav<T>>>, channel: usize) -> SingleChannelIterator<'a, T> {
+        let n_channels = wav.borrow().n_channels().max(1) as usize;
+        SingleChannelIterator {
+            wav,
+            channel,
+            n_channels,
+            next_frame: 0,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Reads the next frames of the file into the buffer. Returns false if there are no frames left to read.
+    fn fill_buffer(&mut self) -> crate::WaversResult<bool> {
+        let mut wav = self.wav.borrow_mut();
+        if wav.encoding().is_block_encoded() {
+            // Block encoded samples can only be decoded a whole file at a time
+            if self.next_frame > 0 {
+                return Ok(false);
+            }
+            wav.to_data()?;
+            wav.read_into_vec(&mut self.buffer)?;
+            self.next_frame = (self.buffer.len() / self.n_channels) as u64;
+        } else {
+            wav.seek_frame(self.next_frame)?;
+            self.buffer
+                .resize(FRAME_BUFFER_SIZE * self.n_channels, T::zero());
+            let n_frames = wav.read_block(&mut self.buffer, FRAME_BUFFER_SIZE)?;
+            self.buffer.truncate(n_frames * self.n_channels);
+            self.next_frame += n_frames as u64;
+        }
+        self.buffer_pos = self.channel;
+        Ok(self.buffer_pos < self.buffer.len())
+    }
+
+    /// Stops the iterator and moves the Wav struct back to the start of the data chunk.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.buffer = Vec::new();
+        if let Err(e) = self.wav.borrow_mut().to_data() {
+            eprintln!("Error: {}", e);
         }
-        let mut samples: Box<[T]> = alloc_sample_buffer(self.n_samples_per_channel);
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for SingleChannelIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = T;
 
-        for i in 0..self.n_samples_per_channel {
-            // read a sample
-            let sample: T = match self.wav.read_sample() {
-                Ok(frame) => frame,
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if self.buffer_pos >= self.buffer.len() {
+            match self.fill_buffer() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.finish();
+                    return None;
+                }
                 Err(e) => {
                     eprintln!("Error: {}", e);
+                    self.finish();
                     return None;
                 }
-            };
-            samples[i] = sample;
-            // seek to the next sample in the channel, if we go beyond the data chunk, break. This is indicated by the error.
-            match self.wav.seek_by_samples(n_channels - 1) {
-                Ok(_) => (),
-                Err(_) => {
-                    break;
-                }
             }
         }
-        self.current_channel += 1;
-        match self.wav.to_data() {
-            Ok(_) => (),
-            Err(_) => return None,
-        }
-        Some(Samples::from(samples))
+        let sample = self.buffer[self.buffer_pos];
+        self.buffer_pos += self.n_channels;
+        Some(sample)
     }
 }
 
@@ -211,11 +631,13 @@ where
 #[derive(Debug)]
 pub struct BlockIterator<'a, T: 'a + AudioSample>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -231,11 +653,13 @@ where
 }
 impl<'a, T: 'a + AudioSample> BlockIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -289,11 +713,13 @@ where
 
 impl<'a, T: 'a + AudioSample> Iterator for BlockIterator<'a, T>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -360,50 +786,653 @@ where
     }
 }
 
-#[cfg(test)]
-mod iter_tests {
-    use crate::DATA;
-
-    use super::*;
-
-    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
-
-    #[test]
-    fn test_frame_iterator() {
-        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
-        let max_pos = wav.max_data_pos();
-        let frame_iter = FrameIterator::new(max_pos, &mut wav);
-
-        for frame in frame_iter {
-            assert_eq!(frame.len(), 2, "Frame length should be 2");
-        }
-
-        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
-        let max_pos = wav.max_data_pos();
-        let frame_iter = FrameIterator::new(max_pos, &mut wav);
+/// How a window iterator handles the final window when it extends past the last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowPadding {
+    /// Pads the final window with zeros, so that the windows reach the last frame.
+    #[default]
+    Zeros,
+    /// Drops any window which extends past the last frame.
+    Drop,
+}
 
-        let mut shadow_wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
-        let data: Samples<i16> = shadow_wav.read().unwrap();
-        let mut idx = 0;
-        for frame in frame_iter {
-            assert_eq!(
-                frame[0], data[idx],
-                "Frame[0] should be equal to data at index {}",
-                idx
-            );
-            assert_eq!(
-                frame[1],
-                data[idx + 1],
-                "Frame[1] should be equal to data at index {}",
-                idx + 1
-            );
-            idx += 2;
+impl WindowPadding {
+    /// Returns the number of windows of ``win_len`` frames, starting every ``hop`` frames, over ``n_frames`` frames.
+    pub fn n_windows(&self, n_frames: usize, win_len: usize, hop: usize) -> usize {
+        match self {
+            WindowPadding::Drop if n_frames < win_len => 0,
+            WindowPadding::Drop => (n_frames - win_len) / hop + 1,
+            WindowPadding::Zeros if n_frames == 0 => 0,
+            // Stop at the first window which reaches the last frame, or at the last window starting before it if the hop skips it
+            WindowPadding::Zeros => {
+                (n_frames.saturating_sub(win_len).div_ceil(hop) + 1).min(n_frames.div_ceil(hop))
+            }
         }
     }
+}
 
-    #[test]
-    fn test_frame_iterator_resets() {
-        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+/// A window iterator for the Wav struct.
+/// Returns windows of ``win_len`` frames which start every ``hop`` frames, so consecutive windows overlap by ``win_len - hop`` frames when the hop is smaller than the window.
+/// The samples of each window are interleaved. The final window is handled according to the ``WindowPadding`` of the iterator, which pads it with zeros by default.
+/// Frames shared by consecutive windows are only read from the file once, and block encoded files such as MS ADPCM are decoded in one go.
+/// This should only be used via the ``windows`` function on the Wav struct.
+///
+/// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
+pub struct WindowIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: &'a mut Wav<T>,
+    win_len: usize,
+    hop: usize,
+    padding: WindowPadding,
+    n_frames: usize,
+    current_window: usize,
+    buffer: Vec<T>,
+    buffer_start: usize,
+}
+
+impl<'a, T: 'a + AudioSample> WindowIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates an iterator over the windows of the Wav struct, starting from the start of the data chunk.
+    ///
+    /// Panics if the window length or the hop size is zero.
+    pub fn new(wav: &'a mut Wav<T>, win_len: usize, hop: usize) -> WindowIterator<'a, T> {
+        assert!(win_len > 0, "Window length must be positive");
+        assert!(hop > 0, "Hop size must be positive");
+        let n_frames = wav.n_frames();
+        WindowIterator {
+            wav,
+            win_len,
+            hop,
+            padding: WindowPadding::default(),
+            n_frames,
+            current_window: 0,
+            buffer: Vec::new(),
+            buffer_start: 0,
+        }
+    }
+
+    /// Sets how the final window is handled when it extends past the last frame.
+    pub fn padding(mut self, padding: WindowPadding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns the number of windows the iterator returns in total.
+    pub fn n_windows(&self) -> usize {
+        self.padding
+            .n_windows(self.n_frames, self.win_len, self.hop)
+    }
+
+    /// Reads the frames of the current window which are not already in the buffer, dropping the frames before it.
+    fn read_window(&mut self) -> crate::WaversResult<Samples<T>> {
+        let n_channels = self.wav.n_channels() as usize;
+        let start = self.current_window * self.hop;
+        let end = (start + self.win_len).min(self.n_frames);
+
+        if self.current_window == 0 && self.wav.encoding().is_block_encoded() {
+            // Block encoded samples can only be decoded a whole file at a time, so every window is taken from the decoded file
+            self.wav.read_into_vec(&mut self.buffer)?;
+            self.buffer.resize(self.n_frames * n_channels, T::zero());
+        }
+
+        let buffer_end = self.buffer_start + self.buffer.len() / n_channels;
+        if start >= buffer_end {
+            // The hop skips over the frames between the windows
+            self.wav
+                .seek_by_samples(((start - buffer_end) * n_channels) as u64)?;
+            self.buffer.clear();
+        } else {
+            self.buffer
+                .drain(..(start - self.buffer_start) * n_channels);
+        }
+        let buffer_end = buffer_end.max(start);
+        self.buffer_start = start;
+
+        if end > buffer_end {
+            let samples = self.wav.read_samples((end - buffer_end) * n_channels)?;
+            self.buffer.extend_from_slice(&samples);
+        }
+
+        let mut window = Vec::with_capacity(self.win_len * n_channels);
+        window.extend_from_slice(&self.buffer);
+        window.resize(self.win_len * n_channels, T::zero());
+        Ok(Samples::from(window))
+    }
+
+    /// Clears the buffer and moves the Wav struct back to the start of the data chunk.
+    fn reset(&mut self) -> crate::WaversResult<()> {
+        self.buffer.clear();
+        self.buffer_start = 0;
+        self.wav.to_data()
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for WindowIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = Samples<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_window >= self.n_windows() {
+            if let Err(e) = self.reset() {
+                eprintln!("Error: {}", e);
+            }
+            return None;
+        }
+        if self.current_window == 0 {
+            if let Err(e) = self.reset() {
+                eprintln!("Seek To Data Error: {}", e);
+                return None;
+            }
+        }
+
+        match self.read_window() {
+            Ok(window) => {
+                self.current_window += 1;
+                Some(window)
+            }
+            Err(e) => {
+                eprintln!("Read Error: {}", e);
+                self.current_window = usize::MAX;
+                if let Err(e) = self.reset() {
+                    eprintln!("Error: {}", e);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// A window iterator for the Samples struct.
+/// Returns windows of ``win_len`` frames of interleaved samples which start every ``hop`` frames, in the same way as the ``WindowIterator``.
+/// This should only be used via the ``windows`` function on the Samples struct.
+pub struct SampleWindowIterator<'a, T: 'a + AudioSample> {
+    samples: &'a [T],
+    win_len: usize,
+    hop: usize,
+    n_channels: usize,
+    padding: WindowPadding,
+    current_window: usize,
+}
+
+impl<'a, T: 'a + AudioSample> SampleWindowIterator<'a, T> {
+    /// Creates an iterator over the windows of the interleaved samples.
+    ///
+    /// Panics if the window length, the hop size or the number of channels is zero.
+    pub fn new(
+        samples: &'a [T],
+        win_len: usize,
+        hop: usize,
+        n_channels: u16,
+    ) -> SampleWindowIterator<'a, T> {
+        assert!(win_len > 0, "Window length must be positive");
+        assert!(hop > 0, "Hop size must be positive");
+        assert!(n_channels > 0, "Number of channels must be positive");
+        SampleWindowIterator {
+            samples,
+            win_len,
+            hop,
+            n_channels: n_channels as usize,
+            padding: WindowPadding::default(),
+            current_window: 0,
+        }
+    }
+
+    /// Sets how the final window is handled when it extends past the last frame.
+    pub fn padding(mut self, padding: WindowPadding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns the number of windows the iterator returns in total.
+    pub fn n_windows(&self) -> usize {
+        let n_frames = self.samples.len() / self.n_channels;
+        self.padding.n_windows(n_frames, self.win_len, self.hop)
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for SampleWindowIterator<'a, T> {
+    type Item = Samples<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_window >= self.n_windows() {
+            return None;
+        }
+        let n_frames = self.samples.len() / self.n_channels;
+        let start = self.current_window * self.hop;
+        let end = (start + self.win_len).min(n_frames);
+        self.current_window += 1;
+
+        let mut window = Vec::with_capacity(self.win_len * self.n_channels);
+        window.extend_from_slice(&self.samples[start * self.n_channels..end * self.n_channels]);
+        window.resize(self.win_len * self.n_channels, T::zero());
+        Some(Samples::from(window))
+    }
+}
+
+/// An iterator which repeats the frames of the Wav struct, wrapping from the last frame back to the first, a given number of times or indefinitely.
+/// Each frame is returned as a ``Frame``. The file is decoded ``FRAME_BUFFER_SIZE`` frames at a time as it is played, so the repeated audio is never held in memory.
+/// Frames returned by ``next_frame`` borrow the iterator's buffer, while those returned through ``Iterator`` own their samples.
+/// Samples can also be read straight into a buffer with ``read_into``, e.g. to fill the buffers of an audio callback.
+/// This should only be used via the ``looped`` and ``looped_forever`` functions on the Wav struct.
+///
+/// Block encoded files, such as MS ADPCM, cannot be read a buffer at a time, so they cannot be looped.
+///
+/// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
+pub struct LoopIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: &'a mut Wav<T>,
+    n_loops: Option<usize>,
+    current_loop: usize,
+    buffer: Vec<T>,
+    buffer_pos: usize,
+    finished: bool,
+}
+
+impl<'a, T: 'a + AudioSample> LoopIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates an iterator which plays the file ``n_loops`` times, or indefinitely if ``n_loops`` is ``None``, from the current position of the reader.
+    ///
+    /// This function will return an error if the file is block encoded.
+    pub fn new(wav: &'a mut Wav<T>, n_loops: Option<usize>) -> WaversResult<LoopIterator<'a, T>> {
+        let wav_type = wav.encoding();
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        // An empty file would otherwise be looped forever without yielding anything
+        let finished = n_loops == Some(0) || wav.n_samples() == 0;
+        Ok(LoopIterator {
+            wav,
+            n_loops,
+            current_loop: 0,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            finished,
+        })
+    }
+
+    /// Returns the number of times the file is played, or ``None`` if it is played indefinitely.
+    pub fn n_loops(&self) -> Option<usize> {
+        self.n_loops
+    }
+
+    /// Returns the number of complete times the file has been played so far.
+    pub fn current_loop(&self) -> usize {
+        self.current_loop
+    }
+
+    /// Fills ``out`` with the next samples, wrapping back to the start of the data chunk whenever its end is reached.
+    /// Returns the number of samples read, which is less than the length of ``out`` only once the last loop has been played.
+    ///
+    /// This function will return an error if there is an issue reading the file.
+    pub fn read_into(&mut self, out: &mut [T]) -> WaversResult<usize> {
+        let mut n_read = 0;
+        while n_read < out.len() && !self.finished {
+            n_read += self.wav.read_into(&mut out[n_read..])?;
+            if n_read < out.len() {
+                self.current_loop += 1;
+                self.finished = Some(self.current_loop) == self.n_loops;
+                self.wav.to_data()?;
+            }
+        }
+        Ok(n_read)
+    }
+
+    /// Returns the next frame as a ``Frame`` which borrows the iterator's buffer, so no allocation is made per frame.
+    /// The frame must be dropped before the next call, so this is used in a ``while let`` loop rather than through ``Iterator``.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// let mut wav: Wav<f32> = Wav::from_path("path/to/bed.wav").unwrap();
+    /// let mut looped = wav.looped(4).unwrap();
+    /// while let Some(frame) = looped.next_frame() {
+    ///     println!("{:?}", frame.channel(0));
+    /// }
+    /// ```
+    pub fn next_frame(&mut self) -> Option<Frame<'_, T>> {
+        let n_channels = self.wav.n_channels().max(1) as usize;
+        if self.buffer_pos + n_channels > self.buffer.len() {
+            // The buffer keeps its capacity, so it is only allocated once
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.resize(FRAME_BUFFER_SIZE * n_channels, T::zero());
+            match self.read_into(&mut buffer) {
+                Ok(n_read) if n_read >= n_channels => buffer.truncate(n_read),
+                Ok(_) => {
+                    self.finish();
+                    return None;
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    self.finish();
+                    return None;
+                }
+            }
+            self.buffer = buffer;
+            self.buffer_pos = 0;
+        }
+        let frame = &self.buffer[self.buffer_pos..self.buffer_pos + n_channels];
+        self.buffer_pos += n_channels;
+        Some(Frame::from(frame))
+    }
+
+    /// Stops the iterator and moves the Wav struct back to the start of the data chunk.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.buffer = Vec::new();
+        if let Err(e) = self.wav.to_data() {
+            eprintln!("Error: {}", e);
+        }
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for LoopIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = Frame<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().map(Frame::into_owned)
+    }
+}
+
+/// A segment of a wav file, given by a label and a range of sample frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub label: Option<String>,
+    /// The first sample frame of the segment.
+    pub start: u64,
+    /// The sample frame after the last sample frame of the segment.
+    pub end: u64,
+}
+
+impl Segment {
+    /// Returns the segments defined by the markers.
+    /// A marker with a length ends after that many sample frames, otherwise it ends at the next marker or the end of the file.
+    pub fn from_markers(markers: &[Marker], n_frames: u64) -> Vec<Segment> {
+        let mut markers: Vec<&Marker> = markers.iter().collect();
+        markers.sort_by_key(|marker| marker.sample_offset);
+        let mut segments = Vec::with_capacity(markers.len());
+        for (i, marker) in markers.iter().enumerate() {
+            let start = (marker.sample_offset as u64).min(n_frames);
+            let end = match (marker.length, markers.get(i + 1)) {
+                (Some(length), _) => start + length as u64,
+                (None, Some(next)) => next.sample_offset as u64,
+                (None, None) => n_frames,
+            };
+            segments.push(Segment {
+                label: marker.label.clone(),
+                start,
+                end: end.clamp(start, n_frames),
+            });
+        }
+        segments
+    }
+
+    /// Returns the segments defined by the loops of a smpl chunk. The end of a loop is included in its segment.
+    pub fn from_loops(loops: &[SampleLoop], n_frames: u64) -> Vec<Segment> {
+        loops
+            .iter()
+            .map(|sample_loop| {
+                let start = (sample_loop.start as u64).min(n_frames);
+                Segment {
+                    label: None,
+                    start,
+                    end: (sample_loop.end as u64 + 1).clamp(start, n_frames),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A segment iterator for the Wav struct.
+/// The SegmentIterator returns the label and samples of each segment, reading each segment from the file only when it is reached.
+/// This should only be used via the ``segments`` function on the Wav struct.
+///
+/// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
+pub struct SegmentIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: &'a mut Wav<T>,
+    segments: std::vec::IntoIter<Segment>,
+}
+
+impl<'a, T: 'a + AudioSample> SegmentIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    pub fn new(wav: &'a mut Wav<T>, segments: Vec<Segment>) -> SegmentIterator<'a, T> {
+        SegmentIterator {
+            wav,
+            segments: segments.into_iter(),
+        }
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for SegmentIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = (Option<String>, Samples<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let segment = match self.segments.next() {
+            Some(segment) => segment,
+            None => {
+                if let Err(e) = self.wav.to_data() {
+                    eprintln!("Error: {}", e);
+                }
+                return None;
+            }
+        };
+
+        match self.wav.read_range(segment.start..segment.end) {
+            Ok(samples) => Some((segment.label, samples)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod iter_tests {
+    use crate::DATA;
+
+    use super::*;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn test_frame_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let max_pos = wav.max_data_pos();
+        let frame_iter = FrameIterator::new(max_pos, &mut wav);
+
+        for frame in frame_iter {
+            assert_eq!(frame.len(), 2, "Frame length should be 2");
+        }
+
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let max_pos = wav.max_data_pos();
+        let frame_iter = FrameIterator::new(max_pos, &mut wav);
+
+        let mut shadow_wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = shadow_wav.read().unwrap();
+        let mut idx = 0;
+        for frame in frame_iter {
+            assert_eq!(
+                frame[0], data[idx],
+                "Frame[0] should be equal to data at index {}",
+                idx
+            );
+            assert_eq!(
+                frame[1],
+                data[idx + 1],
+                "Frame[1] should be equal to data at index {}",
+                idx + 1
+            );
+            idx += 2;
+        }
+    }
+
+    #[test]
+    fn test_frame_iterator_reads_every_frame() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+        wav.to_data().unwrap();
+
+        // The file is longer than the buffer, so the frames are read over several refills
+        assert!(data.len() / 2 > FRAME_BUFFER_SIZE);
+        let frames: Vec<Samples<i16>> = wav.frames().collect();
+        assert_eq!(frames.len(), data.len() / 2);
+        let flattened: Vec<i16> = frames
+            .iter()
+            .flat_map(|frame| frame.iter().copied())
+            .collect();
+        assert_eq!(&flattened[..], &data[..]);
+    }
+
+    #[test]
+    fn test_frames_of_samples_match_frame_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+
+        let borrowed: Vec<Frame<i16>> = data.frames(2).collect();
+        let mut frames = wav.frames();
+        let mut lent = Vec::new();
+        while let Some(frame) = frames.next_frame() {
+            lent.push(frame.into_owned());
+        }
+        assert_eq!(borrowed, lent);
+
+        let frame = &lent[10];
+        assert_eq!(frame.n_channels(), 2);
+        assert_eq!(frame.channel(0), Some(data[20]));
+        assert_eq!(frame.channel(1), Some(data[21]));
+        assert_eq!(frame.channel(2), None);
+        assert_eq!(frame.as_slice(), &data[20..22]);
+        assert_eq!(Samples::from(frame.clone()), Samples::from(&data[20..22]));
+
+        assert_eq!(Samples::from(vec![1, 2, 3]).frames(2).len(), 1);
+    }
+
+    #[test]
+    fn test_frame_iterator_resets() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
 
         for _ in wav.frames() {}
 
@@ -428,9 +1457,9 @@ mod iter_tests {
         let mut curr_channel = 0;
         for channel in channel_iter {
             let mut idx = 0;
-            for sample in channel.iter() {
+            for sample in channel {
                 assert_eq!(
-                    *sample,
+                    sample,
                     data[curr_channel + idx],
                     "Sample should be equal to data at index {}",
                     curr_channel + idx
@@ -439,6 +1468,21 @@ mod iter_tests {
             }
             curr_channel += 1;
         }
+        assert_eq!(curr_channel, 2);
+    }
+
+    #[test]
+    fn test_channels_are_read_side_by_side() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+
+        let mut channels = wav.channels();
+        let left = channels.next().unwrap();
+        let right = channels.next().unwrap();
+        assert!(channels.next().is_none());
+
+        let frames: Vec<i16> = left.zip(right).flat_map(|(l, r)| [l, r]).collect();
+        assert_eq!(&frames[..], &data[..]);
     }
 
     #[test]
@@ -457,6 +1501,170 @@ mod iter_tests {
         );
     }
 
+    #[test]
+    fn test_single_channel_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+
+        let right: Vec<i16> = wav.channel(1).unwrap().collect();
+        let expected: Vec<i16> = data.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(right, expected, "Channel 1 should hold every other sample");
+
+        let current_pos = wav.current_pos().unwrap();
+        let expected_pos = wav.header().get_chunk_info(DATA.into()).unwrap().offset + 8;
+        assert_eq!(current_pos, expected_pos as u64);
+
+        assert!(wav.channel(2).is_err(), "Channel 2 should be out of range");
+    }
+
+    #[test]
+    fn test_loop_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+
+        let looped: Vec<i16> = wav
+            .looped(3)
+            .unwrap()
+            .flat_map(|frame| frame.to_vec())
+            .collect();
+        assert_eq!(looped.len(), data.len() * 3);
+        for repeat in looped.chunks(data.len()) {
+            assert_eq!(repeat, &data[..], "Each loop should repeat the file");
+        }
+
+        let current_pos = wav.current_pos().unwrap();
+        let expected_pos = wav.header().get_chunk_info(DATA.into()).unwrap().offset + 8;
+        assert_eq!(current_pos, expected_pos as u64);
+
+        // Reading past the end of the file wraps back to the start
+        let mut looped = wav.looped_forever().unwrap();
+        let mut buffer = vec![0; data.len() + 10];
+        assert_eq!(looped.read_into(&mut buffer).unwrap(), buffer.len());
+        assert_eq!(&buffer[data.len()..], &data[..10]);
+        assert_eq!(looped.current_loop(), 1);
+        assert_eq!(looped.n_loops(), None);
+
+        assert_eq!(wav.looped(0).unwrap().count(), 0);
+
+        // Frames can be borrowed from the iterator's buffer
+        let mut looped = wav.looped(2).unwrap();
+        let mut borrowed = Vec::new();
+        while let Some(frame) = looped.next_frame() {
+            borrowed.extend_from_slice(&frame);
+        }
+        assert_eq!(&borrowed[..data.len()], &data[..]);
+        assert_eq!(&borrowed[data.len()..], &data[..]);
+
+        let mut wav = Wav::<i16>::from_path("./test_resources/one_channel_ms_adpcm.wav").unwrap();
+        assert!(matches!(
+            wav.looped(2),
+            Err(crate::WaversError::Format(FormatError::BlockEncodedFormat(
+                _
+            )))
+        ));
+        assert!(wav.looped_forever().is_err());
+    }
+
+    #[test]
+    fn test_sample_iterator() {
+        let mut wav = Wav::<f32>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let expected: Samples<f32> = wav.read().unwrap();
+        assert!(expected.len() > FRAME_BUFFER_SIZE);
+
+        let samples: Vec<f32> = wav.into_iter().collect::<crate::WaversResult<_>>().unwrap();
+        assert_eq!(&samples[..], &expected[..]);
+
+        let mut wav = Wav::<i16>::from_path("./test_resources/one_channel_ms_adpcm.wav").unwrap();
+        let expected: Samples<i16> = wav.read().unwrap();
+        let samples: Vec<i16> = wav.into_iter().map(|sample| sample.unwrap()).collect();
+        assert_eq!(&samples[..], &expected[..]);
+    }
+
+    #[test]
+    fn block_encoded_frames_and_channels_are_decoded() {
+        let mut wav = Wav::<i16>::from_path("./test_resources/two_channel_ms_adpcm.wav").unwrap();
+        let expected: Samples<i16> = wav.read().unwrap();
+        assert!(!expected.is_empty());
+
+        let frames: Vec<i16> = wav.frames().flat_map(|frame| frame.to_vec()).collect();
+        assert_eq!(&frames[..], &expected[..]);
+
+        let left: Vec<i16> = wav.channel(0).unwrap().collect();
+        let expected_left: Vec<i16> = expected.iter().step_by(2).copied().collect();
+        assert_eq!(left, expected_left);
+
+        let channels: Vec<Vec<i16>> = wav.channels().map(|channel| channel.collect()).collect();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0], expected_left);
+        assert_eq!(channels[1].len(), expected_left.len());
+    }
+
+    #[test]
+    fn block_encoded_windows_are_decoded() {
+        let mut wav = Wav::<i16>::from_path("./test_resources/two_channel_ms_adpcm.wav").unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+        assert_eq!(data.len(), wav.n_frames() * 2);
+
+        for (win_len, hop) in [(100, 40), (100, 250)] {
+            let expected: Vec<Samples<i16>> = data.windows(win_len, hop, 2).collect();
+            let windows: Vec<Samples<i16>> = wav.windows(win_len, hop).collect();
+            assert_eq!(windows, expected, "Windows of {} hopping {}", win_len, hop);
+        }
+    }
+
+    #[test]
+    fn test_window_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+        let n_frames = data.len() / 2;
+
+        for (win_len, hop) in [(1000, 400), (1000, 1000), (300, 700)] {
+            let expected: Vec<Samples<i16>> = data.windows(win_len, hop, 2).collect();
+            let windows: Vec<Samples<i16>> = wav.windows(win_len, hop).collect();
+            assert_eq!(windows, expected, "Windows of {} hopping {}", win_len, hop);
+
+            let last = windows.last().unwrap();
+            let last_start = (windows.len() - 1) * hop;
+            assert_eq!(last.len(), win_len * 2);
+            assert!(last_start < n_frames);
+            assert!(last_start + win_len >= n_frames || last_start + hop >= n_frames);
+        }
+
+        let current_pos = wav.current_pos().unwrap();
+        let expected_pos = wav.header().get_chunk_info(DATA.into()).unwrap().offset + 8;
+        assert_eq!(current_pos, expected_pos as u64);
+
+        let dropped: Vec<Samples<i16>> = wav
+            .windows(1000, 400)
+            .padding(WindowPadding::Drop)
+            .collect();
+        assert_eq!(dropped.len(), (n_frames - 1000) / 400 + 1);
+        assert_eq!(&dropped[1][..], &data[800..2800]);
+    }
+
+    #[test]
+    fn test_window_padding() {
+        let samples = Samples::from(vec![1, 2, 3, 4, 5]);
+        let windows: Vec<Samples<i32>> = samples.windows(2, 2, 1).collect();
+        assert_eq!(
+            windows,
+            vec![
+                Samples::from(vec![1, 2]),
+                Samples::from(vec![3, 4]),
+                Samples::from(vec![5, 0])
+            ]
+        );
+        let windows = samples.windows(2, 2, 1).padding(WindowPadding::Drop);
+        assert_eq!(windows.n_windows(), 2);
+
+        assert_eq!(WindowPadding::Zeros.n_windows(0, 4, 2), 0);
+        assert_eq!(WindowPadding::Zeros.n_windows(3, 4, 2), 1);
+        assert_eq!(WindowPadding::Drop.n_windows(3, 4, 2), 0);
+        assert_eq!(WindowPadding::Zeros.n_windows(10, 4, 3), 3);
+        assert_eq!(WindowPadding::Zeros.n_windows(11, 4, 3), 4);
+        assert_eq!(WindowPadding::Zeros.n_windows(10, 2, 5), 2);
+    }
+
     #[test]
     fn test_block_iterator() {
         // the test file is sampled at 16Khz and has 2 channels. It is 10 seconds long.
@@ -498,6 +1706,59 @@ mod iter_tests {
         );
     }
 
+    #[test]
+    fn segments_from_markers_and_loops() {
+        let markers = vec![
+            Marker::with_label(2, 300, "b"),
+            Marker {
+                length: Some(50),
+                ..Marker::with_label(1, 100, "a")
+            },
+            Marker::new(3, 900),
+        ];
+        let segments = Segment::from_markers(&markers, 1000);
+        let ranges: Vec<(u64, u64)> = segments.iter().map(|s| (s.start, s.end)).collect();
+        assert_eq!(ranges, vec![(100, 150), (300, 900), (900, 1000)]);
+        assert_eq!(segments[0].label.as_deref(), Some("a"));
+        assert_eq!(segments[2].label, None);
+
+        let loops = vec![SampleLoop::new(0, 10, 19), SampleLoop::new(1, 990, 2000)];
+        let ranges: Vec<(u64, u64)> = Segment::from_loops(&loops, 1000)
+            .iter()
+            .map(|s| (s.start, s.end))
+            .collect();
+        assert_eq!(ranges, vec![(10, 20), (990, 1000)]);
+    }
+
+    #[test]
+    fn test_segment_iterator() {
+        let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let data: Samples<i16> = wav.read().unwrap();
+        let segments = vec![
+            Segment {
+                label: Some("first".to_string()),
+                start: 1000,
+                end: 1100,
+            },
+            Segment {
+                label: None,
+                start: 10,
+                end: 12,
+            },
+        ];
+
+        let read: Vec<(Option<String>, Samples<i16>)> =
+            SegmentIterator::new(&mut wav, segments).collect();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].0.as_deref(), Some("first"));
+        assert_eq!(&read[0].1[..], &data[2000..2200]);
+        assert_eq!(&read[1].1[..], &data[20..24]);
+
+        let current_pos = wav.current_pos().unwrap();
+        let expected_pos = wav.header().get_chunk_info(DATA.into()).unwrap().offset + 8;
+        assert_eq!(current_pos, expected_pos as u64);
+    }
+
     #[test]
     fn test_block_iterator_resets() {
         let mut wav = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16).unwrap();
@@ -0,0 +1,138 @@
+//! Module containing helpers which convert markers to and from the label track format of Audacity.
+//!
+//! An Audacity label track is a text file with one label per line, made up of the start time, the end time and the label separated by tabs, e.g. ``1.500000\t2.000000\tChorus``.
+//! The times are in seconds, so a sample rate is needed to convert them to and from the sample frames of a ``Marker``.
+//! A marker with a length becomes a region label, and a marker without a length becomes a point label, whose start and end are the same.
+use std::fs;
+use std::path::Path;
+
+use crate::chunks::Marker;
+use crate::error::{FormatError, WaversResult};
+
+/// Converts the markers to the Audacity label track format at the given sample rate.
+/// Notes are not supported by Audacity and are not included. Tabs and line breaks in labels are replaced with spaces.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::{markers_to_audacity_labels, Marker};
+///
+/// let markers = vec![Marker::with_label(1, 24000, "Intro")];
+/// assert_eq!(markers_to_audacity_labels(&markers, 48000), "0.500000\t0.500000\tIntro\n");
+/// ```
+pub fn markers_to_audacity_labels(markers: &[Marker], sample_rate: i32) -> String {
+    let sample_rate = sample_rate as f64;
+    let mut labels = String::new();
+    for marker in markers.iter() {
+        let start = marker.sample_offset as f64 / sample_rate;
+        let end =
+            (marker.sample_offset as u64 + marker.length.unwrap_or(0) as u64) as f64 / sample_rate;
+        let label = marker
+            .label
+            .as_deref()
+            .unwrap_or_default()
+            .replace(['\t', '\r', '\n'], " ");
+        labels.push_str(&format!("{:.6}\t{:.6}\t{}\n", start, end, label));
+    }
+    labels
+}
+
+/// Parses markers from the Audacity label track format at the given sample rate.
+/// The markers are given the IDs 1, 2, 3, ... in the order of the labels. Times are rounded to the nearest sample frame.
+/// Lines starting with a backslash, which Audacity uses for the frequency range of spectral labels, and empty lines are ignored.
+///
+/// This function will return an error if a line does not start with a start and end time in seconds.
+pub fn markers_from_audacity_labels(labels: &str, sample_rate: i32) -> WaversResult<Vec<Marker>> {
+    let sample_rate = sample_rate as f64;
+    let mut markers = Vec::new();
+    for line in labels.lines() {
+        if line.trim().is_empty() || line.starts_with('\\') {
+            continue;
+        }
+        let invalid = || FormatError::InvalidAudacityLabel(line.to_string());
+        let mut fields = line.splitn(3, '\t');
+        let mut next_time = || -> WaversResult<f64> {
+            let time = fields
+                .next()
+                .and_then(|field| field.trim().parse::<f64>().ok())
+                .filter(|time| *time >= 0.0)
+                .ok_or_else(invalid)?;
+            Ok(time)
+        };
+        let start = next_time()?;
+        let end = next_time()?;
+        let label = fields.next().unwrap_or_default();
+
+        let sample_offset = (start * sample_rate).round() as u32;
+        let length = ((end - start).max(0.0) * sample_rate).round() as u32;
+        markers.push(Marker {
+            id: markers.len() as u32 + 1,
+            sample_offset,
+            label: match label.is_empty() {
+                true => None,
+                false => Some(label.to_string()),
+            },
+            note: None,
+            length: match length {
+                0 => None,
+                _ => Some(length),
+            },
+        });
+    }
+    Ok(markers)
+}
+
+/// Writes the markers to an Audacity label track file at the given path. See ``markers_to_audacity_labels``.
+pub fn write_audacity_labels<P: AsRef<Path>>(
+    p: P,
+    markers: &[Marker],
+    sample_rate: i32,
+) -> WaversResult<()> {
+    fs::write(p, markers_to_audacity_labels(markers, sample_rate))?;
+    Ok(())
+}
+
+/// Reads markers from an Audacity label track file at the given path. See ``markers_from_audacity_labels``.
+pub fn read_audacity_labels<P: AsRef<Path>>(p: P, sample_rate: i32) -> WaversResult<Vec<Marker>> {
+    markers_from_audacity_labels(&fs::read_to_string(p)?, sample_rate)
+}
+
+#[cfg(test)]
+mod labels_tests {
+    use super::*;
+
+    #[test]
+    fn markers_round_trip_through_audacity_labels() {
+        let markers = vec![
+            Marker::with_label(1, 0, "Intro"),
+            Marker {
+                length: Some(22050),
+                ..Marker::with_label(2, 44100, "Take 2")
+            },
+            Marker::new(3, 88200),
+        ];
+        let labels = markers_to_audacity_labels(&markers, 44100);
+        assert_eq!(
+            labels,
+            "0.000000\t0.000000\tIntro\n1.000000\t1.500000\tTake 2\n2.000000\t2.000000\t\n"
+        );
+        assert_eq!(
+            markers_from_audacity_labels(&labels, 44100).unwrap(),
+            markers
+        );
+    }
+
+    #[test]
+    fn spectral_ranges_are_skipped_and_bad_lines_rejected() {
+        let labels = "0.25\t0.75\tBird\n\\\t200.0\t4000.0\n\n";
+        assert!(
+            markers_from_audacity_labels(&format!("{}1,5\t2.0\tBad\n", labels), 16000).is_err()
+        );
+
+        let markers = markers_from_audacity_labels(labels, 16000).unwrap();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].sample_offset, 4000);
+        assert_eq!(markers[0].length, Some(8000));
+        assert_eq!(markers[0].label.as_deref(), Some("Bird"));
+    }
+}
@@ -1,7 +1,7 @@
 //!
 //! # Wavers
 //! WaveRs is a fast and lightweight library for reading and writing ``wav`` files.
-//! Currently, it supports reading and writing of ``i16``, ``i24``, ``i32``, ``f32``, and ``f64`` audio samples.
+//! Currently, it supports reading and writing of ``u8``, ``i16``, ``i24``, ``i32``, ``f32``, and ``f64`` audio samples.
 //!
 //! Feedback and bugs welcome!
 //!
@@ -10,11 +10,27 @@
 //! * Simple API, read a wav file with ``read`` and write a wav file with ``write``
 //! * Easy and efficient conversion between different types of audio samples (**should** compile down to simd instructions provided you build with the appropriate SIMD instruction set for your architecture).
 //! * Support for the Extensible format (Happy to try and support anything else that pops up, just ask or open a PR).
+//! * Support for A-law and µ-law companded files, which are expanded on read and can be written using ``write_with_options``.
+//! * Decoding of block encoded MS ADPCM and GSM 6.10 files, which are read as a whole using ``read``.
 //! * Increasing support for different chunks in the wav file.
 //! * Support for iteration over the frames, channels and overlapping blocks of the wav file.
+//! * Incremental writing of recordings whose length is not known up front using ``WavWriter``.
 //! * Support for the ``ndarray`` crate. Enable the ``ndarray`` feature to enable ndarray support.
 //! * Support for the ``pyo3`` crate. Enable the ``pyo3`` feature to enable pyo3 support. This is mostly for [PyWavers](https://github.com/jmg049/Pywavers).
 //! * Supports logging through the ``log`` crate. Enable the ``logging`` feature to enable logging.
+//! * Half-precision float samples through the ``half`` crate. Enable the ``half`` feature to read and write ``f16`` samples.
+//! * Reading of AIFF and AIFF-C files. Enable the ``aiff`` feature to use ``Wav::from_aiff``.
+//! * Reading of NIST SPHERE files from speech corpora such as TIMIT. Enable the ``sphere`` feature to use ``Wav::from_sphere``.
+//! * Passthrough of ID3v2 tags stored in wav files. Enable the ``id3`` feature to parse them using the ``id3`` crate.
+//! * Short-time Fourier transforms using the ``rustfft`` crate. Enable the ``fft`` feature to use ``Wav::stft``.
+//! * Memory-mapped reading and writing using the ``memmap2`` crate. Enable the ``mmap`` feature to use ``MmapWav`` and ``mmap::write``.
+//! * Asynchronous reading and writing using the ``tokio`` crate. Enable the ``tokio`` feature to use ``AsyncWav``.
+//! * Runtime-agnostic asynchronous reading and writing through the ``futures`` traits. Enable the ``futures`` feature to use ``AsyncWav`` with ``FuturesIo``.
+//! * Reading of remote files over HTTP byte-range requests using the ``ureq`` crate. Enable the ``http`` feature to use ``Wav::from_url``.
+//! * Reading from S3, GCS and Azure using the ``object_store`` crate. Enable the ``object_store`` feature to use ``AsyncWav::from_url``.
+//! * Batched reading and writing of many files using io_uring on Linux. Enable the ``io_uring`` feature to use ``UringBatch``.
+//! * Sample rate conversion using the ``rubato`` crate. Enable the ``resampling`` feature to use ``resample``.
+//! * Loudness measurement and normalization following EBU R128. Enable the ``loudness`` feature to use ``Wav::integrated_loudness`` and ``normalize_loudness``.
 //!
 //! ## Crate Status
 //! * This crate is currently in development. Changes to the core API will either not happen or they will be kept to a minimum. Any planned additions to the API will be built on top of the existing API.
@@ -46,6 +62,8 @@
 //! }
 //! ```
 //!
+//! Wav files which are already in memory, such as downloaded files or files embedded with ``include_bytes!``, can be read without touching the filesystem using ``Wav::from_bytes``, ``Wav::from_vec`` or ``Wav::from_cursor``. Readers which cannot seek, such as ``std::io::stdin()``, pipes and network streams, can be read with ``Wav::from_stream``, which keeps only the header in memory and streams the samples as they are read. The size of the buffer files are read through can be tuned, e.g. for network file systems or small embedded targets, using ``Wav::from_path_with_buffer_size``, and likewise for writers with ``WriteOptions::buffer_size``.
+//!
 //! ## Conversion
 //! ```no_run
 //! use wavers::{Wav, read, ConvertTo};
@@ -86,8 +104,23 @@
 //!     write(out_fp, samples, sample_rate, n_channels).unwrap();
 //! }
 //! ```
+//!
+//! Samples can also be written to any writer rather than a path, such as a socket or an in-memory buffer, using ``write_to_writer`` or ``Wav::write_to_writer``, or straight to a ``Vec<u8>`` using ``write_to_vec`` or ``Wav::to_bytes``. Samples can be stored with an encoding other than the native encoding of their type using ``write_as``, e.g. ``write_as("out.wav", &samples, WavType::Pcm16, 48000, 2)`` writes ``f32`` samples as 16-bit PCM. When the bit depth is reduced like this, ``WriteOptions::dither`` adds TPDF dither drawn from a seeded random number generator, so that quiet passages are not left with quantization distortion. The dither can be shaped towards less audible frequencies with ``WriteOptions::noise_shaping``, e.g. ``NoiseShaping::Lipshitz`` for 16-bit masters at 44.1 kHz. Samples produced by an iterator, such as generated audio hours long, are written a block at a time with ``write_from_iter``, and planar samples with a separate buffer per channel are interleaved while writing with ``write_planar``. The format and options of the written files can also be collected in a ``WavSpec`` and a ``WavBuilder``, e.g. ``WavBuilder::new(WavSpec::new(16000, 2).wav_type(WavType::Pcm24)).with_metadata([(InfoTag::Name, "Take 1")]).write("take1.wav", &samples)``, and ``Wav::spec`` returns the spec of an existing file. With ``WriteOptions::atomic`` set, files are written to a temporary file which replaces the destination only once writing has succeeded, so a failed write never leaves a truncated file behind. For recordings which must be on disk once writing returns, ``WriteOptions::durability`` syncs the file, and optionally its directory, before ``write_with_options`` or ``WavWriter::finalize`` return. A single channel of an existing file can be replaced in place with ``Wav::overwrite_channel``, without re-encoding the other channels. Conversely, ``extract_channel`` streams one channel of a file to a new mono file. Whole files are converted to another encoding, sample rate and number of channels with ``transcode``, e.g. ``transcode("in.wav", "out.wav", &TranscodeOptions::new().target_type(WavType::Pcm16).target_rate(16000).target_channels(1))``, which streams the file a block at a time so that hours long recordings are never loaded into memory. For custom effects or cleanup, ``Wav::process_to`` streams a file through a closure a block of frames at a time and writes the processed blocks to a new file. Levels can be matched with ``Samples::normalize_peak``, which scales samples so that their absolute peak is at a level in dBFS, or with ``normalize_peak`` for files, which finds the peak of a file in a first pass and scales it in a second without loading it into memory. Fixed gains in dB are applied with ``Samples::apply_gain_db`` and, streaming from file to file, with ``apply_gain``, whose ``ClipPolicy`` either clamps integer samples pushed beyond full scale or rejects the gain before anything is written. Recorded takes and dataset clips can be cleaned up with ``Samples::trim_silence``, which drops the leading and trailing frames below a threshold in dBFS, while ``Wav::trimmed_range`` scans a file a block at a time and returns the frames of the trimmed take. Long recordings can be split into their active segments with ``Samples::active_segments`` and ``Wav::active_segments``, which compare the energy of fixed-length windows against a threshold and keep a segment open for a number of hangover windows so that short pauses do not split it. The DC offset which cheap capture hardware adds to recordings is measured per channel with ``Samples::dc_offset`` or ``Wav::dc_offset`` and subtracted with ``Samples::remove_dc``, while ``DcBlocker`` removes it from a stream a block at a time with a one-pole high-pass filter, e.g. inside ``Wav::process_to``. Quality checks can flag damaged recordings with ``Samples::detect_clipping`` and ``Wav::detect_clipping``, which report the runs of consecutive full-scale samples in each channel. A recording whose ``WavWriter`` was never finalized, e.g. after a power loss, can be repaired with ``WavWriter::recover``, which infers the length of the data chunk from the size of the file.
+//!
+//! When the length of a recording is not known up front, a ``WavWriter`` writes it a block of frames at a time and patches the header when it is finalized. Writers which cannot seek, such as pipes into ffmpeg or sox, are written to by a ``StreamWavWriter``, which is either given the number of frames up front or records placeholder sizes for a stream of ``StreamLength::Unknown`` length.
+//! ```no_run
+//! use wavers::{WavWriter, WriteOptions};
+//!
+//! fn main() {
+//!     let mut writer: WavWriter<f32> = WavWriter::create("recording.wav", 48000, 1, &WriteOptions::new()).unwrap();
+//!     for block in [[0.0f32; 480], [0.5f32; 480]] {
+//!         writer.write_frames(&block).unwrap();
+//!     }
+//!     writer.finalize().unwrap();
+//! }
+//! ```
 //! ## Iteration
-//! ``WaveRs`` provides two primary methods of iteration: Frame-wise and Channel-wise. These can be performed using the ``Wav::frames`` and ``Wav::channels`` functions respectively. Both methods return an iterator over the samples in the wav file. The ``frames`` method returns an iterator over the frames of the wav file, where a frame is a single sample from each channel. The ``channels`` method returns an iterator over the channels of the wav file, where a channel is all the samples for a single channel.
+//! ``WaveRs`` provides two primary methods of iteration: Frame-wise and Channel-wise. These can be performed using the ``Wav::frames`` and ``Wav::channels`` functions respectively. Both methods return an iterator over the samples in the wav file. The ``frames`` method returns an iterator over the frames of the wav file, where a frame is a single sample from each channel. The ``channels`` method returns an iterator over the channels of the wav file, where a channel is all the samples for a single channel. A single channel can also be read lazily, without reading the other channels into memory, using ``Wav::channel``. For STFT-style processing, ``Wav::windows`` returns overlapping windows of a fixed number of frames which start every hop frames, zero-padding the final window by default. Files which are too large to hold in memory, such as long RF64 recordings, can be read a page at a time with ``StreamingWav``, which never keeps more than a given number of bytes of samples in memory. Random fixed-length crops, e.g. for training machine learning models, can be read with ``WindowSampler``, which seeks to a start frame drawn from a seeded random number generator and decodes only the frames of the crop. Recordings split across several files, e.g. one file per hour, can be read as one continuous stream with ``WavChain``, which counts, seeks and iterates frames across all of the files. ``Wav::looped`` and ``Wav::looped_forever`` repeat the frames of a file, wrapping from the end back to the start without a gap, which is useful for test stimuli and background beds. Datasets recorded at mixed sample rates can be read at a single rate with ``Wav::with_target_sample_rate``, e.g. ``Wav::<f32>::from_path("in.wav")?.with_target_sample_rate(16000)?``, after which ``read`` and ``frames`` return samples resampled to 16 kHz while positional reads and seeking still address the frames of the file. Speech pipelines which expect a single channel can load any file as mono with ``Wav::read_mono``, which averages the channels of each frame, while ``Samples::to_mono_weighted`` mixes the channels with chosen weights, e.g. ``&[1.0, 0.0]`` to keep only the left channel. More generally, ``Samples::remix`` and ``Wav::read_remixed`` map the channels through a ``Matrix`` of gains, e.g. ``Matrix::surround_to_stereo`` for a 5.1 downmix, ``Matrix::swap_stereo`` or ``Matrix::mid_side``, and ``Matrix::remix_into`` remixes streamed samples a block at a time. In the other direction, ``Samples::upmix`` copies a mono signal, such as a test tone or synthesised speech, to every channel of a stereo or multichannel file.
 //!
 //! ```no_run
 //! use wavers::Wav;
@@ -99,9 +132,10 @@
 //!        // do something with the frame
 //!     }
 //!
+//!     let n_frames = wav.n_frames();
 //!     for channel in wav.channels() {
 //!         // do something with the channel
-//!         assert_eq!(channel.len(), wav.n_samples() / wav.n_channels(), "The channel should have the same number of samples as the wav file divided by the number of channels");
+//!         assert_eq!(channel.count(), n_frames, "The channel should have one sample for every frame of the wav file");
 //!     }
 //! }
 //! ````
@@ -143,32 +177,278 @@
 //! }
 //! ```
 //!
+//! ### Half
+//!
+//! The ``half`` feature implements ``AudioSample`` for ``half::f16``, so wav files of any supported encoding can be read directly into half-precision buffers.
+//! Since f16 has no standard wav encoding, ``f16`` samples are written as 32-bit float by default. Use ``write_with_options`` to choose another encoding.
+//!
+//! ```no_run
+//! use wavers::{write_with_options, f16, Samples, Wav, WavType, WriteOptions};
+//!
+//! fn main() {
+//!     let mut wav: Wav<f16> = Wav::from_path("path/to/wav.wav").unwrap();
+//!     let samples: Samples<f16> = wav.read().unwrap();
+//!
+//!     let options = WriteOptions::new().encoding(WavType::Pcm16);
+//!     write_with_options("out.wav", &samples, wav.sample_rate(), wav.n_channels(), &options).unwrap();
+//! }
+//! ```
+//!
+//! ### Aiff
+//!
+//! The ``aiff`` feature adds ``Wav::from_aiff`` and ``Wav::from_aiff_path`` for reading AIFF and AIFF-C files, so they can be read and converted like wav files.
+//! Uncompressed PCM, float, mu-law and A-law files are supported.
+//!
+//! ### Sphere
+//!
+//! The ``sphere`` feature adds ``Wav::from_sphere`` and ``Wav::from_sphere_path`` for reading NIST SPHERE files, such as those in the TIMIT corpus.
+//! Uncompressed PCM, mu-law and A-law SPHERE files are supported, shorten compressed files are not.
+//!
+//! ### Id3
+//!
+//! ID3v2 tags stored in the "id3 " chunk can always be read as raw bytes using ``Wav::id3_bytes`` and written using ``WriteOptions::id3``.
+//! The ``id3`` feature adds ``Wav::id3_tag``, which parses the tag using the ``id3`` crate.
+//!
+//! ```no_run
+//! use wavers::Wav;
+//! use id3::TagLike;
+//!
+//! fn main() {
+//!     let mut wav: Wav<f32> = Wav::from_path("path/to/episode.wav").unwrap();
+//!     if let Some(tag) = wav.id3_tag().unwrap() {
+//!         println!("{:?}", tag.title());
+//!     }
+//! }
+//! ```
+//!
+//! ### Fft
+//!
+//! The ``fft`` feature adds ``Wav::stft``, which returns the spectrum of each channel for overlapping windows of the file, computed using the ``rustfft`` crate.
+//! Each window is multiplied by a ``WindowFunction``, such as a Hann window, before it is transformed.
+//!
+//! ```no_run
+//! use wavers::Wav;
+//! use wavers::stft::WindowFunction;
+//!
+//! fn main() {
+//!     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+//!     for spectra in wav.stft(1024, 256, WindowFunction::Hann) {
+//!         let magnitudes: Vec<f32> = spectra[0].iter().map(|bin| bin.norm()).collect();
+//!     }
+//! }
+//! ```
+//!
+//! ### Mmap
+//!
+//! The ``mmap`` feature adds ``MmapWav``, which reads a wav file through a memory map created using the ``memmap2`` crate.
+//! When the samples are stored as the requested type, ``MmapWav::as_slice`` returns them straight from the map without copying them.
+//! ``wavers::mmap::write`` goes the other way, sizing the output file up front and copying the encoded samples into a mutable map of it.
+//!
+//! ```no_run
+//! use wavers::mmap::MmapWav;
+//!
+//! fn main() {
+//!     let wav: MmapWav<i16> = MmapWav::from_path("path/to/wav.wav").unwrap();
+//!     let samples: &[i16] = wav.as_slice().unwrap();
+//! }
+//! ```
+//!
+//! ### Tokio
+//!
+//! The ``tokio`` feature adds ``AsyncWav``, which reads a wav file from any ``AsyncRead + AsyncSeek`` reader, such as a ``tokio::fs::File``,
+//! and the ``async_wav::write`` functions, which write to any ``AsyncWrite`` writer, so that wav files can be served and ingested without blocking worker threads.
+//!
+//! ```no_run
+//! use wavers::async_wav::{self, AsyncWav};
+//!
+//! async fn copy() {
+//!     let mut wav: AsyncWav<f32, _> = AsyncWav::from_path("path/to/wav.wav").await.unwrap();
+//!     let samples = wav.read().await.unwrap();
+//!     let mut file = tokio::fs::File::create("path/to/copy.wav").await.unwrap();
+//!     async_wav::write(&mut file, &samples, wav.sample_rate(), wav.n_channels()).await.unwrap();
+//! }
+//! ```
+//!
+//! ### Futures
+//!
+//! The ``futures`` feature allows ``AsyncWav`` and the ``async_wav::write`` functions to be used with any reader or writer implementing the ``futures`` ``AsyncRead``, ``AsyncSeek`` and ``AsyncWrite`` traits,
+//! such as those of smol and async-std, by wrapping it in ``FuturesIo``. The same parsing code is used as with the ``tokio`` feature.
+//!
+//! ```no_run
+//! use wavers::async_wav::{AsyncWav, FuturesIo};
+//!
+//! async fn read<R: futures_io::AsyncRead + futures_io::AsyncSeek + Unpin>(reader: R) {
+//!     let mut wav: AsyncWav<f32, _> = AsyncWav::new(FuturesIo::new(reader)).await.unwrap();
+//!     let samples = wav.read().await.unwrap();
+//! }
+//! ```
+//!
+//! ### Http
+//!
+//! The ``http`` feature adds ``HttpReader``, which reads a remote file using HTTP byte-range requests made with the ``ureq`` crate, and ``Wav::from_url``.
+//! Only the parts of the file which are read are downloaded, so the header of a remote wav file can be inspected, and part of it decoded, without downloading the whole file.
+//!
+//! ```no_run
+//! use wavers::Wav;
+//!
+//! fn main() {
+//!     let mut wav: Wav<f32> = Wav::from_url("https://example.com/speech.wav").unwrap();
+//!     let first_second = wav.read_frames(0, wav.sample_rate() as usize).unwrap();
+//! }
+//! ```
+//!
+//! ### Object Store
+//!
+//! The ``object_store`` feature adds ``ObjectStoreReader``, which reads an object from cloud storage such as S3, GCS or Azure using the ``object_store`` crate, with a range request for every read.
+//! It is read using an ``AsyncWav``, which can be opened straight from the URL of the object. This feature enables the ``futures`` feature.
+//!
+//! ```no_run
+//! use wavers::async_wav::AsyncWav;
+//! use wavers::cloud::ObjectStoreReader;
+//!
+//! async fn read() {
+//!     let mut wav: AsyncWav<f32, ObjectStoreReader> = AsyncWav::from_url("s3://bucket/path/to/wav.wav").await.unwrap();
+//!     let samples = wav.read().await.unwrap();
+//! }
+//! ```
+//!
+//! ### Io Uring
+//!
+//! The ``io_uring`` feature adds ``UringBatch``, which reads or writes a whole batch of wav files at once using io_uring on Linux, through the ``io-uring`` crate.
+//! This reduces the number of system calls made when ingesting datasets of many small files. The feature has no effect on other platforms.
+//!
+//! ```no_run
+//! use wavers::uring::{UringBatch, URING_QUEUE_DEPTH};
+//!
+//! fn main() {
+//!     let paths = ["path/to/first.wav", "path/to/second.wav"];
+//!     let mut batch = UringBatch::new(URING_QUEUE_DEPTH).unwrap();
+//!     for wav in batch.read_wavs::<f32, _>(&paths).unwrap() {
+//!         let samples = wav.unwrap().read().unwrap();
+//!     }
+//! }
+//! ```
+//!
+//! ### Resampling
+//!
+//! The ``resampling`` feature adds the ``resample`` module, which converts interleaved samples to another sample rate using the sinc resampler of the ``rubato`` crate.
+//! ``resample`` resamples a buffer of samples, while ``StreamResampler`` resamples a file a block at a time as it is read.
+//! Its filter is sharper than the built-in filter which ``transcode`` and ``Wav::with_target_sample_rate`` use without the feature, at the cost of more work and latency, see the ``resample`` module for the differences.
+//!
+//! ```no_run
+//! use wavers::{read, resample::resample};
+//!
+//! fn main() {
+//!     let (samples, sample_rate) = read::<f32, _>("path/to/48k_mono.wav").unwrap();
+//!     let (samples, sample_rate) = resample(&samples, 1, sample_rate, 16000).unwrap();
+//! }
+//! ```
+//!
+//! ### Loudness
+//!
+//! The ``loudness`` feature adds the ``loudness`` module, which measures the integrated loudness of audio in LUFS as defined by ITU-R BS.1770 and EBU R128.
+//! ``Samples::normalize_loudness`` and ``normalize_loudness`` scale samples and files to a target loudness, e.g. -23 LUFS for broadcast or -16 LUFS for podcasts.
+//!
+//! ```no_run
+//! use wavers::{loudness::normalize_loudness, Wav};
+//!
+//! fn main() {
+//!     let mut wav: Wav<f32> = Wav::from_path("path/to/episode.wav").unwrap();
+//!     println!("{:.1} LUFS", wav.integrated_loudness().unwrap());
+//!     normalize_loudness("path/to/episode.wav", "path/to/episode_normalized.wav", -16.0).unwrap();
+//! }
+//! ```
+//!
 //! ## Benchmarks
 //! To check out the benchmarks head on over to the benchmarks wiki page on the WaveRs <a href=https://github.com/jmg049/wavers/wiki/Benchmarks>GitHub</a>.
 //! Benchmarks were conducted on the reading and writing functionality of WaveRs and compared to the ``hound`` crate.
 //!
+#[cfg(feature = "aiff")]
+pub mod aiff;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub mod async_wav;
+pub mod chain;
+pub mod channel_layout;
 pub mod chunks;
+pub mod clipping;
+#[cfg(feature = "object_store")]
+pub mod cloud;
+pub mod codecs;
 pub mod conversion;
 pub mod core;
+pub mod dc;
 
 pub mod error;
+pub mod gain;
 pub mod header;
+#[cfg(feature = "http")]
+pub mod http;
 
 pub mod iter;
+pub mod labels;
+#[cfg(feature = "loudness")]
+pub mod loudness;
+pub mod mix;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "resampling")]
+pub mod resample;
+pub mod sampler;
+pub mod silence;
+#[cfg(feature = "sphere")]
+pub mod sphere;
+#[cfg(feature = "fft")]
+pub mod stft;
+pub mod streaming;
+pub mod transcode;
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub mod uring;
 pub mod wav_type;
+pub mod writer;
+use crate::chunks::{Chunk, DS64, FMT};
+use crate::conversion::Dither;
+use crate::core::{mask_padding_bits, PROGRESS_INTERVAL};
+use crate::header::header_identifier;
 use error::FormatError;
-use i24::i24;
+use std::any::TypeId;
+use std::borrow::Cow;
 use std::fs;
-use std::io::Write;
+use std::io::{IoSlice, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-pub use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+pub use crate::chain::WavChain;
+pub use crate::channel_layout::ChannelLayout;
+pub use crate::conversion::{AudioSample, ConvertSlice, ConvertTo, NoiseShaping};
+/// Re-export of the half-precision float sample type, available with the ``half`` feature.
+#[cfg(feature = "half")]
+pub use half::f16;
+/// Re-export of the 24-bit signed integer sample type, so ``Wav<i24>`` can be used without depending on the ``i24`` crate directly.
+pub use i24::i24;
 
-pub use crate::chunks::{FactChunk, FmtChunk, ListChunk, DATA, FACT, LIST, RIFF, WAVE};
-pub use crate::core::{wav_spec, ReadSeek, Samples, Wav};
+pub use crate::chunks::{
+    AcidChunk, AdtlChunk, AxmlChunk, BextChunk, CueChunk, CuePoint, FactChunk, FmtChunk, InfoTag,
+    InstChunk, IxmlChunk, LabeledText, ListChunk, LoopType, Marker, PlaylistSegment, PlstChunk,
+    RawChunk, SampleLoop, SmplChunk, ACID, ADTL, AXML, BEXT, CUE, DATA, FACT, ID3, INST, IXML,
+    LIST, PLST, RIFF, RIFX, SMPL, WAVE,
+};
+pub use crate::core::{wav_spec, CancelToken, ProgressFn, ReadSeek, Samples, Wav, WavHandle};
 pub use crate::error::{WaversError, WaversResult};
-pub use crate::header::WavHeader;
+pub use crate::gain::{apply_gain, normalize_peak, ClipPolicy};
+pub use crate::header::{ChunkIdentifier, WavHeader};
+pub use crate::labels::{
+    markers_from_audacity_labels, markers_to_audacity_labels, read_audacity_labels,
+    write_audacity_labels,
+};
+pub use crate::mix::Matrix;
+pub use crate::sampler::WindowSampler;
+pub use crate::streaming::StreamingWav;
+pub use crate::transcode::{transcode, TranscodeOptions};
 pub use crate::wav_type::{format_info_to_wav_type, wav_type_to_format_info, FormatCode, WavType};
+pub use crate::writer::{
+    Durability, Endianness, StreamLength, StreamWavWriter, WavBuilder, WavSpec, WavWriter,
+    WriteOptions, ITER_BLOCK_FRAMES,
+};
 
 /// A macro for logging messages if the logging feature is enabled.
 #[macro_export]
@@ -199,11 +479,13 @@ macro_rules! log {
 #[inline(always)]
 pub fn read<T: AudioSample, P: AsRef<Path>>(path: P) -> WaversResult<(Samples<T>, i32)>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
@@ -252,197 +534,2510 @@ pub fn write<T: AudioSample, P: AsRef<Path>>(
     n_channels: u16,
 ) -> WaversResult<()>
 where
+    u8: ConvertTo<T>,
     i16: ConvertTo<T>,
     i24: ConvertTo<T>,
     i32: ConvertTo<T>,
     f32: ConvertTo<T>,
     f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
     Box<[i16]>: ConvertSlice<T>,
     Box<[i24]>: ConvertSlice<T>,
     Box<[i32]>: ConvertSlice<T>,
     Box<[f32]>: ConvertSlice<T>,
     Box<[f64]>: ConvertSlice<T>,
 {
-    let s = Samples::from(samples);
-    let samples_bytes = s.as_bytes();
-
-    let new_header = WavHeader::new_header::<T>(sample_rate, n_channels, s.len())?;
-
-    let mut f = fs::File::create(&fp)?;
-
-    match new_header.fmt_chunk.format {
-        FormatCode::WAV_FORMAT_PCM => {
-            let header_bytes = new_header.as_base_bytes();
-            f.write_all(&header_bytes)?;
-        }
-        FormatCode::WAV_FORMAT_IEEE_FLOAT | FormatCode::WAVE_FORMAT_EXTENSIBLE => {
-            let header_bytes = new_header.as_extended_bytes();
-            f.write_all(&header_bytes)?;
-        }
-        _ => {
-            return Err(FormatError::InvalidTypeId("Invalid type ID").into());
-        }
-    }
-
-    f.write_all(&DATA)?;
-    let data_size_bytes = samples_bytes.len() as u32; // write up to the data size
-    f.write_all(&data_size_bytes.to_ne_bytes())?; // write the data size
-    f.write_all(&samples_bytes)?; // write the data
-    log!(
-        log::Level::Debug,
-        "Wrote wav file to {}",
-        fp.as_ref().display()
-    );
-    Ok(())
+    write_with_options(
+        fp,
+        samples,
+        sample_rate,
+        n_channels,
+        &WriteOptions::default(),
+    )
 }
 
-#[cfg(test)]
-mod lib_tests {
-    use approx_eq::assert_approx_eq;
-    use std::io::BufRead;
-    use std::{fs::File, path::Path, str::FromStr};
-
-    use super::{read, write, Samples, Wav};
+/// The metadata chunks written before the data chunk, and those written after it.
+pub(crate) type SplitChunks<'a> = (Vec<&'a dyn Chunk>, Vec<&'a dyn Chunk>);
 
-    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+/// Splits the metadata chunks into those written before and after the data chunk, sorted by their position in the given chunk order.
+/// Chunks which are not in the order are written just before the data chunk. Without an order, every chunk is written before the data chunk in the given order.
+fn order_chunks<'a>(
+    chunks: Vec<&'a dyn Chunk>,
+    chunk_order: &[ChunkIdentifier],
+) -> SplitChunks<'a> {
+    let data_position = match chunk_order.iter().position(|id| *id == DATA.into()) {
+        Some(data_position) => 2 * data_position + 2,
+        None => return (chunks, Vec::new()),
+    };
+    let mut chunks: Vec<(usize, &dyn Chunk)> = chunks
+        .into_iter()
+        .map(|chunk| {
+            let id = header_identifier(chunk);
+            let position = match chunk_order.iter().position(|x| *x == id) {
+                Some(position) => 2 * position + 2,
+                None => data_position - 1,
+            };
+            (position, chunk)
+        })
+        .collect();
+    chunks.sort_by_key(|(position, _)| *position);
+    let (leading, trailing): (Vec<_>, Vec<_>) = chunks
+        .into_iter()
+        .partition(|(position, _)| *position < data_position);
+    (
+        leading.into_iter().map(|(_, chunk)| chunk).collect(),
+        trailing.into_iter().map(|(_, chunk)| chunk).collect(),
+    )
+}
 
-    #[test]
-    fn test_write() {
-        let expected_path = "./test_resources/one_channel_f32.txt";
-        let out_path = "./test_resources/tmp/one_channel_f32_tmp.wav";
+/// Writes wav samples to disk stored with the given encoding rather than the native encoding of ``T``, converting them while writing.
+/// For example, an ``f32`` processing pipeline can write 16-bit or 24-bit PCM files without converting its buffer first.
+/// This is equivalent to ``write_with_options`` with ``WriteOptions::encoding`` set.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{write_as, WavType};
+///
+/// fn main() {
+///     let samples: Vec<f32> = vec![0.0; 16000];
+///     write_as("./pcm16.wav", &samples, WavType::Pcm16, 16000, 1).unwrap();
+/// }
+/// ```
+pub fn write_as<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    samples: &[T],
+    wav_type: WavType,
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let options = WriteOptions::new().encoding(wav_type);
+    write_with_options(fp, samples, sample_rate, n_channels, &options)
+}
 
-        let mut wav: Wav<f32> = Wav::from_path("./test_resources/one_channel_i16.wav")
-            .expect("Failed to open file wav file");
+/// Writes wav samples to disk using the provided ``WriteOptions``.
+///
+/// The options can be used to store the samples with a different encoding to the native encoding of ``T``.
+/// For example, ``WavType::ALaw`` or ``WavType::MuLaw`` compands the samples to 8-bit A-law or µ-law respectively,
+/// while any of the PCM or float types converts the samples before writing.
+/// ``WavType::Pcm24`` packs each sample into 3 little-endian bytes, so 24-bit files can be written from ``i32`` or ``f32`` buffers.
+/// Samples with fewer valid bits than their container, such as 20-bit audio in a 24-bit container, can be written using ``WriteOptions::valid_bits_per_sample``.
+/// Sample types which have no wav encoding of their own, such as ``f16``, are written as 32-bit float unless an encoding is given.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{write_with_options, WavType, WriteOptions};
+///
+/// fn main() {
+///     let samples: Vec<f32> = vec![0.0; 16000];
+///     let options = WriteOptions::new().encoding(WavType::ALaw);
+///     write_with_options("./alaw.wav", &samples, 16000, 1, &options).unwrap();
+/// }
+/// ```
+pub fn write_with_options<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    // The samples are encoded before the file is opened, so that invalid options do not truncate an existing file
+    let file = encode_file(samples, sample_rate, n_channels, options)?;
+    write_file(fp, options, |f| {
+        write_encoded(f, &file, options, &mut |done, total| {
+            options.report(done, total)
+        })
+    })
+}
 
-        let samples = wav.read().expect("Failed to read data");
+/// Writes wav samples to ``writer`` in the same way as ``write``, so that a wav file can be written to a socket, a compressed stream,
+/// an archive or an in-memory buffer rather than to a path. The writer does not need to seek, as the whole file is written in order.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::write_to_writer;
+///
+/// fn main() {
+///     let samples: Vec<f32> = vec![0.0; 16000];
+///     let mut stream = std::net::TcpStream::connect("127.0.0.1:8000").unwrap();
+///     write_to_writer(&mut stream, &samples, 16000, 1).unwrap();
+///
+///     let mut bytes: Vec<u8> = Vec::new();
+///     write_to_writer(&mut bytes, &samples, 16000, 1).unwrap();
+/// }
+/// ```
+pub fn write_to_writer<T: AudioSample, W: Write>(
+    writer: &mut W,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_to_writer_with_options(
+        writer,
+        samples,
+        sample_rate,
+        n_channels,
+        &WriteOptions::default(),
+    )
+}
 
-        write(out_path, &samples, wav.sample_rate(), wav.n_channels())
-            .expect("Failed to write data");
+/// Writes wav samples to ``writer`` using the provided ``WriteOptions``, in the same way as ``write_with_options``. See ``write_to_writer``.
+pub fn write_to_writer_with_options<T: AudioSample, W: Write>(
+    writer: &mut W,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_to(
+        writer,
+        samples,
+        sample_rate,
+        n_channels,
+        options,
+        &mut |done, total| options.report(done, total),
+    )
+}
 
-        let mut wav: Wav<f32> = Wav::from_path(out_path).expect("Failed to open file wav file");
-        let samples: Samples<f32> = wav.read().expect("Failed to read data");
+/// Writes wav samples to a complete wav file in memory, in the same way as ``write``, e.g. for the body of an HTTP response or for embedding without a temporary file.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::{write_to_vec, Wav};
+///
+/// let samples: Vec<i16> = vec![0; 16000];
+/// let bytes: Vec<u8> = write_to_vec(&samples, 16000, 1).unwrap();
+/// let mut wav: Wav<i16> = Wav::from_vec(bytes).unwrap();
+/// assert_eq!(&wav.read().unwrap()[..], &samples[..]);
+/// ```
+pub fn write_to_vec<T: AudioSample>(
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<Vec<u8>>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_to_vec_with_options(samples, sample_rate, n_channels, &WriteOptions::default())
+}
+
+/// Writes wav samples to a complete wav file in memory using the provided ``WriteOptions``, in the same way as ``write_with_options``. See ``write_to_vec``.
+pub fn write_to_vec_with_options<T: AudioSample>(
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<Vec<u8>>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let mut bytes: Vec<u8> = Vec::new();
+    write_to_writer_with_options(&mut bytes, samples, sample_rate, n_channels, options)?;
+    Ok(bytes)
+}
+
+/// Writes the interleaved samples produced by an iterator to disk, in the same way as ``write``, without holding them all in memory.
+/// The samples are taken and written ``ITER_BLOCK_FRAMES`` frames at a time, so arbitrarily long files, e.g. hours of generated audio, can be written using a fixed amount of memory.
+///
+/// The function will return an error if the iterator does not produce a whole number of frames, or if there is an issue writing the file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::write_from_iter;
+///
+/// fn main() {
+///     // An hour of a 440Hz tone
+///     let sample_rate = 48000;
+///     let tone = (0..sample_rate as u64 * 3600)
+///         .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / sample_rate as f64).sin() as f32);
+///     write_from_iter("./tone.wav", tone, sample_rate, 1).unwrap();
+/// }
+/// ```
+pub fn write_from_iter<T: AudioSample, P: AsRef<Path>, I: IntoIterator<Item = T>>(
+    fp: P,
+    samples: I,
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_from_iter_with_options(
+        fp,
+        samples,
+        sample_rate,
+        n_channels,
+        &WriteOptions::default(),
+    )
+}
+
+/// Writes the interleaved samples produced by an iterator to disk using the provided ``WriteOptions``. See ``write_from_iter``.
+/// The cancel token of the options is checked between blocks, while the progress callback is not used, as the number of samples is not known.
+pub fn write_from_iter_with_options<T: AudioSample, P: AsRef<Path>, I: IntoIterator<Item = T>>(
+    fp: P,
+    samples: I,
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    check_write_options::<T>(sample_rate, n_channels, options)?;
+    write_file(fp, options, |f| {
+        let mut writer: WavWriter<T, _> =
+            WavWriter::new(options.buf_writer(f), sample_rate, n_channels, options)?;
+        let block_len = ITER_BLOCK_FRAMES * n_channels.max(1) as usize;
+        let mut block: Vec<T> = Vec::with_capacity(block_len);
+        let mut samples = samples.into_iter();
+        loop {
+            block.clear();
+            block.extend(samples.by_ref().take(block_len));
+            if block.is_empty() {
+                break;
+            }
+            if let Some(token) = &options.cancel {
+                token.check()?;
+            }
+            writer.write_frames(&block)?;
+        }
+        writer.finalize()?;
+        Ok(())
+    })
+}
+
+/// Writes planar samples, with a separate buffer for each channel, to disk in the same way as ``write``.
+/// The channels are interleaved a block at a time while writing, so no interleaved copy of the samples is made.
+///
+/// The function will return an error if there are no channels or more than ``u16::MAX``, if the channels do not all have the same length, or if there is an issue writing the file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::write_planar;
+///
+/// fn main() {
+///     let left: Vec<f32> = vec![0.0; 16000];
+///     let right: Vec<f32> = vec![0.5; 16000];
+///     write_planar("./stereo.wav", &[&left, &right], 16000).unwrap();
+/// }
+/// ```
+pub fn write_planar<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    channels: &[&[T]],
+    sample_rate: i32,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_planar_with_options(fp, channels, sample_rate, &WriteOptions::default())
+}
+
+/// Writes planar samples to disk using the provided ``WriteOptions``. See ``write_planar``.
+pub fn write_planar_with_options<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    channels: &[&[T]],
+    sample_rate: i32,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let n_channels = u16::try_from(channels.len())
+        .ok()
+        .filter(|&n| n > 0)
+        .ok_or(FormatError::InvalidNumberOfChannels(channels.len()))?;
+    let n_frames = channels.first().map_or(0, |c| c.len());
+    if let Some((channel, c)) = channels
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.len() != n_frames)
+    {
+        return Err(FormatError::PlanarChannelLength {
+            channel,
+            len: c.len(),
+            expected: n_frames,
+        }
+        .into());
+    }
+    let samples = (0..n_frames).flat_map(|i| channels.iter().map(move |c| c[i]));
+    write_from_iter_with_options(fp, samples, sample_rate, n_channels, options)
+}
+
+/// Extracts one channel of the wav file at ``src`` to a new mono wav file at ``dst``, with the same sample rate and encoding.
+/// The source is read a block of frames at a time, so the other channels are never held in memory.
+/// Files encoded with MS ADPCM or GSM 6.10, which cannot be written, are decoded a few blocks at a time and extracted to 16-bit PCM.
+///
+/// The function will return an error if the channel is out of range, or if there is an issue reading or writing the files.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::extract_channel;
+///
+/// fn main() {
+///     // The left channel of a stereo recording
+///     extract_channel("./stereo.wav", "./left.wav", 0).unwrap();
+/// }
+/// ```
+pub fn extract_channel<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    channel: usize,
+) -> WaversResult<()> {
+    // The samples are read as their native type, so they are copied without being converted
+    let wav: Wav<i16> = Wav::from_path(src)?;
+    match wav.encoding() {
+        WavType::Pcm8 | WavType::EPcm8 => {
+            extract_channel_as::<u8>(wav.into_sample_type(), dst, channel)
+        }
+        WavType::Pcm24 | WavType::EPcm24 => {
+            extract_channel_as::<i24>(wav.into_sample_type(), dst, channel)
+        }
+        WavType::Pcm32 | WavType::EPcm32 => {
+            extract_channel_as::<i32>(wav.into_sample_type(), dst, channel)
+        }
+        WavType::Float32 | WavType::EFloat32 => {
+            extract_channel_as::<f32>(wav.into_sample_type(), dst, channel)
+        }
+        WavType::Float64 | WavType::EFloat64 => {
+            extract_channel_as::<f64>(wav.into_sample_type(), dst, channel)
+        }
+        WavType::Pcm16
+        | WavType::EPcm16
+        | WavType::ALaw
+        | WavType::MuLaw
+        | WavType::MsAdpcm
+        | WavType::Gsm610 => extract_channel_as(wav, dst, channel),
+    }
+}
+
+/// Writes one channel of ``wav`` to a new mono wav file at ``dst``. See ``extract_channel``.
+fn extract_channel_as<T: AudioSample>(
+    mut wav: Wav<T>,
+    dst: impl AsRef<Path>,
+    channel: usize,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let n_channels = wav.n_channels();
+    if channel >= n_channels as usize {
+        return Err(FormatError::InvalidChannel {
+            channel,
+            n_channels,
+        }
+        .into());
+    }
+    let n_channels = n_channels as usize;
+    let mut options = WriteOptions::new();
+    options = match wav.encoding() {
+        WavType::MsAdpcm | WavType::Gsm610 => options.encoding(WavType::Pcm16),
+        encoding => options.encoding(encoding),
+    };
+    if wav.padding_bits() > 0 {
+        options = options.valid_bits_per_sample(wav.valid_bits_per_sample());
+    }
+    let sample_rate = wav.sample_rate();
+
+    check_write_options::<T>(sample_rate, 1, &options)?;
+    let mut decoder = match wav.encoding().is_block_encoded() {
+        true => Some(wav.block_decoder()?),
+        false => None,
+    };
+    write_file(dst, &options, |f| {
+        let mut writer: WavWriter<T, _> =
+            WavWriter::new(options.buf_writer(f), sample_rate, 1, &options)?;
+        let mut frames = vec![T::zero(); ITER_BLOCK_FRAMES * n_channels];
+        let mut decoded: Vec<i16> = Vec::new();
+        let mut block: Vec<T> = Vec::with_capacity(ITER_BLOCK_FRAMES);
+        loop {
+            block.clear();
+            match decoder.as_mut() {
+                // Block encoded files are decoded a few blocks at a time
+                Some(decoder) => {
+                    wav.read_encoded_blocks(decoder, &mut decoded)?;
+                    block.extend(
+                        decoded
+                            .iter()
+                            .skip(channel)
+                            .step_by(n_channels)
+                            .map(|&sample| sample.convert_to()),
+                    );
+                }
+                None => {
+                    let n_frames = wav.read_block(&mut frames, ITER_BLOCK_FRAMES)?;
+                    block.extend(
+                        frames[..n_frames * n_channels]
+                            .iter()
+                            .skip(channel)
+                            .step_by(n_channels),
+                    );
+                }
+            }
+            if block.is_empty() {
+                break;
+            }
+            writer.write_frames(&block)?;
+        }
+        writer.finalize()?;
+        Ok(())
+    })
+}
+
+/// Creates the file at ``fp`` and writes a wav file to it using ``write``.
+/// A write which fails, including one which is cancelled, removes the partially written file, so options should be checked
+/// using ``check_write_options`` or ``encode_file`` before calling this, as an existing file at ``fp`` is truncated when it is opened.
+pub(crate) fn write_file<P: AsRef<Path>>(
+    fp: P,
+    options: &WriteOptions,
+    write: impl FnOnce(&mut fs::File) -> WaversResult<()>,
+) -> WaversResult<()> {
+    let fp = fp.as_ref();
+    // An atomic write goes to a temporary file next to the destination, which replaces it once the write has succeeded
+    let tmp_fp = options.atomic.then(|| atomic_temp_path(fp));
+    let out_fp = tmp_fp.as_deref().unwrap_or(fp);
+
+    // Opened for reading as well so that the file can be mapped into memory
+    let mut f = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(out_fp)?;
+    if let Err(e) = write(&mut f) {
+        // A failed write should not leave a truncated file behind
+        drop(f);
+        let _ = fs::remove_file(out_fp);
+        return Err(e);
+    }
+    if let Err(e) = options.durability.sync(&f, out_fp) {
+        drop(f);
+        let _ = fs::remove_file(out_fp);
+        return Err(e.into());
+    }
+    drop(f);
+    if let Some(tmp_fp) = tmp_fp {
+        if let Err(e) = fs::rename(&tmp_fp, fp) {
+            let _ = fs::remove_file(&tmp_fp);
+            return Err(e.into());
+        }
+        // The rename is only durable once the directory has been synced
+        if options.durability == Durability::FileAndDirectory {
+            Durability::sync_dir(fp)?;
+        }
+    }
+    log!(log::Level::Debug, "Wrote wav file to {}", fp.display());
+    Ok(())
+}
+
+/// Returns the path of the temporary file an atomic write to ``fp`` is written to, a hidden file in the same directory so that it can be renamed over ``fp``.
+/// Every call returns a new path, so that concurrent writes to the same destination never share a temporary file.
+fn atomic_temp_path(fp: &Path) -> std::path::PathBuf {
+    static N_ATOMIC_WRITES: AtomicU64 = AtomicU64::new(0);
+    let n = N_ATOMIC_WRITES.fetch_add(1, Ordering::Relaxed);
+    let file_name = fp.file_name().unwrap_or_default().to_string_lossy();
+    fp.with_file_name(format!(".{}.{}.{}.tmp", file_name, std::process::id(), n))
+}
+
+/// Writes a wav file to ``writer`` in the same way as ``write_with_options``, calling ``progress`` with the number of bytes of samples written so far and the total number to write, stopping with the error of ``progress`` if it returns one.
+pub(crate) fn write_to<T: AudioSample, W: Write>(
+    writer: &mut W,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+    progress: &mut dyn FnMut(u64, u64) -> WaversResult<()>,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let file = encode_file(samples, sample_rate, n_channels, options)?;
+    write_encoded(writer, &file, options, progress)
+}
+
+/// Writes an encoded wav file to ``writer``, calling ``progress`` in the same way as ``write_to``.
+pub(crate) fn write_encoded<W: Write>(
+    writer: &mut W,
+    file: &EncodedFile,
+    options: &WriteOptions,
+    progress: &mut dyn FnMut(u64, u64) -> WaversResult<()>,
+) -> WaversResult<()> {
+    let samples_bytes = &file.samples;
+    let head = file.head.iter().map(|bytes| IoSlice::new(bytes));
+    let tail = file.tail.iter().map(|bytes| IoSlice::new(bytes));
+
+    // Without progress to report, the whole file is gathered into a single vectored write, rather than a write for every piece
+    let n_bytes = samples_bytes.len() as u64;
+    if options.progress.is_none() && options.cancel.is_none() {
+        let mut bufs: Vec<IoSlice> = head
+            .chain(std::iter::once(IoSlice::new(samples_bytes)))
+            .chain(tail)
+            .collect();
+        write_all_vectored(writer, &mut bufs)?;
+        return progress(n_bytes, n_bytes);
+    }
+
+    write_all_vectored(writer, &mut head.collect::<Vec<_>>())?;
+    // The data is written a piece at a time so that progress can be reported
+    let mut n_written = 0;
+    for bytes in samples_bytes.chunks(PROGRESS_INTERVAL.max(1)) {
+        writer.write_all(bytes)?; // write the data
+        n_written += bytes.len() as u64;
+        progress(n_written, n_bytes)?;
+    }
+    if samples_bytes.is_empty() {
+        progress(0, 0)?;
+    }
+    write_all_vectored(writer, &mut tail.collect::<Vec<_>>())?;
+    Ok(())
+}
+
+/// The bytes of an encoded wav file: everything before the samples, the samples, and everything after them.
+pub(crate) struct EncodedFile<'a> {
+    pub(crate) head: Vec<Box<[u8]>>,
+    pub(crate) samples: Cow<'a, [u8]>,
+    pub(crate) tail: Vec<Box<[u8]>>,
+}
+
+#[cfg(feature = "mmap")]
+impl EncodedFile<'_> {
+    /// Returns the length of the whole file in bytes.
+    pub(crate) fn len(&self) -> usize {
+        let head: usize = self.head.iter().map(|bytes| bytes.len()).sum();
+        let tail: usize = self.tail.iter().map(|bytes| bytes.len()).sum();
+        head + self.samples.len() + tail
+    }
+}
+
+/// Encodes ``samples`` and the header and metadata chunks of a wav file as set by ``options``, in the same way as ``write_with_options``.
+pub(crate) fn encode_file<'a, T: AudioSample>(
+    samples: &'a [T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<EncodedFile<'a>>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let (encoding, padding_bits) = write_encoding::<T>(n_channels, options)?;
+    let mut dither = options.new_dither(n_channels);
+    let samples_bytes = encode_samples(samples, encoding, padding_bits, dither.as_mut())?;
+    let (new_header, (leading_chunks, trailing_chunks)) = write_header(
+        encoding,
+        sample_rate,
+        n_channels,
+        samples_bytes.len() / encoding.n_bytes(),
+        options,
+    )?;
+
+    let mut head = vec![new_header.as_bytes()];
+    head.extend(leading_chunks.iter().map(|chunk| chunk.as_bytes()));
+    head.push(Box::new(new_header.data_chunk_bytes())); // the data id and size
+    let mut tail: Vec<Box<[u8]>> = trailing_chunks
+        .iter()
+        .map(|chunk| chunk.as_bytes())
+        .collect();
+    if !tail.is_empty() && samples_bytes.len() % 2 == 1 {
+        tail.insert(0, Box::new([0])); // the padding byte of the data chunk
+    }
+    Ok(EncodedFile {
+        head,
+        samples: samples_bytes,
+        tail,
+    })
+}
+
+/// Writes every buffer of ``bufs`` to ``writer`` using as few calls to ``Write::write_vectored`` as possible, retrying until all of them have been written.
+pub(crate) fn write_all_vectored<W: Write>(
+    writer: &mut W,
+    mut bufs: &mut [IoSlice<'_>],
+) -> std::io::Result<()> {
+    // Skip any leading empty buffers, which would otherwise look like a writer which cannot accept more bytes
+    IoSlice::advance_slices(&mut bufs, 0);
+    while !bufs.is_empty() {
+        match writer.write_vectored(bufs) {
+            Ok(0) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
+            Ok(n) => IoSlice::advance_slices(&mut bufs, n),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a wav file of samples of type ``T`` can be written with ``options``, so that invalid options are rejected before the file is opened.
+pub(crate) fn check_write_options<T: AudioSample>(
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()> {
+    let (encoding, _) = write_encoding::<T>(n_channels, options)?;
+    write_header(encoding, sample_rate, n_channels, 0, options)?;
+    Ok(())
+}
+
+/// Checks that the sample rate and the number of channels of samples which are to be processed are both positive.
+pub(crate) fn check_sample_rate_and_channels(
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()> {
+    if sample_rate <= 0 {
+        return Err(FormatError::InvalidSampleRate(sample_rate).into());
+    }
+    if n_channels == 0 {
+        return Err(FormatError::InvalidNumberOfChannels(n_channels as usize).into());
+    }
+    Ok(())
+}
+
+/// Returns the encoding samples of type ``T`` are written with, and the number of padding bits in each sample, as set by ``options``.
+pub(crate) fn write_encoding<T: AudioSample>(
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<(WavType, u16)> {
+    // Sample types without a wav encoding of their own (e.g. f16) are stored as 32-bit float by default
+    let native_type: Option<WavType> = TypeId::of::<T>().try_into().ok();
+    let encoding = options.encoding.or(native_type).unwrap_or(WavType::Float32);
+
+    // Only the extensible format records the number of valid bits
+    let (encoding, padding_bits) = match options.valid_bits_per_sample {
+        None => (encoding, 0),
+        Some(valid_bits_per_sample) => {
+            let invalid = FormatError::InvalidValidBitsPerSample {
+                valid_bits_per_sample,
+                wav_type: encoding,
+            };
+            let extensible_encoding = match encoding {
+                WavType::Pcm16 | WavType::EPcm16 => WavType::EPcm16,
+                WavType::Pcm24 | WavType::EPcm24 => WavType::EPcm24,
+                WavType::Pcm32 | WavType::EPcm32 => WavType::EPcm32,
+                _ => return Err(invalid.into()),
+            };
+            if valid_bits_per_sample == 0 || valid_bits_per_sample > encoding.n_bits() {
+                return Err(invalid.into());
+            }
+            (
+                extensible_encoding,
+                encoding.n_bits() - valid_bits_per_sample,
+            )
+        }
+    };
+
+    // Likewise, only the extensible format records the speaker positions of the channels
+    let encoding = match options.channel_layout {
+        None => encoding,
+        Some(layout) => {
+            if layout.n_channels() > n_channels {
+                return Err(FormatError::InvalidChannelLayout { layout, n_channels }.into());
+            }
+            match encoding {
+                WavType::Pcm8 | WavType::EPcm8 => WavType::EPcm8,
+                WavType::Pcm16 | WavType::EPcm16 => WavType::EPcm16,
+                WavType::Pcm24 | WavType::EPcm24 => WavType::EPcm24,
+                WavType::Pcm32 | WavType::EPcm32 => WavType::EPcm32,
+                WavType::Float32 | WavType::EFloat32 => WavType::EFloat32,
+                WavType::Float64 | WavType::EFloat64 => WavType::EFloat64,
+                _ => return Err(FormatError::ChannelLayoutNotSupported(encoding).into()),
+            }
+        }
+    };
+
+    Ok((encoding, padding_bits))
+}
+
+/// Encodes ``samples`` with ``encoding``, zeroing the lowest ``padding_bits`` bits of each sample.
+pub(crate) fn encode_samples<'a, T: AudioSample>(
+    samples: &'a [T],
+    encoding: WavType,
+    padding_bits: u16,
+    dither: Option<&mut Dither>,
+) -> WaversResult<Cow<'a, [u8]>>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let native_type: Option<WavType> = TypeId::of::<T>().try_into().ok();
+    if let Some(dither) = dither.filter(|_| needs_dither(native_type, encoding, padding_bits)) {
+        let dithered = dither.apply(samples, encoding.n_bits(), padding_bits);
+        let bytes = encode_samples::<f64>(&dithered, encoding, padding_bits, None)?;
+        return Ok(Cow::Owned(bytes.into_owned()));
+    }
+    // Samples which are already in the encoding are written without being copied
+    let samples_bytes: Cow<[u8]> = match Some(encoding) == native_type {
+        true => Cow::Borrowed(bytemuck::cast_slice(samples)),
+        false => {
+            let s = || Samples::from(samples);
+            match encoding {
+                WavType::Pcm8 | WavType::EPcm8 => {
+                    Cow::Owned(s().convert::<u8>().as_bytes().to_vec())
+                }
+                WavType::Pcm16 | WavType::EPcm16 => {
+                    Cow::Owned(s().convert::<i16>().as_bytes().to_vec())
+                }
+                WavType::Pcm24 | WavType::EPcm24 => {
+                    // Buffers are rarely 24-bit in memory, so each sample is packed straight into 3 little-endian bytes
+                    let mut bytes = Vec::with_capacity(samples.len() * 3);
+                    for sample in samples {
+                        let sample: i24 = sample.convert_to();
+                        bytes.extend_from_slice(&sample.to_i32().to_le_bytes()[..3]);
+                    }
+                    Cow::Owned(bytes)
+                }
+                WavType::Pcm32 | WavType::EPcm32 => {
+                    Cow::Owned(s().convert::<i32>().as_bytes().to_vec())
+                }
+                WavType::Float32 | WavType::EFloat32 => {
+                    Cow::Owned(s().convert::<f32>().as_bytes().to_vec())
+                }
+                WavType::Float64 | WavType::EFloat64 => {
+                    Cow::Owned(s().convert::<f64>().as_bytes().to_vec())
+                }
+                WavType::ALaw => {
+                    let s: Samples<i16> = s().convert();
+                    Cow::Owned(codecs::alaw::encode(&s).into_vec())
+                }
+                WavType::MuLaw => {
+                    let s: Samples<i16> = s().convert();
+                    Cow::Owned(codecs::mulaw::encode(&s).into_vec())
+                }
+                _ => {
+                    let (main, _, sub) = wav_type_to_format_info(encoding);
+                    return Err(FormatError::UnsupportedWriteFormat { main, sub }.into());
+                }
+            }
+        }
+    };
+
+    Ok(match padding_bits {
+        0 => samples_bytes,
+        _ => {
+            let mut samples_bytes = samples_bytes.into_owned();
+            mask_padding_bits(&mut samples_bytes, encoding, padding_bits);
+            Cow::Owned(samples_bytes)
+        }
+    })
+}
+
+/// Returns whether samples stored as ``native_type``, or as floats if it is ``None``, lose resolution when written with ``encoding``,
+/// which is when dither is added to them.
+fn needs_dither(native_type: Option<WavType>, encoding: WavType, padding_bits: u16) -> bool {
+    let is_integer = |wav_type: WavType| {
+        matches!(
+            wav_type,
+            WavType::Pcm8
+                | WavType::EPcm8
+                | WavType::Pcm16
+                | WavType::EPcm16
+                | WavType::Pcm24
+                | WavType::EPcm24
+                | WavType::Pcm32
+                | WavType::EPcm32
+        )
+    };
+    if !is_integer(encoding) {
+        return false;
+    }
+    match native_type {
+        Some(native_type) if is_integer(native_type) => {
+            native_type.n_bits() > encoding.n_bits() - padding_bits
+        }
+        _ => true,
+    }
+}
+
+/// Returns the header of a file of ``n_samples`` samples written with ``encoding`` and ``options``,
+/// along with the metadata chunks written before and after the data chunk.
+pub(crate) fn write_header(
+    encoding: WavType,
+    sample_rate: i32,
+    n_channels: u16,
+    n_samples: usize,
+    options: &WriteOptions,
+) -> WaversResult<(WavHeader, SplitChunks<'_>)> {
+    let mut new_header =
+        WavHeader::new_header_with_type(encoding, sample_rate, n_channels, n_samples)?;
+    if let Some(valid_bits_per_sample) = options.valid_bits_per_sample {
+        new_header
+            .fmt_chunk
+            .ext_fmt_chunk
+            .set_valid_bits_per_sample(valid_bits_per_sample);
+    }
+    if let Some(layout) = options.channel_layout {
+        new_header
+            .fmt_chunk
+            .ext_fmt_chunk
+            .set_channel_mask(layout.bits());
+    }
+
+    // Metadata chunks are written between the fmt and data chunks, unless the order of a source file is being reproduced
+    let metadata_chunks = options.metadata_chunks();
+    for chunk in metadata_chunks.iter() {
+        if [RIFF, RIFX, WAVE, DS64, FMT, FACT, DATA].contains(chunk.id()) {
+            return Err(FormatError::ReservedChunkIdentifier(
+                String::from_utf8_lossy(chunk.id()).to_string(),
+            )
+            .into());
+        }
+    }
+    let (leading_chunks, trailing_chunks) = order_chunks(metadata_chunks, &options.chunk_order);
+    for chunk in leading_chunks.iter() {
+        new_header.add_chunk_info(header_identifier(*chunk), chunk.size() as u64);
+    }
+    for chunk in trailing_chunks.iter() {
+        new_header.add_trailing_chunk_info(header_identifier(*chunk), chunk.size() as u64);
+    }
+
+    Ok((new_header, (leading_chunks, trailing_chunks)))
+}
+
+#[cfg(test)]
+mod lib_tests {
+    use approx_eq::assert_approx_eq;
+    use std::io::BufRead;
+    use std::{fs::File, path::Path, str::FromStr};
+
+    use super::{
+        chunks::{Chunk, FMT},
+        conversion::ConvertTo,
+        error::FormatError,
+        extract_channel, read, write, write_as, write_from_iter, write_planar, write_to_vec,
+        write_to_vec_with_options, write_to_writer, write_to_writer_with_options,
+        write_with_options, AcidChunk, BextChunk, CancelToken, ChannelLayout, ChunkIdentifier,
+        CuePoint, InfoTag, InstChunk, Marker, NoiseShaping, PlaylistSegment, RawChunk, Samples,
+        SmplChunk, Wav, WavType, WavWriter, WaversError, WriteOptions, BEXT, DATA, IXML,
+    };
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    #[test]
+    fn test_write() {
+        let expected_path = "./test_resources/one_channel_f32.txt";
+        let out_path = "./test_resources/tmp/one_channel_f32_tmp.wav";
+
+        let mut wav: Wav<f32> = Wav::from_path("./test_resources/one_channel_i16.wav")
+            .expect("Failed to open file wav file");
+
+        let samples = wav.read().expect("Failed to read data");
+
+        write(out_path, &samples, wav.sample_rate(), wav.n_channels())
+            .expect("Failed to write data");
+
+        let mut wav: Wav<f32> = Wav::from_path(out_path).expect("Failed to open file wav file");
+        let samples: Samples<f32> = wav.read().expect("Failed to read data");
 
         let expected: Vec<f32> = read_text_to_vec(expected_path).expect("failed to load from txt");
 
-        for (exp, act) in expected.iter().zip(samples.as_ref()) {
-            assert_approx_eq!(*exp as f64, *act as f64, 1e-4);
+        for (exp, act) in expected.iter().zip(samples.as_ref()) {
+            assert_approx_eq!(*exp as f64, *act as f64, 1e-4);
+        }
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+    }
+
+    #[test]
+    fn test_read() {
+        let input_path = "./test_resources/one_channel_i16.wav";
+        let expected_path = "./test_resources/one_channel_i16.txt";
+        let expected_sr = 16000;
+
+        let mut wav: Wav<i16> = Wav::from_path(input_path).expect("Failed to open file wav file");
+        let samples: Samples<i16> = wav.read().expect("Failed to read data");
+        let actual_sr = wav.sample_rate();
+        let expected: Vec<i16> = read_text_to_vec(expected_path).expect("failed to load from txt");
+
+        assert_eq!(expected_sr, actual_sr, "Sample rates do not match");
+        for (exp, act) in expected.iter().zip(samples.as_ref()) {
+            assert_eq!(*exp, *act, "Samples do not match");
+        }
+    }
+
+    use std::stringify;
+    macro_rules! read_tests {
+        ($($T:ident), *) => {
+            $(
+                paste::item! {
+                    #[test]
+                    fn [<read_$T>]() {
+                        let t_string: &str = stringify!($T);
+
+                        let wav_str = format!("./test_resources/one_channel_{}.wav", t_string);
+                        let expected_str = format!("./test_resources/one_channel_{}.txt", t_string);
+
+                        let (sample_data, _): (Samples<$T>, i32) = match read::<$T, _>(&wav_str) {
+                            Ok((s, sr)) => (s, sr),
+                            Err(e) => {eprintln!("{}\n{}", wav_str, e); panic!("Failed to read wav file")}
+                        };
+
+                        let expected_data: Vec<$T> = match read_text_to_vec(Path::new(&expected_str)) {
+                            Ok(w) => w,
+                            Err(e) => {eprintln!("{}\n{}", wav_str, e); panic!("Failed to read txt file")}
+                        };
+
+                        for (expected, actual) in expected_data.iter().zip(sample_data.iter()) {
+                            assert_eq!(*expected, *actual, "{} != {}", expected, actual);
+                        }
+                    }
+                }
+            )*
+        }
+    }
+    read_tests!(u8, i16, i32, f32, f64);
+
+    // No tests for i24 as it it requires a different approach to testing.
+    // It is tested in crate::core::core_tests.
+    macro_rules! write_tests {
+        ($($T:ident), *) => {
+            $(
+                paste::item! {
+                    #[test]
+                    fn [<write_$T>]() {
+                        if !Path::new(TEST_OUTPUT).exists() {
+                            std::fs::create_dir(TEST_OUTPUT).unwrap();
+                        }
+                        let t_string: &str = stringify!($T);
+
+                        let wav_str = format!("./test_resources/one_channel_{}.wav", t_string);
+                        let expected_str = format!("./test_resources/one_channel_{}.txt", t_string);
+
+                        let mut wav: Wav<$T> =
+                            Wav::from_path(wav_str).expect("Failed to create wav file");
+                        let expected_samples: Samples<$T> = Samples::from(
+                            read_text_to_vec(&Path::new(&expected_str)).expect("Failed to read to vec"),
+                        );
+
+
+                        let out = format!("{}_one_channel_{}.wav", TEST_OUTPUT, t_string);
+                        let out_path = Path::new(&out);
+
+                        wav.write::<$T, _>(out_path)
+                            .expect("Failed to write file");
+
+                        let mut new_wav: Wav<$T> = Wav::<$T>::from_path(out_path).unwrap();
+
+                        for (expected, actual) in expected_samples
+                            .iter()
+                            .zip(new_wav.read().unwrap().iter())
+                        {
+                            assert_eq!(expected, actual, "{} != {}", expected, actual);
+                        }
+                        std::fs::remove_file(Path::new(&out_path)).unwrap();
+                    }
+                }
+            )*
+        };
+    }
+
+    // No tests for i24 as it it requires a different approach to testing.
+    // It is tested in the crate::core::core_tests.
+    write_tests!(u8, i16, i32, f32, f64);
+
+    use crate::ConvertSlice;
+    #[test]
+    fn write_sin_wav() {
+        let fp = "./wav.wav";
+        let sr: i32 = 16000;
+        let duration = 10;
+        let mut samples: Vec<f32> = (0..sr * duration).map(|x| (x as f32 / sr as f32)).collect();
+        for sample in samples.iter_mut() {
+            *sample *= 440.0 * 2.0 * std::f32::consts::PI;
+            *sample = sample.sin();
+            *sample *= i16::MAX as f32;
+        }
+        let samples: Samples<f32> = Samples::from(samples.into_boxed_slice().convert_slice());
+
+        write(fp, &samples, sr, 1).unwrap();
+        std::fs::remove_file(fp).unwrap();
+    }
+
+    #[test]
+    fn progress_is_reported_for_reads_writes_and_transcodes() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static CALLS: AtomicU64 = AtomicU64::new(0);
+        static DONE: AtomicU64 = AtomicU64::new(0);
+        static TOTAL: AtomicU64 = AtomicU64::new(0);
+        fn record(done: u64, total: u64) {
+            assert!(done <= total, "More bytes done than in total");
+            assert!(
+                done >= DONE.load(Ordering::SeqCst),
+                "Progress went backwards"
+            );
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            DONE.store(done, Ordering::SeqCst);
+            TOTAL.store(total, Ordering::SeqCst);
+        }
+        fn take() -> (u64, u64, u64) {
+            (
+                CALLS.swap(0, Ordering::SeqCst),
+                DONE.swap(0, Ordering::SeqCst),
+                TOTAL.swap(0, Ordering::SeqCst),
+            )
+        }
+
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let f32_path = format!("{}progress_f32.wav", TEST_OUTPUT);
+        let i16_path = format!("{}progress_i16.wav", TEST_OUTPUT);
+
+        // Just over one progress interval of samples
+        let samples: Vec<f32> = (0..300_000).map(|x| (x as f32 / 1000.0).sin()).collect();
+        let options = WriteOptions::new().progress(record);
+        write_with_options(&f32_path, &samples, 16000, 1, &options).unwrap();
+        assert_eq!(take(), (2, 1_200_000, 1_200_000));
+
+        let mut wav: Wav<f32> = Wav::from_path(&f32_path).unwrap();
+        let read_samples = wav.read_with_progress(record).unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        assert_eq!(take(), (2, 1_200_000, 1_200_000));
+
+        // Transcoding counts the bytes read and the bytes written
+        let options = options.encoding(WavType::Pcm16);
+        wav.write_with_options(&i16_path, &options).unwrap();
+        assert_eq!(take(), (3, 1_800_000, 1_800_000));
+        assert_eq!(
+            Wav::<i16>::from_path(&i16_path).unwrap().n_samples(),
+            300_000
+        );
+
+        std::fs::remove_file(&f32_path).unwrap();
+        std::fs::remove_file(&i16_path).unwrap();
+    }
+
+    #[test]
+    fn atomic_writes_replace_the_destination_on_success() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}atomic_one_channel_i16.wav", TEST_OUTPUT);
+        let samples: Vec<i16> = (0..16000).map(|i| (i % 100) as i16).collect();
+        write(&out_path, &samples, 16000, 1).unwrap();
+        let before = std::fs::read(&out_path).unwrap();
+
+        // A failed write leaves the destination as it was
+        let options = WriteOptions::new().atomic(true).add_chunk(DATA, &[0]);
+        assert!(write_with_options(&out_path, &samples[..100], 16000, 1, &options).is_err());
+        assert_eq!(std::fs::read(&out_path).unwrap(), before);
+
+        // A file can be transcoded in place
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).unwrap();
+        let options = WriteOptions::new().atomic(true).encoding(WavType::ALaw);
+        wav.write_with_options(&out_path, &options).unwrap();
+        let wav: Wav<i16> = Wav::from_path(&out_path).unwrap();
+        assert_eq!(wav.encoding(), WavType::ALaw);
+        assert_eq!(wav.n_samples(), samples.len());
+
+        let temp_files = std::fs::read_dir(TEST_OUTPUT)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy()
+                    .starts_with(".atomic_one_channel_i16.wav")
+            })
+            .count();
+        assert_eq!(temp_files, 0);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+    }
+
+    #[test]
+    fn concurrent_atomic_writes_do_not_share_a_temporary_file() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}concurrent_atomic_one_channel_i16.wav", TEST_OUTPUT);
+        let writers: Vec<_> = (0..8i16)
+            .map(|writer| {
+                let out_path = out_path.clone();
+                std::thread::spawn(move || {
+                    let samples = vec![writer; 16000];
+                    let options = WriteOptions::new().atomic(true);
+                    write_with_options(&out_path, &samples, 16000, 1, &options)
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        // The destination holds the whole file of one of the writers
+        let samples = read::<i16, _>(&out_path).unwrap().0;
+        assert_eq!(samples.len(), 16000);
+        assert!(samples.iter().all(|&s| s == samples[0]));
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+    }
+
+    #[test]
+    fn cancelled_reads_writes_and_transcodes_stop() {
+        use std::sync::OnceLock;
+
+        // Cancels the write part way through, from within its progress callback
+        static TOKEN: OnceLock<CancelToken> = OnceLock::new();
+        fn cancel(_: u64, _: u64) {
+            TOKEN.get().unwrap().cancel();
+        }
+
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}cancelled.wav", TEST_OUTPUT);
+        let token = TOKEN.get_or_init(CancelToken::new);
+
+        // Just over one progress interval of samples
+        let samples: Vec<f32> = (0..300_000).map(|x| (x as f32 / 1000.0).sin()).collect();
+        let options = WriteOptions::new()
+            .progress(cancel)
+            .cancel_token(token.clone());
+        let result = write_with_options(&out_path, &samples, 16000, 1, &options);
+        assert!(matches!(result, Err(WaversError::Cancelled)));
+        assert!(token.is_cancelled());
+        assert!(!Path::new(&out_path).exists());
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/two_channel_i16.wav").unwrap();
+        assert!(matches!(
+            wav.read_cancellable(token),
+            Err(WaversError::Cancelled)
+        ));
+        let options = WriteOptions::new().cancel_token(token.clone());
+        assert!(matches!(
+            wav.write_with_options(&out_path, &options),
+            Err(WaversError::Cancelled)
+        ));
+        assert!(!Path::new(&out_path).exists());
+
+        // A token which is never cancelled changes nothing
+        let token = CancelToken::new();
+        wav.seek_frame(0).unwrap();
+        let expected = wav.read().unwrap();
+        wav.seek_frame(0).unwrap();
+        assert_eq!(wav.read_cancellable(&token).unwrap(), expected);
+    }
+
+    #[test]
+    fn writers_match_files() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}writer.wav", TEST_OUTPUT);
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/two_channel_i16.wav").unwrap();
+        let samples = wav.read().unwrap();
+
+        write(&out_path, &samples, 16000, 2).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        write_to_writer(&mut bytes, &samples, 16000, 2).unwrap();
+        assert_eq!(bytes, std::fs::read(&out_path).unwrap());
+
+        let options = WriteOptions::new().encoding(WavType::MuLaw).title("Writer");
+        write_with_options(&out_path, &samples, 16000, 2, &options).unwrap();
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        write_to_writer_with_options(&mut cursor, &samples, 16000, 2, &options).unwrap();
+        assert_eq!(cursor.into_inner(), std::fs::read(&out_path).unwrap());
+
+        // Writing replaces the header of the Wav struct, so each write uses a newly opened file
+        let open = || Wav::<i16>::from_path("./test_resources/two_channel_i16.wav").unwrap();
+        open().write::<f32, _>(&out_path).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        open().write_to_writer::<f32, _>(&mut bytes).unwrap();
+        assert_eq!(bytes, std::fs::read(&out_path).unwrap());
+
+        open().write_with_options(&out_path, &options).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        open()
+            .write_to_writer_with_options(&mut bytes, &options)
+            .unwrap();
+        assert_eq!(bytes, std::fs::read(&out_path).unwrap());
+        let written: Wav<i16> = Wav::from_vec(bytes).unwrap();
+        assert_eq!(written.encoding(), WavType::MuLaw);
+        assert_eq!(written.n_samples(), samples.len());
+
+        std::fs::remove_file(&out_path).unwrap();
+    }
+
+    #[test]
+    fn wavs_are_written_to_vecs() {
+        let path = "./test_resources/two_channel_i16.wav";
+        let mut wav: Wav<i16> = Wav::from_path(path).unwrap();
+        let samples = wav.read().unwrap();
+
+        let bytes = write_to_vec(&samples, 16000, 2).unwrap();
+        let mut expected: Vec<u8> = Vec::new();
+        write_to_writer(&mut expected, &samples, 16000, 2).unwrap();
+        assert_eq!(bytes, expected);
+
+        let options = WriteOptions::new().encoding(WavType::ALaw);
+        let bytes = write_to_vec_with_options(&samples, 16000, 2, &options).unwrap();
+        assert_eq!(
+            Wav::<i16>::from_vec(bytes).unwrap().encoding(),
+            WavType::ALaw
+        );
+
+        // The encoding of the file is kept, and the Wav struct can still be read afterwards
+        let mut wav: Wav<f32> = Wav::from_path(path).unwrap();
+        let bytes = wav.to_bytes().unwrap();
+        let mut copy: Wav<f32> = Wav::from_vec(bytes).unwrap();
+        assert_eq!(copy.encoding(), WavType::Pcm16);
+        assert_eq!(copy.read().unwrap(), wav.read().unwrap());
+    }
+
+    #[test]
+    fn iterators_are_written_in_blocks() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}iter_two_channel_i16.wav", TEST_OUTPUT);
+        let n_frames = 2 * super::ITER_BLOCK_FRAMES + 17;
+        let samples: Vec<i16> = (0..2 * n_frames)
+            .map(|i| (i % 2000) as i16 - 1000)
+            .collect();
+
+        write_from_iter(&out_path, samples.iter().copied(), 16000, 2).unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).unwrap();
+        assert_eq!(wav.n_samples(), samples.len());
+        assert_eq!(&*wav.read().unwrap(), &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        // A trailing partial frame is an error, which removes the partially written file
+        let out_path = format!("{}iter_partial_frame.wav", TEST_OUTPUT);
+        assert!(write_from_iter(&out_path, samples[1..].iter().copied(), 16000, 2).is_err());
+        assert!(!Path::new(&out_path).exists());
+    }
+
+    #[test]
+    fn planar_channels_are_interleaved() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}planar_two_channel_i16.wav", TEST_OUTPUT);
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/two_channel_i16.wav").unwrap();
+        let samples = wav.read().unwrap();
+        let left: Vec<i16> = samples.iter().step_by(2).copied().collect();
+        let right: Vec<i16> = samples.iter().skip(1).step_by(2).copied().collect();
+
+        write_planar(&out_path, &[&left, &right], 16000).unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).unwrap();
+        assert_eq!(wav.n_channels(), 2);
+        assert_eq!(wav.read().unwrap(), samples);
+
+        assert!(matches!(
+            write_planar(&out_path, &[&left, &right[1..]], 16000),
+            Err(WaversError::Format(FormatError::PlanarChannelLength {
+                channel: 1,
+                ..
+            }))
+        ));
+        let no_channels: [&[i16]; 0] = [];
+        assert!(matches!(
+            write_planar(&out_path, &no_channels, 16000),
+            Err(WaversError::Format(FormatError::InvalidNumberOfChannels(0)))
+        ));
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+    }
+
+    #[test]
+    fn float_samples_are_written_as_pcm() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let mut wav: Wav<f32> = Wav::from_path("./test_resources/one_channel_f32.wav").unwrap();
+        let samples = wav.read().unwrap();
+
+        for wav_type in [WavType::Pcm16, WavType::Pcm24] {
+            let out_path = format!("{}write_as_{:?}.wav", TEST_OUTPUT, wav_type);
+            write_as(&out_path, &samples, wav_type, 16000, 1).unwrap();
+            let mut wav: Wav<i32> = Wav::from_path(&out_path).unwrap();
+            assert_eq!(wav.encoding(), wav_type);
+            let expected: Vec<i32> = samples
+                .iter()
+                .map(|s| match wav_type {
+                    WavType::Pcm16 => ConvertTo::<i16>::convert_to(s).convert_to(),
+                    _ => ConvertTo::<super::i24>::convert_to(s).convert_to(),
+                })
+                .collect();
+            assert_eq!(&*wav.read().unwrap(), &expected[..]);
+            std::fs::remove_file(Path::new(&out_path)).unwrap();
+        }
+    }
+
+    #[test]
+    fn dither_decorrelates_quantization_from_quiet_signals() {
+        // A sine of a third of the step between two 16-bit values, which rounds to silence without dither
+        let lsb = 1.0 / i16::MAX as f64;
+        let sine: Vec<f64> = (0..48000)
+            .map(|i| lsb / 3.0 * (i as f64 * 2.0 * std::f64::consts::PI / 48.0).sin())
+            .collect();
+        let pcm16 = WriteOptions::new().encoding(WavType::Pcm16);
+        let read_i16 = |bytes: Vec<u8>| Wav::<i16>::from_vec(bytes).unwrap().read().unwrap();
+
+        let undithered = read_i16(write_to_vec_with_options(&sine, 48000, 1, &pcm16).unwrap());
+        assert!(undithered.iter().all(|&s| s == 0));
+
+        let dithered =
+            read_i16(write_to_vec_with_options(&sine, 48000, 1, &pcm16.clone().dither(7)).unwrap());
+        // The dither is at most a step either way, and the sine survives in the average of the dithered samples
+        assert!(dithered.iter().all(|s| s.abs() <= 1));
+        let correlation: f64 = dithered
+            .iter()
+            .zip(sine.iter())
+            .map(|(&d, &s)| d as f64 * s / lsb)
+            .sum::<f64>()
+            / sine.len() as f64;
+        let expected = (1.0 / 3.0) * (1.0 / 3.0) / 2.0;
+        assert!((correlation - expected).abs() < 0.01, "{}", correlation);
+
+        // The same seed gives the same file, including when it is written a block at a time
+        let options = pcm16.clone().dither(7);
+        let mut writer: WavWriter<f64, _> =
+            WavWriter::new(std::io::Cursor::new(Vec::new()), 48000, 1, &options).unwrap();
+        for block in sine.chunks(1000) {
+            writer.write_frames(block).unwrap();
+        }
+        let blocks = read_i16(writer.finalize().unwrap().into_inner());
+        assert_eq!(blocks, dithered);
+        let reseeded =
+            read_i16(write_to_vec_with_options(&sine, 48000, 1, &pcm16.clone().dither(8)).unwrap());
+        assert_ne!(reseeded, dithered);
+
+        // Samples which lose no resolution are written unchanged
+        let samples: Vec<i16> = vec![-32768, -1, 0, 1, 32767];
+        for encoding in [WavType::Pcm16, WavType::Pcm24, WavType::Float32] {
+            let options = WriteOptions::new().encoding(encoding).dither(7);
+            let bytes = write_to_vec_with_options(&samples, 48000, 1, &options).unwrap();
+            let expected = write_to_vec_with_options(
+                &samples,
+                48000,
+                1,
+                &WriteOptions::new().encoding(encoding),
+            )
+            .unwrap();
+            assert_eq!(bytes, expected);
+        }
+    }
+
+    #[test]
+    fn noise_shaping_moves_noise_away_from_low_frequencies() {
+        let lsb = 1.0 / i16::MAX as f64;
+        // A quiet stereo sine with a fraction of a step between its values, as from a 24-bit master
+        let sine: Vec<f64> = (0..44100 * 2)
+            .map(|i| 100.3 * lsb * ((i / 2) as f64 * 2.0 * std::f64::consts::PI / 441.0).sin())
+            .collect();
+        // The power of the quantization error below roughly 700 Hz, from the sums of the errors of each channel over blocks of 64 frames
+        let low_frequency_noise = |options: &WriteOptions| {
+            let bytes = write_to_vec_with_options(&sine, 44100, 2, options).unwrap();
+            let written = Wav::<i16>::from_vec(bytes).unwrap().read().unwrap();
+            let errors: Vec<f64> = written
+                .iter()
+                .zip(sine.iter())
+                .map(|(&w, &s)| w as f64 - s / lsb)
+                .collect();
+            errors
+                .chunks(128)
+                .map(|block| {
+                    let left: f64 = block.iter().step_by(2).sum();
+                    let right: f64 = block.iter().skip(1).step_by(2).sum();
+                    left * left + right * right
+                })
+                .sum::<f64>()
+        };
+
+        let pcm16 = WriteOptions::new().encoding(WavType::Pcm16).dither(3);
+        let flat = low_frequency_noise(&pcm16);
+        for noise_shaping in [NoiseShaping::SecondOrder, NoiseShaping::Lipshitz] {
+            let shaped = low_frequency_noise(&pcm16.clone().noise_shaping(noise_shaping));
+            assert!(
+                shaped < flat / 4.0,
+                "{:?} {} {}",
+                noise_shaping,
+                shaped,
+                flat
+            );
+        }
+
+        // Clipped samples do not destabilise the shaping filter
+        let loud: Vec<f32> = (0..4800).map(|i| 1.5 * (i as f32 / 10.0).sin()).collect();
+        let options = WriteOptions::new()
+            .encoding(WavType::Pcm16)
+            .noise_shaping(NoiseShaping::Lipshitz);
+        let bytes = write_to_vec_with_options(&loud, 48000, 1, &options).unwrap();
+        let written = Wav::<f32>::from_vec(bytes).unwrap().read().unwrap();
+        for (w, s) in written.iter().zip(loud.iter()) {
+            assert!((w - s.clamp(-1.0, 1.0)).abs() < 20.0 / i16::MAX as f32);
+        }
+    }
+
+    #[test]
+    fn pcm24_is_packed_from_i32_and_f32() {
+        let samples: Vec<i32> = vec![0x1234_5678, -0x100, i32::MAX];
+        let bytes = write_to_vec_with_options(
+            &samples,
+            48000,
+            1,
+            &WriteOptions::new().encoding(WavType::Pcm24),
+        )
+        .unwrap();
+        assert_eq!(
+            &bytes[bytes.len() - 9..],
+            &[0x56, 0x34, 0x12, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]
+        );
+        let mut wav: Wav<i32> = Wav::from_vec(bytes).unwrap();
+        assert_eq!(wav.encoding(), WavType::Pcm24);
+        assert_eq!(wav.n_samples(), 3);
+        assert_eq!(&*wav.read().unwrap(), &[0x1234_5600, -0x100, 0x7fff_ff00]);
+
+        let samples: Vec<f32> = vec![0.5, -1.0, 0.0];
+        let bytes = write_to_vec_with_options(
+            &samples,
+            48000,
+            1,
+            &WriteOptions::new().encoding(WavType::Pcm24),
+        )
+        .unwrap();
+        assert_eq!(
+            &bytes[bytes.len() - 9..],
+            &[0x00, 0x00, 0x40, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn channels_are_extracted_to_mono_files() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        for path in [
+            "./test_resources/two_channel_i16.wav",
+            "./test_resources/two_channel_ms_adpcm.wav",
+            "./test_resources/one_channel_gsm.wav",
+        ] {
+            let mut wav: Wav<i16> = Wav::from_path(path).unwrap();
+            let samples = wav.read().unwrap();
+            let n_channels = wav.n_channels() as usize;
+            for channel in 0..n_channels {
+                let out_path = format!("{}extract_channel_{}.wav", TEST_OUTPUT, channel);
+                extract_channel(path, &out_path, channel).unwrap();
+                let mut mono: Wav<i16> = Wav::from_path(&out_path).unwrap();
+                assert_eq!(mono.n_channels(), 1);
+                assert_eq!(mono.sample_rate(), wav.sample_rate());
+                assert_eq!(mono.encoding(), WavType::Pcm16);
+                let expected: Vec<i16> = samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(n_channels)
+                    .copied()
+                    .collect();
+                assert_eq!(&*mono.read().unwrap(), &expected[..]);
+                std::fs::remove_file(Path::new(&out_path)).unwrap();
+            }
+        }
+        let out_path = format!("{}extract_channel_2.wav", TEST_OUTPUT);
+        assert!(extract_channel("./test_resources/two_channel_i16.wav", &out_path, 2).is_err());
+        assert!(!Path::new(&out_path).exists());
+    }
+
+    /// A writer which counts its calls and accepts at most ``limit`` bytes per call.
+    struct CountingWriter {
+        bytes: Vec<u8>,
+        calls: usize,
+        limit: usize,
+    }
+
+    impl std::io::Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.write_vectored(&[std::io::IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+            self.calls += 1;
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(self.limit - n);
+                self.bytes.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn files_are_written_in_one_vectored_write() {
+        let samples: Vec<i16> = (0..16001).map(|i| (i % 100) as i16).collect();
+        let mut options = WriteOptions::new()
+            .title("Vectored")
+            .add_chunk(*b"tail", &[1, 2, 3]);
+        options.chunk_order = vec![DATA.into(), (*b"tail").into()];
+        let expected = write_to_vec_with_options(&samples, 16000, 1, &options).unwrap();
+
+        let mut writer = CountingWriter {
+            bytes: Vec::new(),
+            calls: 0,
+            limit: usize::MAX,
+        };
+        write_to_writer_with_options(&mut writer, &samples, 16000, 1, &options).unwrap();
+        assert_eq!(writer.calls, 1);
+        assert_eq!(writer.bytes, expected);
+
+        // Writers which accept fewer bytes are called until everything is written
+        let mut writer = CountingWriter {
+            bytes: Vec::new(),
+            calls: 0,
+            limit: 1000,
+        };
+        write_to_writer_with_options(&mut writer, &samples, 16000, 1, &options).unwrap();
+        assert_eq!(writer.calls, expected.len().div_ceil(1000));
+        assert_eq!(writer.bytes, expected);
+
+        // Writing a Wav struct gathers its header and samples in the same way
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        let mut expected = Vec::new();
+        wav.write_to_writer::<i16, _>(&mut expected).unwrap();
+        let mut writer = CountingWriter {
+            bytes: Vec::new(),
+            calls: 0,
+            limit: usize::MAX,
+        };
+        wav.write_to_writer::<i16, _>(&mut writer).unwrap();
+        assert_eq!(writer.calls, 1);
+        assert_eq!(writer.bytes, expected);
+    }
+
+    #[test]
+    fn write_and_read_alaw() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_alaw.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let options = WriteOptions::new().encoding(WavType::ALaw);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.encoding(), WavType::ALaw, "Encoding is not A-law");
+        assert_eq!(
+            wav.n_samples(),
+            samples.len(),
+            "Number of samples do not match"
+        );
+        assert_eq!(wav.sample_rate(), sr, "Sample rates do not match");
+        assert_eq!(
+            wav.get_fact_chunk().unwrap().map(|fact| fact.num_samples),
+            Some(samples.len() as u32),
+            "The fact chunk does not record the number of samples"
+        );
+        assert_eq!(wav.duration(), 10);
+
+        let alaw_samples: Samples<i16> = wav.read().expect("Failed to read data");
+        for (exp, act) in samples.iter().zip(alaw_samples.iter()) {
+            // A-law keeps 4 bits of mantissa, so the error grows with the magnitude of the sample
+            let tolerance = (exp.unsigned_abs() as i32 / 16).max(16);
+            assert!(
+                (*exp as i32 - *act as i32).abs() <= tolerance,
+                "{} != {}",
+                exp,
+                act
+            );
+        }
+
+        let mut wav: Wav<f32> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        let f32_samples: Samples<f32> = wav.read().expect("Failed to read data");
+        for (i16_sample, f32_sample) in alaw_samples.iter().zip(f32_samples.iter()) {
+            assert_approx_eq!(
+                (*i16_sample as f32 / i16::MAX as f32) as f64,
+                *f32_sample as f64,
+                1e-4
+            );
         }
         std::fs::remove_file(Path::new(&out_path)).unwrap();
     }
 
     #[test]
-    fn test_read() {
-        let input_path = "./test_resources/one_channel_i16.wav";
-        let expected_path = "./test_resources/one_channel_i16.txt";
-        let expected_sr = 16000;
+    fn write_and_read_mulaw() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_mulaw.wav", TEST_OUTPUT);
 
-        let mut wav: Wav<i16> = Wav::from_path(input_path).expect("Failed to open file wav file");
-        let samples: Samples<i16> = wav.read().expect("Failed to read data");
-        let actual_sr = wav.sample_rate();
-        let expected: Vec<i16> = read_text_to_vec(expected_path).expect("failed to load from txt");
+        let (samples, sr): (Samples<f32>, i32) =
+            read::<f32, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let options = WriteOptions::new().encoding(WavType::MuLaw);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
 
-        assert_eq!(expected_sr, actual_sr, "Sample rates do not match");
-        for (exp, act) in expected.iter().zip(samples.as_ref()) {
-            assert_eq!(*exp, *act, "Samples do not match");
+        let mut wav: Wav<f32> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.encoding(), WavType::MuLaw, "Encoding is not µ-law");
+        assert_eq!(
+            wav.n_samples(),
+            samples.len(),
+            "Number of samples do not match"
+        );
+
+        let mulaw_samples: Samples<f32> = wav.read().expect("Failed to read data");
+        for (exp, act) in samples.iter().zip(mulaw_samples.iter()) {
+            // µ-law keeps 4 bits of mantissa, so the error grows with the magnitude of the sample
+            let tolerance = (exp.abs() / 16.0).max(8.0 / i16::MAX as f32);
+            assert!((exp - act).abs() <= tolerance, "{} != {}", exp, act);
         }
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
     }
 
-    use std::stringify;
-    macro_rules! read_tests {
-        ($($T:ident), *) => {
-            $(
-                paste::item! {
-                    #[test]
-                    fn [<read_$T>]() {
-                        let t_string: &str = stringify!($T);
+    #[test]
+    fn write_with_valid_bits() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_20_bit.wav", TEST_OUTPUT);
 
-                        let wav_str = format!("./test_resources/one_channel_{}.wav", t_string);
-                        let expected_str = format!("./test_resources/one_channel_{}.txt", t_string);
+        let (samples, sr): (Samples<i32>, i32) =
+            read::<i32, _>("./test_resources/one_channel_i16.wav").unwrap();
+        // Set some of the low bits, which are padding in the 20-bit output
+        let samples: Vec<i32> = samples.iter().map(|s| s | 0x7FF).collect();
+        let options = WriteOptions::new()
+            .encoding(WavType::Pcm24)
+            .valid_bits_per_sample(20);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
 
-                        let (sample_data, _): (Samples<$T>, i32) = match read::<$T, _>(&wav_str) {
-                            Ok((s, sr)) => (s, sr),
-                            Err(e) => {eprintln!("{}\n{}", wav_str, e); panic!("Failed to read wav file")}
-                        };
+        let mut wav: Wav<i32> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(
+            wav.encoding(),
+            WavType::EPcm24,
+            "Encoding is not extensible"
+        );
+        assert_eq!(wav.valid_bits_per_sample(), 20);
+        let written: Samples<i32> = wav.read().expect("Failed to read data");
+        for (exp, act) in samples.iter().zip(written.iter()) {
+            // 20 valid bits of a 32-bit sample leaves the low 12 bits empty
+            assert_eq!(*act, exp & !0xFFF, "{} != {}", exp, act);
+        }
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
 
-                        let expected_data: Vec<$T> = match read_text_to_vec(Path::new(&expected_str)) {
-                            Ok(w) => w,
-                            Err(e) => {eprintln!("{}\n{}", wav_str, e); panic!("Failed to read txt file")}
-                        };
+        // The valid bits must fit in an integer container
+        let options = WriteOptions::new()
+            .encoding(WavType::Pcm16)
+            .valid_bits_per_sample(20);
+        assert!(write_with_options(&out_path, &samples, sr, 1, &options).is_err());
+        let options = WriteOptions::new()
+            .encoding(WavType::Float32)
+            .valid_bits_per_sample(20);
+        assert!(write_with_options(&out_path, &samples, sr, 1, &options).is_err());
+        assert!(!Path::new(&out_path).exists());
+    }
 
-                        for (expected, actual) in expected_data.iter().zip(sample_data.iter()) {
-                            assert_eq!(*expected, *actual, "{} != {}", expected, actual);
-                        }
-                    }
-                }
-            )*
+    #[test]
+    fn write_with_channel_layout() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
         }
+        let out_path = format!("{}three_channel_layout.wav", TEST_OUTPUT);
+        let samples: Vec<i16> = (0..300).collect();
+        let layout =
+            ChannelLayout::FRONT_LEFT | ChannelLayout::FRONT_RIGHT | ChannelLayout::LOW_FREQUENCY;
+
+        let options = WriteOptions::new().channel_layout(layout);
+        write_with_options(&out_path, &samples, 16000, 3, &options).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file");
+        assert_eq!(wav.encoding(), WavType::EPcm16);
+        assert_eq!(wav.channel_layout(), Some(layout));
+        let written: Samples<i16> = wav.read().expect("Failed to read data");
+        assert_eq!(&written[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        // Files which are not extensible have no layout
+        let wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.channel_layout(), None);
+
+        // The layout must fit the channels and the encoding must support the extensible format
+        let options = WriteOptions::new().channel_layout(ChannelLayout::SURROUND_5_1);
+        assert!(write_with_options(&out_path, &samples, 16000, 3, &options).is_err());
+        let options = WriteOptions::new()
+            .encoding(WavType::ALaw)
+            .channel_layout(layout);
+        assert!(write_with_options(&out_path, &samples, 16000, 3, &options).is_err());
+        assert!(!Path::new(&out_path).exists());
     }
-    read_tests!(i16, i32, f32, f64);
 
-    // No tests for i24 as it it requires a different approach to testing.
-    // It is tested in crate::core::core_tests.
-    macro_rules! write_tests {
-        ($($T:ident), *) => {
-            $(
-                paste::item! {
-                    #[test]
-                    fn [<write_$T>]() {
-                        if !Path::new(TEST_OUTPUT).exists() {
-                            std::fs::create_dir(TEST_OUTPUT).unwrap();
-                        }
-                        let t_string: &str = stringify!($T);
+    #[test]
+    fn rejected_writes_leave_existing_files_untouched() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}rejected_write.wav", TEST_OUTPUT);
+        let samples: Vec<i16> = (0..300).collect();
+        write(&out_path, &samples, 16000, 3).unwrap();
+        let bytes = std::fs::read(&out_path).unwrap();
 
-                        let wav_str = format!("./test_resources/one_channel_{}.wav", t_string);
-                        let expected_str = format!("./test_resources/one_channel_{}.txt", t_string);
+        // Options which are rejected are checked before the destination is opened
+        let options = WriteOptions::new().channel_layout(ChannelLayout::SURROUND_5_1);
+        assert!(write_with_options(&out_path, &samples, 16000, 3, &options).is_err());
+        let iter = samples.iter().copied();
+        assert!(crate::write_from_iter_with_options(&out_path, iter, 16000, 3, &options).is_err());
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/two_channel_i16.wav").unwrap();
+        assert!(wav.write_with_options(&out_path, &options).is_err());
+        assert_eq!(std::fs::read(&out_path).unwrap(), bytes);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+    }
 
-                        let mut wav: Wav<$T> =
-                            Wav::from_path(wav_str).expect("Failed to create wav file");
-                        let expected_samples: Samples<$T> = Samples::from(
-                            read_text_to_vec(&Path::new(&expected_str)).expect("Failed to read to vec"),
-                        );
+    #[test]
+    fn write_with_metadata_chunks() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_metadata.wav", TEST_OUTPUT);
 
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let mut bext = BextChunk::new("Test recording", "wavers", 16000 * 3600);
+        bext.origination_date = "2024-01-31".to_string();
+        bext.origination_time = "12:30:00".to_string();
+        bext.coding_history = "A=PCM,F=16000,W=16,M=mono".to_string(); // odd length, so the chunk is padded
+        let ixml = "<BWFXML><SCENE>1</SCENE><TAKE>2</TAKE></BWFXML>";
+        let axml = "<ebuCoreMain><coreMetadata/></ebuCoreMain>";
+        let options = WriteOptions::new()
+            .bext(bext.clone())
+            .ixml(ixml)
+            .axml(axml)
+            .artist("Jack Geraghty")
+            .title("Sine wavé") // non-ASCII
+            .comment("Odd");
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
 
-                        let out = format!("{}_one_channel_{}.wav", TEST_OUTPUT, t_string);
-                        let out_path = Path::new(&out);
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(
+            wav.header().file_size() as u64,
+            std::fs::metadata(&out_path).unwrap().len()
+        );
+        let first: Samples<i16> = wav.read_samples(10).unwrap();
+        assert_eq!(wav.bext().unwrap(), Some(bext));
+        let read_ixml = wav.ixml().unwrap().unwrap();
+        assert_eq!(read_ixml.xml, ixml);
+        assert_eq!(read_ixml.take(), Some("2"));
+        assert_eq!(wav.axml().unwrap().unwrap().xml, axml);
+        let metadata = wav.metadata().unwrap();
+        assert_eq!(metadata.len(), 3);
+        assert_eq!(metadata[&InfoTag::Artist], "Jack Geraghty");
+        assert_eq!(metadata[&InfoTag::Name], "Sine wavé");
+        assert_eq!(metadata[&InfoTag::Comment], "Odd");
+        let rest: Samples<i16> = wav.read_samples(samples.len() - 10).unwrap();
+        assert_eq!(&first[..], &samples[..10]);
+        assert_eq!(&rest[..], &samples[10..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
 
-                        wav.write::<$T, _>(out_path)
-                            .expect("Failed to write file");
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.bext().unwrap(), None);
+        assert_eq!(wav.ixml().unwrap(), None);
+        assert_eq!(wav.axml().unwrap(), None);
+    }
 
-                        let mut new_wav: Wav<$T> = Wav::<$T>::from_path(out_path).unwrap();
+    #[test]
+    fn write_and_read_time_reference() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_time_reference.wav", TEST_OUTPUT);
 
-                        for (expected, actual) in expected_samples
-                            .iter()
-                            .zip(new_wav.read().unwrap().iter())
-                        {
-                            assert_eq!(expected, actual, "{} != {}", expected, actual);
-                        }
-                        std::fs::remove_file(Path::new(&out_path)).unwrap();
-                    }
-                }
-            )*
-        };
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let bext = BextChunk::new("Recorder A", "wavers", 0);
+        // 13:45:30.25
+        let time_reference = (13 * 3600 + 45 * 60 + 30) * sr as u64 + sr as u64 / 4;
+        let options = WriteOptions::new()
+            .bext(bext)
+            .time_reference(time_reference);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.time_reference().unwrap(), Some(time_reference));
+        assert_eq!(
+            wav.time_of_day().unwrap(),
+            Some(std::time::Duration::from_millis(49_530_250))
+        );
+        assert_eq!(wav.bext().unwrap().unwrap().description, "Recorder A");
+
+        // A time reference set on the Wav struct is written in a copy of its bext chunk
+        let retimed_path = format!("{}one_channel_retimed.wav", TEST_OUTPUT);
+        wav.set_time_reference(sr as u64);
+        assert_eq!(wav.time_reference().unwrap(), Some(sr as u64));
+        assert_eq!(
+            wav.time_of_day().unwrap(),
+            Some(std::time::Duration::from_secs(1))
+        );
+        wav.write_with_options(&retimed_path, &WriteOptions::new())
+            .expect("Failed to write data");
+        let mut retimed: Wav<i16> = Wav::from_path(&retimed_path).unwrap();
+        assert_eq!(retimed.time_reference().unwrap(), Some(sr as u64));
+        assert_eq!(retimed.bext().unwrap().unwrap().description, "Recorder A");
+        assert_eq!(&retimed.read().unwrap()[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+        std::fs::remove_file(Path::new(&retimed_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.time_reference().unwrap(), None);
+        assert_eq!(wav.time_of_day().unwrap(), None);
+
+        // A bext chunk is added to files which have none
+        let synced_path = format!("{}one_channel_synced.wav", TEST_OUTPUT);
+        wav.set_time_reference(time_reference);
+        wav.write_with_options(&synced_path, &WriteOptions::new())
+            .expect("Failed to write data");
+        let mut synced: Wav<i16> = Wav::from_path(&synced_path).unwrap();
+        assert_eq!(synced.time_reference().unwrap(), Some(time_reference));
+        std::fs::remove_file(Path::new(&synced_path)).unwrap();
     }
 
-    // No tests for i24 as it it requires a different approach to testing.
-    // It is tested in the crate::core::core_tests.
-    write_tests!(i16, i32, f32, f64);
+    #[test]
+    fn write_and_read_cue_points() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_cue.wav", TEST_OUTPUT);
+        let modified_out_path = format!("{}one_channel_cue_modified.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let options =
+            WriteOptions::new().cue_points(vec![CuePoint::new(1, 0), CuePoint::new(2, 8000)]);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        // Read, add a marker and write again
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        let mut cue_points = wav.cue_points().unwrap();
+        assert_eq!(
+            cue_points,
+            vec![CuePoint::new(1, 0), CuePoint::new(2, 8000)]
+        );
+        cue_points.push(CuePoint::new(3, 16000));
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        let options = WriteOptions::new().cue_points(cue_points.clone());
+        write_with_options(&modified_out_path, &read_samples, sr, 1, &options)
+            .expect("Failed to write data");
+
+        let mut wav: Wav<i16> =
+            Wav::from_path(&modified_out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.cue_points().unwrap(), cue_points);
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+        std::fs::remove_file(Path::new(&modified_out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert!(wav.cue_points().unwrap().is_empty());
+    }
 
-    use crate::ConvertSlice;
     #[test]
-    fn write_sin_wav() {
-        let fp = "./wav.wav";
-        let sr: i32 = 16000;
-        let duration = 10;
-        let mut samples: Vec<f32> = (0..sr * duration).map(|x| (x as f32 / sr as f32)).collect();
-        for sample in samples.iter_mut() {
-            *sample *= 440.0 * 2.0 * std::f32::consts::PI;
-            *sample = sample.sin();
-            *sample *= i16::MAX as f32;
+    fn write_and_read_markers() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
         }
-        let samples: Samples<f32> = Samples::from(samples.into_boxed_slice().convert_slice());
+        let out_path = format!("{}one_channel_markers.wav", TEST_OUTPUT);
+        let rewritten_path = format!("{}one_channel_markers_rewritten.wav", TEST_OUTPUT);
 
-        write(fp, &samples, sr, 1).unwrap();
-        std::fs::remove_file(fp).unwrap();
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let markers = vec![
+            Marker::with_label(1, 0, "Intro"),
+            Marker {
+                note: Some("Breath noise".to_string()),
+                length: Some(4000),
+                ..Marker::with_label(2, 8000, "Verse")
+            },
+            Marker::new(3, 16000),
+        ];
+        let options = WriteOptions::new().title("Markers").markers(&markers);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.markers().unwrap(), markers);
+        assert_eq!(wav.cue_points().unwrap()[1], CuePoint::new(2, 8000));
+        let metadata = wav.metadata().unwrap();
+        assert_eq!(metadata.get(&InfoTag::Name), Some(&"Markers".to_string()));
+
+        // The markers split the file into segments, the second of which is a region
+        let segments: Vec<(Option<String>, Samples<i16>)> = wav.segments().unwrap().collect();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].0.as_deref(), Some("Intro"));
+        assert_eq!(&segments[0].1[..], &samples[..8000]);
+        assert_eq!(&segments[1].1[..], &samples[8000..12000]);
+        assert_eq!(segments[2].0, None);
+        assert_eq!(&segments[2].1[..], &samples[16000..]);
+
+        // Block encoded files cannot be read a segment at a time
+        let mut adpcm: Wav<i16> =
+            Wav::from_path("./test_resources/one_channel_ms_adpcm.wav").unwrap();
+        assert!(adpcm.segments().is_err());
+
+        // Markers set when rewriting replace the adtl list of the source file
+        let mut renamed = markers.clone();
+        renamed[0].label = Some("Count in".to_string());
+        let options = WriteOptions::new().preserve_chunks(true).markers(&renamed);
+        wav.write_with_options(&rewritten_path, &options)
+            .expect("Failed to write data");
+        let mut wav: Wav<i16> = Wav::from_path(&rewritten_path).unwrap();
+        assert_eq!(wav.markers().unwrap(), renamed);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+        std::fs::remove_file(Path::new(&rewritten_path)).unwrap();
+    }
+
+    #[test]
+    fn rewrite_preserving_chunks() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let source_path = format!("{}one_channel_preserve_source.wav", TEST_OUTPUT);
+        let preserved_path = format!("{}one_channel_preserved.wav", TEST_OUTPUT);
+        let dropped_path = format!("{}one_channel_dropped.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let bext = BextChunk::new("Source recording", "wavers", 0);
+        let options = WriteOptions::new()
+            .bext(bext.clone())
+            .cue_points(vec![CuePoint::new(1, 100)])
+            .artist("Jack Geraghty");
+        write_with_options(&source_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        // Add a vendor specific chunk, with an odd size, directly after the fmt chunk
+        let mut bytes = std::fs::read(&source_path).unwrap();
+        let vendor_chunk = RawChunk::new(*b"umid", vec![1, 2, 3]);
+        bytes.splice(36..36, vendor_chunk.as_bytes().iter().copied());
+        let riff_size = (bytes.len() - 8) as u32;
+        bytes[4..8].copy_from_slice(&riff_size.to_ne_bytes());
+        std::fs::write(&source_path, &bytes).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path(&source_path).unwrap();
+        let options = WriteOptions::new()
+            .encoding(WavType::Pcm24)
+            .preserve_chunks(true)
+            .comment("Transcoded");
+        wav.write_with_options(&preserved_path, &options)
+            .expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&preserved_path).unwrap();
+        assert_eq!(wav.encoding(), WavType::Pcm24);
+        assert_eq!(wav.bext().unwrap(), Some(bext));
+        assert_eq!(wav.cue_points().unwrap(), vec![CuePoint::new(1, 100)]);
+        assert_eq!(
+            wav.read_chunk_bytes((*b"umid").into()).unwrap().as_deref(),
+            Some(&[1u8, 2, 3][..])
+        );
+        // The INFO list set in the options replaces that of the source file
+        let metadata = wav.metadata().unwrap();
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[&InfoTag::Comment], "Transcoded");
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+
+        // Without preserving, only the samples are written
+        let mut wav: Wav<i16> = Wav::from_path(&source_path).unwrap();
+        wav.write_with_options(&dropped_path, &WriteOptions::new())
+            .expect("Failed to write data");
+        let wav: Wav<i16> = Wav::from_path(&dropped_path).unwrap();
+        assert_eq!(wav.encoding(), WavType::Pcm16);
+        let ids: Vec<ChunkIdentifier> = wav.chunks().map(|(id, _, _)| id).collect();
+        assert_eq!(ids, vec![FMT.into(), DATA.into()]);
+
+        for path in [source_path, preserved_path, dropped_path] {
+            std::fs::remove_file(Path::new(&path)).unwrap();
+        }
+    }
+
+    #[test]
+    fn rewrite_preserving_chunk_order() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let source_path = format!("{}one_channel_order_source.wav", TEST_OUTPUT);
+        let ordered_path = format!("{}one_channel_order_preserved.wav", TEST_OUTPUT);
+        let unordered_path = format!("{}one_channel_order_dropped.wav", TEST_OUTPUT);
+
+        // An odd number of 8-bit samples, so the data chunk is followed by a padding byte
+        let samples: Vec<u8> = (0..101).collect();
+        let options = WriteOptions::new()
+            .add_chunk(*b"umid", &[1, 2, 3])
+            .bext(BextChunk::new("Source", "wavers", 0));
+        write_with_options(&source_path, &samples, 8000, 1, &options).unwrap();
+
+        // Append a chunk after the data chunk, as some software does with LIST chunks
+        let mut bytes = std::fs::read(&source_path).unwrap();
+        bytes.push(0);
+        bytes.extend_from_slice(&RawChunk::new(*b"tail", vec![4, 5, 6]).as_bytes());
+        let riff_size = bytes.len() as u32 - 8;
+        bytes[4..8].copy_from_slice(&riff_size.to_ne_bytes());
+        std::fs::write(&source_path, &bytes).unwrap();
+
+        let mut wav: Wav<u8> = Wav::from_path(&source_path).unwrap();
+        let source_ids: Vec<ChunkIdentifier> = wav.chunks().map(|(id, _, _)| id).collect();
+        assert_eq!(
+            source_ids,
+            vec![
+                FMT.into(),
+                BEXT.into(),
+                (*b"umid").into(),
+                DATA.into(),
+                (*b"tail").into()
+            ]
+        );
+
+        // The replacement bext chunk keeps its place, and the new ixml chunk is written just before the data chunk
+        let options = WriteOptions::new()
+            .preserve_chunk_order(true)
+            .ixml("<BWFXML/>")
+            .bext(BextChunk::new("Rewritten", "wavers", 0));
+        wav.write_with_options(&ordered_path, &options).unwrap();
+        let mut wav: Wav<u8> = Wav::from_path(&ordered_path).unwrap();
+        let ids: Vec<ChunkIdentifier> = wav.chunks().map(|(id, _, _)| id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                FMT.into(),
+                BEXT.into(),
+                (*b"umid").into(),
+                IXML.into(),
+                DATA.into(),
+                (*b"tail").into()
+            ]
+        );
+        assert_eq!(wav.bext().unwrap().unwrap().description, "Rewritten");
+        assert_eq!(
+            wav.read_chunk_bytes((*b"tail").into()).unwrap().as_deref(),
+            Some(&[4u8, 5, 6][..])
+        );
+        let read_samples: Samples<u8> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        assert_eq!(
+            std::fs::metadata(&ordered_path).unwrap().len(),
+            wav.header().file_size() as u64
+        );
+
+        // Preserving the chunks alone writes them all before the data chunk
+        let mut wav: Wav<u8> = Wav::from_path(&source_path).unwrap();
+        wav.write_with_options(&unordered_path, &WriteOptions::new().preserve_chunks(true))
+            .unwrap();
+        let wav: Wav<u8> = Wav::from_path(&unordered_path).unwrap();
+        let ids: Vec<ChunkIdentifier> = wav.chunks().map(|(id, _, _)| id).collect();
+        assert_eq!(ids.last(), Some(&DATA.into()));
+
+        for path in [source_path, ordered_path, unordered_path] {
+            std::fs::remove_file(Path::new(&path)).unwrap();
+        }
+    }
+
+    #[test]
+    fn write_custom_chunks() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_custom_chunks.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let options = WriteOptions::new()
+            .add_chunk(*b"anly", b"odd") // padded to an even size
+            .add_chunk(*b"fprt", &[0xAB; 16])
+            .title("Custom");
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(
+            wav.header().file_size() as u64,
+            std::fs::metadata(&out_path).unwrap().len()
+        );
+        assert_eq!(
+            wav.read_chunk_bytes((*b"anly").into()).unwrap().as_deref(),
+            Some(&b"odd"[..])
+        );
+        assert_eq!(
+            wav.read_chunk_bytes((*b"fprt").into()).unwrap().as_deref(),
+            Some(&[0xAB; 16][..])
+        );
+        assert_eq!(wav.metadata().unwrap()[&InfoTag::Name], "Custom");
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let options = WriteOptions::new().add_chunk(DATA, &[0; 4]);
+        assert!(write_with_options(&out_path, &samples, sr, 1, &options).is_err());
+        assert!(!Path::new(&out_path).exists());
+    }
+
+    /// Builds an ID3v2.4 tag containing a UTF-8 title frame.
+    fn id3_tag_bytes(title: &str) -> Vec<u8> {
+        let frame_size = 1 + title.len() as u8; // small enough that the sync-safe size is a single byte
+        let mut frame = b"TIT2".to_vec();
+        frame.extend_from_slice(&[0, 0, 0, frame_size, 0, 0, 3]);
+        frame.extend_from_slice(title.as_bytes());
+        let mut tag = b"ID3".to_vec();
+        tag.extend_from_slice(&[4, 0, 0, 0, 0, 0, frame.len() as u8]);
+        tag.extend_from_slice(&frame);
+        tag
+    }
+
+    #[test]
+    fn write_and_read_id3() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_id3.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let tag = id3_tag_bytes("Episode 1");
+        let options = WriteOptions::new()
+            .id3(&id3_tag_bytes("Replaced"))
+            .id3(&tag);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.id3_bytes().unwrap().as_deref(), Some(&tag[..]));
+        #[cfg(feature = "id3")]
+        {
+            use id3::TagLike;
+            let parsed_tag = wav.id3_tag().unwrap().unwrap();
+            assert_eq!(parsed_tag.title(), Some("Episode 1"));
+        }
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.id3_bytes().unwrap(), None);
+    }
+
+    #[test]
+    fn write_and_read_smpl() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_smpl.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let mut smpl = SmplChunk::new(sr, 69);
+        smpl.add_loop(1000, samples.len() as u32 - 1);
+        let options = WriteOptions::new()
+            .cue_points(vec![CuePoint::new(0, 1000)])
+            .smpl(smpl.clone());
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        let read_smpl = wav.smpl().unwrap().unwrap();
+        assert_eq!(read_smpl, smpl);
+        assert_eq!(read_smpl.midi_unity_note, 69);
+        assert_eq!(
+            read_smpl.loops[0].cue_point_id,
+            wav.cue_points().unwrap()[0].id
+        );
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.smpl().unwrap(), None);
+    }
+
+    #[test]
+    fn write_and_read_playlist() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_plst.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let playlist = vec![
+            PlaylistSegment::new(2, 8000, 1),
+            PlaylistSegment::new(1, 8000, 2),
+        ];
+        let options = WriteOptions::new()
+            .markers(&[
+                Marker::with_label(1, 0, "A"),
+                Marker::with_label(2, 8000, "B"),
+            ])
+            .playlist(playlist.clone());
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        let read_playlist = wav.playlist().unwrap();
+        assert_eq!(read_playlist, playlist);
+        let markers = wav.markers().unwrap();
+        let first = markers
+            .iter()
+            .find(|marker| marker.id == read_playlist[0].cue_point_id)
+            .unwrap();
+        assert_eq!(first.label.as_deref(), Some("B"));
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert!(wav.playlist().unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_and_read_inst() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_inst.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let mut inst = InstChunk::new(69);
+        inst.fine_tune = -5;
+        inst.low_note = 60;
+        inst.high_note = 72;
+        let options = WriteOptions::new().inst(inst).smpl(SmplChunk::new(sr, 69));
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        assert_eq!(wav.inst().unwrap(), Some(inst));
+        assert!(wav.smpl().unwrap().is_some());
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.inst().unwrap(), None);
+    }
+
+    #[test]
+    fn write_and_read_acid() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}one_channel_acid.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<i16>, i32) =
+            read::<i16, _>("./test_resources/one_channel_i16.wav").unwrap();
+        let acid = AcidChunk::new(96.0, 16, 62);
+        let options = WriteOptions::new().acid(acid);
+        write_with_options(&out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let mut wav: Wav<i16> = Wav::from_path(&out_path).expect("Failed to open file wav file");
+        let read_acid = wav.acid().unwrap().unwrap();
+        assert_eq!(read_acid, acid);
+        assert_eq!(read_acid.tempo, 96.0);
+        assert_eq!(read_acid.root_note(), Some(62));
+        let read_samples: Samples<i16> = wav.read().unwrap();
+        assert_eq!(&read_samples[..], &samples[..]);
+        std::fs::remove_file(Path::new(&out_path)).unwrap();
+
+        let mut wav: Wav<i16> = Wav::from_path("./test_resources/one_channel_i16.wav").unwrap();
+        assert_eq!(wav.acid().unwrap(), None);
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn read_and_write_f16() {
+        use super::f16;
+
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let float_out_path = format!("{}one_channel_f16_as_f32.wav", TEST_OUTPUT);
+        let pcm_out_path = format!("{}one_channel_f16_as_i16.wav", TEST_OUTPUT);
+
+        let (samples, sr): (Samples<f16>, i32) =
+            read::<f16, _>("./test_resources/one_channel_i16.wav").unwrap();
+
+        // No encoding given, so the samples are stored as 32-bit float
+        write(&float_out_path, &samples, sr, 1).expect("Failed to write data");
+        let wav: Wav<f16> = Wav::from_path(&float_out_path).unwrap();
+        assert!(
+            matches!(wav.encoding(), WavType::Float32 | WavType::EFloat32),
+            "Encoding is not f32"
+        );
+
+        let options = WriteOptions::new().encoding(WavType::Pcm16);
+        write_with_options(&pcm_out_path, &samples, sr, 1, &options).expect("Failed to write data");
+
+        let expected: Vec<f32> = read_text_to_vec("./test_resources/one_channel_f32.txt")
+            .expect("failed to load from txt");
+        for out_path in [&float_out_path, &pcm_out_path] {
+            let (actual, _): (Samples<f32>, i32) = read::<f32, _>(out_path).unwrap();
+            assert_eq!(
+                actual.len(),
+                expected.len(),
+                "Number of samples do not match"
+            );
+            for (exp, act) in expected.iter().zip(actual.iter()) {
+                assert_approx_eq!(*exp as f64, *act as f64, 1e-3);
+            }
+            std::fs::remove_file(Path::new(out_path)).unwrap();
+        }
     }
 
     fn read_lines<P>(filename: P) -> std::io::Result<std::io::Lines<std::io::BufReader<File>>>
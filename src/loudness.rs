@@ -0,0 +1,394 @@
+//! Module for measuring and normalizing the integrated loudness of audio as defined by ITU-R BS.1770 and EBU R128, available with the ``loudness`` feature.
+//!
+//! Each channel is passed through the K-weighting filter, a high shelf which models the acoustic effect of the head followed by a high pass which removes the lowest frequencies.
+//! The mean square of the weighted channels is measured over blocks of 400 ms which start every 100 ms, and the channels are summed with the weight of their speaker position.
+//! Blocks below the absolute gate of -70 LUFS, and then blocks more than 10 LU below the loudness of the remaining blocks, are left out of the integrated loudness,
+//! so that silence and quiet passages do not pull the measurement down.
+use std::f64::consts::PI;
+use std::path::Path;
+
+use i24::i24;
+
+use crate::channel_layout::ChannelLayout;
+use crate::check_sample_rate_and_channels;
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::WaversResult;
+use crate::gain::{db_to_gain, scale_samples};
+
+/// Blocks quieter than this are left out of the integrated loudness.
+pub const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Blocks which are this much quieter than the absolutely gated blocks are left out of the integrated loudness.
+pub const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// The number of 100 ms steps in each 400 ms gating block.
+const STEPS_PER_BLOCK: usize = 4;
+
+/// The weight of the surround channels when the channels are summed.
+const SURROUND_WEIGHT: f64 = 1.41;
+
+/// A second order IIR filter in transposed direct form II.
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    /// The high shelf of the K-weighting filter at the given sample rate.
+    fn high_shelf(sample_rate: f64) -> Self {
+        let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        Biquad {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        }
+    }
+
+    /// The high pass of the K-weighting filter at the given sample rate.
+    fn high_pass(sample_rate: f64) -> Self {
+        let (f0, q) = (38.13547087602444, 0.5003270373238773);
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        }
+    }
+
+    /// Filters one sample, updating the state of the filter.
+    fn process(&self, x: f64, state: &mut [f64; 2]) -> f64 {
+        let y = self.b[0] * x + state[0];
+        state[0] = self.b[1] * x - self.a[0] * y + state[1];
+        state[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// Converts the weighted mean square of a block to its loudness in LUFS.
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Measures the integrated loudness of interleaved samples which are passed to it a block at a time, e.g. as a file is read.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::loudness::LoudnessMeter;
+///
+/// // A 997 Hz sine wave with a peak of -20 dBFS in one channel measures -23 LUFS
+/// let sample_rate = 48000;
+/// let tone: Vec<f32> = (0..sample_rate * 5)
+///     .map(|i| 0.1 * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / sample_rate as f32).sin())
+///     .collect();
+/// let mut meter = LoudnessMeter::new(sample_rate, 1).unwrap();
+/// meter.process(&tone);
+/// assert!((meter.integrated_loudness() + 23.0).abs() < 0.1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    n_channels: usize,
+    weights: Vec<f64>,
+    filters: [Biquad; 2],
+    /// The state of both filters of each channel.
+    states: Vec<[[f64; 2]; 2]>,
+    /// The number of frames in each 100 ms step.
+    step_frames: usize,
+    /// The sum of squares of each channel and the number of frames in the current step.
+    step_sums: Vec<f64>,
+    step_pos: usize,
+    /// The weighted mean square of each complete step.
+    step_powers: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    /// Creates a meter for samples with the given sample rate and number of channels.
+    /// Six channels are taken to be 5.1 surround in the order of ``ChannelLayout::SURROUND_5_1``, while any other channels are weighted equally.
+    /// See ``with_layout`` to weight the channels by their speaker positions.
+    ///
+    /// This function will return an error if the sample rate or the number of channels is not positive.
+    pub fn new(sample_rate: i32, n_channels: u16) -> WaversResult<Self> {
+        check_sample_rate_and_channels(sample_rate, n_channels)?;
+        let layout = match n_channels {
+            6 => ChannelLayout::SURROUND_5_1,
+            _ => ChannelLayout::default(),
+        };
+        let n_channels = n_channels as usize;
+        Ok(LoudnessMeter {
+            n_channels,
+            weights: Self::channel_weights(layout, n_channels),
+            filters: [
+                Biquad::high_shelf(sample_rate as f64),
+                Biquad::high_pass(sample_rate as f64),
+            ],
+            states: vec![[[0.0; 2]; 2]; n_channels],
+            step_frames: ((sample_rate as f64 / 10.0).round() as usize).max(1),
+            step_sums: vec![0.0; n_channels],
+            step_pos: 0,
+            step_powers: Vec::new(),
+        })
+    }
+
+    /// Weights the channels by their speaker positions in ``layout``: the LFE channel is left out and the back and side channels are weighted by 1.41.
+    /// Channels beyond the speakers of the layout are weighted by 1.0.
+    pub fn with_layout(mut self, layout: ChannelLayout) -> Self {
+        self.weights = Self::channel_weights(layout, self.n_channels);
+        self
+    }
+
+    fn channel_weights(layout: ChannelLayout, n_channels: usize) -> Vec<f64> {
+        let mut speakers = layout.speakers().into_iter();
+        (0..n_channels)
+            .map(|_| match speakers.next() {
+                Some(ChannelLayout::LOW_FREQUENCY) => 0.0,
+                Some(
+                    ChannelLayout::BACK_LEFT
+                    | ChannelLayout::BACK_RIGHT
+                    | ChannelLayout::SIDE_LEFT
+                    | ChannelLayout::SIDE_RIGHT,
+                ) => SURROUND_WEIGHT,
+                _ => 1.0,
+            })
+            .collect()
+    }
+
+    /// Adds interleaved samples to the measurement. Any trailing samples which do not make up a whole frame are skipped.
+    pub fn process<T: AudioSample>(&mut self, samples: &[T]) {
+        for frame in samples.chunks_exact(self.n_channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                let x: f64 = sample.convert_to();
+                let state = &mut self.states[channel];
+                let y = self.filters[0].process(x, &mut state[0]);
+                let y = self.filters[1].process(y, &mut state[1]);
+                self.step_sums[channel] += y * y;
+            }
+            self.step_pos += 1;
+            if self.step_pos == self.step_frames {
+                let power = self
+                    .step_sums
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(sum, weight)| weight * sum / self.step_frames as f64)
+                    .sum();
+                self.step_powers.push(power);
+                self.step_sums.iter_mut().for_each(|sum| *sum = 0.0);
+                self.step_pos = 0;
+            }
+        }
+    }
+
+    /// Returns the gated integrated loudness in LUFS of the samples measured so far,
+    /// or negative infinity if there are fewer than 400 ms of samples or every block is below the absolute gate.
+    pub fn integrated_loudness(&self) -> f64 {
+        let blocks: Vec<f64> = self
+            .step_powers
+            .windows(STEPS_PER_BLOCK)
+            .map(|steps| steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64)
+            .filter(|&power| power_to_lufs(power) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if blocks.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+        let mean = |powers: &[f64]| powers.iter().sum::<f64>() / powers.len() as f64;
+        let relative_gate = power_to_lufs(mean(&blocks)) + RELATIVE_GATE_LU;
+        let gated: Vec<f64> = blocks
+            .into_iter()
+            .filter(|&power| power_to_lufs(power) > relative_gate)
+            .collect();
+        power_to_lufs(mean(&gated))
+    }
+}
+
+/// Returns the gain which brings audio measured at ``loudness`` to ``target_lufs``, or 1.0 if the audio is too quiet to be measured.
+fn loudness_gain(loudness: f64, target_lufs: f64) -> f64 {
+    match loudness.is_finite() {
+        true => db_to_gain(target_lufs - loudness),
+        false => 1.0,
+    }
+}
+
+impl<T: AudioSample> Samples<T> {
+    /// Returns the integrated loudness in LUFS of interleaved samples with the given sample rate and number of channels.
+    /// See the ``LoudnessMeter`` struct for more information.
+    pub fn integrated_loudness(&self, sample_rate: i32, n_channels: u16) -> WaversResult<f64> {
+        let mut meter = LoudnessMeter::new(sample_rate, n_channels)?;
+        meter.process(&self.samples);
+        Ok(meter.integrated_loudness())
+    }
+
+    /// Scales interleaved samples with the given sample rate and number of channels so that their integrated loudness is ``target_lufs``,
+    /// returning the linear gain which was applied. Samples which are too quiet to be measured are left unchanged with a gain of 1.0.
+    /// Integer samples are clamped at full scale, so check the ``peak`` of the samples when normalizing quiet audio to a loud target.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::read;
+    ///
+    /// fn main() {
+    ///     let (mut samples, sample_rate) = read::<f32, _>("path/to/podcast.wav").unwrap();
+    ///     samples.normalize_loudness(sample_rate, 2, -16.0).unwrap();
+    /// }
+    /// ```
+    pub fn normalize_loudness(
+        &mut self,
+        sample_rate: i32,
+        n_channels: u16,
+        target_lufs: f64,
+    ) -> WaversResult<f64>
+    where
+        f64: ConvertTo<T>,
+    {
+        let gain = loudness_gain(
+            self.integrated_loudness(sample_rate, n_channels)?,
+            target_lufs,
+        );
+        scale_samples(&mut self.samples, gain);
+        Ok(gain)
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Returns the integrated loudness in LUFS of the wav file, scanning the file with ``scan_blocks``.
+    /// The channels are weighted by the channel layout of the file, if it has one. See the ``LoudnessMeter`` struct for more information.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/programme.wav").unwrap();
+    ///     println!("{:.1} LUFS", wav.integrated_loudness().unwrap());
+    /// }
+    /// ```
+    pub fn integrated_loudness(&mut self) -> WaversResult<f64> {
+        let mut meter = LoudnessMeter::new(self.sample_rate(), self.n_channels())?;
+        if let Some(layout) = self.channel_layout() {
+            meter = meter.with_layout(layout);
+        }
+        self.scan_blocks(|block| meter.process(block))?;
+        Ok(meter.integrated_loudness())
+    }
+}
+
+/// Scales the wav file at ``src`` so that its integrated loudness is ``target_lufs`` and writes it to ``dst``, returning the linear gain which was applied,
+/// e.g. -23 LUFS for EBU R128 broadcast or -16 LUFS for podcasts.
+///
+/// The file is read twice, a block of frames at a time: once to measure its loudness and once to scale and write the samples.
+/// The encoding of ``src`` is kept and ``dst`` can be ``src``, see ``Wav::process_to``. A file which is too quiet to be measured is copied unchanged with a gain of 1.0.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::loudness::normalize_loudness;
+///
+/// fn main() {
+///     normalize_loudness("./episode.wav", "./episode_normalized.wav", -16.0).unwrap();
+/// }
+/// ```
+pub fn normalize_loudness<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    target_lufs: f64,
+) -> WaversResult<f64> {
+    let mut wav: Wav<f64> = Wav::from_path(src)?;
+    let gain = loudness_gain(wav.integrated_loudness()?, target_lufs);
+    wav.process_to(dst, |block| {
+        block.iter_mut().for_each(|sample| *sample *= gain)
+    })?;
+    Ok(gain)
+}
+
+#[cfg(test)]
+mod loudness_tests {
+    use super::*;
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    fn tone(sample_rate: i32, frequency: f64, amplitude: f64, seconds: f64) -> Vec<f64> {
+        (0..(sample_rate as f64 * seconds) as usize)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f64 / sample_rate as f64).sin())
+            .collect()
+    }
+
+    #[test]
+    fn sine_waves_measure_their_reference_loudness() {
+        // A full scale 997 Hz sine wave in one channel measures -3.01 LUFS at any sample rate
+        for sample_rate in [44100, 48000, 96000] {
+            let samples = Samples::from(tone(sample_rate, 997.0, 1.0, 3.0));
+            let loudness = samples.integrated_loudness(sample_rate, 1).unwrap();
+            assert!((loudness + 3.01).abs() < 0.05, "{sample_rate}: {loudness}");
+        }
+
+        // The same tone in both channels is 3 dB louder
+        let stereo = Samples::from(tone(48000, 997.0, 0.1, 3.0)).upmix(2);
+        assert!((stereo.integrated_loudness(48000, 2).unwrap() + 20.0).abs() < 0.05);
+
+        // Silence is below the gates, although the three blocks which overlap the end of the tone are still counted,
+        // while without gating the silence would lower the measurement by 4.3 dB
+        let mut padded = tone(48000, 997.0, 0.1, 3.0);
+        padded.extend(vec![0.0; 48000 * 5]);
+        let padded = Samples::from(padded);
+        let expected = -23.01 + 10.0 * (28.5f64 / 30.0).log10();
+        assert!((padded.integrated_loudness(48000, 1).unwrap() - expected).abs() < 0.05);
+        let short = Samples::from(tone(48000, 997.0, 0.1, 0.3));
+        assert_eq!(
+            short.integrated_loudness(48000, 1).unwrap(),
+            f64::NEG_INFINITY
+        );
+
+        // The LFE channel is left out and the surround channels are weighted up
+        let surround: Vec<f64> = tone(48000, 997.0, 0.1, 3.0)
+            .into_iter()
+            .flat_map(|x| [0.0, 0.0, 0.0, x, x, 0.0])
+            .collect();
+        let loudness = Samples::from(surround)
+            .integrated_loudness(48000, 6)
+            .unwrap();
+        assert!((loudness - (-23.01 + 10.0 * SURROUND_WEIGHT.log10())).abs() < 0.05);
+
+        assert!(short.integrated_loudness(0, 1).is_err());
+        assert!(short.integrated_loudness(48000, 0).is_err());
+    }
+
+    #[test]
+    fn files_are_normalized_to_the_target_loudness() {
+        let fp = format!("{}loudness_src.wav", TEST_OUTPUT);
+        let out_fp = format!("{}loudness_normalized.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let samples = Samples::from(tone(16000, 440.0, 0.05, 4.0));
+        crate::write_as(&fp, &samples, crate::WavType::Pcm16, 16000, 1).unwrap();
+
+        let gain = normalize_loudness(&fp, &out_fp, -16.0).unwrap();
+        let mut normalized: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert!((normalized.integrated_loudness().unwrap() + 16.0).abs() < 0.05);
+
+        let mut expected: Samples<i16> = Wav::from_path(&fp).unwrap().read().unwrap();
+        assert!((expected.normalize_loudness(16000, 1, -16.0).unwrap() - gain).abs() < 1e-9);
+        assert_eq!(normalized.read().unwrap(), expected);
+        std::fs::remove_file(&fp).unwrap();
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! Module containing the ``Matrix`` type, which maps the channels of interleaved samples to a new set of channels.
+//!
+//! Each output channel is a weighted sum of the input channels, e.g. a 5.1 to stereo downmix, swapping the left and right channels or converting between left/right and mid/side.
+//! The gains are stored with one row per output channel and one column per input channel.
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use crate::conversion::{AudioSample, ConvertTo};
+use crate::error::{FormatError, WaversResult};
+
+/// A gain matrix which maps ``n_inputs`` channels to ``n_outputs`` channels.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::{Matrix, Samples};
+///
+/// let stereo: Samples<f32> = Samples::from(vec![0.5, -0.5, 1.0, 0.0]);
+/// let swapped = stereo.remix(&Matrix::swap_stereo());
+/// assert_eq!(&swapped[..], &[-0.5, 0.5, 0.0, 1.0]);
+///
+/// // Keep the left channel and add a channel which is the sum of both
+/// let matrix = Matrix::new(vec![vec![1.0, 0.0], vec![1.0, 1.0]]).unwrap();
+/// assert_eq!(&stereo.remix(&matrix)[..], &[0.5, 0.0, 1.0, 1.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    n_inputs: usize,
+    n_outputs: usize,
+    gains: Box<[f64]>,
+}
+
+impl Matrix {
+    /// Creates a matrix from one row of gains per output channel, where each row holds the gain of every input channel.
+    ///
+    /// This function will return an error if there are no rows, a row is empty or the rows have different lengths.
+    pub fn new(rows: Vec<Vec<f64>>) -> WaversResult<Self> {
+        let n_inputs = rows.first().map_or(0, Vec::len);
+        if n_inputs == 0 || rows.iter().any(|row| row.len() != n_inputs) {
+            return Err(FormatError::InvalidMatrix.into());
+        }
+        Ok(Matrix {
+            n_inputs,
+            n_outputs: rows.len(),
+            gains: rows.concat().into_boxed_slice(),
+        })
+    }
+
+    /// Creates a matrix which passes ``n_channels`` channels through unchanged.
+    ///
+    /// Panics if the number of channels is zero.
+    pub fn identity(n_channels: u16) -> Self {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        let n_channels = n_channels as usize;
+        let mut gains = vec![0.0; n_channels * n_channels];
+        gains
+            .iter_mut()
+            .step_by(n_channels + 1)
+            .for_each(|gain| *gain = 1.0);
+        Matrix {
+            n_inputs: n_channels,
+            n_outputs: n_channels,
+            gains: gains.into_boxed_slice(),
+        }
+    }
+
+    /// Creates a matrix which swaps the left and right channels of a stereo signal.
+    pub fn swap_stereo() -> Self {
+        Self::from_rows(2, &[0.0, 1.0, 1.0, 0.0])
+    }
+
+    /// Creates a matrix which converts a left/right stereo signal to mid/side, where mid is ``(L + R) / 2`` and side is ``(L - R) / 2``.
+    pub fn mid_side() -> Self {
+        Self::from_rows(2, &[0.5, 0.5, 0.5, -0.5])
+    }
+
+    /// Creates a matrix which converts a mid/side signal back to left/right stereo, undoing ``mid_side``.
+    pub fn mid_side_to_stereo() -> Self {
+        Self::from_rows(2, &[1.0, 1.0, 1.0, -1.0])
+    }
+
+    /// Creates a matrix which mixes 5.1 surround, in the order of ``ChannelLayout::SURROUND_5_1``, down to stereo.
+    /// The centre and back channels are added to both sides at -3 dB, as in ITU-R BS.775, and the LFE channel is dropped.
+    pub fn surround_to_stereo() -> Self {
+        let g = FRAC_1_SQRT_2;
+        Self::from_rows(6, &[1.0, 0.0, g, 0.0, g, 0.0, 0.0, 1.0, g, 0.0, 0.0, g])
+    }
+
+    fn from_rows(n_inputs: usize, gains: &[f64]) -> Self {
+        Matrix {
+            n_inputs,
+            n_outputs: gains.len() / n_inputs,
+            gains: Box::from(gains),
+        }
+    }
+
+    /// Returns the number of input channels of the matrix.
+    pub fn n_inputs(&self) -> usize {
+        self.n_inputs
+    }
+
+    /// Returns the number of output channels of the matrix.
+    pub fn n_outputs(&self) -> usize {
+        self.n_outputs
+    }
+
+    /// Returns the gain of the input channel ``input`` in the output channel ``output``.
+    ///
+    /// Panics if either channel is out of range.
+    pub fn gain(&self, output: usize, input: usize) -> f64 {
+        assert!(
+            output < self.n_outputs && input < self.n_inputs,
+            "Channel out of range"
+        );
+        self.gains[output * self.n_inputs + input]
+    }
+
+    /// Remixes a block of interleaved frames with ``n_inputs`` channels, appending the frames with ``n_outputs`` channels to ``out``.
+    /// The samples are mixed as ``f64`` and converted back to ``T``. Any trailing samples which do not make up a whole frame are skipped.
+    ///
+    /// Since each frame is mixed independently, a long stream can be remixed a block at a time.
+    pub fn remix_into<T>(&self, frames: &[T], out: &mut Vec<T>)
+    where
+        T: AudioSample + ConvertTo<f64>,
+        f64: ConvertTo<T>,
+    {
+        out.reserve(frames.len() / self.n_inputs * self.n_outputs);
+        let mut converted = vec![0.0; self.n_inputs];
+        for frame in frames.chunks_exact(self.n_inputs) {
+            for (converted, sample) in converted.iter_mut().zip(frame) {
+                *converted = sample.convert_to();
+            }
+            out.extend(self.gains.chunks_exact(self.n_inputs).map(|row| {
+                let mixed: f64 = row
+                    .iter()
+                    .zip(&converted)
+                    .map(|(gain, sample)| gain * sample)
+                    .sum();
+                mixed.convert_to()
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod mix_tests {
+    use super::*;
+
+    #[test]
+    fn matrices_map_input_channels_to_output_channels() {
+        assert!(Matrix::new(vec![]).is_err());
+        assert!(Matrix::new(vec![vec![]]).is_err());
+        assert!(Matrix::new(vec![vec![1.0, 0.0], vec![1.0]]).is_err());
+
+        let identity = Matrix::identity(3);
+        assert_eq!(
+            identity,
+            Matrix::new(vec![
+                vec![1.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0]
+            ])
+            .unwrap()
+        );
+
+        let surround = Matrix::surround_to_stereo();
+        assert_eq!((surround.n_inputs(), surround.n_outputs()), (6, 2));
+        assert_eq!(surround.gain(0, 3), 0.0);
+        assert_eq!(surround.gain(1, 5), FRAC_1_SQRT_2);
+
+        // Converting to mid/side and back restores the signal
+        let stereo: Vec<f64> = vec![0.5, -0.25, 0.125, 1.0, -1.0, 0.0];
+        let mut mid_side = Vec::new();
+        Matrix::mid_side().remix_into(&stereo, &mut mid_side);
+        assert_eq!(&mid_side[..2], &[0.125, 0.375]);
+        let mut restored = Vec::new();
+        Matrix::mid_side_to_stereo().remix_into(&mid_side, &mut restored);
+        assert_eq!(restored, stereo);
+
+        // Mixing surround down to stereo with integer samples
+        let frame: Vec<i16> = vec![1000, 2000, 3000, 4000, 5000, 6000];
+        let mut out = Vec::new();
+        surround.remix_into(&frame, &mut out);
+        let g = FRAC_1_SQRT_2;
+        let expected_left = (1000.0 + 3000.0 * g + 5000.0 * g).round() as i16;
+        assert!((out[0] - expected_left).abs() <= 1);
+        assert_eq!(out.len(), 2);
+    }
+}
@@ -0,0 +1,292 @@
+//! Module containing the ``MmapWav`` struct, which reads a wav file through a memory map, available with the ``mmap`` feature.
+//!
+//! The file is mapped into memory using the ``memmap2`` crate, so the operating system loads its pages as they are read rather than the whole file being copied into the process.
+//! When the samples are stored on disk as ``T``, they can be accessed as a ``&[T]`` straight from the map without being copied at all.
+//!
+//! ``write`` and ``write_with_options`` go the other way, sizing the output file up front and copying the encoded file into a mutable map of it.
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Arc;
+
+use bytemuck::try_cast_slice;
+use i24::i24;
+use memmap2::{Mmap, MmapMut};
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::{Samples, Wav, PROGRESS_INTERVAL},
+    error::WaversResult,
+    wav_type::WavType,
+    writer::{Durability, WriteOptions},
+};
+
+/// A memory map shared between the ``MmapWav`` and the reader of its Wav struct.
+struct SharedMmap(Arc<Mmap>);
+
+impl AsRef<[u8]> for SharedMmap {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// A wav file which is read through a memory map.
+///
+/// The underlying Wav struct reads from the map, so every function of the Wav struct can be used through ``wav_mut``.
+/// ``as_slice`` returns the samples without copying them when the file stores them as ``T``.
+///
+/// The file must not be modified or truncated while it is mapped, e.g. by another process, since the map would change underneath the samples.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::mmap::MmapWav;
+///
+/// let wav: MmapWav<i16> = MmapWav::from_path("path/to/huge_i16.wav").unwrap();
+/// if let Some(samples) = wav.as_slice() {
+///     let peak = samples.iter().map(|sample| sample.unsigned_abs()).max();
+/// }
+/// ```
+pub struct MmapWav<T: AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    mmap: Arc<Mmap>,
+    wav: Wav<T>,
+}
+
+impl<T: AudioSample> MmapWav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Maps the file at the given path into memory and reads its header.
+    ///
+    /// This function will return an error if the file cannot be opened or mapped, or if its header is invalid.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> WaversResult<Self> {
+        let file = File::open(path)?;
+        // Safety: the map is only read, and the caller is told not to modify the file while it is mapped
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+        let wav = Wav::from_cursor(Cursor::new(SharedMmap(Arc::clone(&mmap))))?;
+        Ok(MmapWav { mmap, wav })
+    }
+
+    /// Returns the underlying Wav struct, e.g. to inspect its header or metadata.
+    pub fn wav(&self) -> &Wav<T> {
+        &self.wav
+    }
+
+    /// Returns the underlying Wav struct, which reads its samples from the map.
+    pub fn wav_mut(&mut self) -> &mut Wav<T> {
+        &mut self.wav
+    }
+
+    /// Consumes the MmapWav and returns the underlying Wav struct, which keeps the file mapped.
+    pub fn into_inner(self) -> Wav<T> {
+        self.wav
+    }
+
+    /// Returns the bytes of the data chunk, straight from the map.
+    pub fn data_bytes(&self) -> &[u8] {
+        let info = self.wav.header().data();
+        let start = (info.offset + 8).min(self.mmap.len());
+        let end = (start as u64 + info.size).min(self.mmap.len() as u64) as usize;
+        &self.mmap[start..end]
+    }
+
+    /// Returns the samples of the data chunk straight from the map, without copying or converting them.
+    ///
+    /// Returns ``None`` if the samples cannot be used as they are stored, which is when the file does not store them as ``T``,
+    /// when they are big-endian (RIFX) or have padding bits, or when the data chunk is not aligned for ``T``. Use ``read`` in that case.
+    pub fn as_slice(&self) -> Option<&[T]> {
+        let desired_type = WavType::try_from(std::any::TypeId::of::<T>()).ok();
+        if desired_type != Some(self.wav.encoding())
+            || self.wav.header().is_rifx() != cfg!(target_endian = "big")
+            || self.wav.padding_bits() != 0
+        {
+            return None;
+        }
+        let bytes = self.data_bytes();
+        let n_bytes = bytes.len() - bytes.len() % std::mem::size_of::<T>();
+        try_cast_slice(&bytes[..n_bytes]).ok()
+    }
+
+    /// Returns the samples of the data chunk, copied straight from the map when possible (see ``as_slice``) and otherwise read and converted as by ``Wav::read``.
+    ///
+    /// This function will return an error if the samples need to be read and there is an issue reading them.
+    pub fn read(&mut self) -> WaversResult<Samples<T>> {
+        match self.as_slice() {
+            Some(samples) => Ok(Samples::from(samples)),
+            None => self.wav.read(),
+        }
+    }
+}
+
+/// Writes a wav file to ``fp`` through a memory map, in the same way as ``wavers::write``.
+///
+/// # Examples
+///
+/// ```no_run
+/// let samples: &[i16] = &[0; 48000 * 60];
+/// wavers::mmap::write("path/to/huge.wav", samples, 48000, 1).unwrap();
+/// ```
+pub fn write<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    write_with_options(
+        fp,
+        samples,
+        sample_rate,
+        n_channels,
+        &WriteOptions::default(),
+    )
+}
+
+/// Writes a wav file to ``fp`` through a memory map, in the same way as ``wavers::write_with_options``.
+///
+/// The file is sized to its final length before anything is written and the header, samples and metadata chunks are then copied into a mutable map of it,
+/// so the operating system writes the pages back to disk rather than the samples going through a series of ``write`` calls.
+/// The progress callback and cancel token of the options are checked as the samples are copied.
+///
+/// This function will return an error if the file cannot be created, sized or mapped, or for any of the reasons ``wavers::write_with_options`` would.
+pub fn write_with_options<T: AudioSample, P: AsRef<Path>>(
+    fp: P,
+    samples: &[T],
+    sample_rate: i32,
+    n_channels: u16,
+    options: &WriteOptions,
+) -> WaversResult<()>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    let file = crate::encode_file(samples, sample_rate, n_channels, options)?;
+    crate::write_file(fp, options, |f| {
+        f.set_len(file.len() as u64)?;
+        // Safety: the file was just created by this function and is only accessed through the map until it is unmapped
+        let mut mmap = unsafe { MmapMut::map_mut(&*f)? };
+
+        let mut offset = 0;
+        let mut copy = |bytes: &[u8]| {
+            mmap[offset..offset + bytes.len()].copy_from_slice(bytes);
+            offset += bytes.len();
+        };
+        file.head.iter().for_each(|bytes| copy(bytes));
+        let total = file.samples.len() as u64;
+        let mut done = 0;
+        for bytes in file.samples.chunks(PROGRESS_INTERVAL.max(1)) {
+            copy(bytes);
+            done += bytes.len() as u64;
+            options.report(done, total)?;
+        }
+        file.tail.iter().for_each(|bytes| copy(bytes));
+
+        // The map is written back by the operating system, so it only needs flushing when the file is to be synced
+        if options.durability != Durability::None {
+            mmap.flush()?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod mmap_tests {
+    use super::*;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn mapped_samples_match_read_samples() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let mut wav: MmapWav<i16> = MmapWav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        assert_eq!(wav.data_bytes().len(), expected.len() * 2);
+        assert_eq!(wav.as_slice().unwrap(), &expected[..]);
+        assert_eq!(wav.read().unwrap(), expected);
+        assert_eq!(
+            &wav.wav_mut().read_frames(10, 5).unwrap()[..],
+            &expected[20..30]
+        );
+
+        // Samples stored as another type are converted as they are read
+        let mut wav: MmapWav<f32> = MmapWav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        assert!(wav.as_slice().is_none());
+        let expected: Samples<f32> = expected.convert();
+        assert_eq!(wav.read().unwrap(), expected);
+    }
+
+    #[test]
+    fn mapped_writes_match_buffered_writes() {
+        let out_fp = "./test_resources/tmp/mmap_write.wav";
+        std::fs::create_dir_all("./test_resources/tmp").unwrap();
+
+        // An odd number of 8-bit samples, so the data chunk is padded before the trailing chunk
+        let samples: Vec<u8> = (0..999).map(|i| (i % 256) as u8).collect();
+        let mut options = WriteOptions::new()
+            .add_chunk(*b"tail", &[1, 2, 3])
+            .durability(Durability::File);
+        options.chunk_order = vec![crate::DATA.into(), (*b"tail").into()];
+        write_with_options(out_fp, &samples, 8000, 1, &options).unwrap();
+        let expected = crate::write_to_vec_with_options(&samples, 8000, 1, &options).unwrap();
+        assert_eq!(std::fs::read(out_fp).unwrap(), expected);
+
+        let samples: Vec<f32> = (0..2000).map(|i| (i as f32 / 2000.0).sin()).collect();
+        write(out_fp, &samples, 16000, 2).unwrap();
+        let wav: MmapWav<f32> = MmapWav::from_path(out_fp).unwrap();
+        assert_eq!(wav.wav().n_channels(), 2);
+        assert_eq!(wav.as_slice().unwrap(), &samples[..]);
+
+        std::fs::remove_file(out_fp).unwrap();
+    }
+}
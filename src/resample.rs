@@ -0,0 +1,299 @@
+//! Module containing the ``StreamResampler`` struct and the ``resample`` function, which convert interleaved samples to another sample rate, available with the ``resampling`` feature.
+//!
+//! Resampling is done by the sinc resampler of the ``rubato`` crate, which works on one buffer per channel.
+//! The samples are deinterleaved and converted to ``f64`` on the way in and interleaved and converted back to ``T`` on the way out,
+//! and the delay of the resampling filter is removed, so the resampled audio lines up with the original.
+//!
+//! The sinc resampler places its ``n``th output frame at the input frame ``(n + 1) / ratio - 1``, where ``ratio`` is ``target_rate / sample_rate``,
+//! which is a fraction of a frame away from ``n / ratio``. With ``ratio = p / q`` in lowest terms, ``q - 1`` frames of silence are put in front of the input
+//! and the first ``p - 1`` output frames are dropped, which places every output frame exactly at ``n / ratio``.
+//!
+//! ``transcode``, ``Wav::with_target_sample_rate`` and the iterators of a Wav struct resample with a smaller windowed sinc filter of their own instead,
+//! so that they work without this feature. That filter reaches 16 zero crossings either side of each output frame, cuts off at the lower of the two Nyquist frequencies
+//! and returns each output frame as soon as the input frames it reaches have arrived. The resampler of this module reaches 128 zero crossings either side
+//! and cuts off at 95% of the lower Nyquist frequency, giving a sharper cutoff and less aliasing near the Nyquist frequency,
+//! at the cost of more work per frame and of returning frames ``RESAMPLER_CHUNK_FRAMES`` input frames at a time.
+//! Use this module where the quality of the resampling matters most, e.g. for mastering, and the built-in filter otherwise.
+use std::marker::PhantomData;
+
+use rubato::{
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+
+use crate::conversion::{AudioSample, ConvertTo};
+use crate::core::Samples;
+use crate::error::{FormatError, WaversResult};
+use crate::transcode::gcd;
+use crate::writer::check_frames;
+
+/// The number of frames passed to the resampler at a time.
+pub const RESAMPLER_CHUNK_FRAMES: usize = 1024;
+
+/// Returns the number of frames of silence to put in front of the input and the number of frames to drop from the start of the output,
+/// so that the output lines up with the input. See the module documentation.
+fn alignment(sample_rate: i32, target_rate: i32) -> (usize, usize) {
+    let divisor = gcd(sample_rate as u64, target_rate as u64);
+    (
+        (sample_rate as u64 / divisor - 1) as usize,
+        (target_rate as u64 / divisor - 1) as usize,
+    )
+}
+
+/// Resamples interleaved frames from one sample rate to another as they arrive, e.g. while a long file is read a block at a time.
+///
+/// Frames are given to ``process`` in blocks of any length, which returns the resampled frames which are ready so far.
+/// Once every frame has been given, ``finish`` returns the remaining frames, so that ``ceil(n_frames * target_rate / sample_rate)`` frames are returned in total.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::resample::StreamResampler;
+/// use wavers::Wav;
+///
+/// let mut wav: Wav<f32> = Wav::from_path("path/to/48k.wav").unwrap();
+/// let mut resampler: StreamResampler<f32> = StreamResampler::new(wav.sample_rate(), 16000, wav.n_channels()).unwrap();
+/// let mut block = vec![0.0; 4096 * wav.n_channels() as usize];
+/// loop {
+///     let n_frames = wav.read_block(&mut block, 4096).unwrap();
+///     if n_frames == 0 {
+///         break;
+///     }
+///     let resampled = resampler.process(&block[..n_frames * wav.n_channels() as usize]).unwrap();
+/// }
+/// let tail = resampler.finish().unwrap();
+/// ```
+pub struct StreamResampler<T: AudioSample>
+where
+    f64: ConvertTo<T>,
+{
+    resampler: SincFixedIn<f64>,
+    sample_rate: i32,
+    target_rate: i32,
+    n_channels: usize,
+    /// Deinterleaved frames waiting to make up a whole chunk.
+    pending: Vec<Vec<f64>>,
+    /// The number of frames of silence still to be put in front of the input, and of frames still to be dropped from the start of the output,
+    /// which together remove the delay of the filter.
+    lead_in: usize,
+    delay: usize,
+    n_frames_in: u64,
+    n_frames_out: u64,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: AudioSample> StreamResampler<T>
+where
+    f64: ConvertTo<T>,
+{
+    /// Creates a resampler of frames of ``n_channels`` channels from ``sample_rate`` to ``target_rate``.
+    ///
+    /// This function will return an error if either sample rate is not positive, or if there are no channels.
+    pub fn new(sample_rate: i32, target_rate: i32, n_channels: u16) -> WaversResult<Self> {
+        for rate in [sample_rate, target_rate] {
+            if rate <= 0 {
+                return Err(FormatError::InvalidSampleRate(rate).into());
+            }
+        }
+        if n_channels == 0 {
+            return Err(FormatError::InvalidChannel {
+                channel: 0,
+                n_channels,
+            }
+            .into());
+        }
+        let parameters = SincInterpolationParameters {
+            sinc_len: 256,
+            f_cutoff: 0.95,
+            oversampling_factor: 256,
+            interpolation: SincInterpolationType::Linear,
+            window: WindowFunction::BlackmanHarris2,
+        };
+        let resampler = SincFixedIn::new(
+            target_rate as f64 / sample_rate as f64,
+            1.0,
+            parameters,
+            RESAMPLER_CHUNK_FRAMES,
+            n_channels as usize,
+        )?;
+        let (lead_in, delay) = alignment(sample_rate, target_rate);
+        Ok(StreamResampler {
+            resampler,
+            lead_in,
+            delay,
+            sample_rate,
+            target_rate,
+            n_channels: n_channels as usize,
+            pending: vec![Vec::with_capacity(RESAMPLER_CHUNK_FRAMES); n_channels as usize],
+            n_frames_in: 0,
+            n_frames_out: 0,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the sample rate of the frames given to the resampler.
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Returns the sample rate of the frames returned by the resampler.
+    pub fn target_rate(&self) -> i32 {
+        self.target_rate
+    }
+
+    /// Returns the number of channels of the frames.
+    pub fn n_channels(&self) -> u16 {
+        self.n_channels as u16
+    }
+
+    /// Resamples the given interleaved frames, returning the resampled frames which are ready so far.
+    /// Frames are held back until the resampler has enough of them, so fewer frames than expected may be returned until ``finish`` is called.
+    ///
+    /// This function will return an error if the samples do not make up a whole number of frames, or if the resampler fails.
+    pub fn process(&mut self, frames: &[T]) -> WaversResult<Samples<T>> {
+        check_frames(frames.len(), self.n_channels as u16)?;
+        self.n_frames_in += (frames.len() / self.n_channels) as u64;
+
+        let mut out = Vec::new();
+        self.push_lead_in(&mut out)?;
+        let frames = frames.iter().map(|sample| sample.convert_to());
+        self.push(frames, &mut out)?;
+        Ok(Samples::from(out))
+    }
+
+    /// Deinterleaves whole frames onto the pending frames, resampling each chunk once it is complete and interleaving the result onto ``out``.
+    fn push<I: Iterator<Item = f64>>(&mut self, samples: I, out: &mut Vec<T>) -> WaversResult<()> {
+        for (i, sample) in samples.enumerate() {
+            let channel = i % self.n_channels;
+            self.pending[channel].push(sample);
+            if channel == self.n_channels - 1
+                && self.pending[channel].len() == self.resampler.input_frames_next()
+            {
+                let resampled = self.resampler.process(&self.pending, None)?;
+                self.pending.iter_mut().for_each(Vec::clear);
+                self.append(&resampled, u64::MAX, out);
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts the frames of silence which align the output with the input in front of the first frames of a stream.
+    fn push_lead_in(&mut self, out: &mut Vec<T>) -> WaversResult<()> {
+        let n_samples = std::mem::take(&mut self.lead_in) * self.n_channels;
+        self.push(std::iter::repeat_n(0.0, n_samples), out)
+    }
+
+    /// Resamples the frames still held back by the resampler and returns the remaining resampled frames, padding the end of the input with silence.
+    /// The resampler can then be used for a new stream.
+    ///
+    /// This function will return an error if the resampler fails.
+    pub fn finish(&mut self) -> WaversResult<Samples<T>> {
+        let n_frames =
+            (self.n_frames_in * self.target_rate as u64).div_ceil(self.sample_rate as u64);
+        let mut out = Vec::new();
+        while self.n_frames_out < n_frames {
+            let resampled = match self.pending[0].is_empty() {
+                true => self.resampler.process_partial(None::<&[Vec<f64>]>, None)?,
+                false => self.resampler.process_partial(Some(&self.pending), None)?,
+            };
+            self.pending.iter_mut().for_each(Vec::clear);
+            self.append(&resampled, n_frames, &mut out);
+        }
+
+        self.resampler.reset();
+        (self.lead_in, self.delay) = alignment(self.sample_rate, self.target_rate);
+        self.n_frames_in = 0;
+        self.n_frames_out = 0;
+        Ok(Samples::from(out))
+    }
+
+    /// Interleaves the resampled frames onto ``out``, dropping those within the delay of the filter and any past the ``n_frames``th frame.
+    fn append(&mut self, resampled: &[Vec<f64>], n_frames: u64, out: &mut Vec<T>) {
+        let n_resampled = resampled.first().map_or(0, Vec::len);
+        let skip = self.delay.min(n_resampled);
+        self.delay -= skip;
+        let take = ((n_resampled - skip) as u64).min(n_frames - self.n_frames_out) as usize;
+        out.reserve(take * self.n_channels);
+        for i in skip..skip + take {
+            out.extend(resampled.iter().map(|channel| channel[i].convert_to()));
+        }
+        self.n_frames_out += take as u64;
+    }
+}
+
+/// Resamples interleaved ``samples`` of ``n_channels`` channels from ``sample_rate`` to ``target_rate``,
+/// returning ``ceil(n_frames * target_rate / sample_rate)`` resampled frames along with the new sample rate.
+/// Samples already at the target rate are returned unchanged.
+///
+/// This function will return an error if either sample rate is not positive, if the samples do not make up a whole number of frames, or if the resampler fails.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{read, resample::resample};
+///
+/// let (samples, sample_rate) = read::<f32, _>("path/to/48k_mono.wav").unwrap();
+/// let (samples, sample_rate) = resample(&samples, 1, sample_rate, 16000).unwrap();
+/// assert_eq!(sample_rate, 16000);
+/// ```
+pub fn resample<T: AudioSample>(
+    samples: &[T],
+    n_channels: u16,
+    sample_rate: i32,
+    target_rate: i32,
+) -> WaversResult<(Samples<T>, i32)>
+where
+    f64: ConvertTo<T>,
+{
+    if sample_rate == target_rate && sample_rate > 0 {
+        return Ok((Samples::from(samples), target_rate));
+    }
+    let mut resampler = StreamResampler::new(sample_rate, target_rate, n_channels)?;
+    let mut out = resampler.process(samples)?.to_vec();
+    out.extend_from_slice(&resampler.finish()?);
+    Ok((Samples::from(out), target_rate))
+}
+
+#[cfg(test)]
+mod resample_tests {
+    use super::*;
+
+    #[test]
+    fn sines_are_resampled_in_line_with_the_original() {
+        let sine = |t: f64| 0.5 * (2.0 * std::f64::consts::PI * 440.0 * t).sin();
+        for (sample_rate, target_rate) in [(48000, 16000), (22050, 44100)] {
+            // Stereo, with the right channel inverted
+            let samples: Vec<f32> = (0..sample_rate / 2)
+                .flat_map(|i| {
+                    let s = sine(i as f64 / sample_rate as f64) as f32;
+                    [s, -s]
+                })
+                .collect();
+            let (resampled, rate) = resample(&samples, 2, sample_rate, target_rate).unwrap();
+            assert_eq!(rate, target_rate);
+            assert_eq!(resampled.len(), 2 * (target_rate / 2) as usize);
+
+            // Away from the edges, where the filter reaches past the samples
+            let n_frames = resampled.len() / 2;
+            for i in n_frames / 10..n_frames - n_frames / 10 {
+                let expected = sine(i as f64 / target_rate as f64) as f32;
+                assert!((resampled[2 * i] - expected).abs() < 1e-2);
+                assert!((resampled[2 * i + 1] + expected).abs() < 1e-2);
+            }
+
+            // Streaming in uneven blocks gives the same frames
+            let mut resampler: StreamResampler<f32> =
+                StreamResampler::new(sample_rate, target_rate, 2).unwrap();
+            let mut streamed = Vec::new();
+            for block in samples.chunks(2 * 777) {
+                streamed.extend_from_slice(&resampler.process(block).unwrap());
+            }
+            streamed.extend_from_slice(&resampler.finish().unwrap());
+            assert_eq!(&streamed[..], &resampled[..]);
+        }
+
+        let samples = [1i16, 2, 3];
+        let (same, rate) = resample(&samples, 1, 8000, 8000).unwrap();
+        assert_eq!((&same[..], rate), (&samples[..], 8000));
+        assert!(resample(&samples, 2, 8000, 16000).is_err());
+    }
+}
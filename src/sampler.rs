@@ -0,0 +1,219 @@
+//! Module containing the ``WindowSampler`` struct, which reads random fixed-length crops of a wav file, e.g. for training machine learning models.
+//!
+//! Each crop is read by seeking to a random frame and decoding only the frames of the crop, so the whole file is never read into memory.
+//! The crops are drawn from a seeded pseudo-random number generator, so the same seed always gives the same sequence of crops.
+use std::path::Path;
+
+use i24::i24;
+
+#[cfg(feature = "ndarray")]
+use ndarray::{Array, Array2};
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::{FormatError, WaversResult};
+
+/// A wav file from which random crops of ``win_len`` frames are read.
+/// Every crop lies entirely within the data chunk, and every start frame is equally likely.
+///
+/// The sampler is also an iterator which never ends, so ``take`` can be used to read a given number of crops.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{Samples, WindowSampler};
+///
+/// // Crops of one second at 16 kHz
+/// let mut sampler: WindowSampler<f32> = WindowSampler::from_path("path/to/wav.wav", 16000, 42).unwrap();
+/// let batch: Vec<Samples<f32>> = sampler.by_ref().take(32).collect::<Result<_, _>>().unwrap();
+/// ```
+pub struct WindowSampler<T: AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: Wav<T>,
+    win_len: usize,
+    n_offsets: u64,
+    state: u64,
+}
+
+impl<T: AudioSample> WindowSampler<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new WindowSampler from a Wav struct, which reads crops of ``win_len`` frames starting at frames drawn using the given seed.
+    ///
+    /// This function will return an error if the window length is zero or longer than the file,
+    /// or if the file is block encoded, since block encoded files, such as MS ADPCM, can only be decoded a whole file at a time.
+    pub fn new(wav: Wav<T>, win_len: usize, seed: u64) -> WaversResult<Self> {
+        let wav_type = wav.encoding();
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_frames = wav.n_frames() as u64;
+        if win_len == 0 || win_len as u64 > n_frames {
+            return Err(FormatError::InvalidWindowLength { win_len, n_frames }.into());
+        }
+
+        Ok(WindowSampler {
+            wav,
+            win_len,
+            n_offsets: n_frames - win_len as u64 + 1,
+            state: seed,
+        })
+    }
+
+    /// Construct a new WindowSampler from a path. See ``new``.
+    pub fn from_path<P: AsRef<Path>>(path: P, win_len: usize, seed: u64) -> WaversResult<Self> {
+        Self::new(Wav::from_path(path)?, win_len, seed)
+    }
+
+    /// Returns the underlying Wav struct, e.g. to inspect its header or metadata.
+    pub fn wav(&self) -> &Wav<T> {
+        &self.wav
+    }
+
+    /// Consumes the WindowSampler and returns the underlying Wav struct.
+    pub fn into_inner(self) -> Wav<T> {
+        self.wav
+    }
+
+    /// Returns the number of frames in each crop.
+    pub fn win_len(&self) -> usize {
+        self.win_len
+    }
+
+    /// Draws the start frame of the next crop without reading it.
+    pub fn next_offset(&mut self) -> u64 {
+        let z = splitmix64(&mut self.state);
+        // Scale to the number of start frames, the bias of which is negligible for any realistic file
+        ((z as u128 * self.n_offsets as u128) >> 64) as u64
+    }
+
+    /// Reads a crop of ``win_len`` frames starting at a random frame.
+    /// Returns the start frame of the crop and its ``win_len * n_channels`` interleaved samples.
+    ///
+    /// This function will return an error if there is an issue reading the file.
+    pub fn sample(&mut self) -> WaversResult<(u64, Samples<T>)> {
+        let offset = self.next_offset();
+        let samples = self.wav.read_frames(offset, self.win_len)?;
+        Ok((offset, samples))
+    }
+
+    /// Reads a crop of ``win_len`` frames starting at a random frame as a 2-D array of shape (frames x channels), like ``as_ndarray``.
+    /// Returns the start frame of the crop and the array.
+    ///
+    /// This function will return an error if there is an issue reading the file.
+    #[cfg(feature = "ndarray")]
+    pub fn sample_ndarray(&mut self) -> WaversResult<(u64, Array2<T>)> {
+        let n_channels = self.wav.n_channels().max(1) as usize;
+        let (offset, samples) = self.sample()?;
+        let arr: Array2<T> =
+            Array::from_shape_vec((self.win_len, n_channels), samples.samples.into_vec())?;
+        Ok((offset, arr))
+    }
+}
+
+impl<T: AudioSample> Iterator for WindowSampler<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = WaversResult<Samples<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.sample().map(|(_, samples)| samples))
+    }
+}
+
+/// Advances ``state`` and returns the next value of the SplitMix64 generator, which is small and fast and has no bad seeds.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod sampler_tests {
+    use super::*;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+    const ONE_CHANNEL_WAV_MS_ADPCM: &str = "./test_resources/one_channel_ms_adpcm.wav";
+
+    #[test]
+    fn crops_match_the_file_and_repeat_for_a_seed() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        let n_frames = expected.len() as u64 / 2;
+
+        let mut sampler: WindowSampler<i16> =
+            WindowSampler::from_path(TWO_CHANNEL_WAV_I16, 100, 7).unwrap();
+        let mut offsets = Vec::new();
+        for _ in 0..20 {
+            let (offset, crop) = sampler.sample().unwrap();
+            assert!(offset + 100 <= n_frames);
+            let start = offset as usize * 2;
+            assert_eq!(&crop[..], &expected[start..start + 200]);
+            offsets.push(offset);
+        }
+        // The crops are spread over the file rather than all starting at the same frame
+        assert!(offsets.iter().any(|offset| *offset != offsets[0]));
+
+        let mut sampler: WindowSampler<i16> =
+            WindowSampler::from_path(TWO_CHANNEL_WAV_I16, 100, 7).unwrap();
+        let repeated: Vec<u64> = (0..20).map(|_| sampler.next_offset()).collect();
+        assert_eq!(repeated, offsets);
+        assert_eq!(sampler.next().unwrap().unwrap().len(), 200);
+    }
+
+    #[test]
+    fn invalid_windows_are_rejected() {
+        let wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let n_frames = wav.n_frames();
+        assert!(WindowSampler::new(wav, 0, 0).is_err());
+        assert!(WindowSampler::<i16>::from_path(TWO_CHANNEL_WAV_I16, n_frames + 1, 0).is_err());
+
+        // A window covering the whole file can only start at the first frame
+        let mut sampler: WindowSampler<i16> =
+            WindowSampler::from_path(TWO_CHANNEL_WAV_I16, n_frames, 3).unwrap();
+        assert!((0..10).all(|_| sampler.next_offset() == 0));
+
+        assert!(WindowSampler::<i16>::from_path(ONE_CHANNEL_WAV_MS_ADPCM, 10, 0).is_err());
+    }
+}
@@ -0,0 +1,442 @@
+//! Module for finding silence in audio, e.g. to trim the silence before and after a recorded take or to split a long recording into its active segments.
+//!
+//! When trimming, a frame is silent when the absolute value of every one of its samples is at or below a threshold given in dBFS, where 0 dBFS is full scale.
+//! When segmenting, the audio is split into windows of frames, and a window is active when its mean square energy is above a threshold in dBFS.
+use std::ops::Range;
+use std::time::Duration;
+
+use i24::i24;
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::{Samples, Wav};
+use crate::error::WaversResult;
+use crate::gain::db_to_gain;
+
+/// Returns the number of frames in ``duration`` at the given sample rate, rounded to the nearest frame.
+pub(crate) fn duration_to_frames(duration: Duration, sample_rate: i32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
+}
+
+/// The options of a ``Segmenter``.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::silence::SegmentOptions;
+///
+/// // 20 ms windows at 16 kHz, bridging pauses of up to 200 ms
+/// let options = SegmentOptions::new().window_len(320).threshold_db(-45.0).hangover(10);
+/// assert_eq!(options.window_len, 320);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentOptions {
+    /// The number of frames in each analysis window.
+    pub window_len: usize,
+    /// The mean square energy in dBFS above which a window is active.
+    pub threshold_db: f64,
+    /// The number of windows a segment stays active for after the energy falls below the threshold,
+    /// so that short pauses do not split a segment and the quiet ends of words are kept.
+    pub hangover: usize,
+}
+
+impl Default for SegmentOptions {
+    fn default() -> Self {
+        SegmentOptions {
+            window_len: 512,
+            threshold_db: -40.0,
+            hangover: 4,
+        }
+    }
+}
+
+impl SegmentOptions {
+    /// Creates the default options: windows of 512 frames, a threshold of -40 dBFS and a hangover of 4 windows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of frames in each analysis window.
+    pub fn window_len(mut self, window_len: usize) -> Self {
+        self.window_len = window_len;
+        self
+    }
+
+    /// Sets the mean square energy in dBFS above which a window is active.
+    pub fn threshold_db(mut self, threshold_db: f64) -> Self {
+        self.threshold_db = threshold_db;
+        self
+    }
+
+    /// Sets the number of windows a segment stays active for after the energy falls below the threshold.
+    pub fn hangover(mut self, hangover: usize) -> Self {
+        self.hangover = hangover;
+        self
+    }
+}
+
+/// Splits interleaved samples which are passed to it a block at a time, e.g. as a file is read, into the ranges of frames which are not silent.
+///
+/// Each window of ``window_len`` frames whose mean square energy, over all of its samples, is above the threshold is active.
+/// A segment starts at the first frame of an active window and ends ``hangover`` windows after the last active window, or at the end of the samples.
+pub struct Segmenter {
+    options: SegmentOptions,
+    n_channels: usize,
+    /// The linear mean square energy above which a window is active.
+    threshold: f64,
+    /// The sum of squares and the number of frames of the current window.
+    window_sum: f64,
+    window_pos: usize,
+    n_frames: usize,
+    /// The start of the open segment and the end of its last active window, and the number of silent windows since then.
+    open: Option<(usize, usize)>,
+    n_silent: usize,
+    segments: Vec<Range<usize>>,
+}
+
+impl Segmenter {
+    /// Creates a segmenter for samples with the given number of channels.
+    ///
+    /// Panics if the window length or the number of channels is zero.
+    pub fn new(n_channels: u16, options: SegmentOptions) -> Self {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        assert!(options.window_len > 0, "Window length must be positive");
+        Segmenter {
+            options,
+            n_channels: n_channels as usize,
+            threshold: 10f64.powf(options.threshold_db / 10.0),
+            window_sum: 0.0,
+            window_pos: 0,
+            n_frames: 0,
+            open: None,
+            n_silent: 0,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Adds interleaved samples to the segmentation. Any trailing samples which do not make up a whole frame are skipped.
+    pub fn process<T: AudioSample>(&mut self, samples: &[T]) {
+        for frame in samples.chunks_exact(self.n_channels) {
+            self.window_sum += frame
+                .iter()
+                .map(|sample| ConvertTo::<f64>::convert_to(sample).powi(2))
+                .sum::<f64>();
+            self.window_pos += 1;
+            self.n_frames += 1;
+            if self.window_pos == self.options.window_len {
+                self.end_window();
+            }
+        }
+    }
+
+    /// Classifies the current window as active or silent and opens or closes a segment accordingly.
+    fn end_window(&mut self) {
+        let energy = self.window_sum / (self.window_pos * self.n_channels) as f64;
+        let window_start = self.n_frames - self.window_pos;
+        self.window_sum = 0.0;
+        self.window_pos = 0;
+        if energy > self.threshold {
+            let start = self.open.map_or(window_start, |(start, _)| start);
+            self.open = Some((start, self.n_frames));
+            self.n_silent = 0;
+        } else if let Some((start, end)) = self.open {
+            self.n_silent += 1;
+            if self.n_silent > self.options.hangover {
+                self.segments
+                    .push(start..end + self.options.hangover * self.options.window_len);
+                self.open = None;
+            }
+        }
+    }
+
+    /// Returns the ranges of frames which are not silent, in order, classifying any final partial window and closing the last segment at the end of the samples.
+    pub fn finish(mut self) -> Vec<Range<usize>> {
+        if self.window_pos > 0 {
+            self.end_window();
+        }
+        if let Some((start, end)) = self.open {
+            let end = (end + self.options.hangover * self.options.window_len).min(self.n_frames);
+            self.segments.push(start..end);
+        }
+        self.segments
+    }
+}
+
+/// Returns true if any sample of the frame is louder than the linear ``threshold``.
+fn is_sound<T: AudioSample>(frame: &[T], threshold: f64) -> bool {
+    frame
+        .iter()
+        .any(|sample| ConvertTo::<f64>::convert_to(sample).abs() > threshold)
+}
+
+/// Tracks the first and last frames which are not silent as frames are passed to it a block at a time.
+struct SoundBounds {
+    threshold: f64,
+    n_channels: usize,
+    n_frames: usize,
+    first: Option<usize>,
+    last: usize,
+}
+
+impl SoundBounds {
+    fn new(threshold_db: f64, n_channels: u16) -> Self {
+        assert!(n_channels > 0, "Number of channels must be positive");
+        SoundBounds {
+            threshold: db_to_gain(threshold_db),
+            n_channels: n_channels as usize,
+            n_frames: 0,
+            first: None,
+            last: 0,
+        }
+    }
+
+    fn process<T: AudioSample>(&mut self, samples: &[T]) {
+        for frame in samples.chunks_exact(self.n_channels) {
+            if is_sound(frame, self.threshold) {
+                self.first.get_or_insert(self.n_frames);
+                self.last = self.n_frames + 1;
+            }
+            self.n_frames += 1;
+        }
+    }
+
+    /// Returns the frames left once the leading and trailing silence is trimmed,
+    /// keeping any silence at either end which is shorter than ``min_frames``.
+    fn trimmed_range(&self, min_frames: usize) -> Range<usize> {
+        let Some(first) = self.first else {
+            return 0..0;
+        };
+        let start = match first >= min_frames {
+            true => first,
+            false => 0,
+        };
+        let end = match self.n_frames - self.last >= min_frames {
+            true => self.last,
+            false => self.n_frames,
+        };
+        start..end
+    }
+}
+
+impl<T: AudioSample> Samples<T> {
+    /// Returns the range of frames left once the leading and trailing silence of interleaved samples is trimmed.
+    /// Frames whose samples are all at or below ``threshold_db`` dBFS are silent, and silence at either end which lasts less than ``min_duration`` is kept,
+    /// so that short pauses before a take are not cut. If every frame is silent the range is empty.
+    ///
+    /// Panics if the number of channels is zero.
+    pub fn trimmed_range(
+        &self,
+        n_channels: u16,
+        sample_rate: i32,
+        threshold_db: f64,
+        min_duration: Duration,
+    ) -> Range<usize> {
+        let mut bounds = SoundBounds::new(threshold_db, n_channels);
+        bounds.process(&self.samples);
+        bounds.trimmed_range(duration_to_frames(min_duration, sample_rate))
+    }
+
+    /// Returns the interleaved samples without their leading and trailing silence. See ``trimmed_range`` for how silence is found.
+    ///
+    /// Panics if the number of channels is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use wavers::Samples;
+    ///
+    /// let mut take = vec![0.0f32; 800];
+    /// take.extend(vec![0.5f32; 1600]);
+    /// take.extend(vec![0.001f32; 1600]);
+    /// let take = Samples::from(take);
+    /// let trimmed = take.trim_silence(1, 16000, -40.0, Duration::from_millis(10));
+    /// assert_eq!(trimmed.len(), 1600);
+    /// ```
+    pub fn trim_silence(
+        &self,
+        n_channels: u16,
+        sample_rate: i32,
+        threshold_db: f64,
+        min_duration: Duration,
+    ) -> Samples<T> {
+        let frames = self.trimmed_range(n_channels, sample_rate, threshold_db, min_duration);
+        let n_channels = n_channels as usize;
+        Samples::from(&self.samples[frames.start * n_channels..frames.end * n_channels])
+    }
+
+    /// Returns the ranges of frames of interleaved samples which are not silent, as found by a ``Segmenter`` with the given options.
+    ///
+    /// Panics if the window length or the number of channels is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use wavers::silence::SegmentOptions;
+    /// use wavers::Samples;
+    ///
+    /// let mut recording = vec![0.0f32; 1000];
+    /// recording.extend(vec![0.5f32; 2000]);
+    /// recording.extend(vec![0.0f32; 3000]);
+    /// let recording = Samples::from(recording);
+    /// let options = SegmentOptions::new().window_len(100).hangover(2);
+    /// assert_eq!(recording.active_segments(1, &options), vec![1000..3200]);
+    /// ```
+    pub fn active_segments(&self, n_channels: u16, options: &SegmentOptions) -> Vec<Range<usize>> {
+        let mut segmenter = Segmenter::new(n_channels, *options);
+        segmenter.process(&self.samples);
+        segmenter.finish()
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Returns the range of frames of the wav file left once its leading and trailing silence is trimmed, scanning the file with ``scan_blocks``.
+    /// See ``Samples::trimmed_range`` for how silence is found. The trimmed take can then be read with ``read_frames``.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/take.wav").unwrap();
+    ///     let frames = wav.trimmed_range(-50.0, Duration::from_millis(100)).unwrap();
+    ///     let take = wav.read_frames(frames.start, (frames.end - frames.start) as usize).unwrap();
+    /// }
+    /// ```
+    pub fn trimmed_range(
+        &mut self,
+        threshold_db: f64,
+        min_duration: Duration,
+    ) -> WaversResult<Range<u64>> {
+        let mut bounds = SoundBounds::new(threshold_db, self.n_channels());
+        self.scan_blocks(|block| bounds.process(block))?;
+        let frames = bounds.trimmed_range(duration_to_frames(min_duration, self.sample_rate()));
+        Ok(frames.start as u64..frames.end as u64)
+    }
+
+    /// Returns the ranges of frames of the wav file which are not silent, as found by a ``Segmenter`` with the given options, scanning the file with ``scan_blocks``.
+    /// Each segment can then be read with ``read_frames``, e.g. to split a long recording into utterances.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::silence::SegmentOptions;
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/interview.wav").unwrap();
+    ///     for segment in wav.active_segments(&SegmentOptions::new()).unwrap() {
+    ///         let utterance = wav.read_frames(segment.start as u64, segment.len()).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn active_segments(&mut self, options: &SegmentOptions) -> WaversResult<Vec<Range<usize>>> {
+        let mut segmenter = Segmenter::new(self.n_channels(), *options);
+        self.scan_blocks(|block| segmenter.process(block))?;
+        Ok(segmenter.finish())
+    }
+}
+
+#[cfg(test)]
+mod silence_tests {
+    use super::*;
+
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    fn padded_take(lead: usize, sound: usize, tail: usize) -> Vec<i16> {
+        let mut take = vec![0i16; lead * 2];
+        take.extend((0..sound * 2).map(|i| if i % 2 == 0 { 8000 } else { -8000 }));
+        // Low level noise stays below the threshold
+        take.extend((0..tail * 2).map(|i| (i % 7) as i16));
+        take
+    }
+
+    #[test]
+    fn leading_and_trailing_silence_is_trimmed() {
+        let samples = Samples::from(padded_take(1600, 3200, 4800));
+        let min_duration = Duration::from_millis(50);
+        assert_eq!(
+            samples.trimmed_range(2, 16000, -40.0, min_duration),
+            1600..4800
+        );
+        let trimmed = samples.trim_silence(2, 16000, -40.0, min_duration);
+        assert_eq!(&trimmed[..], &samples[1600 * 2..4800 * 2]);
+
+        // Silence shorter than the minimum duration is kept
+        let samples = Samples::from(padded_take(400, 3200, 4800));
+        assert_eq!(
+            samples.trimmed_range(2, 16000, -40.0, min_duration),
+            0..3600
+        );
+
+        // A silent signal has nothing left
+        let silence: Samples<i16> = Samples::from(vec![0; 1000]);
+        assert!(silence
+            .trimmed_range(2, 16000, -40.0, min_duration)
+            .is_empty());
+    }
+
+    #[test]
+    fn active_windows_are_grouped_into_segments() {
+        let options = SegmentOptions::new().window_len(100).hangover(2);
+        // Windows of sound, in blocks of 100 frames: 3..5, 7..8 after a pause within the hangover, and 15..20 at the end
+        let mut recording = padded_take(300, 200, 0);
+        recording.extend(padded_take(200, 100, 0));
+        recording.extend(padded_take(700, 500, 0));
+        let recording = Samples::from(recording);
+        assert_eq!(
+            recording.active_segments(2, &options),
+            vec![300..1000, 1500..2000]
+        );
+
+        // The result does not depend on how the samples are split into blocks
+        let mut segmenter = Segmenter::new(2, options);
+        recording
+            .chunks(334)
+            .for_each(|block| segmenter.process(block));
+        assert_eq!(segmenter.finish(), vec![300..1000, 1500..2000]);
+
+        // A final partial window is classified on its own, and a quieter threshold keeps the noise
+        let recording = Samples::from(padded_take(250, 50, 10));
+        assert_eq!(recording.active_segments(2, &options), vec![200..310]);
+        let options = options.threshold_db(-90.0);
+        assert_eq!(recording.active_segments(2, &options), vec![200..310]);
+        assert!(Samples::from(vec![0i16; 100])
+            .active_segments(2, &options)
+            .is_empty());
+    }
+
+    #[test]
+    fn files_are_trimmed_a_block_at_a_time() {
+        let fp = format!("{}trim_silence.wav", TEST_OUTPUT);
+        std::fs::create_dir_all(TEST_OUTPUT).unwrap();
+        let samples = Samples::from(padded_take(10000, 20000, 5000));
+        crate::write(&fp, &samples, 16000, 2).unwrap();
+
+        let mut wav: Wav<f32> = Wav::from_path(&fp).unwrap();
+        let frames = wav.trimmed_range(-40.0, Duration::from_millis(50)).unwrap();
+        assert_eq!(frames, 10000..30000);
+        let take = wav
+            .read_frames(frames.start, (frames.end - frames.start) as usize)
+            .unwrap();
+        assert_eq!(take.len(), 40000);
+
+        let options = SegmentOptions::new();
+        assert_eq!(
+            wav.active_segments(&options).unwrap(),
+            samples.active_segments(2, &options)
+        );
+        std::fs::remove_file(&fp).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! Module for reading NIST SPHERE (.sph) files, as used by speech corpora such as TIMIT and Switchboard.
+//!
+//! A SPHERE file starts with a plain text header, typically 1024 bytes long, followed by the sample data.
+//! Only uncompressed PCM, mu-law and A-law sample data is supported. Files compressed with shorten must be decompressed first, for example using ``sph2pipe``.
+use std::{
+    collections::HashMap,
+    io::{Read, SeekFrom},
+    path::Path,
+};
+
+use i24::i24;
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::{alloc_box_buffer, ReadSeek, Wav},
+    error::{FormatError, WaversResult},
+    wav_type::WavType,
+    writer::Endianness,
+};
+
+/// The identifier found at the start of every SPHERE file.
+pub const SPHERE_ID: [u8; 8] = *b"NIST_1A\n";
+
+/// The size of the identifier and header size lines which start every SPHERE header.
+const SPHERE_PREAMBLE_SIZE: usize = 16;
+
+/// The header of a NIST SPHERE file. Stores each field of the header as text, keyed by the field name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SphereHeader {
+    /// The size of the header in bytes. The sample data starts directly after the header.
+    pub header_size: usize,
+    pub fields: HashMap<String, String>,
+}
+
+impl SphereHeader {
+    /// Reads the SPHERE header from the start of the reader.
+    pub fn from_reader(reader: &mut Box<dyn ReadSeek>) -> WaversResult<Self> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut preamble = [0; SPHERE_PREAMBLE_SIZE];
+        reader.read_exact(&mut preamble)?;
+        if preamble[0..8] != SPHERE_ID {
+            return Err(FormatError::InvalidSphereHeader("missing NIST_1A identifier").into());
+        }
+        let header_size: usize = std::str::from_utf8(&preamble[8..16])
+            .ok()
+            .and_then(|size| size.trim().parse().ok())
+            .ok_or(FormatError::InvalidSphereHeader("invalid header size"))?;
+        if header_size < SPHERE_PREAMBLE_SIZE {
+            return Err(FormatError::InvalidSphereHeader("invalid header size").into());
+        }
+
+        let mut bytes = alloc_box_buffer(header_size - SPHERE_PREAMBLE_SIZE);
+        reader.read_exact(&mut bytes)?;
+        let text = String::from_utf8_lossy(&bytes);
+
+        let mut fields = HashMap::new();
+        for line in text.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim() == "end_head" {
+                return Ok(SphereHeader {
+                    header_size,
+                    fields,
+                });
+            }
+            // Each field is written as "name -type value", where string values of type "-sN" are N bytes long
+            let mut parts = line.trim_start().splitn(3, ' ');
+            let (name, field_type, value) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(name), Some(field_type), Some(value)) => (name, field_type, value),
+                _ => continue,
+            };
+            let value = match field_type
+                .strip_prefix("-s")
+                .and_then(|len| len.parse::<usize>().ok())
+            {
+                Some(len) if len <= value.len() && value.is_char_boundary(len) => &value[..len],
+                _ => value.trim(),
+            };
+            fields.insert(name.to_string(), value.to_string());
+        }
+        Err(FormatError::InvalidSphereHeader("missing end_head").into())
+    }
+
+    /// Returns the value of the field with the given name, if present.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(|value| value.as_str())
+    }
+
+    fn get_int(&self, name: &str) -> Option<usize> {
+        self.get(name).and_then(|value| value.parse().ok())
+    }
+
+    /// Returns the sample rate of the audio.
+    pub fn sample_rate(&self) -> WaversResult<i32> {
+        self.get_int("sample_rate")
+            .map(|sample_rate| sample_rate as i32)
+            .ok_or(FormatError::InvalidSphereHeader("missing sample_rate").into())
+    }
+
+    /// Returns the number of channels, which defaults to 1.
+    pub fn channel_count(&self) -> u16 {
+        self.get_int("channel_count").unwrap_or(1) as u16
+    }
+
+    /// Returns the number of samples per channel, if present.
+    pub fn sample_count(&self) -> Option<usize> {
+        self.get_int("sample_count")
+    }
+
+    /// Returns the byte order of the samples. Single byte samples are reported as little-endian.
+    pub fn endianness(&self) -> Endianness {
+        match self.get("sample_byte_format") {
+            // e.g. "10" for big-endian 16-bit samples, and "01" for little-endian
+            Some(byte_format) if byte_format.len() > 1 && !byte_format.starts_with('0') => {
+                Endianness::Big
+            }
+            _ => Endianness::Little,
+        }
+    }
+
+    /// Returns the encoding of the samples, as the equivalent ``WavType``.
+    pub fn wav_type(&self) -> WaversResult<WavType> {
+        let coding = self.get("sample_coding").unwrap_or("pcm");
+        let n_bytes = self.get_int("sample_n_bytes");
+        match (coding, n_bytes) {
+            ("pcm", Some(2) | None) => Ok(WavType::Pcm16),
+            ("pcm", Some(3)) => Ok(WavType::Pcm24),
+            ("pcm", Some(4)) => Ok(WavType::Pcm32),
+            ("ulaw" | "mu-law", _) => Ok(WavType::MuLaw),
+            ("alaw", _) => Ok(WavType::ALaw),
+            // Compressed data, such as "pcm,embedded-shorten-v2.00", and signed 8-bit PCM
+            _ => Err(FormatError::UnsupportedSphereCoding(coding.to_string()).into()),
+        }
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new Wav struct from a NIST SPHERE file.
+    /// The samples are read using the same API as a wav file and can be written as a wav file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    ///
+    /// fn main() {
+    ///     let f = std::fs::File::open("path/to/utterance.sph").unwrap();
+    ///     let mut sph: Wav<f32> = Wav::from_sphere(Box::new(f)).unwrap();
+    ///     let samples = sph.read().unwrap();
+    /// }
+    /// ```
+    pub fn from_sphere(mut reader: Box<dyn ReadSeek>) -> WaversResult<Self> {
+        let header = SphereHeader::from_reader(&mut reader)?;
+        let n_channels = header.channel_count();
+        reader.seek(SeekFrom::Start(header.header_size as u64))?;
+        Wav::from_raw_parts(
+            reader,
+            header.sample_rate()?,
+            n_channels,
+            header.wav_type()?,
+            header.endianness(),
+            header
+                .sample_count()
+                .map(|sample_count| sample_count * n_channels as usize),
+        )
+    }
+
+    /// Construct a new Wav struct from the path of a NIST SPHERE file.
+    /// Uses a BufReader to read the file.
+    pub fn from_sphere_path<P: AsRef<Path>>(path: P) -> WaversResult<Self> {
+        let f = std::fs::File::open(path)?;
+        let buf_reader: Box<dyn ReadSeek> = Box::new(std::io::BufReader::new(f));
+        Self::from_sphere(buf_reader)
+    }
+}
+
+#[cfg(test)]
+mod sphere_tests {
+    use super::*;
+    use crate::Samples;
+
+    const ONE_CHANNEL_WAV_I16: &str = "./test_resources/one_channel_i16.wav";
+
+    /// Builds a SPHERE file containing the samples of the one channel i16 test file.
+    fn sphere_bytes(byte_format: &str, coding: &str) -> Vec<u8> {
+        let wav_bytes = std::fs::read(ONE_CHANNEL_WAV_I16).unwrap();
+        let data = &wav_bytes[44..];
+        let header = format!(
+            "NIST_1A\n   1024\ndatabase_id -s5 TIMIT\nsample_count -i {}\nsample_rate -i 16000\nchannel_count -i 1\nsample_n_bytes -i 2\nsample_byte_format -s2 {}\nsample_coding -s{} {}\nsample_sig_bits -i 16\nend_head\n",
+            data.len() / 2,
+            byte_format,
+            coding.len(),
+            coding
+        );
+        let mut bytes = header.into_bytes();
+        bytes.resize(1024, b' ');
+        for sample in data.chunks_exact(2) {
+            match byte_format {
+                "10" => bytes.extend_from_slice(&[sample[1], sample[0]]),
+                _ => bytes.extend_from_slice(sample),
+            }
+        }
+        // Trailing bytes beyond the sample count are ignored
+        bytes.extend_from_slice(&[0; 4]);
+        bytes
+    }
+
+    #[test]
+    fn can_read_header() {
+        let mut reader: Box<dyn ReadSeek> =
+            Box::new(std::io::Cursor::new(sphere_bytes("10", "pcm")));
+        let header = SphereHeader::from_reader(&mut reader).unwrap();
+        assert_eq!(header.header_size, 1024);
+        assert_eq!(header.get("database_id"), Some("TIMIT"));
+        assert_eq!(header.sample_rate().unwrap(), 16000);
+        assert_eq!(header.channel_count(), 1);
+        assert_eq!(header.endianness(), Endianness::Big);
+        assert_eq!(header.wav_type().unwrap(), WavType::Pcm16);
+    }
+
+    #[test]
+    fn can_read_samples() {
+        let mut wav: Wav<i16> = Wav::from_path(ONE_CHANNEL_WAV_I16).unwrap();
+        let expected_samples: Samples<i16> = wav.read().unwrap();
+
+        for byte_format in ["01", "10"] {
+            let reader: Box<dyn ReadSeek> =
+                Box::new(std::io::Cursor::new(sphere_bytes(byte_format, "pcm")));
+            let mut sph: Wav<i16> = Wav::from_sphere(reader).unwrap();
+            assert_eq!(sph.sample_rate(), 16000);
+            assert_eq!(sph.n_samples(), expected_samples.len());
+            let samples: Samples<i16> = sph.read().unwrap();
+            assert_eq!(&samples[..], &expected_samples[..], "Samples do not match");
+        }
+    }
+
+    #[test]
+    fn shorten_is_unsupported() {
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(sphere_bytes(
+            "01",
+            "pcm,embedded-shorten-v2.00",
+        )));
+        assert!(Wav::<i16>::from_sphere(reader).is_err());
+
+        let reader: Box<dyn ReadSeek> = Box::new(std::io::Cursor::new(vec![0; 1024]));
+        assert!(Wav::<i16>::from_sphere(reader).is_err());
+    }
+}
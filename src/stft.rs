@@ -0,0 +1,267 @@
+//! Module for computing the short-time Fourier transform (STFT) of a wav file, available with the ``fft`` feature.
+//!
+//! The STFT is computed over the windows of a ``WindowIterator``, so each frame of the transform covers ``win_len`` frames of the file and consecutive frames start ``hop`` frames apart.
+//! Each window is multiplied by a ``WindowFunction`` and transformed using the ``rustfft`` crate. Only the non-negative frequencies are kept, since the samples are real.
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use i24::i24;
+pub use rustfft::num_complex::Complex32;
+use rustfft::{Fft, FftPlanner};
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::Wav,
+    iter::{WindowIterator, WindowPadding},
+};
+
+/// The window function applied to each window before it is transformed.
+/// The periodic form of each window is used, which is the usual choice for spectral analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowFunction {
+    /// Leaves the samples unchanged.
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl WindowFunction {
+    /// Returns the coefficients of the window function for a window of the given length.
+    pub fn coefficients(&self, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|n| {
+                let phase = 2.0 * PI * n as f32 / len as f32;
+                match self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 - 0.5 * phase.cos(),
+                    WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+                    WindowFunction::Blackman => {
+                        0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos()
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// An iterator over the frames of the short-time Fourier transform of the Wav struct.
+/// Each item holds the spectrum of every channel for one window, in channel order. A spectrum has ``win_len / 2 + 1`` frequency bins, from 0 Hz up to the Nyquist frequency.
+/// The final window is padded with zeros by default, see ``padding`` to drop it instead.
+/// This should only be used via the ``stft`` function on the Wav struct.
+///
+/// Note: This iterator *should* reset the Wav struct to the beginning of the data chunk when it is done iterating.
+pub struct StftIterator<'a, T: 'a + AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    windows: WindowIterator<'a, T>,
+    fft: Arc<dyn Fft<f32>>,
+    coefficients: Vec<f32>,
+    n_channels: usize,
+    buffer: Vec<Complex32>,
+}
+
+impl<'a, T: 'a + AudioSample> StftIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates an iterator over the STFT of the Wav struct, starting from the start of the data chunk.
+    ///
+    /// Panics if the window length or the hop size is zero.
+    pub fn new(
+        wav: &'a mut Wav<T>,
+        win_len: usize,
+        hop: usize,
+        window_fn: WindowFunction,
+    ) -> StftIterator<'a, T> {
+        let n_channels = wav.n_channels().max(1) as usize;
+        StftIterator {
+            windows: WindowIterator::new(wav, win_len, hop),
+            fft: FftPlanner::new().plan_fft_forward(win_len),
+            coefficients: window_fn.coefficients(win_len),
+            n_channels,
+            buffer: Vec::with_capacity(win_len),
+        }
+    }
+
+    /// Sets how the final window is handled when it extends past the last frame of the file.
+    pub fn padding(mut self, padding: WindowPadding) -> Self {
+        self.windows = self.windows.padding(padding);
+        self
+    }
+
+    /// Returns the number of frequency bins in each spectrum.
+    pub fn n_bins(&self) -> usize {
+        self.coefficients.len() / 2 + 1
+    }
+
+    /// Returns the number of frames the iterator returns in total.
+    pub fn n_frames(&self) -> usize {
+        self.windows.n_windows()
+    }
+}
+
+impl<'a, T: 'a + AudioSample> Iterator for StftIterator<'a, T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    type Item = Vec<Box<[Complex32]>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.windows.next()?;
+        let n_bins = self.n_bins();
+        let spectra = (0..self.n_channels)
+            .map(|channel| {
+                self.buffer.clear();
+                self.buffer.extend(
+                    window
+                        .iter()
+                        .skip(channel)
+                        .step_by(self.n_channels)
+                        .zip(self.coefficients.iter())
+                        .map(|(sample, coefficient)| {
+                            let sample: f32 = sample.convert_to();
+                            Complex32::new(sample * coefficient, 0.0)
+                        }),
+                );
+                self.fft.process(&mut self.buffer);
+                Box::from(&self.buffer[..n_bins])
+            })
+            .collect();
+        Some(spectra)
+    }
+}
+
+impl<T: AudioSample> Wav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Returns an iterator over the short-time Fourier transform of the wav file, using windows of ``win_len`` frames which start every ``hop`` frames.
+    /// Each window is multiplied by ``window_fn`` before it is transformed. See the ``StftIterator`` struct for more information.
+    ///
+    /// Panics if the window length or the hop size is zero.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use wavers::Wav;
+    /// use wavers::stft::WindowFunction;
+    ///
+    /// fn main() {
+    ///     let mut wav: Wav<f32> = Wav::from_path("path/to/wav.wav").unwrap();
+    ///     let spectrogram: Vec<Vec<f32>> = wav
+    ///         .stft(1024, 256, WindowFunction::Hann)
+    ///         .map(|spectra| spectra[0].iter().map(|bin| bin.norm()).collect())
+    ///         .collect();
+    /// }
+    /// ```
+    pub fn stft(
+        &mut self,
+        win_len: usize,
+        hop: usize,
+        window_fn: WindowFunction,
+    ) -> StftIterator<'_, T> {
+        StftIterator::new(self, win_len, hop, window_fn)
+    }
+}
+
+#[cfg(test)]
+mod stft_tests {
+    use super::*;
+    use crate::write;
+
+    #[test]
+    fn sine_peaks_in_its_frequency_bin() {
+        // 1 kHz falls exactly on bin 32 of a 512 point transform at 16 kHz
+        let sample_rate = 16000;
+        let samples: Vec<f32> = (0..sample_rate)
+            .flat_map(|n| {
+                let sample = (2.0 * PI * 1000.0 * n as f32 / sample_rate as f32).sin() * 0.5;
+                [sample, 0.0]
+            })
+            .collect();
+        std::fs::create_dir_all("./test_resources/tmp").unwrap();
+        let fp = "./test_resources/tmp/stft_sine.wav";
+        write(fp, &samples, sample_rate, 2).unwrap();
+
+        let mut wav: Wav<f32> = Wav::from_path(fp).unwrap();
+        let stft = wav.stft(512, 256, WindowFunction::Hann);
+        assert_eq!(stft.n_bins(), 257);
+        assert_eq!(stft.n_frames(), 62);
+
+        let frames: Vec<Vec<Box<[Complex32]>>> = stft.padding(WindowPadding::Drop).collect();
+        assert_eq!(frames.len(), 61);
+        for spectra in frames.iter() {
+            assert_eq!(spectra.len(), 2);
+            let peak = spectra[0]
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.norm().total_cmp(&b.1.norm()))
+                .unwrap()
+                .0;
+            assert_eq!(peak, 32);
+            // A Hann windowed sine has a peak of amplitude * win_len / 4
+            assert!((spectra[0][32].norm() - 64.0).abs() < 0.5);
+            assert!(spectra[1].iter().all(|bin| bin.norm() < 1e-6));
+        }
+
+        std::fs::remove_file(fp).unwrap();
+    }
+
+    #[test]
+    fn window_functions_are_periodic() {
+        let hann = WindowFunction::Hann.coefficients(4);
+        assert_eq!(hann, vec![0.0, 0.5, 1.0, 0.5]);
+        assert!(WindowFunction::Rectangular
+            .coefficients(8)
+            .iter()
+            .all(|coefficient| *coefficient == 1.0));
+        assert!((WindowFunction::Hamming.coefficients(4)[0] - 0.08).abs() < 1e-6);
+        assert!(WindowFunction::Blackman.coefficients(4)[0].abs() < 1e-6);
+    }
+}
@@ -0,0 +1,250 @@
+//! Module containing the ``StreamingWav`` struct, which reads a wav file a page at a time so that the memory used is bounded regardless of the size of the file.
+//!
+//! A page is a whole number of frames which fits in the maximum buffer size given when the ``StreamingWav`` is created.
+//! Pages are read in turn with ``next_page``, or loaded on demand when a sample outside of the current page is requested with ``get``,
+//! so even very large RF64 recordings can be processed on machines with little memory.
+use std::mem::size_of;
+use std::path::Path;
+
+use i24::i24;
+
+use crate::conversion::{AudioSample, ConvertSlice, ConvertTo};
+use crate::core::Wav;
+use crate::error::{FormatError, WaversResult};
+
+/// A wav file which is read a page at a time, keeping at most ``max_buffer_size`` bytes of decoded samples in memory.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::StreamingWav;
+///
+/// // Keep at most 64 MiB of samples in memory
+/// let mut wav: StreamingWav<f32> = StreamingWav::from_path("path/to/huge.wav", 64 << 20).unwrap();
+/// let mut peak = 0.0f32;
+/// while let Some(page) = wav.next_page().unwrap() {
+///     peak = page.iter().fold(peak, |peak, sample| peak.max(sample.abs()));
+/// }
+/// ```
+pub struct StreamingWav<T: AudioSample>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    wav: Wav<T>,
+    page: Vec<T>,
+    page_len: usize,
+    page_start: usize,
+    next_page_start: usize,
+    n_samples: usize,
+}
+
+impl<T: AudioSample> StreamingWav<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new StreamingWav from a Wav struct, which keeps at most ``max_buffer_size`` bytes of decoded samples in memory.
+    ///
+    /// This function will return an error if the buffer cannot hold a single frame, or if the file is block encoded,
+    /// since block encoded files, such as MS ADPCM, can only be decoded a whole file at a time.
+    pub fn new(mut wav: Wav<T>, max_buffer_size: usize) -> WaversResult<Self> {
+        let wav_type = wav.encoding();
+        if wav_type.is_block_encoded() {
+            return Err(FormatError::BlockEncodedFormat(wav_type).into());
+        }
+        let n_channels = wav.n_channels().max(1) as usize;
+        let frame_size = n_channels * size_of::<T>();
+        if max_buffer_size < frame_size {
+            return Err(FormatError::StreamingBufferTooSmall {
+                max_buffer_size,
+                frame_size,
+            }
+            .into());
+        }
+        wav.to_data()?;
+
+        let n_samples = wav.n_samples();
+        Ok(StreamingWav {
+            wav,
+            page: Vec::new(),
+            page_len: max_buffer_size / frame_size * n_channels,
+            page_start: 0,
+            next_page_start: 0,
+            n_samples,
+        })
+    }
+
+    /// Construct a new StreamingWav from a path. See ``new``.
+    pub fn from_path<P: AsRef<Path>>(path: P, max_buffer_size: usize) -> WaversResult<Self> {
+        Self::new(Wav::from_path(path)?, max_buffer_size)
+    }
+
+    /// Returns the underlying Wav struct, e.g. to inspect its header or metadata.
+    pub fn wav(&self) -> &Wav<T> {
+        &self.wav
+    }
+
+    /// Consumes the StreamingWav and returns the underlying Wav struct.
+    pub fn into_inner(self) -> Wav<T> {
+        self.wav
+    }
+
+    /// Returns the number of samples in each full page, which is a whole number of frames.
+    pub fn page_len(&self) -> usize {
+        self.page_len
+    }
+
+    /// Returns the total number of samples in the data chunk.
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    /// Reads the page following the previously read page, starting from the start of the data chunk.
+    /// Returns ``None`` once every page has been read, after which ``rewind`` can be used to read the file again.
+    ///
+    /// This function will return an error if there is an issue reading the file.
+    pub fn next_page(&mut self) -> WaversResult<Option<&[T]>> {
+        if self.next_page_start >= self.n_samples {
+            return Ok(None);
+        }
+        self.load_page(self.next_page_start)?;
+        Ok(Some(&self.page))
+    }
+
+    /// Moves back to the start of the data chunk, so that the next call to ``next_page`` returns the first page.
+    pub fn rewind(&mut self) {
+        self.next_page_start = 0;
+    }
+
+    /// Returns the sample at the given index of the data chunk, loading the page which contains it if it is not the current page.
+    /// Returns ``None`` if the index is past the end of the data chunk.
+    ///
+    /// This function will return an error if there is an issue reading the file, or if the file ends before the sample, e.g. because it is shorter than its header claims.
+    pub fn get(&mut self, index: usize) -> WaversResult<Option<T>> {
+        if index >= self.n_samples {
+            return Ok(None);
+        }
+        if index < self.page_start || index >= self.page_start + self.page.len() {
+            self.load_page(index / self.page_len * self.page_len)?;
+        }
+        match self.page.get(index - self.page_start) {
+            Some(&sample) => Ok(Some(sample)),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("The file ends before sample {} of the data chunk", index),
+            )
+            .into()),
+        }
+    }
+
+    /// Reads the page starting at the given sample into the buffer, reusing its memory.
+    fn load_page(&mut self, start: usize) -> WaversResult<()> {
+        // Consecutive pages follow on from the position of the reader, so only seek when jumping elsewhere
+        if start != self.page_start + self.page.len() || self.page.is_empty() {
+            self.wav.to_data()?;
+            self.wav.seek_by_samples(start as u64)?;
+        }
+        let len = self.page_len.min(self.n_samples - start);
+        self.page.resize(len, T::zero());
+        let n_read = match self.wav.read_into(&mut self.page) {
+            Ok(n_read) => n_read,
+            Err(e) => {
+                // The page no longer holds the samples from page_start, so it is loaded again by the next call
+                self.page.clear();
+                return Err(e);
+            }
+        };
+        self.page.truncate(n_read);
+
+        self.page_start = start;
+        self.next_page_start = start + len;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod streaming_tests {
+    use super::*;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn pages_cover_the_data_chunk_within_the_buffer_size() {
+        let expected = Wav::<f32>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        // 1001 bytes holds 125 stereo f32 frames
+        let mut wav: StreamingWav<f32> =
+            StreamingWav::from_path(TWO_CHANNEL_WAV_I16, 1001).unwrap();
+        assert_eq!(wav.page_len(), 250);
+
+        let mut samples = Vec::new();
+        while let Some(page) = wav.next_page().unwrap() {
+            assert!(std::mem::size_of_val(page) <= 1001);
+            samples.extend_from_slice(page);
+        }
+        assert_eq!(&samples[..], &expected[..]);
+
+        let last = expected.len() - 1;
+        assert_eq!(wav.get(last).unwrap(), Some(expected[last]));
+        assert_eq!(wav.get(3).unwrap(), Some(expected[3]));
+        assert_eq!(wav.get(251).unwrap(), Some(expected[251]));
+        assert_eq!(wav.get(expected.len()).unwrap(), None);
+
+        wav.rewind();
+        assert_eq!(wav.next_page().unwrap().unwrap(), &expected[..250]);
+
+        assert!(StreamingWav::<f32>::from_path(TWO_CHANNEL_WAV_I16, 7).is_err());
+    }
+
+    #[test]
+    fn samples_past_the_end_of_a_truncated_file_are_an_error() {
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+
+        // Cut the data chunk short, leaving the header claiming every sample
+        let mut bytes = std::fs::read(TWO_CHANNEL_WAV_I16).unwrap();
+        let (data_offset, _) = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .header()
+            .data()
+            .into();
+        bytes.truncate(data_offset + 8 + 1000 * 2);
+        let mut wav: StreamingWav<i16> =
+            StreamingWav::new(Wav::from_vec(bytes).unwrap(), 1200).unwrap();
+        assert_eq!(wav.n_samples(), expected.len());
+
+        // Pages of 600 samples, the second of which is cut short
+        assert_eq!(wav.get(599).unwrap(), Some(expected[599]));
+        assert!(wav.get(999).is_err());
+        assert!(wav.get(expected.len() - 1).is_err());
+        assert_eq!(wav.get(3).unwrap(), Some(expected[3]));
+        assert!(wav.get(999).is_err());
+        assert_eq!(wav.get(598).unwrap(), Some(expected[598]));
+    }
+}
@@ -0,0 +1,442 @@
+//! Module containing the ``transcode`` function, which converts a wav file to another encoding, sample rate and number of channels.
+//!
+//! The file is streamed a block of frames at a time, so the memory used is bounded regardless of the length of the file.
+//! Samples are converted to ``f64`` as they are read, mixed to the target number of channels, resampled using a windowed sinc filter and then written in the target encoding.
+//! The filter is built in, so that transcoding does not need the ``resampling`` feature, see the ``resample`` module for how it compares to the resampler of that feature.
+use std::path::Path;
+
+use crate::conversion::{AudioSample, ConvertTo, NoiseShaping};
+use crate::core::Wav;
+use crate::error::{FormatError, WaversResult};
+use crate::wav_type::WavType;
+use crate::writer::{WavWriter, WriteOptions, ITER_BLOCK_FRAMES};
+use crate::{check_write_options, write_file};
+
+/// The number of zero crossings of the resampling filter on each side of its centre.
+/// More zero crossings give a sharper cutoff at the cost of more work per frame.
+const SINC_ZERO_CROSSINGS: usize = 16;
+
+/// The number of points of the filter table between two zero crossings, between which the filter is linearly interpolated.
+const SINC_RESOLUTION: usize = 512;
+
+/// The target of a ``transcode``. Any field left as ``None`` is kept as it is in the source file.
+///
+/// # Examples
+///
+/// ```
+/// use wavers::{TranscodeOptions, WavType};
+///
+/// let options = TranscodeOptions::new()
+///     .target_type(WavType::Pcm16)
+///     .target_rate(16000)
+///     .target_channels(1);
+/// assert_eq!(options.target_rate, Some(16000));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranscodeOptions {
+    /// The encoding of the written file.
+    pub target_type: Option<WavType>,
+    /// The sample rate of the written file.
+    pub target_rate: Option<i32>,
+    /// The number of channels of the written file.
+    pub target_channels: Option<u16>,
+    /// The seed of the TPDF dither added when the bit depth is reduced. See ``WriteOptions::dither``.
+    pub dither: Option<u64>,
+    /// The noise shaping of the dither. See ``WriteOptions::noise_shaping``.
+    pub noise_shaping: NoiseShaping,
+}
+
+impl TranscodeOptions {
+    /// Creates options which keep the encoding, sample rate and number of channels of the source file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the encoding of the written file.
+    pub fn target_type(mut self, target_type: WavType) -> Self {
+        self.target_type = Some(target_type);
+        self
+    }
+
+    /// Sets the sample rate of the written file.
+    pub fn target_rate(mut self, target_rate: i32) -> Self {
+        self.target_rate = Some(target_rate);
+        self
+    }
+
+    /// Sets the number of channels of the written file.
+    pub fn target_channels(mut self, target_channels: u16) -> Self {
+        self.target_channels = Some(target_channels);
+        self
+    }
+
+    /// Adds TPDF dither seeded with ``seed`` when the bit depth is reduced, e.g. when transcoding 32-bit PCM to 16-bit PCM. See ``WriteOptions::dither``.
+    pub fn dither(mut self, seed: u64) -> Self {
+        self.dither = Some(seed);
+        self
+    }
+
+    /// Shapes the dither added when the bit depth is reduced with the given curve. See ``WriteOptions::noise_shaping``.
+    pub fn noise_shaping(mut self, noise_shaping: NoiseShaping) -> Self {
+        self.noise_shaping = noise_shaping;
+        self
+    }
+}
+
+/// Converts the wav file at ``src`` to the encoding, sample rate and number of channels of ``options`` and writes it to ``dst``.
+///
+/// The file is read and written a block of frames at a time, so long recordings are never loaded into memory as a whole.
+/// Block encoded files (MS ADPCM and GSM 6.10) are the exception, as they are decoded as a whole before being converted.
+/// When ``options`` does not set an encoding the encoding of ``src`` is kept, except that block encoded files are written as 16-bit PCM.
+/// ``dst`` is written atomically, so it can be ``src``, see ``WriteOptions::atomic``.
+///
+/// Channels can be mixed down to mono, in which case every channel is given the same weight, or a mono file can be copied to every channel.
+/// Any other change to the number of channels is an error, as there is no one way to map between the channels.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{transcode, TranscodeOptions, WavType};
+///
+/// fn main() {
+///     let options = TranscodeOptions::new()
+///         .target_type(WavType::Pcm16)
+///         .target_rate(16000)
+///         .target_channels(1);
+///     transcode("./recording.wav", "./recording_16k_mono.wav", &options).unwrap();
+/// }
+/// ```
+pub fn transcode<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    options: &TranscodeOptions,
+) -> WaversResult<()> {
+    let mut wav: Wav<f64> = Wav::from_path(src)?;
+    let encoding = wav.encoding();
+    let n_channels = wav.n_channels();
+    let sample_rate = wav.sample_rate();
+
+    let target_channels = options.target_channels.unwrap_or(n_channels);
+    if target_channels == 0
+        || !(target_channels == n_channels || target_channels == 1 || n_channels == 1)
+    {
+        return Err(FormatError::ChannelConversion {
+            from: n_channels,
+            to: target_channels,
+        }
+        .into());
+    }
+    let target_rate = options.target_rate.unwrap_or(sample_rate);
+    if target_rate <= 0 {
+        return Err(FormatError::InvalidSampleRate(target_rate).into());
+    }
+    // Written to a temporary file which replaces ``dst``, so that a file can be transcoded in place
+    let mut write_options = WriteOptions::new().atomic(true);
+    write_options = match (options.target_type, encoding) {
+        (Some(target_type), _) => write_options.encoding(target_type),
+        (None, WavType::MsAdpcm | WavType::Gsm610) => write_options.encoding(WavType::Pcm16),
+        (None, encoding) => write_options.encoding(encoding),
+    };
+    if options.target_type.is_none() && wav.padding_bits() > 0 {
+        write_options = write_options.valid_bits_per_sample(wav.valid_bits_per_sample());
+    }
+    if let Some(seed) = options.dither {
+        write_options = write_options.dither(seed);
+    }
+    write_options = write_options.noise_shaping(options.noise_shaping);
+
+    // Block encoded files cannot be read a block of frames at a time, so they are decoded up front
+    let mut decoded = match encoding.is_block_encoded() {
+        true => Some((wav.read()?, 0)),
+        false => None,
+    };
+    let n_in = n_channels as usize;
+    let mut read = |buf: &mut [f64]| -> WaversResult<usize> {
+        match decoded.as_mut() {
+            Some((samples, offset)) => {
+                let n = buf.len().min(samples.len() - *offset);
+                buf[..n].copy_from_slice(&samples[*offset..*offset + n]);
+                *offset += n;
+                Ok(n / n_in)
+            }
+            None => wav.read_block(buf, buf.len() / n_in),
+        }
+    };
+
+    let mut resampler = (target_rate != sample_rate)
+        .then(|| Resampler::new(sample_rate, target_rate, target_channels));
+    check_write_options::<f64>(target_rate, target_channels, &write_options)?;
+    write_file(dst, &write_options, |f| {
+        let mut writer: WavWriter<f64, _> = WavWriter::new(
+            write_options.buf_writer(f),
+            target_rate,
+            target_channels,
+            &write_options,
+        )?;
+        let mut frames = vec![0.0; ITER_BLOCK_FRAMES * n_in];
+        let mut mixed = Vec::with_capacity(ITER_BLOCK_FRAMES * target_channels as usize);
+        let mut resampled = Vec::new();
+        loop {
+            let n_frames = read(&mut frames)?;
+            mixed.clear();
+            mix_channels(
+                &frames[..n_frames * n_in],
+                n_in,
+                target_channels as usize,
+                &mut mixed,
+            );
+            let block = match resampler.as_mut() {
+                Some(resampler) => {
+                    resampled.clear();
+                    resampler.process(&mixed, n_frames == 0, &mut resampled);
+                    &resampled
+                }
+                None => &mixed,
+            };
+            writer.write_frames(block)?;
+            if n_frames == 0 {
+                break;
+            }
+        }
+        writer.finalize()?;
+        Ok(())
+    })
+}
+
+/// Appends the interleaved ``frames`` of ``from`` channels to ``out`` as frames of ``to`` channels,
+/// averaging the channels when mixing down to mono and copying the channel when mixing up from mono.
+fn mix_channels(frames: &[f64], from: usize, to: usize, out: &mut Vec<f64>) {
+    if from == to {
+        out.extend_from_slice(frames);
+    } else if to == 1 {
+        out.extend(
+            frames
+                .chunks_exact(from)
+                .map(|frame| frame.iter().sum::<f64>() / from as f64),
+        );
+    } else {
+        for &sample in frames {
+            out.resize(out.len() + to, sample);
+        }
+    }
+}
+
+/// Converts interleaved frames from one sample rate to another, a block at a time, using a windowed sinc filter.
+///
+/// The filter cuts off at the lower of the two Nyquist frequencies, so downsampling does not alias.
+/// Only the input frames which are still needed by the filter are kept between blocks.
+pub(crate) struct Resampler {
+    /// The input and output sample rates, divided by their greatest common divisor.
+    in_rate: u64,
+    out_rate: u64,
+    n_channels: usize,
+    /// The cutoff frequency of the filter relative to the input Nyquist frequency.
+    cutoff: f64,
+    /// The number of input frames on each side of an output frame which the filter reaches.
+    half_width: f64,
+    /// One side of the filter, from its centre to its last zero crossing.
+    table: Vec<f64>,
+    /// The buffered input frames and the index of the first of them.
+    input: Vec<f64>,
+    input_start: u64,
+    /// The index of the next output frame.
+    next_out: u64,
+}
+
+impl Resampler {
+    pub(crate) fn new(in_rate: i32, out_rate: i32, n_channels: u16) -> Self {
+        let (in_rate, out_rate) = (in_rate as u64, out_rate as u64);
+        let divisor = gcd(in_rate, out_rate);
+        let cutoff = (out_rate as f64 / in_rate as f64).min(1.0);
+        let n_points = SINC_ZERO_CROSSINGS * SINC_RESOLUTION;
+        let table = (0..=n_points + 1)
+            .map(|i| {
+                let x = i as f64 / SINC_RESOLUTION as f64;
+                let sinc = match i {
+                    0 => 1.0,
+                    _ => (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x),
+                };
+                // A Blackman window, which reaches zero at the last zero crossing
+                let w = std::f64::consts::PI * (x / SINC_ZERO_CROSSINGS as f64 + 1.0);
+                let window = (0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos()).max(0.0);
+                cutoff * sinc * window
+            })
+            .collect();
+        Resampler {
+            in_rate: in_rate / divisor,
+            out_rate: out_rate / divisor,
+            n_channels: n_channels as usize,
+            cutoff,
+            half_width: SINC_ZERO_CROSSINGS as f64 / cutoff,
+            table,
+            input: Vec::new(),
+            input_start: 0,
+            next_out: 0,
+        }
+    }
+
+    /// Returns the filter at ``distance`` input frames from its centre.
+    fn filter(&self, distance: f64) -> f64 {
+        let position = distance.abs() * self.cutoff * SINC_RESOLUTION as f64;
+        let index = position as usize;
+        if index + 1 >= self.table.len() {
+            return 0.0;
+        }
+        let fraction = position - index as f64;
+        self.table[index] + fraction * (self.table[index + 1] - self.table[index])
+    }
+
+    /// Adds the interleaved ``frames`` to the input and appends every output frame which can be computed to ``out``.
+    /// Once ``finished`` is set the input is taken to be silent after its last frame and the remaining output frames are computed.
+    pub(crate) fn process(&mut self, frames: &[f64], finished: bool, out: &mut Vec<f64>) {
+        let n_channels = self.n_channels;
+        self.input.extend_from_slice(frames);
+        let input_end = self.input_start + (self.input.len() / n_channels) as u64;
+        // The number of output frames covering the whole input, rounded up
+        let out_end = (input_end * self.out_rate).div_ceil(self.in_rate);
+
+        loop {
+            // The position of the output frame in input frames, kept exact as a whole number and a fraction
+            let whole = self.next_out * self.in_rate / self.out_rate;
+            let fraction =
+                (self.next_out * self.in_rate % self.out_rate) as f64 / self.out_rate as f64;
+            let centre = whole as f64 + fraction;
+            if finished {
+                if self.next_out >= out_end {
+                    break;
+                }
+            } else if centre + self.half_width >= input_end as f64 {
+                break;
+            }
+
+            let first = ((centre - self.half_width).ceil().max(0.0) as u64).max(self.input_start);
+            let last = ((centre + self.half_width).floor() as u64).min(input_end.saturating_sub(1));
+            let start = out.len();
+            out.resize(start + n_channels, 0.0);
+            for frame in first..=last {
+                let weight = self.filter(centre - frame as f64);
+                let offset = (frame - self.input_start) as usize * n_channels;
+                for (sample, input) in out[start..]
+                    .iter_mut()
+                    .zip(&self.input[offset..offset + n_channels])
+                {
+                    *sample += weight * input;
+                }
+            }
+            self.next_out += 1;
+        }
+
+        // Frames before the reach of the next output frame are no longer needed
+        let centre = (self.next_out * self.in_rate) as f64 / self.out_rate as f64;
+        let keep_from =
+            ((centre - self.half_width).floor().max(0.0) as u64).clamp(self.input_start, input_end);
+        self.input
+            .drain(..(keep_from - self.input_start) as usize * n_channels);
+        self.input_start = keep_from;
+    }
+}
+
+impl Resampler {
+    /// Resamples the interleaved ``frames`` in the same way as ``process``, converting them to ``f64`` and back.
+    pub(crate) fn process_samples<T: AudioSample>(&mut self, frames: &[T], finished: bool) -> Vec<T>
+    where
+        f64: ConvertTo<T>,
+    {
+        let frames: Vec<f64> = frames.iter().map(|sample| sample.convert_to()).collect();
+        let mut out = Vec::new();
+        self.process(&frames, finished, &mut out);
+        out.iter().map(|sample| sample.convert_to()).collect()
+    }
+}
+
+/// Returns the greatest common divisor of ``a`` and ``b``.
+pub(crate) fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+#[cfg(test)]
+mod transcode_tests {
+    use super::*;
+    use crate::error::WaversError;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+    const OUT_DIR: &str = "./test_resources/tmp";
+
+    #[test]
+    fn unchanged_files_are_copied() {
+        let out_fp = format!("{}/transcode_copy.wav", OUT_DIR);
+        std::fs::create_dir_all(OUT_DIR).unwrap();
+        transcode(TWO_CHANNEL_WAV_I16, &out_fp, &TranscodeOptions::new()).unwrap();
+
+        let expected = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut wav: Wav<i16> = Wav::from_path(&out_fp).unwrap();
+        assert_eq!(wav.encoding(), WavType::Pcm16);
+        assert_eq!(wav.n_channels(), 2);
+        assert_eq!(wav.read().unwrap(), expected);
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+
+    #[test]
+    fn sines_are_resampled_and_mixed() {
+        let in_fp = format!("{}/transcode_sine_in.wav", OUT_DIR);
+        let out_fp = format!("{}/transcode_sine_out.wav", OUT_DIR);
+        std::fs::create_dir_all(OUT_DIR).unwrap();
+        let sine = |t: f64| 0.5 * (2.0 * std::f64::consts::PI * 440.0 * t).sin();
+
+        for (in_rate, out_rate) in [(48000, 16000), (16000, 44100)] {
+            // A stereo sine, the left channel in phase with the right at half the amplitude, so the mono mix is 0.75 of the sine
+            let n_frames = in_rate as usize / 2;
+            let samples: Vec<f32> = (0..n_frames)
+                .flat_map(|i| {
+                    let s = sine(i as f64 / in_rate as f64) as f32;
+                    [s / 2.0, s]
+                })
+                .collect();
+            crate::write(&in_fp, &samples, in_rate, 2).unwrap();
+
+            let options = TranscodeOptions::new()
+                .target_type(WavType::Pcm16)
+                .target_rate(out_rate)
+                .target_channels(1);
+            transcode(&in_fp, &out_fp, &options).unwrap();
+
+            let mut wav: Wav<f64> = Wav::from_path(&out_fp).unwrap();
+            assert_eq!(wav.encoding(), WavType::Pcm16);
+            assert_eq!(wav.sample_rate(), out_rate);
+            assert_eq!(wav.n_channels(), 1);
+            let out = wav.read().unwrap();
+            assert_eq!(out.len(), n_frames * out_rate as usize / in_rate as usize);
+            // Away from the edges of the file, where the filter reaches past the samples
+            let edge = out.len() / 10;
+            for (i, sample) in out.iter().enumerate().take(out.len() - edge).skip(edge) {
+                let expected = 0.75 * sine(i as f64 / out_rate as f64);
+                assert!(
+                    (sample - expected).abs() < 1e-3,
+                    "{} {} {}",
+                    i,
+                    sample,
+                    expected
+                );
+            }
+        }
+        std::fs::remove_file(&in_fp).unwrap();
+        std::fs::remove_file(&out_fp).unwrap();
+    }
+
+    #[test]
+    fn only_mono_channels_are_mixed() {
+        let out_fp = format!("{}/transcode_channels.wav", OUT_DIR);
+        let options = TranscodeOptions::new().target_channels(3);
+        match transcode(TWO_CHANNEL_WAV_I16, &out_fp, &options) {
+            Err(WaversError::Format(FormatError::ChannelConversion { from: 2, to: 3 })) => {}
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert!(!Path::new(&out_fp).exists());
+    }
+}
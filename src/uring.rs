@@ -0,0 +1,305 @@
+//! Module containing the ``UringBatch`` struct, which reads and writes many wav files at once using io_uring on Linux, available with the ``io_uring`` feature.
+//!
+//! When ingesting a dataset of thousands of small files, the time spent in system calls can outweigh the time spent decoding.
+//! A ``UringBatch`` submits the reads, or writes, of a whole batch of files to the kernel together using the ``io-uring`` crate,
+//! and each file is then decoded from, or encoded into, memory in the same way as by ``Wav::from_vec`` and ``write_with_options``.
+use std::collections::VecDeque;
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use i24::i24;
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::{
+    conversion::{AudioSample, ConvertSlice, ConvertTo},
+    core::Wav,
+    error::WaversResult,
+    writer::WriteOptions,
+};
+
+/// The default number of reads or writes which are in flight at once.
+pub const URING_QUEUE_DEPTH: u32 = 64;
+
+/// An io_uring instance which reads and writes batches of wav files, keeping up to ``queue_depth`` reads or writes in flight at once.
+///
+/// The result of each file is returned separately, so one missing or invalid file does not fail the rest of its batch.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::uring::UringBatch;
+/// use wavers::Wav;
+///
+/// let paths: Vec<String> = (0..1000).map(|i| format!("dataset/{i}.wav")).collect();
+/// let mut batch = UringBatch::new(wavers::uring::URING_QUEUE_DEPTH).unwrap();
+/// for wav in batch.read_wavs::<f32, _>(&paths).unwrap() {
+///     let samples = wav.unwrap().read().unwrap();
+/// }
+/// ```
+pub struct UringBatch {
+    ring: IoUring,
+    queue_depth: usize,
+}
+
+impl UringBatch {
+    /// Construct a new UringBatch which keeps up to ``queue_depth`` reads or writes in flight at once.
+    ///
+    /// This function will return an error if io_uring is not available, e.g. on kernels older than 5.1 or where it has been disabled.
+    pub fn new(queue_depth: u32) -> WaversResult<Self> {
+        let queue_depth = queue_depth.max(1);
+        Ok(UringBatch {
+            ring: IoUring::new(queue_depth)?,
+            queue_depth: queue_depth as usize,
+        })
+    }
+
+    /// Reads the wav files at the given paths into memory and returns a Wav struct for each, in the same order, as if created by ``Wav::from_vec``.
+    ///
+    /// This function will return an error if the ring cannot be submitted to. Errors opening, reading or parsing each file are returned in its place.
+    pub fn read_wavs<T: AudioSample, P: AsRef<Path>>(
+        &mut self,
+        paths: &[P],
+    ) -> WaversResult<Vec<WaversResult<Wav<T>>>>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        let mut files: Vec<std::io::Result<File>> = Vec::with_capacity(paths.len());
+        let mut buffers: Vec<Vec<u8>> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let file = File::open(path).and_then(|file| {
+                let len = file.metadata()?.len() as usize;
+                buffers.push(vec![0; len]);
+                Ok(file)
+            });
+            if file.is_err() {
+                buffers.push(Vec::new());
+            }
+            files.push(file);
+        }
+
+        let lens: Vec<usize> = buffers.iter().map(|buffer| buffer.len()).collect();
+        let results = self.run(&lens, |i, done| {
+            let fd = files[i].as_ref().map(|file| file.as_raw_fd()).unwrap_or(-1);
+            let buffer = &mut buffers[i][done..];
+            let len = buffer.len().min(u32::MAX as usize) as u32;
+            opcode::Read::new(types::Fd(fd), buffer.as_mut_ptr(), len)
+                .offset(done as u64)
+                .build()
+        })?;
+
+        Ok(files
+            .into_iter()
+            .zip(results)
+            .zip(buffers)
+            .map(|((file, result), buffer)| {
+                file?;
+                result?;
+                Wav::from_vec(buffer)
+            })
+            .collect())
+    }
+
+    /// Writes each set of samples to its path as a wav file, in the same way as ``write_with_options``.
+    /// Every file is written with the same sample rate, number of channels and options.
+    ///
+    /// This function will return an error if the ring cannot be submitted to. Errors encoding, creating or writing each file are returned in its place.
+    pub fn write_wavs<T: AudioSample, P: AsRef<Path>>(
+        &mut self,
+        files: &[(P, &[T])],
+        sample_rate: i32,
+        n_channels: u16,
+        options: &WriteOptions,
+    ) -> WaversResult<Vec<WaversResult<()>>>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        let mut buffers: Vec<Vec<u8>> = Vec::with_capacity(files.len());
+        let mut created: Vec<WaversResult<File>> = Vec::with_capacity(files.len());
+        for (path, samples) in files {
+            let mut buffer: Vec<u8> = Vec::new();
+            let file = crate::write_to(
+                &mut buffer,
+                samples,
+                sample_rate,
+                n_channels,
+                options,
+                &mut |_, _| Ok(()),
+            )
+            .and_then(|_| Ok(File::create(path)?));
+            if file.is_err() {
+                buffer.clear();
+            }
+            buffers.push(buffer);
+            created.push(file);
+        }
+
+        let lens: Vec<usize> = buffers.iter().map(|buffer| buffer.len()).collect();
+        let results = self.run(&lens, |i, done| {
+            let fd = created[i]
+                .as_ref()
+                .map(|file| file.as_raw_fd())
+                .unwrap_or(-1);
+            let buffer = &buffers[i][done..];
+            let len = buffer.len().min(u32::MAX as usize) as u32;
+            opcode::Write::new(types::Fd(fd), buffer.as_ptr(), len)
+                .offset(done as u64)
+                .build()
+        })?;
+
+        Ok(created
+            .into_iter()
+            .zip(results)
+            .map(|(file, result)| {
+                file?;
+                Ok(result?)
+            })
+            .collect())
+    }
+
+    /// Transfers ``lens[i]`` bytes for every ``i``, submitting the entry returned by ``entry(i, done)`` to transfer the rest of them after the first ``done``,
+    /// until every transfer has finished or failed. Short transfers are resubmitted, and empty transfers are never submitted.
+    /// If waiting on the ring fails, nothing more is submitted, but the entries already in flight are still waited for before the error is returned.
+    fn run(
+        &mut self,
+        lens: &[usize],
+        mut entry: impl FnMut(usize, usize) -> squeue::Entry,
+    ) -> std::io::Result<Vec<std::io::Result<()>>> {
+        let mut done = vec![0usize; lens.len()];
+        let mut results: Vec<std::io::Result<()>> = lens.iter().map(|_| Ok(())).collect();
+        let mut pending: VecDeque<usize> = (0..lens.len()).filter(|i| lens[*i] > 0).collect();
+        let mut n_in_flight = 0;
+        let mut error = None;
+
+        while n_in_flight > 0 || (error.is_none() && !pending.is_empty()) {
+            if error.is_none() {
+                let mut submission = self.ring.submission();
+                while n_in_flight < self.queue_depth {
+                    let Some(i) = pending.pop_front() else {
+                        break;
+                    };
+                    let sqe = entry(i, done[i]).user_data(i as u64);
+                    // Safety: the buffers the entries point to outlive the ring's use of them, since every submitted entry is waited for before returning
+                    if unsafe { submission.push(&sqe) }.is_err() {
+                        pending.push_front(i);
+                        break;
+                    }
+                    n_in_flight += 1;
+                }
+            }
+
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => (),
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::Interrupted | std::io::ErrorKind::WouldBlock
+                    ) => {}
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+
+            for cqe in self.ring.completion() {
+                let i = cqe.user_data() as usize;
+                n_in_flight -= 1;
+                match cqe.result() {
+                    n if n < 0 => results[i] = Err(std::io::Error::from_raw_os_error(-n)),
+                    0 => results[i] = Err(std::io::ErrorKind::UnexpectedEof.into()),
+                    n => {
+                        done[i] += n as usize;
+                        if done[i] < lens[i] {
+                            pending.push_back(i);
+                        }
+                    }
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(results),
+        }
+    }
+}
+
+#[cfg(test)]
+mod uring_tests {
+    use super::*;
+
+    const ONE_CHANNEL_WAV_I16: &str = "./test_resources/one_channel_i16.wav";
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+
+    #[test]
+    fn batches_match_single_files() {
+        let paths = [
+            ONE_CHANNEL_WAV_I16,
+            "./test_resources/missing.wav",
+            TWO_CHANNEL_WAV_I16,
+        ];
+        // A small queue depth so that the batch does not fit in the ring at once
+        let mut batch = UringBatch::new(1).unwrap();
+        let mut wavs = batch.read_wavs::<i16, _>(&paths).unwrap();
+        assert_eq!(wavs.len(), 3);
+        assert!(wavs[1].is_err());
+        let one_channel = wavs[0].as_mut().unwrap().read().unwrap();
+        let two_channel = wavs[2].as_mut().unwrap().read().unwrap();
+        assert_eq!(
+            one_channel,
+            Wav::<i16>::from_path(ONE_CHANNEL_WAV_I16)
+                .unwrap()
+                .read()
+                .unwrap()
+        );
+        assert_eq!(
+            two_channel,
+            Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+                .unwrap()
+                .read()
+                .unwrap()
+        );
+
+        std::fs::create_dir_all("./test_resources/tmp").unwrap();
+        let out_paths = [
+            "./test_resources/tmp/uring_0.wav",
+            "./test_resources/tmp/uring_1.wav",
+        ];
+        let reversed: Vec<i16> = one_channel.iter().rev().copied().collect();
+        let files = [
+            (out_paths[0], &one_channel[..]),
+            (out_paths[1], &reversed[..]),
+        ];
+        let written = batch
+            .write_wavs(&files, 16000, 1, &WriteOptions::new())
+            .unwrap();
+        assert!(written.iter().all(|result| result.is_ok()));
+        for (path, samples) in files {
+            assert_eq!(
+                &Wav::<i16>::from_path(path).unwrap().read().unwrap()[..],
+                samples
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -9,11 +9,17 @@ use pyo3::prelude::*;
 
 use crate::{error::FormatError, i24, WaversError, WaversResult};
 
+const PCM_8_BITS: u16 = (std::mem::size_of::<u8>() * 8) as u16;
 const PCM_16_BITS: u16 = (std::mem::size_of::<i16>() * 8) as u16;
 const PCM_24_BITS: u16 = (std::mem::size_of::<i24>() * 8) as u16;
 const PCM_32_BITS: u16 = (std::mem::size_of::<i32>() * 8) as u16;
 const FLOAT_32_BITS: u16 = (std::mem::size_of::<f32>() * 8) as u16;
 const FLOAT_64_BITS: u16 = (std::mem::size_of::<f64>() * 8) as u16;
+const ALAW_BITS: u16 = 8;
+const MULAW_BITS: u16 = 8;
+const MS_ADPCM_BITS: u16 = 4;
+// GSM 6.10 files conventionally store zero bits per sample since the samples are packed in 260-bit frames
+const GSM_610_BITS: u16 = 0;
 
 /// An enum representing some of the format codes in the wav file format.
 #[allow(non_camel_case_types)]
@@ -21,9 +27,11 @@ const FLOAT_64_BITS: u16 = (std::mem::size_of::<f64>() * 8) as u16;
 #[cfg_attr(feature = "pyo3", pyclass)]
 pub enum FormatCode {
     WAV_FORMAT_PCM = 0x0001,
+    WAVE_FORMAT_ADPCM = 0x0002,
     WAV_FORMAT_IEEE_FLOAT = 0x0003,
     WAVE_FORMAT_ALAW = 0x0006,
     WAVE_FORMAT_MULAW = 0x0007,
+    WAVE_FORMAT_GSM610 = 0x0031,
     WAVE_FORMAT_EXTENSIBLE = 0xFFFE,
 }
 
@@ -48,9 +56,11 @@ impl Display for FormatCode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FormatCode::WAV_FORMAT_PCM => write!(f, "WAV_FORMAT_PCM"),
+            FormatCode::WAVE_FORMAT_ADPCM => write!(f, "WAVE_FORMAT_ADPCM"),
             FormatCode::WAV_FORMAT_IEEE_FLOAT => write!(f, "WAV_FORMAT_IEEE_FLOAT"),
             FormatCode::WAVE_FORMAT_ALAW => write!(f, "WAVE_FORMAT_ALAW"),
             FormatCode::WAVE_FORMAT_MULAW => write!(f, "WAVE_FORMAT_MULAW"),
+            FormatCode::WAVE_FORMAT_GSM610 => write!(f, "WAVE_FORMAT_GSM610"),
             FormatCode::WAVE_FORMAT_EXTENSIBLE => write!(f, "WAV_EXTENSIBLE_FORMAT"),
         }
     }
@@ -62,9 +72,11 @@ impl TryFrom<u16> for FormatCode {
     fn try_from(value: u16) -> Result<Self, Self::Error> {
         match value {
             0x0001 => Ok(FormatCode::WAV_FORMAT_PCM),
+            0x0002 => Ok(FormatCode::WAVE_FORMAT_ADPCM),
             0x0003 => Ok(FormatCode::WAV_FORMAT_IEEE_FLOAT),
             0x0006 => Ok(FormatCode::WAVE_FORMAT_ALAW),
             0x0007 => Ok(FormatCode::WAVE_FORMAT_MULAW),
+            0x0031 => Ok(FormatCode::WAVE_FORMAT_GSM610),
             0xFFFE => Ok(FormatCode::WAVE_FORMAT_EXTENSIBLE),
             _ => Err(FormatError::InvalidBitsPerSample(value).into()),
         }
@@ -75,6 +87,7 @@ impl TryFrom<u16> for FormatCode {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "pyo3", pyclass)]
 pub enum WavType {
+    Pcm8,
     Pcm16,
     Pcm24,
     Pcm32,
@@ -85,35 +98,63 @@ pub enum WavType {
     EPcm32,
     EFloat32,
     EFloat64,
+    EPcm8,
+    ALaw,
+    MuLaw,
+    MsAdpcm,
+    Gsm610,
 }
 
 impl WavType {
     /// Converts the WavType to the number of bytes per sample.
+    /// Block encoded types, such as MS ADPCM and GSM 6.10, do not have a whole number of bytes per sample and return 0.
     pub const fn n_bytes(&self) -> usize {
         match self {
+            WavType::Pcm8 | WavType::EPcm8 => std::mem::size_of::<u8>(),
             WavType::Pcm16 | WavType::EPcm16 => std::mem::size_of::<i16>(),
             WavType::Pcm24 | WavType::EPcm24 => std::mem::size_of::<i24>(),
             WavType::Pcm32 | WavType::EPcm32 => std::mem::size_of::<i32>(),
             WavType::Float32 | WavType::EFloat32 => std::mem::size_of::<f32>(),
             WavType::Float64 | WavType::EFloat64 => std::mem::size_of::<f64>(),
+            WavType::ALaw | WavType::MuLaw => std::mem::size_of::<u8>(),
+            WavType::MsAdpcm | WavType::Gsm610 => 0,
         }
     }
 
     /// Converts the WavType to the number of bits per sample.
     pub const fn n_bits(&self) -> u16 {
         match self {
+            WavType::Pcm8 | WavType::EPcm8 => PCM_8_BITS,
             WavType::Pcm16 | WavType::EPcm16 => PCM_16_BITS,
             WavType::Pcm24 | WavType::EPcm24 => PCM_24_BITS,
             WavType::Pcm32 | WavType::EPcm32 => PCM_32_BITS,
             WavType::Float32 | WavType::EFloat32 => FLOAT_32_BITS,
             WavType::Float64 | WavType::EFloat64 => FLOAT_64_BITS,
+            WavType::ALaw => ALAW_BITS,
+            WavType::MuLaw => MULAW_BITS,
+            WavType::MsAdpcm => MS_ADPCM_BITS,
+            WavType::Gsm610 => GSM_610_BITS,
         }
     }
+
+    /// Returns true if the samples are encoded in blocks which must be decoded as a whole, such as MS ADPCM or GSM 6.10.
+    pub const fn is_block_encoded(&self) -> bool {
+        matches!(self, WavType::MsAdpcm | WavType::Gsm610)
+    }
+
+    /// Returns true if the samples are IEEE floats, which unlike the other encodings can hold samples beyond full scale.
+    pub const fn is_float(&self) -> bool {
+        matches!(
+            self,
+            WavType::Float32 | WavType::EFloat32 | WavType::Float64 | WavType::EFloat64
+        )
+    }
 }
 
 impl Display for WavType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            WavType::Pcm8 => write!(f, "PCM_8"),
             WavType::Pcm16 => write!(f, "PCM_16"),
             WavType::Pcm24 => write!(f, "PCM_24"),
             WavType::Pcm32 => write!(f, "PCM_32"),
@@ -124,6 +165,11 @@ impl Display for WavType {
             WavType::EPcm32 => write!(f, "EXTENSIBLE_PCM_32"),
             WavType::EFloat32 => write!(f, "EXTENSIBLE_IEEE_FLOAT_32"),
             WavType::EFloat64 => write!(f, "EXTENSIBLE_IEEE_FLOAT_64"),
+            WavType::EPcm8 => write!(f, "EXTENSIBLE_PCM_8"),
+            WavType::ALaw => write!(f, "A_LAW"),
+            WavType::MuLaw => write!(f, "MU_LAW"),
+            WavType::MsAdpcm => write!(f, "MS_ADPCM"),
+            WavType::Gsm610 => write!(f, "GSM_610"),
         }
     }
 }
@@ -131,11 +177,19 @@ impl Display for WavType {
 /// Converts a tuple of format codes and bits per sample to a WavType.
 pub const fn format_info_to_wav_type(info: (FormatCode, u16, FormatCode)) -> WaversResult<WavType> {
     Ok(match info {
+        (FormatCode::WAV_FORMAT_PCM, PCM_8_BITS, _) => WavType::Pcm8,
         (FormatCode::WAV_FORMAT_PCM, PCM_16_BITS, _) => WavType::Pcm16,
         (FormatCode::WAV_FORMAT_PCM, PCM_24_BITS, _) => WavType::Pcm24,
         (FormatCode::WAV_FORMAT_PCM, PCM_32_BITS, _) => WavType::Pcm32,
         (FormatCode::WAV_FORMAT_IEEE_FLOAT, FLOAT_32_BITS, _) => WavType::Float32,
         (FormatCode::WAV_FORMAT_IEEE_FLOAT, FLOAT_64_BITS, _) => WavType::Float64,
+        (FormatCode::WAVE_FORMAT_ALAW, ALAW_BITS, _) => WavType::ALaw,
+        (FormatCode::WAVE_FORMAT_MULAW, MULAW_BITS, _) => WavType::MuLaw,
+        (FormatCode::WAVE_FORMAT_ADPCM, MS_ADPCM_BITS, _) => WavType::MsAdpcm,
+        (FormatCode::WAVE_FORMAT_GSM610, _, _) => WavType::Gsm610,
+        (FormatCode::WAVE_FORMAT_EXTENSIBLE, PCM_8_BITS, FormatCode::WAV_FORMAT_PCM) => {
+            WavType::EPcm8
+        }
         (FormatCode::WAVE_FORMAT_EXTENSIBLE, PCM_16_BITS, FormatCode::WAV_FORMAT_PCM) => {
             WavType::EPcm16
         }
@@ -151,6 +205,12 @@ pub const fn format_info_to_wav_type(info: (FormatCode, u16, FormatCode)) -> Wav
         (FormatCode::WAVE_FORMAT_EXTENSIBLE, FLOAT_64_BITS, FormatCode::WAV_FORMAT_IEEE_FLOAT) => {
             WavType::EFloat64
         }
+        (FormatCode::WAVE_FORMAT_EXTENSIBLE, ALAW_BITS, FormatCode::WAVE_FORMAT_ALAW) => {
+            WavType::ALaw
+        }
+        (FormatCode::WAVE_FORMAT_EXTENSIBLE, MULAW_BITS, FormatCode::WAVE_FORMAT_MULAW) => {
+            WavType::MuLaw
+        }
         _ => {
             // Cannot turn this into an into statement since into isn't const
             return Err(WaversError::Format(FormatError::InvalidType {
@@ -165,6 +225,11 @@ pub const fn format_info_to_wav_type(info: (FormatCode, u16, FormatCode)) -> Wav
 /// Converts a WavType to a tuple of format codes and bits per sample.
 pub const fn wav_type_to_format_info(wav_type: WavType) -> (FormatCode, u16, FormatCode) {
     match wav_type {
+        WavType::Pcm8 => (
+            FormatCode::WAV_FORMAT_PCM,
+            PCM_8_BITS,
+            FormatCode::WAV_FORMAT_PCM,
+        ),
         WavType::Pcm16 => (
             FormatCode::WAV_FORMAT_PCM,
             PCM_16_BITS,
@@ -215,17 +280,47 @@ pub const fn wav_type_to_format_info(wav_type: WavType) -> (FormatCode, u16, For
             FLOAT_64_BITS,
             FormatCode::WAV_FORMAT_IEEE_FLOAT,
         ),
+        WavType::EPcm8 => (
+            FormatCode::WAVE_FORMAT_EXTENSIBLE,
+            PCM_8_BITS,
+            FormatCode::WAV_FORMAT_PCM,
+        ),
+        WavType::ALaw => (
+            FormatCode::WAVE_FORMAT_ALAW,
+            ALAW_BITS,
+            FormatCode::WAVE_FORMAT_ALAW,
+        ),
+        WavType::MuLaw => (
+            FormatCode::WAVE_FORMAT_MULAW,
+            MULAW_BITS,
+            FormatCode::WAVE_FORMAT_MULAW,
+        ),
+        WavType::MsAdpcm => (
+            FormatCode::WAVE_FORMAT_ADPCM,
+            MS_ADPCM_BITS,
+            FormatCode::WAVE_FORMAT_ADPCM,
+        ),
+        WavType::Gsm610 => (
+            FormatCode::WAVE_FORMAT_GSM610,
+            GSM_610_BITS,
+            FormatCode::WAVE_FORMAT_GSM610,
+        ),
     }
 }
 
 impl From<WavType> for TypeId {
     fn from(value: WavType) -> Self {
         match value {
+            WavType::Pcm8 | WavType::EPcm8 => TypeId::of::<u8>(),
             WavType::Pcm16 | WavType::EPcm16 => TypeId::of::<i16>(),
             WavType::Pcm24 | WavType::EPcm24 => TypeId::of::<i24>(),
             WavType::Pcm32 | WavType::EPcm32 => TypeId::of::<i32>(),
             WavType::Float32 | WavType::EFloat32 => TypeId::of::<f32>(),
             WavType::Float64 | WavType::EFloat64 => TypeId::of::<f64>(),
+            // A-law, µ-law, MS ADPCM and GSM 6.10 are decoded to 16-bit linear PCM
+            WavType::ALaw | WavType::MuLaw | WavType::MsAdpcm | WavType::Gsm610 => {
+                TypeId::of::<i16>()
+            }
         }
     }
 }
@@ -235,6 +330,7 @@ impl TryFrom<TypeId> for WavType {
 
     fn try_from(value: TypeId) -> Result<Self, Self::Error> {
         match value {
+            x if x == TypeId::of::<u8>() => Ok(WavType::Pcm8),
             x if x == TypeId::of::<i16>() => Ok(WavType::Pcm16),
             x if x == TypeId::of::<i24>() => Ok(WavType::Pcm24),
             x if x == TypeId::of::<i32>() => Ok(WavType::Pcm32),
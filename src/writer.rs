@@ -0,0 +1,1565 @@
+//! Module containing the options used to control how wav files are written, and the ``WavWriter`` struct, which writes a wav file a block of frames at a time.
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use i24::i24;
+
+use crate::{
+    channel_layout::ChannelLayout,
+    chunks::{
+        ds64::{DS64_BASE_SIZE, RF64_PLACEHOLDER_SIZE},
+        AcidChunk, AdtlChunk, AxmlChunk, BextChunk, Chunk, CueChunk, CuePoint, Ds64Chunk, InfoTag,
+        InstChunk, IxmlChunk, ListChunk, Marker, PlaylistSegment, PlstChunk, RawChunk, SmplChunk,
+        FACT, ID3, JUNK, RF64, RIFF,
+    },
+    conversion::{AudioSample, ConvertSlice, ConvertTo, Dither, NoiseShaping},
+    core::{CancelToken, ProgressFn, Wav, DEFAULT_BUFFER_SIZE},
+    error::{FormatError, WaversResult},
+    header::ChunkIdentifier,
+    log,
+    wav_type::WavType,
+};
+
+/// The byte order samples are written with when exporting raw PCM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// Little-endian, the byte order used by wav files.
+    #[default]
+    Little,
+    /// Big-endian, the byte order used by RIFX and AIFF files.
+    Big,
+}
+
+impl Endianness {
+    /// Returns the byte order of the target platform.
+    pub const fn native() -> Self {
+        match cfg!(target_endian = "big") {
+            true => Endianness::Big,
+            false => Endianness::Little,
+        }
+    }
+}
+
+/// How thoroughly a file written to a path is synced to disk before writing returns, for recording applications which must guarantee that their data is on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Durability {
+    /// The file is written back to disk by the operating system in its own time.
+    #[default]
+    None,
+    /// The contents and metadata of the file are synced to disk.
+    File,
+    /// The file is synced to disk, followed by the directory containing it, so that the entry of a newly created or atomically renamed file is durable too.
+    /// Directories can only be synced on Unix, so elsewhere only the file is synced.
+    FileAndDirectory,
+}
+
+impl Durability {
+    /// Syncs ``file``, which has been written to ``path``, to disk.
+    pub(crate) fn sync(self, file: &File, path: &Path) -> std::io::Result<()> {
+        if self == Durability::None {
+            return Ok(());
+        }
+        file.sync_all()?;
+        if self == Durability::FileAndDirectory {
+            Durability::sync_dir(path)?;
+        }
+        Ok(())
+    }
+
+    /// Syncs the directory containing ``path`` to disk.
+    pub(crate) fn sync_dir(path: &Path) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            File::open(dir)?.sync_all()?;
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Options used by ``write_with_options`` to control how samples are written to disk.
+///
+/// By default the samples are written using the native encoding of the sample type, which is the same behaviour as ``write``.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{write_with_options, WavType, WriteOptions};
+///
+/// fn main() {
+///     let samples: Vec<i16> = vec![0; 16000];
+///     let options = WriteOptions::new().encoding(WavType::ALaw);
+///     write_with_options("./alaw.wav", &samples, 16000, 1, &options).unwrap();
+/// }
+/// ```
+// The progress callback is compared by address, where a false negative is harmless
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WriteOptions {
+    pub(crate) encoding: Option<WavType>,
+    pub(crate) valid_bits_per_sample: Option<u16>,
+    pub(crate) channel_layout: Option<ChannelLayout>,
+    pub(crate) bext: Option<BextChunk>,
+    pub(crate) ixml: Option<IxmlChunk>,
+    pub(crate) axml: Option<AxmlChunk>,
+    pub(crate) info: Option<ListChunk>,
+    pub(crate) cue: Option<CueChunk>,
+    pub(crate) adtl: Option<AdtlChunk>,
+    pub(crate) plst: Option<PlstChunk>,
+    pub(crate) smpl: Option<SmplChunk>,
+    pub(crate) acid: Option<AcidChunk>,
+    pub(crate) inst: Option<InstChunk>,
+    pub(crate) preserve_chunks: bool,
+    pub(crate) preserve_chunk_order: bool,
+    /// The identifiers of the chunks of the source file, in the order they appear in the file.
+    pub(crate) chunk_order: Vec<ChunkIdentifier>,
+    pub(crate) chunks: Vec<RawChunk>,
+    pub(crate) progress: Option<ProgressFn>,
+    pub(crate) cancel: Option<CancelToken>,
+    pub(crate) atomic: bool,
+    pub(crate) durability: Durability,
+    pub(crate) buffer_size: Option<usize>,
+    pub(crate) dither: Option<u64>,
+    pub(crate) noise_shaping: NoiseShaping,
+}
+
+impl WriteOptions {
+    /// Constructs a new set of write options which write the samples using their native encoding.
+    pub fn new() -> Self {
+        WriteOptions::default()
+    }
+
+    /// Sets the encoding the samples are stored with in the written file, for example ``WavType::ALaw`` or ``WavType::MuLaw``.
+    pub fn encoding(mut self, encoding: WavType) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Sets the number of valid bits in each sample, for example 20-bit samples stored in a 24-bit container.
+    ///
+    /// Only integer PCM encodings of 16 bits or more are supported. The file is written using the extensible format,
+    /// which records the valid bits, and the padding bits of each sample are zeroed.
+    pub fn valid_bits_per_sample(mut self, valid_bits_per_sample: u16) -> Self {
+        self.valid_bits_per_sample = Some(valid_bits_per_sample);
+        self
+    }
+
+    /// Sets the speaker position of each channel, for example ``ChannelLayout::SURROUND_5_1`` for 6 channel audio.
+    ///
+    /// The file is written using the extensible format, which records the layout, so A-law and µ-law encodings are not supported.
+    /// The layout may not assign more speakers than there are channels.
+    pub fn channel_layout(mut self, channel_layout: ChannelLayout) -> Self {
+        self.channel_layout = Some(channel_layout);
+        self
+    }
+
+    /// Attaches a Broadcast Wave bext chunk to the written file. The chunk is written before the data chunk.
+    pub fn bext(mut self, bext: BextChunk) -> Self {
+        self.bext = Some(bext);
+        self
+    }
+
+    /// Attaches an iXML chunk containing the given XML document to the written file. The chunk is written before the data chunk.
+    pub fn ixml(mut self, xml: &str) -> Self {
+        self.ixml = Some(IxmlChunk::new(xml));
+        self
+    }
+
+    /// Attaches an axml chunk containing the given Audio Definition Model XML document to the written file. The chunk is written before the data chunk.
+    pub fn axml(mut self, xml: &str) -> Self {
+        self.axml = Some(AxmlChunk::new(xml));
+        self
+    }
+
+    /// Sets the time reference of the bext chunk of the written file, i.e. the number of samples since midnight of the first sample.
+    /// A bext chunk is added if one is not already set, so any bext chunk should be set using ``bext`` before calling this.
+    pub fn time_reference(mut self, time_reference: u64) -> Self {
+        self.bext
+            .get_or_insert_with(BextChunk::default)
+            .time_reference = time_reference;
+        self
+    }
+
+    /// Adds a LIST INFO metadata tag, such as ``InfoTag::Artist``, to the written file, replacing any previous value of the tag.
+    ///
+    /// The values are written as null terminated UTF-8 strings. Non-ASCII values may not be displayed correctly by older software.
+    pub fn info(mut self, tag: InfoTag, value: &str) -> Self {
+        self.info
+            .get_or_insert_with(ListChunk::default)
+            .set(tag, value);
+        self
+    }
+
+    /// Sets the artist of the written file. Shorthand for ``info(InfoTag::Artist, artist)``.
+    pub fn artist(self, artist: &str) -> Self {
+        self.info(InfoTag::Artist, artist)
+    }
+
+    /// Sets the title of the written file. Shorthand for ``info(InfoTag::Name, title)``.
+    pub fn title(self, title: &str) -> Self {
+        self.info(InfoTag::Name, title)
+    }
+
+    /// Sets the comment of the written file. Shorthand for ``info(InfoTag::Comment, comment)``.
+    pub fn comment(self, comment: &str) -> Self {
+        self.info(InfoTag::Comment, comment)
+    }
+
+    /// Attaches cue points, or markers, to the written file, replacing any previously set cue points or markers.
+    pub fn cue_points(mut self, cue_points: Vec<CuePoint>) -> Self {
+        self.cue = Some(CueChunk::new(cue_points));
+        self.adtl = None;
+        self
+    }
+
+    /// Attaches markers to the written file, replacing any previously set cue points or markers.
+    /// The cue points are written to the cue chunk and any labels, notes and region lengths to an adtl LIST chunk.
+    pub fn markers(mut self, markers: &[Marker]) -> Self {
+        self.cue = Some(CueChunk::new(
+            markers.iter().map(Marker::cue_point).collect(),
+        ));
+        let adtl = AdtlChunk::from_markers(markers);
+        self.adtl = match adtl == AdtlChunk::default() {
+            true => None,
+            false => Some(adtl),
+        };
+        self
+    }
+
+    /// Attaches a playlist to the written file, replacing any previously set playlist.
+    /// Each segment starts at a cue point, so the cue points should be set using ``cue_points`` or ``markers``.
+    pub fn playlist(mut self, segments: Vec<PlaylistSegment>) -> Self {
+        self.plst = Some(PlstChunk::new(segments));
+        self
+    }
+
+    /// Attaches a smpl chunk, containing the MIDI unity note and loop points, to the written file.
+    pub fn smpl(mut self, smpl: SmplChunk) -> Self {
+        self.smpl = Some(smpl);
+        self
+    }
+
+    /// Attaches an acid chunk, containing the tempo, number of beats and root note of a loop, to the written file.
+    pub fn acid(mut self, acid: AcidChunk) -> Self {
+        self.acid = Some(acid);
+        self
+    }
+
+    /// Attaches an inst chunk, containing the pitch, gain and key and velocity ranges of an instrument sample, to the written file.
+    pub fn inst(mut self, inst: InstChunk) -> Self {
+        self.inst = Some(inst);
+        self
+    }
+
+    /// Sets whether ``Wav::write_with_options`` copies the chunks of the source file, such as metadata and vendor specific chunks, to the written file.
+    /// The fmt, fact and data chunks are always regenerated, and chunks set using these options replace those of the source file.
+    pub fn preserve_chunks(mut self, preserve_chunks: bool) -> Self {
+        self.preserve_chunks = preserve_chunks;
+        self
+    }
+
+    /// Sets whether ``Wav::write_with_options`` reproduces the order of the chunks of the source file, including chunks which follow the data chunk.
+    /// Preserving the order also preserves the chunks themselves, as with ``preserve_chunks``.
+    /// Chunks set using these options take the place of the chunk they replace, and any other new chunks are written just before the data chunk.
+    pub fn preserve_chunk_order(mut self, preserve_chunk_order: bool) -> Self {
+        self.preserve_chunk_order = preserve_chunk_order;
+        self.preserve_chunks |= preserve_chunk_order;
+        self
+    }
+
+    /// Attaches an ID3v2 tag, for example one written using the id3 crate, to the written file as an "id3 " chunk.
+    /// The tag is written as is, replacing any previously set tag.
+    pub fn id3(mut self, tag: &[u8]) -> Self {
+        self.chunks.retain(|chunk| chunk.id != ID3);
+        self.chunks.push(RawChunk::new(ID3, tag.to_vec()));
+        self
+    }
+
+    /// Adds a custom chunk with the given identifier and payload to the written file, for example analysis results or vendor specific data.
+    /// The chunk is written before the data chunk, with the size field and any padding byte added automatically.
+    /// Writing fails if the identifier is one which wavers writes itself, e.g. "fmt ", "fact" or "data".
+    pub fn add_chunk(mut self, id: [u8; 4], data: &[u8]) -> Self {
+        self.chunks.push(RawChunk::new(id, data.to_vec()));
+        self
+    }
+
+    /// Sets a callback which is called with the number of bytes of samples written so far and the total number of bytes to write,
+    /// so that tools wrapping wavers can show the progress of writing very large files. The callback is called every ``PROGRESS_INTERVAL`` bytes.
+    /// When a file is transcoded with ``Wav::write_with_options``, the bytes read from the source file are counted as well.
+    pub fn progress(mut self, progress: ProgressFn) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Sets a token which is checked every ``PROGRESS_INTERVAL`` bytes, so that writing, or transcoding with ``Wav::write_with_options``, can be cancelled.
+    /// A cancelled write returns ``WaversError::Cancelled`` and removes the partially written file. See ``CancelToken``.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Sets whether files written to a path are first written to a temporary file in the same directory, which replaces the destination once writing has succeeded.
+    /// A crash or error part way through writing then never leaves a truncated file at the destination.
+    /// As the destination is only replaced once the whole file has been written, it can also be the file which is being read, so a file can be rewritten in place
+    /// with ``Wav::write_with_options``. The functions which stream one file into another, such as ``transcode`` and ``Wav::process_to``, always write atomically.
+    /// A ``WavWriter`` writes to the writer it is given, so it is not affected by this option.
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Sets how thoroughly files written to a path are synced to disk before writing returns. See ``Durability``.
+    /// A ``WavWriter`` created with ``WavWriter::create`` syncs the file when it is finalized, while other writers are flushed but left to the caller to sync.
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Sets the capacity in bytes of the BufWriter used by writers which write a file a block at a time, such as ``WavWriter::create`` and ``write_from_iter``.
+    /// The default is ``DEFAULT_BUFFER_SIZE`` bytes. Whole files written by ``write_with_options`` are written in a single vectored write, so do not use a buffer.
+    /// Writers configured in other ways, such as a pre-built BufWriter, can be used with ``WavWriter::new``.
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// Adds triangular probability density function (TPDF) dither to samples whose bit depth is reduced as they are written, e.g. ``f32`` or ``i32`` samples written as 16-bit PCM,
+    /// drawing the noise from a pseudo-random number generator seeded with ``seed`` so that the written file is reproducible.
+    /// Without dither, samples are rounded to the nearest value of the encoding, which leaves quantization distortion correlated with quiet signals.
+    /// Samples written with an encoding which is not integer PCM, or which loses no resolution, are written unchanged.
+    pub fn dither(mut self, seed: u64) -> Self {
+        self.dither = Some(seed);
+        self
+    }
+
+    /// Shapes the dither added when the bit depth is reduced with the given curve, moving its noise towards frequencies where it is less audible. See ``NoiseShaping``.
+    /// Noise shaping always adds dither, seeded with the seed set by ``dither`` or with 0 if none is set.
+    pub fn noise_shaping(mut self, noise_shaping: NoiseShaping) -> Self {
+        self.noise_shaping = noise_shaping;
+        self
+    }
+
+    /// Returns the dither to add to samples of ``n_channels`` channels, if any.
+    pub(crate) fn new_dither(&self, n_channels: u16) -> Option<Dither> {
+        match (self.dither, self.noise_shaping) {
+            (None, NoiseShaping::None) => None,
+            (seed, noise_shaping) => {
+                Some(Dither::new(seed.unwrap_or(0), noise_shaping, n_channels))
+            }
+        }
+    }
+
+    /// Wraps ``writer`` in a BufWriter with the buffer size of the options.
+    pub(crate) fn buf_writer<W: Write>(&self, writer: W) -> BufWriter<W> {
+        BufWriter::with_capacity(self.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE), writer)
+    }
+
+    /// Calls the progress callback, if one is set, and returns ``WaversError::Cancelled`` if the cancel token has been cancelled.
+    pub(crate) fn report(&self, done: u64, total: u64) -> WaversResult<()> {
+        if let Some(progress) = self.progress {
+            progress(done, total);
+        }
+        match &self.cancel {
+            Some(token) => token.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the metadata chunks to write, in the order they are written.
+    pub(crate) fn metadata_chunks(&self) -> Vec<&dyn Chunk> {
+        let mut chunks: Vec<&dyn Chunk> = Vec::new();
+        if let Some(bext) = &self.bext {
+            chunks.push(bext);
+        }
+        if let Some(ixml) = &self.ixml {
+            chunks.push(ixml);
+        }
+        if let Some(axml) = &self.axml {
+            chunks.push(axml);
+        }
+        if let Some(info) = &self.info {
+            chunks.push(info);
+        }
+        if let Some(cue) = &self.cue {
+            chunks.push(cue);
+        }
+        if let Some(adtl) = &self.adtl {
+            chunks.push(adtl);
+        }
+        if let Some(plst) = &self.plst {
+            chunks.push(plst);
+        }
+        if let Some(smpl) = &self.smpl {
+            chunks.push(smpl);
+        }
+        if let Some(acid) = &self.acid {
+            chunks.push(acid);
+        }
+        if let Some(inst) = &self.inst {
+            chunks.push(inst);
+        }
+        for chunk in self.chunks.iter() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+}
+
+/// The number of frames taken from an iterator at a time by ``write_from_iter``.
+pub const ITER_BLOCK_FRAMES: usize = 4096;
+
+/// Writes a wav file a block of frames at a time, for recordings whose length is not known when writing begins.
+///
+/// The header is written when the writer is created, and the sizes it records are patched by ``finalize``, so the writer must be seekable.
+/// A JUNK chunk is reserved after the RIFF header, which ``finalize`` replaces with a ds64 chunk if the file grows too large for 32-bit sizes, promoting it to RF64.
+/// Metadata chunks set in the options are written before the data chunk, except for those ordered after it, which are written by ``finalize``.
+///
+/// A file which is not finalized records that it has no samples.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{WavWriter, WriteOptions};
+///
+/// let mut writer: WavWriter<f32> = WavWriter::create("recording.wav", 48000, 2, &WriteOptions::new()).unwrap();
+/// for _ in 0..100 {
+///     // 10ms of stereo audio, e.g. from an input device
+///     let block = vec![0.0f32; 2 * 480];
+///     writer.write_frames(&block).unwrap();
+/// }
+/// writer.finalize().unwrap();
+/// ```
+pub struct WavWriter<T: AudioSample, W: Write + Seek = BufWriter<File>>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    writer: W,
+    /// The position of the start of the file in the writer.
+    start: u64,
+    sample_rate: i32,
+    n_channels: u16,
+    encoding: WavType,
+    padding_bits: u16,
+    options: WriteOptions,
+    n_samples: usize,
+    /// The dither added to the samples, which carries on from one block of frames to the next.
+    dither: Option<Dither>,
+    /// Syncs the finalized file to disk, for writers created with ``create``.
+    sync: Option<SyncFn<W>>,
+    _phantom: PhantomData<T>,
+}
+
+/// Syncs the file behind a writer to disk.
+type SyncFn<W> = Box<dyn FnOnce(&mut W) -> std::io::Result<()> + Send>;
+
+impl<T: AudioSample> WavWriter<T>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Creates the file at the given path and writes the header of a wav file with the given sample rate, number of channels and options. See ``new``.
+    /// The file is synced to disk by ``finalize`` according to the durability of the options.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        sample_rate: i32,
+        n_channels: u16,
+        options: &WriteOptions,
+    ) -> WaversResult<Self> {
+        let f = File::create(&path)?;
+        let mut writer = Self::new(options.buf_writer(f), sample_rate, n_channels, options)?;
+        if options.durability != Durability::None {
+            let durability = options.durability;
+            let path = path.as_ref().to_path_buf();
+            writer.sync = Some(Box::new(move |w: &mut BufWriter<File>| {
+                durability.sync(w.get_ref(), &path)
+            }));
+        }
+        Ok(writer)
+    }
+
+    /// Repairs the header of a wav file which was never finalized, e.g. because the recording application lost power, and opens the recovered file.
+    ///
+    /// The length of the data chunk is inferred from the size of the file, dropping any partially written frame at its end,
+    /// and the sizes recorded by the header are patched in place. A recording which grew too large for 32-bit sizes is promoted to RF64,
+    /// using the JUNK chunk reserved by ``WavWriter``. Files which were finalized are opened without being changed.
+    ///
+    /// This function will return an error if the file cannot be parsed or written, if it is block encoded or RIFX,
+    /// or if it needs to be promoted to RF64 but has no JUNK chunk to replace.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wavers::{Wav, WavWriter};
+    ///
+    /// let mut wav: Wav<f32> = WavWriter::recover("interrupted_recording.wav").unwrap();
+    /// let samples = wav.read().unwrap();
+    /// ```
+    pub fn recover<P: AsRef<Path>>(path: P) -> WaversResult<Wav<T>> {
+        recover_header(path.as_ref())?;
+        Wav::from_path(path)
+    }
+}
+
+/// Patches the sizes recorded by the header of an unfinalized wav file to match the length of the file. See ``WavWriter::recover``.
+fn recover_header(path: &Path) -> WaversResult<()> {
+    // Parsing stops at the data chunk, as the samples after it would otherwise be parsed as chunks
+    let wav: Wav<i16> = Wav::from_stream(std::io::BufReader::new(File::open(path)?))?;
+    let header = wav.header();
+    if header.is_rifx() {
+        return Err(FormatError::Unrecoverable("RIFX files are not written by wavers").into());
+    }
+    if wav.encoding().is_block_encoded() {
+        return Err(FormatError::Unrecoverable("block encoded files cannot be written").into());
+    }
+    let file_len = std::fs::metadata(path)?.len();
+    let data = *header.data();
+    let data_start = data.offset as u64 + 8;
+    let riff_size = header
+        .get_chunk_info(RIFF.into())
+        .map_or(0, |riff| riff.size);
+    if riff_size + 8 == file_len && data_start + data.size <= file_len {
+        return Ok(());
+    }
+
+    let block_align = header.fmt_chunk.block_align as u64;
+    if block_align == 0 {
+        return Err(FormatError::Unrecoverable("the fmt chunk has a block align of zero").into());
+    }
+    let n_frames = file_len.saturating_sub(data_start) / block_align;
+    let data_size = n_frames * block_align;
+    // A partially written frame is dropped, and an odd sized data chunk is followed by a padding byte
+    let file_len = data_start + data_size + data_size % 2;
+    let riff_size = file_len - 8;
+    let fact = header.get_chunk_info(FACT.into()).copied();
+    let junk = header.get_chunk_info(JUNK.into()).copied();
+    let is_rf64 = header.is_rf64();
+    drop(wav);
+
+    let mut f = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)?;
+    f.set_len(file_len)?;
+    let mut patch = |offset: u64, bytes: &[u8]| -> std::io::Result<()> {
+        f.seek(SeekFrom::Start(offset))?;
+        f.write_all(bytes)
+    };
+    if !is_rf64 && riff_size <= u32::MAX as u64 {
+        patch(0, &RIFF)?;
+        patch(4, &(riff_size as u32).to_ne_bytes())?;
+        patch(data.offset as u64 + 4, &(data_size as u32).to_ne_bytes())?;
+    } else {
+        match junk {
+            _ if is_rf64 => {}
+            Some(junk) if junk.offset == 12 && junk.size == DS64_BASE_SIZE as u64 => {
+                patch(12, &Ds64Chunk::new(0, 0, 0).as_bytes())?;
+            }
+            _ => {
+                return Err(FormatError::Unrecoverable(
+                    "the file needs to be RF64 but has no JUNK chunk to replace with a ds64 chunk",
+                )
+                .into())
+            }
+        }
+        patch(0, &RF64)?;
+        patch(4, &RF64_PLACEHOLDER_SIZE.to_ne_bytes())?;
+        // The sizes are the first fields of the ds64 chunk, before any table of other chunk sizes
+        patch(20, &riff_size.to_ne_bytes())?;
+        patch(28, &data_size.to_ne_bytes())?;
+        patch(36, &n_frames.to_ne_bytes())?;
+        patch(data.offset as u64 + 4, &RF64_PLACEHOLDER_SIZE.to_ne_bytes())?;
+    }
+    if let Some(fact) = fact {
+        let n_frames = n_frames.min(u32::MAX as u64) as u32;
+        patch(fact.offset as u64 + 8, &n_frames.to_ne_bytes())?;
+    }
+
+    log!(
+        log::Level::Debug,
+        "Recovered {} frames of {}",
+        n_frames,
+        path.display()
+    );
+    Ok(())
+}
+
+impl<T: AudioSample, W: Write + Seek> WavWriter<T, W>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new WavWriter which writes a wav file with the given sample rate, number of channels and options to ``writer``, starting at its current position.
+    /// The samples are encoded in the same way as by ``write_with_options``. The progress callback and cancel token of the options are not used.
+    ///
+    /// This function will return an error if the options are invalid, e.g. the encoding cannot be written, or if the header cannot be written.
+    pub fn new(
+        mut writer: W,
+        sample_rate: i32,
+        n_channels: u16,
+        options: &WriteOptions,
+    ) -> WaversResult<Self> {
+        let (encoding, padding_bits) = crate::write_encoding::<T>(n_channels, options)?;
+        let start = writer.stream_position()?;
+        let mut wav_writer = WavWriter {
+            writer,
+            start,
+            sample_rate,
+            n_channels,
+            encoding,
+            padding_bits,
+            options: options.clone(),
+            n_samples: 0,
+            dither: options.new_dither(n_channels),
+            sync: None,
+            _phantom: PhantomData,
+        };
+        wav_writer.write_header()?;
+        Ok(wav_writer)
+    }
+
+    /// Encodes and writes the given interleaved frames after those already written.
+    ///
+    /// This function will return an error if the samples do not make up a whole number of frames, or if they cannot be written.
+    pub fn write_frames(&mut self, frames: &[T]) -> WaversResult<()> {
+        check_frames(frames.len(), self.n_channels)?;
+        let bytes = crate::encode_samples(
+            frames,
+            self.encoding,
+            self.padding_bits,
+            self.dither.as_mut(),
+        )?;
+        self.writer.write_all(&bytes)?;
+        self.n_samples += frames.len();
+        Ok(())
+    }
+
+    /// Returns the number of frames written so far.
+    pub fn n_frames(&self) -> usize {
+        self.n_samples / self.n_channels.max(1) as usize
+    }
+
+    /// Returns the sample rate of the file.
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Returns the number of channels of the file.
+    pub fn n_channels(&self) -> u16 {
+        self.n_channels
+    }
+
+    /// Returns the encoding the samples are written with.
+    pub fn encoding(&self) -> WavType {
+        self.encoding
+    }
+
+    /// Writes any metadata chunks which follow the data chunk, patches the sizes recorded in the header and flushes the writer, which is returned.
+    /// The writer is left at the end of the file.
+    ///
+    /// This function will return an error if the file cannot be written.
+    pub fn finalize(mut self) -> WaversResult<W> {
+        let (_, (_, trailing_chunks)) = crate::write_header(
+            self.encoding,
+            self.sample_rate,
+            self.n_channels,
+            self.n_samples,
+            &self.options,
+        )?;
+        if !trailing_chunks.is_empty() {
+            if (self.n_samples * self.encoding.n_bytes()) % 2 == 1 {
+                self.writer.write_all(&[0])?; // the padding byte of the data chunk
+            }
+            for chunk in trailing_chunks.iter() {
+                self.writer.write_all(&chunk.as_bytes())?;
+            }
+        }
+
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.start))?;
+        self.write_header()?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        if let Some(sync) = self.sync.take() {
+            sync(&mut self.writer)?;
+        }
+        Ok(self.writer)
+    }
+
+    /// Writes everything before the samples of the data chunk for the number of samples written so far.
+    /// Unless the file is RF64, a JUNK chunk the size of a ds64 chunk is written in its place, so the header is always the same length.
+    fn write_header(&mut self) -> WaversResult<()> {
+        let (header, (leading_chunks, _)) = crate::write_header(
+            self.encoding,
+            self.sample_rate,
+            self.n_channels,
+            self.n_samples,
+            &self.options,
+        )?;
+        let mut bytes = header.as_bytes().into_vec();
+        if !header.is_rf64() {
+            let riff_size = u32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            // Within a few bytes of the limit, the JUNK chunk takes the RIFF size past 32 bits, which is then recorded as unknown
+            let riff_size = riff_size.saturating_add(8 + DS64_BASE_SIZE as u32);
+            bytes[4..8].copy_from_slice(&riff_size.to_ne_bytes());
+
+            let mut junk = Vec::with_capacity(8 + DS64_BASE_SIZE);
+            junk.extend_from_slice(&JUNK);
+            junk.extend_from_slice(&(DS64_BASE_SIZE as u32).to_ne_bytes());
+            junk.resize(8 + DS64_BASE_SIZE, 0);
+            bytes.splice(12..12, junk);
+        }
+        for chunk in leading_chunks.iter() {
+            bytes.extend_from_slice(&chunk.as_bytes());
+        }
+        bytes.extend_from_slice(&header.data_chunk_bytes());
+        self.writer.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// Returns an error if ``n_samples`` samples do not make up a whole number of frames.
+pub(crate) fn check_frames(n_samples: usize, n_channels: u16) -> WaversResult<()> {
+    match n_samples.is_multiple_of(n_channels.max(1) as usize) {
+        true => Ok(()),
+        false => Err(FormatError::IncompleteFrames {
+            n_samples,
+            n_channels,
+        }
+        .into()),
+    }
+}
+
+/// The size written for the RIFF and data chunks of a stream of unknown length, which tools such as ffmpeg and sox take to mean that the samples continue to the end of the stream.
+pub const UNKNOWN_STREAM_SIZE: u32 = u32::MAX;
+
+/// The length of a wav file written by a ``StreamWavWriter``, which must be decided before the header is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamLength {
+    /// The file has exactly this many frames, so the header records the sizes of the file and of its samples.
+    Frames(u64),
+    /// The length of the file is unknown, so the header records ``UNKNOWN_STREAM_SIZE`` as the sizes of the file and of its samples.
+    Unknown,
+}
+
+/// Writes a wav file a block of frames at a time to a writer which cannot seek, such as a pipe or a socket, so that the header cannot be patched afterwards.
+///
+/// The sizes recorded in the header are decided by the ``StreamLength`` given when the writer is created. A file of known length is checked by ``finalize``
+/// to have exactly the number of frames promised. A file of unknown length records placeholder sizes, which is how ffmpeg and sox write wav files to pipes,
+/// so its metadata chunks are all written before the data chunk.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{StreamLength, StreamWavWriter, WriteOptions};
+///
+/// // e.g. wavers_generator | ffmpeg -i - out.flac
+/// let stdout = std::io::stdout().lock();
+/// let mut writer: StreamWavWriter<f32, _> =
+///     StreamWavWriter::new(stdout, 48000, 1, StreamLength::Unknown, &WriteOptions::new()).unwrap();
+/// for i in 0..100 {
+///     let block: Vec<f32> = (0..480).map(|j| ((i * 480 + j) as f32 / 48.0).sin()).collect();
+///     writer.write_frames(&block).unwrap();
+/// }
+/// writer.finalize().unwrap();
+/// ```
+pub struct StreamWavWriter<T: AudioSample, W: Write>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    writer: W,
+    sample_rate: i32,
+    n_channels: u16,
+    length: StreamLength,
+    encoding: WavType,
+    padding_bits: u16,
+    options: WriteOptions,
+    n_samples: usize,
+    /// The dither added to the samples, which carries on from one block of frames to the next.
+    dither: Option<Dither>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: AudioSample, W: Write> StreamWavWriter<T, W>
+where
+    u8: ConvertTo<T>,
+    i16: ConvertTo<T>,
+    i24: ConvertTo<T>,
+    i32: ConvertTo<T>,
+    f32: ConvertTo<T>,
+    f64: ConvertTo<T>,
+    Box<[u8]>: ConvertSlice<T>,
+    Box<[i16]>: ConvertSlice<T>,
+    Box<[i24]>: ConvertSlice<T>,
+    Box<[i32]>: ConvertSlice<T>,
+    Box<[f32]>: ConvertSlice<T>,
+    Box<[f64]>: ConvertSlice<T>,
+{
+    /// Construct a new StreamWavWriter which writes a wav file of the given length, sample rate, number of channels and options to ``writer``,
+    /// writing its header straight away. The samples are encoded in the same way as by ``write_with_options``. The progress callback and cancel token of the options are not used.
+    ///
+    /// This function will return an error if the options are invalid, e.g. the encoding cannot be written, or if the header cannot be written.
+    pub fn new(
+        mut writer: W,
+        sample_rate: i32,
+        n_channels: u16,
+        length: StreamLength,
+        options: &WriteOptions,
+    ) -> WaversResult<Self> {
+        let (encoding, padding_bits) = crate::write_encoding::<T>(n_channels, options)?;
+        let mut options = options.clone();
+        let n_samples = match length {
+            StreamLength::Frames(n_frames) => n_frames as usize * n_channels as usize,
+            StreamLength::Unknown => {
+                // Chunks after the data chunk would be taken for samples, as the data chunk has no known end
+                options.chunk_order.clear();
+                0
+            }
+        };
+
+        let (header, (leading_chunks, _)) =
+            crate::write_header(encoding, sample_rate, n_channels, n_samples, &options)?;
+        let mut bytes = header.as_bytes().into_vec();
+        let mut data_chunk_bytes = header.data_chunk_bytes();
+        if length == StreamLength::Unknown {
+            bytes[4..8].copy_from_slice(&UNKNOWN_STREAM_SIZE.to_ne_bytes());
+            data_chunk_bytes[4..8].copy_from_slice(&UNKNOWN_STREAM_SIZE.to_ne_bytes());
+            // The number of samples recorded by a fact chunk, which ends the header if there is one, is unknown as well
+            let n_bytes = bytes.len();
+            if bytes[n_bytes - 12..n_bytes - 8] == FACT {
+                bytes[n_bytes - 4..].copy_from_slice(&UNKNOWN_STREAM_SIZE.to_ne_bytes());
+            }
+        }
+        for chunk in leading_chunks.iter() {
+            bytes.extend_from_slice(&chunk.as_bytes());
+        }
+        bytes.extend_from_slice(&data_chunk_bytes);
+        writer.write_all(&bytes)?;
+
+        Ok(StreamWavWriter {
+            writer,
+            sample_rate,
+            n_channels,
+            length,
+            encoding,
+            padding_bits,
+            dither: options.new_dither(n_channels),
+            options,
+            n_samples: 0,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Encodes and writes the given interleaved frames after those already written.
+    ///
+    /// This function will return an error if the samples do not make up a whole number of frames, if they would take a file of known length past its length,
+    /// or if they cannot be written.
+    pub fn write_frames(&mut self, frames: &[T]) -> WaversResult<()> {
+        check_frames(frames.len(), self.n_channels)?;
+        if let StreamLength::Frames(n_frames) = self.length {
+            let n_channels = self.n_channels.max(1) as usize;
+            let written = ((self.n_samples + frames.len()) / n_channels) as u64;
+            if written > n_frames {
+                return Err(FormatError::StreamLengthMismatch {
+                    expected: n_frames,
+                    written,
+                }
+                .into());
+            }
+        }
+        let bytes = crate::encode_samples(
+            frames,
+            self.encoding,
+            self.padding_bits,
+            self.dither.as_mut(),
+        )?;
+        self.writer.write_all(&bytes)?;
+        self.n_samples += frames.len();
+        Ok(())
+    }
+
+    /// Returns the number of frames written so far.
+    pub fn n_frames(&self) -> usize {
+        self.n_samples / self.n_channels.max(1) as usize
+    }
+
+    /// Returns the length of the file given when the writer was created.
+    pub fn length(&self) -> StreamLength {
+        self.length
+    }
+
+    /// Returns the encoding the samples are written with.
+    pub fn encoding(&self) -> WavType {
+        self.encoding
+    }
+
+    /// Writes any metadata chunks which follow the data chunk and flushes the writer, which is returned.
+    ///
+    /// This function will return an error if a file of known length was given fewer frames than its length, or if the file cannot be written.
+    pub fn finalize(mut self) -> WaversResult<W> {
+        if let StreamLength::Frames(n_frames) = self.length {
+            let written = self.n_frames() as u64;
+            if written != n_frames {
+                return Err(FormatError::StreamLengthMismatch {
+                    expected: n_frames,
+                    written,
+                }
+                .into());
+            }
+        }
+
+        let (_, (_, trailing_chunks)) = crate::write_header(
+            self.encoding,
+            self.sample_rate,
+            self.n_channels,
+            self.n_samples,
+            &self.options,
+        )?;
+        if !trailing_chunks.is_empty() {
+            if (self.n_samples * self.encoding.n_bytes()) % 2 == 1 {
+                self.writer.write_all(&[0])?; // the padding byte of the data chunk
+            }
+            for chunk in trailing_chunks.iter() {
+                self.writer.write_all(&chunk.as_bytes())?;
+            }
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// The format of a wav file: its sample rate, number of channels and, optionally, its encoding.
+/// When no encoding is given, samples are written using the native encoding of their type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavSpec {
+    pub sample_rate: i32,
+    pub n_channels: u16,
+    pub wav_type: Option<WavType>,
+}
+
+impl WavSpec {
+    /// Constructs a new WavSpec which writes samples using their native encoding.
+    pub fn new(sample_rate: i32, n_channels: u16) -> Self {
+        WavSpec {
+            sample_rate,
+            n_channels,
+            wav_type: None,
+        }
+    }
+
+    /// Sets the encoding the samples are stored with, for example ``WavType::Pcm24``.
+    pub fn wav_type(mut self, wav_type: WavType) -> Self {
+        self.wav_type = Some(wav_type);
+        self
+    }
+}
+
+/// Configures and writes wav files using a ``WavSpec`` and ``WriteOptions``, as an alternative to the positional arguments of ``write_with_options``.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wavers::{InfoTag, WavBuilder, WavSpec, WavType};
+///
+/// fn main() {
+///     let samples: Vec<f32> = vec![0.0; 32000];
+///     let spec = WavSpec::new(16000, 2).wav_type(WavType::Pcm24);
+///     WavBuilder::new(spec)
+///         .with_metadata([(InfoTag::Name, "Silence")])
+///         .write("./silence.wav", &samples)
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavBuilder {
+    spec: WavSpec,
+    options: WriteOptions,
+}
+
+impl WavBuilder {
+    /// Constructs a new WavBuilder which writes files with the given spec and default options.
+    pub fn new(spec: WavSpec) -> Self {
+        let options = match spec.wav_type {
+            Some(wav_type) => WriteOptions::new().encoding(wav_type),
+            None => WriteOptions::new(),
+        };
+        WavBuilder { spec, options }
+    }
+
+    /// Replaces the options used to write files. The encoding of the spec, if it has one, takes precedence over the encoding of the options.
+    pub fn with_options(mut self, options: WriteOptions) -> Self {
+        self.options = match self.spec.wav_type {
+            Some(wav_type) => options.encoding(wav_type),
+            None => options,
+        };
+        self
+    }
+
+    /// Adds LIST INFO metadata, such as the artist and title, to the written files.
+    pub fn with_metadata<S: AsRef<str>>(
+        mut self,
+        metadata: impl IntoIterator<Item = (InfoTag, S)>,
+    ) -> Self {
+        for (tag, value) in metadata {
+            self.options = self.options.info(tag, value.as_ref());
+        }
+        self
+    }
+
+    /// Adds custom chunks to the written files. See ``WriteOptions::add_chunk``.
+    pub fn with_chunks(mut self, chunks: impl IntoIterator<Item = RawChunk>) -> Self {
+        self.options.chunks.extend(chunks);
+        self
+    }
+
+    /// Returns the spec of the written files.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Returns the options used to write files.
+    pub fn options(&self) -> &WriteOptions {
+        &self.options
+    }
+
+    /// Writes the interleaved samples to disk. See ``write_with_options``.
+    pub fn write<T: AudioSample, P: AsRef<Path>>(&self, fp: P, samples: &[T]) -> WaversResult<()>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        crate::write_with_options(
+            fp,
+            samples,
+            self.spec.sample_rate,
+            self.spec.n_channels,
+            &self.options,
+        )
+    }
+
+    /// Writes the interleaved samples as a complete wav file to ``writer``. See ``write_to_writer_with_options``.
+    pub fn write_to_writer<T: AudioSample, W: Write>(
+        &self,
+        writer: &mut W,
+        samples: &[T],
+    ) -> WaversResult<()>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        crate::write_to_writer_with_options(
+            writer,
+            samples,
+            self.spec.sample_rate,
+            self.spec.n_channels,
+            &self.options,
+        )
+    }
+
+    /// Writes the interleaved samples as a complete wav file in memory. See ``write_to_vec_with_options``.
+    pub fn to_vec<T: AudioSample>(&self, samples: &[T]) -> WaversResult<Vec<u8>>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        crate::write_to_vec_with_options(
+            samples,
+            self.spec.sample_rate,
+            self.spec.n_channels,
+            &self.options,
+        )
+    }
+
+    /// Writes the interleaved samples produced by an iterator to disk a block at a time. See ``write_from_iter_with_options``.
+    pub fn write_from_iter<T: AudioSample, P: AsRef<Path>, I: IntoIterator<Item = T>>(
+        &self,
+        fp: P,
+        samples: I,
+    ) -> WaversResult<()>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        crate::write_from_iter_with_options(
+            fp,
+            samples,
+            self.spec.sample_rate,
+            self.spec.n_channels,
+            &self.options,
+        )
+    }
+
+    /// Creates a ``WavWriter`` which writes the file at the given path a block of frames at a time.
+    pub fn create<T: AudioSample, P: AsRef<Path>>(&self, fp: P) -> WaversResult<WavWriter<T>>
+    where
+        u8: ConvertTo<T>,
+        i16: ConvertTo<T>,
+        i24: ConvertTo<T>,
+        i32: ConvertTo<T>,
+        f32: ConvertTo<T>,
+        f64: ConvertTo<T>,
+        Box<[u8]>: ConvertSlice<T>,
+        Box<[i16]>: ConvertSlice<T>,
+        Box<[i24]>: ConvertSlice<T>,
+        Box<[i32]>: ConvertSlice<T>,
+        Box<[f32]>: ConvertSlice<T>,
+        Box<[f64]>: ConvertSlice<T>,
+    {
+        WavWriter::create(
+            fp,
+            self.spec.sample_rate,
+            self.spec.n_channels,
+            &self.options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod writer_tests {
+    use super::*;
+    use crate::core::Wav;
+    use crate::error::WaversError;
+    use std::io::Cursor;
+
+    const TWO_CHANNEL_WAV_I16: &str = "./test_resources/two_channel_i16.wav";
+    const TEST_OUTPUT: &str = "./test_resources/tmp/";
+
+    #[test]
+    fn incremental_writes_match_whole_writes() {
+        let samples = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut options = WriteOptions::new()
+            .encoding(WavType::Float32)
+            .title("Recording")
+            .add_chunk(*b"tail", &[1, 2, 3]);
+        // Ordering the custom chunk after the data chunk writes it in finalize
+        options.chunk_order = vec![crate::DATA.into(), (*b"tail").into()];
+
+        let mut writer: WavWriter<i16, _> =
+            WavWriter::new(Cursor::new(Vec::new()), 16000, 2, &options).unwrap();
+        for block in samples.chunks(2 * 1000) {
+            writer.write_frames(block).unwrap();
+        }
+        assert_eq!(writer.n_frames(), samples.len() / 2);
+        assert!(writer.write_frames(&samples[..3]).is_err());
+        let written = writer.finalize().unwrap().into_inner();
+
+        let mut expected: Vec<u8> = Vec::new();
+        crate::write_to(&mut expected, &samples, 16000, 2, &options, &mut |_, _| {
+            Ok(())
+        })
+        .unwrap();
+        // The only difference is the JUNK chunk reserved for a ds64 chunk
+        assert_eq!(&written[12..16], &JUNK);
+        assert_eq!(&written[48..], &expected[12..]);
+        assert_eq!(
+            u32::from_le_bytes(written[4..8].try_into().unwrap()) as usize,
+            written.len() - 8
+        );
+
+        let mut wav: Wav<i16> = Wav::from_vec(written).unwrap();
+        assert_eq!(wav.encoding(), WavType::EFloat32);
+        assert_eq!(wav.read().unwrap(), samples);
+    }
+
+    #[test]
+    fn builders_match_write_options() {
+        let mut wav: Wav<i16> = Wav::from_path(TWO_CHANNEL_WAV_I16).unwrap();
+        let spec = wav.spec();
+        assert_eq!(spec, WavSpec::new(16000, 2).wav_type(WavType::Pcm16));
+        let samples = wav.read().unwrap();
+
+        let builder = WavBuilder::new(spec.wav_type(WavType::Pcm24))
+            .with_options(WriteOptions::new().encoding(WavType::ALaw))
+            .with_metadata([(InfoTag::Name, "Recording")])
+            .with_chunks([RawChunk::new(*b"anlz", vec![1, 2, 3])]);
+        let options = WriteOptions::new()
+            .encoding(WavType::Pcm24)
+            .title("Recording")
+            .add_chunk(*b"anlz", &[1, 2, 3]);
+        assert_eq!(builder.options(), &options);
+
+        let written = builder.to_vec(&samples).unwrap();
+        assert_eq!(
+            written,
+            crate::write_to_vec_with_options(&samples, 16000, 2, &options).unwrap()
+        );
+        let mut wav: Wav<i16> = Wav::from_vec(written).unwrap();
+        assert_eq!(wav.encoding(), WavType::Pcm24);
+        assert_eq!(wav.read().unwrap(), samples);
+    }
+
+    /// A sink which keeps only the first bytes written to it, so that very large files can be written quickly.
+    struct HeadSink {
+        head: Vec<u8>,
+        pos: u64,
+        len: u64,
+    }
+
+    impl Write for HeadSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            for (i, byte) in buf.iter().enumerate() {
+                let pos = self.pos as usize + i;
+                if pos >= self.head.len() {
+                    break;
+                }
+                self.head[pos] = *byte;
+            }
+            self.pos += buf.len() as u64;
+            self.len = self.len.max(self.pos);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for HeadSink {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.pos = match pos {
+                SeekFrom::Start(pos) => pos,
+                SeekFrom::Current(offset) => self.pos.checked_add_signed(offset).unwrap(),
+                SeekFrom::End(offset) => self.len.checked_add_signed(offset).unwrap(),
+            };
+            Ok(self.pos)
+        }
+    }
+
+    #[test]
+    fn large_files_are_promoted_to_rf64() {
+        let sink = HeadSink {
+            head: vec![0; 1024],
+            pos: 0,
+            len: 0,
+        };
+        let mut writer: WavWriter<i16, _> =
+            WavWriter::new(sink, 48000, 2, &WriteOptions::new()).unwrap();
+        let header_len = writer.writer.len as usize;
+
+        // Just over 4 GiB of samples
+        let block = vec![0i16; 1 << 20];
+        let n_samples = (u32::MAX as usize / 2 / block.len() + 1) * block.len();
+        for _ in 0..n_samples / block.len() {
+            writer.write_frames(&block).unwrap();
+        }
+        let sink = writer.finalize().unwrap();
+        assert_eq!(sink.len, (header_len + 2 * n_samples) as u64);
+
+        // The JUNK chunk is replaced by the ds64 chunk, leaving the header the same length
+        let expected =
+            crate::header::WavHeader::new_header_with_type(WavType::Pcm16, 48000, 2, n_samples)
+                .unwrap();
+        let expected_bytes = expected.as_bytes();
+        assert!(expected.is_rf64());
+        assert_eq!(expected_bytes.len() + 8, header_len);
+        assert_eq!(&sink.head[..expected_bytes.len()], &expected_bytes[..]);
+        assert_eq!(
+            &sink.head[expected_bytes.len()..header_len],
+            &expected.data_chunk_bytes()
+        );
+    }
+
+    #[test]
+    fn streams_of_known_length_match_whole_writes() {
+        let samples = Wav::<i16>::from_path(TWO_CHANNEL_WAV_I16)
+            .unwrap()
+            .read()
+            .unwrap();
+        let options = WriteOptions::new().title("Stream");
+        let n_frames = samples.len() as u64 / 2;
+
+        // A Vec is written to without seeking
+        let mut writer: StreamWavWriter<i16, _> = StreamWavWriter::new(
+            Vec::new(),
+            16000,
+            2,
+            StreamLength::Frames(n_frames),
+            &options,
+        )
+        .unwrap();
+        for block in samples.chunks(2 * 1000) {
+            writer.write_frames(block).unwrap();
+        }
+        assert!(writer.write_frames(&samples[..2]).is_err());
+        let written = writer.finalize().unwrap();
+
+        let mut expected: Vec<u8> = Vec::new();
+        crate::write_to(&mut expected, &samples, 16000, 2, &options, &mut |_, _| {
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(written, expected);
+
+        // Too few frames are reported, although the header has already been written
+        let mut writer: StreamWavWriter<i16, _> = StreamWavWriter::new(
+            Vec::new(),
+            16000,
+            2,
+            StreamLength::Frames(n_frames),
+            &options,
+        )
+        .unwrap();
+        writer.write_frames(&samples[..2000]).unwrap();
+        assert!(matches!(
+            writer.finalize(),
+            Err(WaversError::Format(FormatError::StreamLengthMismatch {
+                written: 1000,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn streams_of_unknown_length_have_placeholder_sizes() {
+        let samples: Vec<f32> = (0..1000).map(|x| (x as f32 / 100.0).sin()).collect();
+        let mut options = WriteOptions::new().add_chunk(*b"tail", &[1, 2, 3]);
+        options.chunk_order = vec![crate::DATA.into(), (*b"tail").into()];
+
+        let mut writer: StreamWavWriter<f32, _> =
+            StreamWavWriter::new(Vec::new(), 8000, 1, StreamLength::Unknown, &options).unwrap();
+        writer.write_frames(&samples[..500]).unwrap();
+        writer.write_frames(&samples[500..]).unwrap();
+        let written = writer.finalize().unwrap();
+
+        // The custom chunk is moved before the data chunk, which runs to the end of the stream
+        let data_start = written.len() - 4 * samples.len();
+        assert_eq!(&written[data_start..], bytemuck::cast_slice(&samples));
+        assert_eq!(&written[data_start - 8..data_start - 4], &crate::DATA);
+        let unknown = UNKNOWN_STREAM_SIZE.to_le_bytes();
+        assert_eq!(&written[data_start - 4..data_start], &unknown);
+        assert_eq!(&written[4..8], &unknown);
+        assert_eq!(&written[data_start - 20..data_start - 16], b"tail");
+    }
+
+    #[test]
+    fn unfinalized_files_are_empty() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}wav_writer.wav", TEST_OUTPUT);
+        let samples: Vec<f32> = (0..1000).map(|x| (x as f32 / 100.0).sin()).collect();
+
+        let mut writer: WavWriter<f32> =
+            WavWriter::create(&out_path, 8000, 1, &WriteOptions::new()).unwrap();
+        writer.write_frames(&samples).unwrap();
+        writer.writer.flush().unwrap();
+        assert_eq!(Wav::<f32>::from_path(&out_path).unwrap().n_samples(), 0);
+
+        writer.write_frames(&samples).unwrap();
+        writer.finalize().unwrap();
+        let mut wav: Wav<f32> = Wav::from_path(&out_path).unwrap();
+        assert_eq!(wav.n_samples(), 2000);
+        assert_eq!(&wav.read().unwrap()[1000..], &samples[..]);
+        std::fs::remove_file(&out_path).unwrap();
+    }
+
+    #[test]
+    fn unfinalized_files_are_recovered() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}recovered.wav", TEST_OUTPUT);
+        let samples: Vec<f32> = (0..2000).map(|x| (x as f32 / 100.0).sin()).collect();
+        let options = WriteOptions::new().title("Interrupted");
+        let mut writer: WavWriter<f32> = WavWriter::create(&out_path, 8000, 2, &options).unwrap();
+        writer.write_frames(&samples).unwrap();
+        // Dropping the writer flushes the samples without finalizing the file, as if the recording was interrupted
+        drop(writer);
+        // Half of a frame was written before the interruption
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&out_path)
+            .unwrap();
+        f.write_all(&[1, 2, 3, 4]).unwrap();
+        drop(f);
+        assert_eq!(Wav::<f32>::from_path(&out_path).unwrap().n_samples(), 0);
+
+        let mut wav: Wav<f32> = WavWriter::recover(&out_path).unwrap();
+        assert_eq!(wav.n_samples(), samples.len());
+        assert_eq!(&*wav.read().unwrap(), &samples[..]);
+        assert_eq!(
+            wav.metadata()
+                .unwrap()
+                .get(&InfoTag::Name)
+                .map(String::as_str),
+            Some("Interrupted")
+        );
+        let recovered = std::fs::read(&out_path).unwrap();
+        assert_eq!(
+            u32::from_le_bytes(recovered[4..8].try_into().unwrap()) as usize,
+            recovered.len() - 8
+        );
+
+        // Recovering a finalized file leaves it unchanged
+        let _: Wav<f32> = WavWriter::recover(&out_path).unwrap();
+        assert_eq!(std::fs::read(&out_path).unwrap(), recovered);
+        std::fs::remove_file(&out_path).unwrap();
+    }
+
+    #[test]
+    fn large_unfinalized_files_are_recovered_as_rf64() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}recovered_rf64.wav", TEST_OUTPUT);
+        let writer: WavWriter<i16> =
+            WavWriter::create(&out_path, 48000, 1, &WriteOptions::new()).unwrap();
+        drop(writer);
+        // Extending the file leaves a hole rather than writing 5 GiB of samples
+        let f = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&out_path)
+            .unwrap();
+        f.set_len(5 << 30).unwrap();
+        drop(f);
+
+        let wav: Wav<i16> = WavWriter::recover(&out_path).unwrap();
+        assert!(wav.header().is_rf64());
+        let data_start = wav.header().data().offset as u64 + 8;
+        assert_eq!(wav.n_samples() as u64, ((5 << 30) - data_start) / 2);
+        drop(wav);
+        std::fs::remove_file(&out_path).unwrap();
+    }
+
+    #[test]
+    fn writers_use_the_buffer_size_of_the_options() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let out_path = format!("{}buffer_size.wav", TEST_OUTPUT);
+        let samples: Vec<f32> = (0..1000).map(|x| (x as f32 / 100.0).sin()).collect();
+        let writer: WavWriter<f32> =
+            WavWriter::create(&out_path, 8000, 1, &WriteOptions::new()).unwrap();
+        assert_eq!(writer.writer.capacity(), DEFAULT_BUFFER_SIZE);
+
+        let options = WriteOptions::new().buffer_size(16);
+        let mut writer: WavWriter<f32> = WavWriter::create(&out_path, 8000, 1, &options).unwrap();
+        assert_eq!(writer.writer.capacity(), 16);
+        writer.write_frames(&samples).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(
+            &*Wav::<f32>::from_path(&out_path).unwrap().read().unwrap(),
+            &samples[..]
+        );
+        std::fs::remove_file(&out_path).unwrap();
+    }
+
+    #[test]
+    fn durable_writes_are_synced() {
+        if !Path::new(TEST_OUTPUT).exists() {
+            std::fs::create_dir(TEST_OUTPUT).unwrap();
+        }
+        let samples: Vec<f32> = (0..1000).map(|x| (x as f32 / 100.0).sin()).collect();
+        let writer_path = format!("{}durable_writer.wav", TEST_OUTPUT);
+        let out_path = format!("{}durable_write.wav", TEST_OUTPUT);
+        for durability in [Durability::File, Durability::FileAndDirectory] {
+            let options = WriteOptions::new().durability(durability);
+            let mut writer: WavWriter<f32> =
+                WavWriter::create(&writer_path, 8000, 1, &options).unwrap();
+            assert!(writer.sync.is_some());
+            writer.write_frames(&samples).unwrap();
+            writer.finalize().unwrap();
+
+            crate::write_with_options(&out_path, &samples, 8000, 1, &options.atomic(true)).unwrap();
+            assert_eq!(
+                &*Wav::<f32>::from_path(&out_path).unwrap().read().unwrap(),
+                &samples[..]
+            );
+        }
+        std::fs::remove_file(&writer_path).unwrap();
+        std::fs::remove_file(&out_path).unwrap();
+        // Relative paths in the current directory have an empty parent
+        Durability::sync_dir(Path::new("Cargo.toml")).unwrap();
+    }
+}